@@ -1,3 +1,8 @@
+use crate::core::clock;
+use crate::core::graph::GraphFormat;
+use crate::core::import::ImportFormat;
+use crate::core::models::DocumentState;
+use chrono::{DateTime, FixedOffset};
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -11,6 +16,33 @@ pub struct Cli {
     #[arg(global = true, long, value_name = "FORMAT", default_value = "human")]
     pub output: OutputFormat,
 
+    /// Keep only document metadata in memory, reading bodies on demand
+    #[arg(global = true, long)]
+    pub low_memory: bool,
+
+    /// Write the command's output to this file instead of stdout, replacing
+    /// it atomically and creating missing parent directories
+    #[arg(global = true, long, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+
+    /// Exit with code 4 if a command that otherwise succeeded raised warnings
+    #[arg(global = true, long)]
+    pub deny_warnings: bool,
+
+    /// Diagnostic output format on stderr: text, or json for one event per line
+    #[arg(global = true, long, value_name = "FORMAT", default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Pin the clock to this RFC 3339 time or date so dates, ages and
+    /// timestamps are reproducible (overrides CONTEXT_FAKE_TIME)
+    #[arg(global = true, long, value_name = "TIME", hide = true, value_parser = clock::parse)]
+    pub fixed_time: Option<DateTime<FixedOffset>>,
+
+    /// Never colour the output. Output is plain today; the flag lets
+    /// snapshot tests pin that should colour ever be added
+    #[arg(global = true, long, hide = true)]
+    pub no_color: bool,
+
     /// The context command to execute
     #[command(subcommand)]
     pub command: Commands,
@@ -37,6 +69,48 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+/// Format of diagnostics written to stderr
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Warnings and progress as human-readable lines
+    Text,
+    /// Every progress event, warning and error as an NDJSON object
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "human" => Ok(LogFormat::Text),
+            "json" | "ndjson" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {s}")),
+        }
+    }
+}
+
+/// Shell syntax for `context env`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// `export NAME='value'` for sh, bash and zsh
+    Posix,
+    /// `set -gx NAME 'value'`
+    Fish,
+}
+
+impl std::str::FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sh" | "bash" | "zsh" | "posix" => Ok(Shell::Posix),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!("Unknown shell: {s}")),
+        }
+    }
+}
+
 /// Arguments for the init command
 #[derive(Args, Debug)]
 pub struct InitArgs {
@@ -47,11 +121,46 @@ pub struct InitArgs {
     /// Create parent directories if they don't exist
     #[arg(short, long)]
     pub create: bool,
+
+    /// Template git URL or directory to bootstrap .context from
+    #[arg(long, value_name = "GIT_URL|PATH", conflicts_with = "preset")]
+    pub from: Option<String>,
+
+    /// Built-in preset to initialize with (see --list-presets)
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// List the built-in presets and exit
+    #[arg(long)]
+    pub list_presets: bool,
+}
+
+/// Pagination options shared by commands with potentially large output
+#[derive(Args, Debug, Clone, Copy)]
+pub struct PageArgs {
+    /// Maximum number of results to show
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Page of results to show, starting at 1 (used with --limit)
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub page: u32,
+}
+
+impl PageArgs {
+    /// Index of the first result on the requested page
+    pub fn offset(&self) -> usize {
+        (self.page as usize - 1) * self.limit.unwrap_or(0)
+    }
 }
 
 /// Arguments for the status command
 #[derive(Args, Debug)]
 pub struct StatusArgs {
+    /// Only check this document, by slug or path
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: Option<String>,
+
     /// Show invalid documents only
     #[arg(short, long)]
     pub invalid_only: bool,
@@ -59,22 +168,83 @@ pub struct StatusArgs {
     /// Show details for each document
     #[arg(short, long)]
     pub detailed: bool,
+
+    /// Only documents under this directory (relative to .context/)
+    #[arg(long, value_name = "DIR")]
+    pub directory: Option<PathBuf>,
+
+    /// Only documents with this tag
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Only documents with this status (valid, stale, orphaned); repeatable
+    #[arg(long, value_name = "STATUS")]
+    pub status: Vec<crate::core::models::Status>,
+
+    /// Only documents referencing this source path or a file beneath it; repeatable
+    #[arg(long = "path", value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Only documents in this lifecycle state (draft, published, deprecated); repeatable
+    #[arg(long, value_name = "STATE")]
+    pub state: Vec<DocumentState>,
+
+    /// Only count reference changes introduced since the merge base of REV
+    /// and HEAD, e.g. `origin/main` in a pull request check
+    #[arg(long, value_name = "REV")]
+    pub merge_base: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
 }
 
 /// Arguments for the sync command
 #[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)] // independent command-line flags
 pub struct SyncArgs {
-    /// Path to a specific document to sync (syncs all if omitted)
-    #[arg(value_name = "PATH")]
-    pub path: Option<PathBuf>,
+    /// Slug or path of a single document to sync (syncs all if omitted)
+    #[arg(value_name = "SLUG|PATH")]
+    pub path: Option<String>,
 
-    /// Remove stale entries from cache
+    /// Drop references bodies no longer mention, also from documents not
+    /// re-hashed, and stored data of deleted documents, listing what was cleaned
     #[arg(short, long)]
     pub cleanup: bool,
 
     /// Force full re-hash of all documents
     #[arg(short, long)]
     pub force: bool,
+
+    /// Record NAME as having reviewed the changes to stale documents being synced
+    #[arg(long, value_name = "NAME")]
+    pub reviewed_by: Option<String>,
+
+    /// Only sync documents with this status (valid, stale, orphaned); repeatable
+    #[arg(long, value_name = "STATUS", conflicts_with = "path")]
+    pub only: Vec<crate::core::models::Status>,
+
+    /// Validate first and only sync stale or orphaned documents, reporting
+    /// how many valid ones were skipped (same as `--only stale --only orphaned`)
+    #[arg(long, conflicts_with_all = ["path", "only"])]
+    pub only_stale: bool,
+
+    /// Only sync documents under this directory (relative to .context/)
+    #[arg(long, value_name = "DIR", conflicts_with = "path")]
+    pub directory: Option<PathBuf>,
+
+    /// Only sync documents with this tag
+    #[arg(long, value_name = "TAG", conflicts_with = "path")]
+    pub tag: Option<String>,
+
+    /// Show the references and hashes each document would change, without
+    /// writing anything
+    #[arg(long, conflicts_with = "reviewed_by")]
+    pub dry_run: bool,
+
+    /// Drop references to files that no longer exist instead of failing on
+    /// them, listing what was pruned
+    #[arg(long)]
+    pub prune_missing: bool,
 }
 
 /// Arguments for the find command
@@ -83,11 +253,647 @@ pub struct FindArgs {
     /// Source file paths to search for
     #[arg(value_name = "PATH", required = true, num_args = 1..)]
     pub paths: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+/// Arguments for the search command
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Text to search for in document bodies (case-insensitive)
+    #[arg(value_name = "QUERY")]
+    pub query: String,
+
+    /// Rank results by freshness (valid, recently updated documents first)
+    /// instead of streaming them in file order
+    #[arg(long)]
+    pub rank: bool,
+
+    /// Only documents in this lifecycle state (draft, published, deprecated); repeatable
+    #[arg(long, value_name = "STATE")]
+    pub state: Vec<DocumentState>,
+
+    /// Search documents in .context/archive/ too
+    #[arg(long)]
+    pub include_archived: bool,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+/// Arguments for the index command
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    /// Reindex documents whose bodies changed since they were indexed (the default)
+    #[arg(long, conflicts_with_all = ["rebuild", "verify"])]
+    pub update: bool,
+
+    /// Drop the index and reindex every document
+    #[arg(long, conflicts_with = "verify")]
+    pub rebuild: bool,
+
+    /// Check the index against the documents without changing it; exits 1 on mismatches
+    #[arg(long)]
+    pub verify: bool,
+}
+
+/// Arguments for the search-index command
+#[derive(Args, Debug)]
+pub struct SearchIndexArgs {
+    /// Write the index to this file instead of stdout
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Index draft documents too
+    #[arg(long)]
+    pub include_drafts: bool,
+}
+
+/// Arguments for the get command
+#[derive(Args, Debug)]
+pub struct GetArgs {
+    /// Document slug or path
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: String,
+
+    /// Print only the section under this heading (e.g. "## Token refresh")
+    #[arg(long, value_name = "HEADING")]
+    pub section: Option<String>,
+}
+
+/// Arguments for the query command
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    /// Build target whose files to check, e.g. //services/auth:lib
+    #[arg(long, value_name = "TARGET")]
+    pub files_for_target: String,
+}
+
+/// Arguments for the graph command
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    /// Rendering: dot (Graphviz) or mermaid
+    #[arg(long, value_name = "FORMAT", default_value = "dot")]
+    pub format: GraphFormat,
+}
+
+/// Arguments for the lint command
+#[derive(Args, Debug)]
+pub struct LintArgs {
+    /// List each document's length and readability metrics instead of issues
+    #[arg(long)]
+    pub metrics: bool,
+}
+
+/// Arguments for the doctor command
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Report pairs of documents whose bodies are at least this percent similar
+    #[arg(long, value_name = "PERCENT", default_value_t = crate::core::similarity::DEFAULT_THRESHOLD * 100.0)]
+    pub duplicate_threshold: f64,
+}
+
+/// Arguments for the coverage command
+#[derive(Args, Debug)]
+pub struct CoverageArgs {
+    /// Only count files under these project-relative directories
+    pub paths: Vec<String>,
+
+    /// Exit 1 when coverage is below this percentage, for CI
+    #[arg(long, value_name = "PERCENT")]
+    pub min: Option<f64>,
+
+    /// Count files only draft documents reference as covered
+    #[arg(long)]
+    pub include_drafts: bool,
+}
+
+/// Arguments for the ls command
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Render the hierarchy as a tree, like tree(1)
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Only documents under this directory (relative to .context/)
+    #[arg(long, value_name = "DIR")]
+    pub directory: Option<PathBuf>,
+
+    /// Only documents with this tag
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Only documents in this lifecycle state (draft, published, deprecated); repeatable
+    #[arg(long, value_name = "STATE")]
+    pub state: Vec<DocumentState>,
+}
+
+/// Arguments for the show command
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    /// Document slug or path
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: String,
+}
+
+/// Arguments for the split command
+#[derive(Args, Debug)]
+pub struct SplitArgs {
+    /// Document slug or path to split
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: String,
+
+    /// Show the documents that would be created without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the merge command
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    /// Document to merge into (slug or path)
+    #[arg(value_name = "DOC_A")]
+    pub into: String,
+
+    /// Document to merge away (slug or path)
+    #[arg(value_name = "DOC_B")]
+    pub from: String,
+
+    /// Delete DOC_B instead of leaving a superseded stub
+    #[arg(long)]
+    pub delete: bool,
+}
+
+/// Arguments for the archive command
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    /// Only archive documents not updated for this many days, instead of
+    /// `[archive] after_days`
+    #[arg(long, value_name = "DAYS")]
+    pub older_than: Option<u32>,
+
+    /// Show what would be archived and relinked without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the resolve command
+#[derive(Args, Debug)]
+pub struct ResolveArgs {
+    /// Path to a specific document to resolve (scans all if omitted)
+    #[arg(value_name = "PATH")]
+    pub path: Option<PathBuf>,
+}
+
+/// Arguments for the describe command
+#[derive(Args, Debug)]
+pub struct DescribeArgs {
+    /// Document to describe (all documents with empty descriptions if omitted)
+    #[arg(value_name = "DOC")]
+    pub document: Option<String>,
+
+    /// Write the generated descriptions to frontmatter
+    #[arg(long)]
+    pub apply: bool,
+}
+
+/// Arguments for the suggest command
+#[derive(Args, Debug)]
+pub struct SuggestArgs {
+    /// Document to suggest references for
+    #[arg(value_name = "DOC")]
+    pub document: String,
+
+    /// Add the suggestions to the document's "Related files" section
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Maximum number of suggestions
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub limit: usize,
+}
+
+/// Arguments for the gaps command
+#[derive(Args, Debug)]
+pub struct GapsArgs {
+    /// How far back to look, e.g. 30d, 2w, 6m or 1y
+    #[arg(long, value_name = "WINDOW", default_value = "30d", value_parser = crate::core::gaps::parse_days)]
+    pub since: u32,
+
+    /// Maximum number of files to report
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub limit: usize,
+}
+
+/// Arguments for the pack command
+#[derive(Args, Debug)]
+pub struct PackArgs {
+    /// Git revision range whose changed files select the documents, e.g.
+    /// main..HEAD; a single revision is compared with the working tree
+    #[arg(long, value_name = "RANGE")]
+    pub diff: String,
+
+    /// How many links to follow from the documents referencing changed files
+    #[arg(long, value_name = "N", default_value_t = crate::core::pack::DEFAULT_HOPS)]
+    pub hops: usize,
+
+    /// Bundle draft documents too
+    #[arg(long)]
+    pub include_drafts: bool,
+}
+
+/// Arguments for the capture command
+#[derive(Args, Debug)]
+pub struct CaptureArgs {
+    /// Git revision range to draft a document from, e.g. main..feature
+    #[arg(long, value_name = "RANGE")]
+    pub from_git: String,
+
+    /// Slug of the new document; defaults to the branch the range ends at
+    #[arg(long)]
+    pub slug: Option<String>,
+
+    /// Where to write the document, relative to .context/; defaults to <SLUG>.md
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<PathBuf>,
+
+    /// Print the draft without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the diff-docs command
+#[derive(Args, Debug)]
+pub struct DiffDocsArgs {
+    /// Revision to compare from, e.g. main or HEAD~3
+    #[arg(value_name = "FROM")]
+    pub from: String,
+
+    /// Revision to compare to; defaults to the working tree
+    #[arg(value_name = "TO")]
+    pub to: Option<String>,
+}
+
+/// Arguments for the checklist command
+#[derive(Args, Debug)]
+pub struct ChecklistArgs {
+    /// Git revision range to review, e.g. main..HEAD
+    #[arg(long, value_name = "RANGE")]
+    pub diff: String,
+
+    /// URL template for links, with {path} replaced by the project-relative
+    /// path; defaults to `[preview] source_url`
+    #[arg(long, value_name = "URL")]
+    pub source_url: Option<String>,
+}
+
+/// Arguments for the new command
+#[derive(Args, Debug)]
+pub struct NewArgs {
+    /// Kind of document: guide or reference
+    #[arg(value_name = "KIND")]
+    pub kind: crate::core::models::DocumentKind,
+
+    /// Slug of the new document, which also names its file
+    #[arg(value_name = "SLUG")]
+    pub slug: String,
+
+    /// Don't open the new document in $EDITOR
+    #[arg(long)]
+    pub no_edit: bool,
+}
+
+/// Arguments for the hooks command
+#[derive(Args, Debug)]
+pub struct HooksArgs {
+    /// The hooks subcommand to execute
+    #[command(subcommand)]
+    pub command: HooksCommand,
+}
+
+/// Subcommands for git hooks
+#[derive(Subcommand, Debug)]
+pub enum HooksCommand {
+    /// Install git hooks into the repository
+    #[command(
+        about = "Install git hooks that remind committers of the documents their changes affect"
+    )]
+    Install(HooksInstallArgs),
+
+    /// Run the commit-msg hook (called by git)
+    #[command(
+        about = "Add a Context-Docs trailer to a commit message for each document referencing the staged files"
+    )]
+    CommitMsg(CommitMsgArgs),
+}
+
+/// Arguments for the hooks install command
+#[derive(Args, Debug)]
+pub struct HooksInstallArgs {
+    /// Install the commit-msg hook
+    #[arg(long, required = true)]
+    pub commit_msg: bool,
+
+    /// Replace existing hooks not installed by context
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for the hooks commit-msg command
+#[derive(Args, Debug)]
+pub struct CommitMsgArgs {
+    /// File holding the commit message, as passed by git
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+/// Arguments for the watch command
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// How long changes must settle before validating, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = crate::core::watch::DEFAULT_DEBOUNCE_MS)]
+    pub debounce_ms: u64,
+}
+
+/// Arguments for the mine command
+#[derive(Args, Debug)]
+pub struct MineArgs {
+    /// Owner to show documents for instead of your git identity (repeatable)
+    #[arg(long = "as", value_name = "OWNER")]
+    pub owners: Vec<String>,
+
+    /// Review the changes to your stale documents and sync them
+    #[arg(long)]
+    pub fix: bool,
+}
+
+/// Arguments for the when-stale command
+#[derive(Args, Debug)]
+pub struct WhenStaleArgs {
+    /// Document slug or path
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: String,
+}
+
+/// Arguments for the explain command
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    /// Source file to explain
+    #[arg(value_name = "SOURCE")]
+    pub path: PathBuf,
+}
+
+/// Arguments for the refs command
+#[derive(Args, Debug)]
+pub struct RefsArgs {
+    /// The refs subcommand to execute
+    #[command(subcommand)]
+    pub command: RefsCommand,
+}
+
+/// Subcommands for managing references directly
+#[derive(Subcommand, Debug)]
+pub enum RefsCommand {
+    /// Import reference declarations produced by external tools
+    #[command(about = "Merge externally produced reference declarations into documents")]
+    Import(RefsImportArgs),
+}
+
+/// Arguments for the refs import command
+#[derive(Args, Debug)]
+pub struct RefsImportArgs {
+    /// File to read declarations from (stdin if omitted or "-")
+    #[arg(value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Input format
+    #[arg(long, value_name = "FORMAT", default_value = "jsonl")]
+    pub format: ImportFormat,
+}
+
+/// Arguments for the policy command
+#[derive(Args, Debug)]
+pub struct PolicyArgs {
+    /// The policy subcommand to execute
+    #[command(subcommand)]
+    pub command: PolicyCommand,
+}
+
+/// Subcommands for organization rules in `.context/policy.toml`
+#[derive(Subcommand, Debug)]
+pub enum PolicyCommand {
+    /// Check every rule
+    #[command(
+        about = "Check the rules in .context/policy.toml; exits 1 if any error rule is violated"
+    )]
+    Check,
+}
+
+/// Arguments for the templates command
+#[derive(Args, Debug)]
+pub struct TemplatesArgs {
+    /// The templates subcommand to execute
+    #[command(subcommand)]
+    pub command: TemplatesCommand,
+}
+
+/// Subcommands for comparing documents with their templates
+#[derive(Subcommand, Debug)]
+pub enum TemplatesCommand {
+    /// Show how a document's structure drifted from its template
+    #[command(about = "Compare a document's headings with its template's current version")]
+    Diff(TemplatesDiffArgs),
+
+    /// Report documents created from outdated template versions
+    #[command(
+        about = "List documents created from templates, flagging outdated versions; exits 1 if any are"
+    )]
+    Status,
+}
+
+/// Arguments for the templates diff command
+#[derive(Args, Debug)]
+pub struct TemplatesDiffArgs {
+    /// Document slug or path
+    #[arg(value_name = "SLUG|PATH")]
+    pub document: String,
+}
+
+/// Arguments for the diagrams command
+#[derive(Args, Debug)]
+pub struct DiagramsArgs {
+    /// The diagrams subcommand to execute
+    #[command(subcommand)]
+    pub command: DiagramsCommand,
+}
+
+/// Subcommands for Mermaid and PlantUML blocks in documents
+#[derive(Subcommand, Debug)]
+pub enum DiagramsCommand {
+    /// Render every diagram to SVG
+    #[command(about = "Render diagrams to SVG with the [diagrams] commands; exits 1 if any fails")]
+    Render(DiagramsRenderArgs),
+}
+
+/// Arguments for the diagrams render command
+#[derive(Args, Debug)]
+pub struct DiagramsRenderArgs {
+    /// Directory to write `<slug>-<n>.svg` files to
+    #[arg(short, long, value_name = "DIR")]
+    pub output: PathBuf,
+}
+
+/// Arguments for the snapshot command
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    /// The snapshot subcommand to execute
+    #[command(subcommand)]
+    pub command: SnapshotCommand,
+}
+
+/// Subcommands for capturing and restoring the context directory
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Capture the current state of the context directory
+    #[command(about = "Archive the whole context directory into .context/.snapshots")]
+    Create(SnapshotCreateArgs),
+
+    /// List stored snapshots
+    #[command(about = "List stored snapshots, oldest first")]
+    List,
+
+    /// Restore a snapshot
+    #[command(
+        about = "Replace the context directory with a snapshot, backing up the current state first"
+    )]
+    Restore(SnapshotRestoreArgs),
+}
+
+/// Arguments for the snapshot create command
+#[derive(Args, Debug)]
+pub struct SnapshotCreateArgs {
+    /// Label appended to the snapshot name (letters, digits, '-' and '_')
+    #[arg(long, value_name = "LABEL")]
+    pub label: Option<String>,
+}
+
+/// Arguments for the snapshot restore command
+#[derive(Args, Debug)]
+pub struct SnapshotRestoreArgs {
+    /// Name of the snapshot, as shown by `context snapshot list`
+    #[arg(value_name = "NAME")]
+    pub name: String,
+}
+
+/// Arguments for the remote command
+#[derive(Args, Debug)]
+pub struct RemoteArgs {
+    /// The remote subcommand to execute
+    #[command(subcommand)]
+    pub command: RemoteCommand,
+}
+
+/// Subcommands for shared context trees from other repositories
+#[derive(Subcommand, Debug)]
+pub enum RemoteCommand {
+    /// Fetch remote context trees declared in config
+    #[command(about = "Clone the remotes declared in config.toml into .context/.remote")]
+    Fetch(RemoteFetchArgs),
+
+    /// Pull the latest revision of remote context trees
+    #[command(about = "Pull the latest shared documents from each remote")]
+    Update(RemoteFetchArgs),
+
+    /// Show drift between remote checkouts and upstream
+    #[command(about = "Compare remote checkouts with upstream and report shadowed slugs")]
+    Status,
+}
+
+/// Arguments for the remote fetch and update commands
+#[derive(Args, Debug)]
+pub struct RemoteFetchArgs {
+    /// Only fetch or update the remote with this name
+    #[arg(value_name = "NAME")]
+    pub name: Option<String>,
+}
+
+/// Arguments for the env command
+#[derive(Args, Debug)]
+pub struct EnvArgs {
+    /// Shell syntax to emit (sh, bash, zsh, fish)
+    #[arg(long, value_name = "SHELL", default_value = "sh")]
+    pub shell: Shell,
+
+    /// Also export CONTEXT_PROMPT with a doc-health line such as "docs: 3 stale"
+    #[arg(long)]
+    pub prompt: bool,
+}
+
+/// Arguments for the selftest command
+#[derive(Args, Debug)]
+pub struct SelftestArgs {
+    /// Keep the temporary project for inspection
+    #[arg(long)]
+    pub keep: bool,
+}
+
+/// Arguments for the stats command
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Show the most- and least-read documents from the usage journal
+    #[arg(long)]
+    pub usage: bool,
+
+    /// Score documentation debt per document and directory
+    #[arg(long, conflicts_with = "usage")]
+    pub debt: bool,
+
+    /// Chart summary metrics recorded by previous runs
+    #[arg(long, conflicts_with_all = ["usage", "debt"])]
+    pub trend: bool,
+
+    /// Number of documents listed in each ranking, or data points charted
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub limit: usize,
+}
+
+/// Arguments for the preview command
+#[derive(Args, Debug)]
+pub struct PreviewArgs {
+    /// Port to listen on (0 picks a free port)
+    #[arg(long, default_value_t = 4000)]
+    pub port: u16,
 }
 
 /// Arguments for the serve command
 #[derive(Args, Debug)]
-pub struct ServeArgs {}
+pub struct ServeArgs {
+    /// Append a JSON line for every tool invocation to this file
+    #[arg(long, value_name = "PATH")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Rotate the audit log once it reaches this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = crate::mcp::audit::DEFAULT_MAX_BYTES)]
+    pub audit_max_bytes: u64,
+
+    /// Maximum number of tool calls executing at once
+    #[arg(long, value_name = "N")]
+    pub max_concurrent: Option<usize>,
+
+    /// Maximum number of tool calls accepted per minute
+    #[arg(long, value_name = "N")]
+    pub calls_per_minute: Option<usize>,
+
+    /// Reject tool responses larger than this many bytes
+    #[arg(long, value_name = "BYTES")]
+    pub max_response_bytes: Option<usize>,
+
+    /// Serve these projects' context trees, comma-separated; tools take a
+    /// `root` parameter naming a project by its directory name
+    #[arg(long, value_name = "DIRS", value_delimiter = ',')]
+    pub roots: Vec<PathBuf>,
+}
 
 /// Available commands
 #[derive(Subcommand)]
@@ -108,7 +914,243 @@ pub enum Commands {
     #[command(about = "Find documents that reference the given source file(s)")]
     Find(FindArgs),
 
+    /// Print a document or one of its sections
+    #[command(about = "Print a document body, or a single section of it")]
+    Get(GetArgs),
+
+    /// List documents
+    #[command(
+        about = "List documents grouped by directory with slug, status, reference count and description; --tree draws the hierarchy"
+    )]
+    Ls(LsArgs),
+
+    /// Show a document with its validity
+    #[command(
+        about = "Print a document's body with each reference annotated inline as valid, stale or missing"
+    )]
+    Show(ShowArgs),
+
+    /// Search document bodies
+    #[command(about = "Search document bodies, streaming matches as they are found")]
+    Search(SearchArgs),
+
+    /// Export a client-side search index
+    #[command(
+        about = "Write a static JSON search index so published docs can be searched without a backend"
+    )]
+    SearchIndex(SearchIndexArgs),
+
+    /// Manage the full-text search index
+    #[command(
+        about = "Update, rebuild or verify the full-text search index of the SQLite storage backend"
+    )]
+    Index(IndexArgs),
+
+    /// Split a document by its top-level headings
+    #[command(about = "Split a document into one document per top-level section")]
+    Split(SplitArgs),
+
+    /// Merge two documents
+    #[command(about = "Merge DOC_B into DOC_A, unioning references and fixing links")]
+    Merge(MergeArgs),
+
+    /// Move deprecated documents into the archive
+    #[command(
+        about = "Move deprecated documents into .context/archive/, fixing links and recording each move in the journal"
+    )]
+    Archive(ArchiveArgs),
+
+    /// Repair documents after a conflicting git merge
+    #[command(about = "Repair frontmatter damaged by git merge conflicts and re-sync references")]
+    Resolve(ResolveArgs),
+
+    /// Manage references directly
+    #[command(about = "Manage document references directly")]
+    Refs(RefsArgs),
+
+    /// Show everything documented about a source file
+    #[command(about = "Show the documents, excerpts, statuses and owners for a source file")]
+    Explain(ExplainArgs),
+
+    /// Generate descriptions for documents
+    #[command(about = "Suggest descriptions for documents, writing them with --apply")]
+    Describe(DescribeArgs),
+
+    /// Suggest references a document is missing
+    #[command(
+        about = "Suggest files a document mentions by name but doesn't reference, adding them with --apply"
+    )]
+    Suggest(SuggestArgs),
+
+    /// Find frequently changed code lacking documentation
+    #[command(
+        about = "Rank files churned in recent git history that have no references or only stale ones"
+    )]
+    Gaps(GapsArgs),
+
+    /// Bundle the documents relevant to a change
+    #[command(
+        about = "Bundle the documents referencing files changed in a git revision range, plus linked documents"
+    )]
+    Pack(PackArgs),
+
+    /// Scaffold a new document
+    #[command(
+        about = "Create guides/<SLUG>.md or references/<SLUG>.md with its frontmatter filled in, and open it in $EDITOR"
+    )]
+    New(NewArgs),
+
+    /// Check the documents covering a build target
+    #[command(
+        about = "Map a build target to its files with the [query] resolver command, then report the documents referencing them; exits 1 if any are stale, 2 if any are orphaned"
+    )]
+    Query(QueryArgs),
+
+    /// List documents to review for a pull request
+    #[command(
+        about = "Print a Markdown checklist of the documents referencing files changed in a git revision range, for CI to post on a pull request"
+    )]
+    Checklist(ChecklistArgs),
+
+    /// Summarize changes to the context tree
+    #[command(
+        about = "Summarize how documents changed between two revisions (or a revision and the working tree): added, removed, descriptions, references and status transitions"
+    )]
+    DiffDocs(DiffDocsArgs),
+
+    /// Draft a document from a feature branch
+    #[command(
+        about = "Draft a new document from a git revision range: changed files become references, commit subjects become bullets"
+    )]
+    Capture(CaptureArgs),
+
+    /// Watch documents and sources, reporting status changes live
+    #[command(
+        about = "Watch the project and .context/ with filesystem events and re-validate documents after each burst of changes, printing status transitions until Ctrl-C"
+    )]
+    Watch(WatchArgs),
+
+    /// Manage git hooks
+    #[command(about = "Install and run git hooks that point commits at the documents they affect")]
+    Hooks(HooksArgs),
+
+    /// Show the documents you own
+    #[command(
+        about = "Show the status of documents owned by you (git user name or email), and with --fix review and sync them"
+    )]
+    Mine(MineArgs),
+
+    /// Find the commits that made a document stale
+    #[command(
+        about = "Show the first commit since a document's last sync that changed each of its references"
+    )]
+    WhenStale(WhenStaleArgs),
+
+    /// Check documents for quality problems
+    #[command(
+        about = "Check documents for quality problems such as empty descriptions or excessive length"
+    )]
+    Lint(LintArgs),
+
+    /// Check the tree for structural problems
+    #[command(
+        about = "Check the tree's structure: missing index.md files, duplicate slugs, slug/file name mismatches, unparseable frontmatter, empty descriptions, documents without references and near-duplicate bodies; exits 1 on errors"
+    )]
+    Doctor(DoctorArgs),
+
+    /// Report which source files documents cover
+    #[command(
+        about = "Report which project files (respecting .gitignore) are referenced by a document and which have no coverage; --min sets a percentage below which it exits 1"
+    )]
+    Coverage(CoverageArgs),
+
+    /// List documents in dependency order
+    #[command(
+        about = "List documents so each follows the documents it links to, reporting link cycles"
+    )]
+    Order,
+
+    /// Export the graph of documents and referenced files
+    #[command(
+        about = "Print the graph of documents, the files they reference and the documents they link to, as Graphviz DOT or Mermaid (--format dot|mermaid)"
+    )]
+    Graph(GraphArgs),
+
+    /// Manage shared context trees from other repositories
+    #[command(
+        about = "Fetch shared context trees that are overlaid read-only onto search and find"
+    )]
+    Remote(RemoteArgs),
+
+    /// Enforce organization rules
+    #[command(
+        about = "Check documents and project files against the rules in .context/policy.toml"
+    )]
+    Policy(PolicyArgs),
+
+    /// Compare documents with their templates
+    #[command(
+        about = "Show structural drift between documents and the templates they were created from"
+    )]
+    Templates(TemplatesArgs),
+
+    /// Export diagrams embedded in documents
+    #[command(about = "Render the Mermaid and PlantUML diagrams in documents to SVG files")]
+    Diagrams(DiagramsArgs),
+
+    /// Check translations of documents
+    #[command(
+        about = "Report missing and out-of-date translations for the [i18n] locales; exits 1 if any"
+    )]
+    I18n,
+
+    /// Capture or restore the whole context directory
+    #[command(
+        about = "Capture the context directory before risky bulk changes, and restore it later"
+    )]
+    Snapshot(SnapshotArgs),
+
+    /// Prune runtime caches
+    #[command(
+        about = "Remove old snapshots and other runtime caches according to the [gc] retention policy"
+    )]
+    Gc,
+
+    /// Report statistics about the context tree
+    #[command(
+        about = "Report document counts and freshness, or with --usage which documents agents read"
+    )]
+    Stats(StatsArgs),
+
+    /// Print version and environment details
+    #[command(about = "Print version, context root, config, document counts and git details")]
+    Info,
+
+    /// Export the context root to the shell
+    #[command(
+        about = "Print shell exports of CONTEXT_ROOT and CONTEXT_PROJECT_ROOT, for eval in scripts and prompts"
+    )]
+    Env(EnvArgs),
+
+    /// Show which context root applies here
+    #[command(
+        about = "Print the context directory resolved from the current directory and why it was chosen"
+    )]
+    Root,
+
+    /// Run an end-to-end self-test
+    #[command(
+        about = "Exercise this binary end-to-end in a temporary project and report environment details"
+    )]
+    Selftest(SelftestArgs),
+
     /// Start the MCP server
     #[command(about = "Start the Context MCP server")]
     Serve(ServeArgs),
+
+    /// Browse the context tree in a web browser
+    #[command(
+        about = "Serve the context tree as a local website that reloads when documents change"
+    )]
+    Preview(PreviewArgs),
 }
@@ -1,26 +1,235 @@
-use crate::core::{find_context_root_from_cwd, Cache};
+use crate::core::cancel::CancellationToken;
+use crate::core::config::CONFIG_FILE_NAME;
+use crate::core::events::{Event, Warnings};
+use crate::core::git;
+use crate::core::hooks;
+use crate::core::import::{self, ImportFormat};
+use crate::core::review;
+use crate::core::search::SearchHit;
+use crate::core::snapshot;
+use crate::core::template::{TemplateDrift, TemplateSource};
+use crate::core::watch;
+use crate::core::{bodies, clock, doctor, presets, sections};
+use crate::core::{
+    find_context_root, find_context_root_from_cwd, resolve_context_root, Cache, ContextRoot, Info,
+    Page, ShellEnv, Status, StatusFilter, Validation,
+};
 use crate::error::{ContextError, Result};
+use crate::mcp::audit::{AuditLog, DEFAULT_KEEP};
+use crate::mcp::limits::Limits;
+use crate::mcp::server::ContextServer;
 
-use super::args::{Cli, Commands, FindArgs, InitArgs, OutputFormat, ServeArgs, StatusArgs, SyncArgs};
+use super::args::{
+    ArchiveArgs, CaptureArgs, ChecklistArgs, Cli, Commands, CoverageArgs, DescribeArgs,
+    DiagramsArgs, DiagramsCommand, DiffDocsArgs, DoctorArgs, EnvArgs, ExplainArgs, FindArgs,
+    GapsArgs, GetArgs, GraphArgs, HooksArgs, HooksCommand, IndexArgs, InitArgs, LintArgs,
+    LogFormat, LsArgs, MergeArgs, MineArgs, NewArgs, OutputFormat, PackArgs, PolicyArgs,
+    PolicyCommand, PreviewArgs, QueryArgs, RefsArgs, RefsCommand, RemoteArgs, RemoteCommand,
+    ResolveArgs, SearchArgs, SearchIndexArgs, SelftestArgs, ServeArgs, ShowArgs, SnapshotArgs,
+    SnapshotCommand, SplitArgs, StatsArgs, StatusArgs, SuggestArgs, SyncArgs, TemplatesArgs,
+    TemplatesCommand, WatchArgs, WhenStaleArgs,
+};
 use super::console;
+use super::selftest;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::LazyLock;
+
+/// Set from the global `--low-memory` flag before a command runs
+static LOW_MEMORY: AtomicBool = AtomicBool::new(false);
+
+/// Set from the global `--log-format json` flag before a command runs
+static LOG_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Interrupted by the first Ctrl-C, so commands stop and report partial results
+static INTERRUPT: LazyLock<CancellationToken> = LazyLock::new(CancellationToken::new);
+
+/// Exit code after an interrupt, as for a process killed by SIGINT
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Set when `--output json` defers warnings to a JSON report at the end
+static OUTPUT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Warnings raised by every cache a command opens
+static WARNINGS: LazyLock<Warnings> = LazyLock::new(Warnings::new);
+
+/// Exit code of an otherwise successful command that raised warnings under
+/// `--deny-warnings`
+pub const WARNINGS_EXIT_CODE: i32 = 4;
+
+/// Exit code of `status <SLUG|PATH>` when the named document doesn't exist,
+/// so scripts can tell it apart from a stale (1) or orphaned (2) one. Other
+/// commands fail with 1 as before.
+pub const DOCUMENT_NOT_FOUND_EXIT_CODE: i32 = 5;
 
 /// Execute a CLI command and return exit code
 pub async fn execute(cli: Cli) -> Result<i32> {
+    LOW_MEMORY.store(cli.low_memory, Ordering::Relaxed);
+    LOG_JSON.store(cli.log_format == LogFormat::Json, Ordering::Relaxed);
+    OUTPUT_JSON.store(matches!(cli.output, OutputFormat::Json), Ordering::Relaxed);
+    clock::fix(match cli.fixed_time {
+        Some(at) => Some(at),
+        None => clock::from_env()?,
+    });
+    // Servers keep the default handling and shut down on Ctrl-C
+    if !matches!(cli.command, Commands::Serve(_) | Commands::Preview(_)) {
+        handle_interrupts();
+    }
+    let (output, deny_warnings) = (cli.output, cli.deny_warnings);
+    let output_file = cli.output_file.clone();
+    if output_file.is_some() {
+        console::capture_output();
+    }
+    let result = run(cli).await;
+    // A failed command leaves any previous report in place, and so does a
+    // lookup of a missing document, which exits with its own code instead of
+    // failing; stale (1) and orphaned (2) statuses still write their report
+    if let (Some(path), Ok(code)) = (&output_file, &result) {
+        if *code != DOCUMENT_NOT_FOUND_EXIT_CODE {
+            console::write_captured(path)?;
+        }
+    }
+
+    let warnings = WARNINGS.to_vec();
+    // Text and NDJSON logs already showed each warning as it happened
+    if matches!(output, OutputFormat::Json) && !LOG_JSON.load(Ordering::Relaxed) {
+        console::print_warnings(&warnings)?;
+    }
+    let code = result?;
+    if deny_warnings && !warnings.is_empty() && code == 0 {
+        if !LOG_JSON.load(Ordering::Relaxed) && matches!(output, OutputFormat::Text) {
+            eprintln!("Error: {} warnings with --deny-warnings", warnings.len());
+        }
+        return Ok(WARNINGS_EXIT_CODE);
+    }
+    Ok(code)
+}
+
+/// Dispatch a parsed command line to its command
+async fn run(cli: Cli) -> Result<i32> {
     match cli.command {
         Commands::Init(args) => init(args).await,
         Commands::Status(args) => status(args, cli.output).await,
         Commands::Sync(args) => sync(args, cli.output).await,
         Commands::Find(args) => find(args, cli.output).await,
+        Commands::Get(args) => get(args, cli.output).await,
+        Commands::Show(args) => show(args, cli.output).await,
+        Commands::Ls(args) => ls(args, cli.output).await,
+        Commands::Search(args) => search(args, cli.output).await,
+        Commands::SearchIndex(args) => search_index(args, cli.output).await,
+        Commands::Split(args) => split(args, cli.output).await,
+        Commands::Merge(args) => merge(args, cli.output).await,
+        Commands::Archive(args) => archive(args, cli.output).await,
+        Commands::Resolve(args) => resolve(args, cli.output).await,
+        Commands::Refs(args) => refs(args, cli.output).await,
+        Commands::Explain(args) => explain(args, cli.output).await,
+        Commands::Describe(args) => describe(args, cli.output).await,
+        Commands::Suggest(args) => suggest(args, cli.output).await,
+        Commands::Gaps(args) => gaps(args, cli.output).await,
+        Commands::Pack(args) => pack(args, cli.output).await,
+        Commands::New(args) => new(args, cli.output).await,
+        Commands::Capture(args) => capture(args, cli.output).await,
+        Commands::DiffDocs(args) => diff_docs(args, cli.output).await,
+        Commands::Checklist(args) => checklist(args, cli.output).await,
+        Commands::Query(args) => query(args, cli.output).await,
+        Commands::Coverage(args) => coverage(args, cli.output).await,
+        Commands::Hooks(args) => hooks(args, cli.output).await,
+        Commands::Watch(args) => watch(args, cli.output).await,
+        Commands::Index(args) => index(args, cli.output).await,
+        Commands::Mine(args) => mine(args, cli.output).await,
+        Commands::WhenStale(args) => when_stale(args, cli.output).await,
+        Commands::Lint(args) => lint(args, cli.output).await,
+        Commands::Doctor(args) => doctor(args, cli.output).await,
+        Commands::Order => order(cli.output).await,
+        Commands::Graph(args) => graph(args, cli.output).await,
+        Commands::Remote(args) => remote(args, cli.output).await,
+        Commands::Policy(args) => policy(args, cli.output).await,
+        Commands::Snapshot(args) => snapshot(args, cli.output).await,
+        Commands::Templates(args) => templates(args, cli.output).await,
+        Commands::Diagrams(args) => diagrams(args, cli.output).await,
+        Commands::I18n => i18n(cli.output).await,
+        Commands::Gc => gc(cli.output).await,
+        Commands::Stats(args) => stats(args, cli.output).await,
+        Commands::Info => info(cli.output).await,
+        Commands::Root => root(cli.output).await,
+        Commands::Env(args) => env(args, cli.output).await,
+        Commands::Selftest(args) => selftest(args, cli.output).await,
         Commands::Serve(args) => serve(args).await,
+        Commands::Preview(args) => preview(args).await,
+    }
+}
+
+/// Turn the first Ctrl-C into an interrupt of the running command; a second
+/// one exits at once
+fn handle_interrupts() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            INTERRUPT.interrupt();
+            let event = Event::Warning {
+                document: None,
+                message: "interrupted; stopping after the current document (Ctrl-C again to abort)"
+                    .to_string(),
+            };
+            if LOG_JSON.load(Ordering::Relaxed) {
+                console::log_event(&event);
+            } else {
+                console::print_warning(&event);
+            }
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        }
+    });
+}
+
+/// Open the cache for a context directory, honouring `--low-memory` and
+/// `--log-format`
+fn open_cache(context_dir: PathBuf) -> Result<Cache> {
+    let mut cache = Cache::create(context_dir)?;
+    cache.set_cancellation(INTERRUPT.clone());
+    cache.set_warnings(WARNINGS.clone());
+    if LOG_JSON.load(Ordering::Relaxed) {
+        cache.subscribe(console::log_event);
+    } else if !OUTPUT_JSON.load(Ordering::Relaxed) {
+        cache.subscribe(console::print_warning);
+    }
+    if LOW_MEMORY.load(Ordering::Relaxed) {
+        cache.set_low_memory(bodies::DEFAULT_CAPACITY);
     }
+    Ok(cache)
 }
 
 /// Initialize a new context cache directory
 #[allow(clippy::unused_async)]
 async fn init(args: InitArgs) -> Result<i32> {
+    if args.list_presets {
+        for preset in presets::PRESETS {
+            outln!("{:<12} {}", preset.name, preset.description);
+        }
+        return Ok(0);
+    }
+
     let context_dir = args.path.join(".context");
-    Cache::init(context_dir)?;
-    println!("Initialized context cache at {}", args.path.display());
+    if let Some(name) = &args.preset {
+        let preset = presets::find(name)
+            .ok_or_else(|| ContextError::TemplateError(format!("unknown preset: {name}")))?;
+        Cache::init_preset(context_dir, preset)?;
+        outln!(
+            "Initialized context cache at {} with preset {name}",
+            args.path.display()
+        );
+    } else if let Some(source) = args.from.as_deref().map(TemplateSource::parse) {
+        Cache::init_from(context_dir, &source)?;
+        outln!(
+            "Initialized context cache at {} from {source}",
+            args.path.display()
+        );
+    } else {
+        Cache::init(context_dir)?;
+        outln!("Initialized context cache at {}", args.path.display());
+    }
     Ok(0)
 }
 
@@ -28,16 +237,44 @@ async fn init(args: InitArgs) -> Result<i32> {
 #[allow(clippy::unused_async)]
 async fn status(args: StatusArgs, output: OutputFormat) -> Result<i32> {
     let context_dir = find_context_root_from_cwd()?;
-    let mut cache = Cache::create(context_dir)?;
-    cache.load()?;
-    let mut statuses = cache.status()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+    let document = match &args.document {
+        Some(slug_or_path) => match cache.get(slug_or_path) {
+            Ok(doc) => Some(doc.path.clone()),
+            Err(e @ ContextError::DocumentNotFound(_)) => {
+                if LOG_JSON.load(Ordering::Relaxed) {
+                    console::log_event(&Event::Error {
+                        document: None,
+                        message: e.to_string(),
+                    });
+                } else {
+                    eprintln!("Error: {e}");
+                }
+                return Ok(DOCUMENT_NOT_FOUND_EXIT_CODE);
+            }
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+    let filter = StatusFilter {
+        directory: args.directory,
+        tag: args.tag,
+        statuses: args.status,
+        paths: args.paths,
+        owners: Vec::new(),
+        states: args.state,
+        document,
+    };
+    let mut statuses = match &args.merge_base {
+        Some(base) => cache.status_since_merge_base(&filter, base)?,
+        None => cache.status_filtered(&filter)?,
+    };
 
     if args.invalid_only {
         statuses.retain(|s| s.status != crate::core::models::Status::Valid);
     }
 
-    console::print_status(output, &statuses)?;
-
     let has_orphaned = statuses
         .iter()
         .any(|s| s.status == crate::core::models::Status::Orphaned);
@@ -45,7 +282,17 @@ async fn status(args: StatusArgs, output: OutputFormat) -> Result<i32> {
         .iter()
         .any(|s| s.status == crate::core::models::Status::Stale);
 
-    if has_orphaned {
+    let shown: Vec<_> = statuses
+        .into_iter()
+        .filter(|s| s.status != crate::core::models::Status::Valid)
+        .collect();
+    let page = Page::new(shown, args.page.offset(), args.page.limit);
+    let partial = INTERRUPT.is_interrupted();
+    console::print_status(output, &page, partial)?;
+
+    if partial {
+        Ok(INTERRUPTED_EXIT_CODE)
+    } else if has_orphaned {
         Ok(2)
     } else {
         Ok(i32::from(has_stale))
@@ -56,19 +303,63 @@ async fn status(args: StatusArgs, output: OutputFormat) -> Result<i32> {
 #[allow(clippy::unused_async)]
 async fn sync(args: SyncArgs, output: OutputFormat) -> Result<i32> {
     let context_dir = find_context_root_from_cwd()?;
-    let mut cache = Cache::create(context_dir)?;
+    let mut cache = open_cache(context_dir.clone())?;
     cache.load()?;
 
+    cache.set_prune_missing(args.prune_missing);
+    cache.set_cleanup(args.cleanup);
+
     let resolved = args
         .path
-        .as_ref()
-        .map(|p| cache.resolve_doc_path(p))
+        .as_deref()
+        .map(|p| cache.sync_target(p))
         .transpose()?;
+    let filter = StatusFilter {
+        directory: args.directory,
+        tag: args.tag,
+        statuses: if args.only_stale {
+            Status::OUT_OF_DATE.to_vec()
+        } else {
+            args.only
+        },
+        ..StatusFilter::default()
+    };
+
+    if args.dry_run {
+        return match cache.sync_plan(resolved.as_deref(), &filter) {
+            Ok(plan) => {
+                console::print_sync_plan(output, &plan)?;
+                Ok(0)
+            }
+            Err(ContextError::InvalidReferences { documents, .. }) => {
+                console::print_invalid_references(output, &documents)?;
+                Ok(1)
+            }
+            Err(e) => Err(e),
+        };
+    }
 
-    match cache.sync(resolved.as_deref()) {
+    let mut reviewer = args.reviewed_by;
+    // Without a named reviewer, a person at a terminal can acknowledge the changes
+    if reviewer.is_none() && cache.config().review.require && std::io::stdin().is_terminal() {
+        let pending = cache.pending_review_matching(resolved.as_deref(), &filter)?;
+        if !pending.is_empty() {
+            if !console::confirm_review(&context_dir, &pending)? {
+                return Ok(1);
+            }
+            let project_root = context_dir.parent().unwrap_or(&context_dir);
+            reviewer = Some(review::local_reviewer(project_root));
+        }
+    }
+
+    match cache.sync_matching(resolved.as_deref(), &filter, reviewer.as_deref()) {
         Ok(result) => {
             console::print_sync(output, &result)?;
-            Ok(i32::from(!result.failed.is_empty()))
+            if result.partial {
+                Ok(INTERRUPTED_EXIT_CODE)
+            } else {
+                Ok(i32::from(!result.failed.is_empty()))
+            }
         }
         Err(ContextError::InvalidReferences { documents, .. }) => {
             console::print_invalid_references(output, &documents)?;
@@ -82,19 +373,18 @@ async fn sync(args: SyncArgs, output: OutputFormat) -> Result<i32> {
 #[allow(clippy::unused_async)]
 async fn find(args: FindArgs, output: OutputFormat) -> Result<i32> {
     let context_dir = find_context_root_from_cwd()?;
-    let mut cache = Cache::create(context_dir)?;
+    let mut cache = open_cache(context_dir)?;
     cache.load()?;
 
     let mut results = Vec::new();
     let mut has_matches = false;
 
-    for path in &args.paths {
-        let path_str = path.display().to_string();
-        let result = cache.find_by_reference(&path_str)?;
+    let paths: Vec<String> = args.paths.iter().map(|p| p.display().to_string()).collect();
+    for result in cache.find_by_references(&paths)? {
         if !result.matches.is_empty() {
             has_matches = true;
         }
-        results.push(result);
+        results.push(result.paginate(args.page.offset(), args.page.limit));
     }
 
     console::print_find(output, &results)?;
@@ -102,15 +392,889 @@ async fn find(args: FindArgs, output: OutputFormat) -> Result<i32> {
     Ok(i32::from(!has_matches))
 }
 
+/// Print a document body, or a single section of it
+#[allow(clippy::unused_async)]
+async fn get(args: GetArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let doc = cache.get_with_body(&args.document)?;
+    let section = match &args.section {
+        Some(heading) => {
+            let index = sections::index(&doc.body);
+            let section = sections::find(&index, heading).cloned().ok_or_else(|| {
+                ContextError::InvalidDocument(format!(
+                    "no section '{heading}' in {}",
+                    doc.path.display()
+                ))
+            })?;
+            Some(section)
+        }
+        None => None,
+    };
+
+    console::print_document(output, &doc, section.as_ref())?;
+    Ok(0)
+}
+
+/// List documents grouped by directory
+#[allow(clippy::unused_async)]
+async fn ls(args: LsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+    let filter = StatusFilter {
+        directory: args.directory,
+        tag: args.tag,
+        states: args.state,
+        ..StatusFilter::default()
+    };
+
+    let documents = cache.list(&filter)?;
+    console::print_ls(output, &documents, args.tree)?;
+    Ok(if INTERRUPT.is_interrupted() {
+        INTERRUPTED_EXIT_CODE
+    } else {
+        0
+    })
+}
+
+/// Print a document with the outcome of validating each reference
+#[allow(clippy::unused_async)]
+async fn show(args: ShowArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let shown = cache.show(&args.document)?;
+    console::print_show(output, &shown)?;
+    Ok(0)
+}
+
+/// Search document bodies, printing each hit as soon as it is found
+#[allow(clippy::unused_async)]
+async fn search(args: SearchArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.set_include_archived(args.include_archived);
+
+    let mut has_matches = false;
+    let hits: Box<dyn Iterator<Item = Result<SearchHit>>> = if args.rank {
+        Box::new(cache.search_ranked(&args.query)?.into_iter().map(Ok))
+    } else {
+        Box::new(cache.search(&args.query))
+    };
+    let hits = hits
+        .filter(|hit| {
+            args.state.is_empty()
+                || hit
+                    .as_ref()
+                    .map_or(true, |hit| args.state.contains(&hit.state))
+        })
+        .skip(args.page.offset())
+        .take(args.page.limit.unwrap_or(usize::MAX));
+
+    for hit in hits {
+        console::print_search_hit(output, &hit?)?;
+        has_matches = true;
+    }
+
+    Ok(i32::from(!has_matches))
+}
+
+/// Export a client-side search index, to stdout or a file
+#[allow(clippy::unused_async)]
+async fn search_index(args: SearchIndexArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.set_include_drafts(args.include_drafts);
+    let index = cache.search_index()?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, serde_json::to_string(&index)?)?;
+            console::print_search_index(output, &index, &path)?;
+        }
+        None => outln!("{}", serde_json::to_string(&index)?),
+    }
+    Ok(0)
+}
+
+/// Split a document into one document per top-level section
+#[allow(clippy::unused_async)]
+async fn split(args: SplitArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let doc_path = cache.get(&args.document)?.path.clone();
+    let plan = cache.split(&doc_path, args.dry_run)?;
+    console::print_split(output, &plan, args.dry_run)?;
+    Ok(0)
+}
+
+/// Merge one document into another
+#[allow(clippy::unused_async)]
+async fn merge(args: MergeArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let into = cache.get(&args.into)?.path.clone();
+    let from = cache.get(&args.from)?.path.clone();
+    let result = cache.merge(&into, &from, args.delete)?;
+    console::print_merge(output, &result)?;
+    Ok(0)
+}
+
+/// Move deprecated documents into the archive
+#[allow(clippy::unused_async)]
+async fn archive(args: ArchiveArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let result = cache.archive(args.older_than, args.dry_run)?;
+    console::print_archive(output, &result)?;
+    Ok(0)
+}
+
+/// Repair documents damaged by git merge conflicts
+#[allow(clippy::unused_async)]
+async fn resolve(args: ResolveArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    // Conflicted documents don't load, so the cache is deliberately not loaded
+    let cache = open_cache(context_dir)?;
+
+    let resolved = args
+        .path
+        .as_ref()
+        .map(|p| cache.resolve_doc_path(p))
+        .transpose()?;
+
+    let result = cache.resolve(resolved.as_deref())?;
+    console::print_resolve(output, &result)?;
+    Ok(i32::from(!result.failed.is_empty()))
+}
+
+/// Manage references directly
+#[allow(clippy::unused_async)]
+async fn refs(args: RefsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    match args.command {
+        RefsCommand::Import(import) => {
+            let declarations = match (import.format, &import.file) {
+                (ImportFormat::Jsonl, Some(file)) if file.as_os_str() != "-" => {
+                    import::parse_jsonl(std::io::BufReader::new(std::fs::File::open(file)?))?
+                }
+                (ImportFormat::Jsonl, _) => import::parse_jsonl(std::io::stdin().lock())?,
+            };
+
+            let result = cache.import_references(&declarations)?;
+            console::print_import(output, &result)?;
+            Ok(i32::from(!result.failed.is_empty()))
+        }
+    }
+}
+
+/// Show everything the context tree knows about a source file
+#[allow(clippy::unused_async)]
+async fn explain(args: ExplainArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let explanation = cache.explain(&args.path.display().to_string())?;
+    console::print_explain(output, &explanation)?;
+    Ok(i32::from(explanation.documents.is_empty()))
+}
+
+/// Suggest (and optionally apply) document descriptions
+#[allow(clippy::unused_async)]
+async fn describe(args: DescribeArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let target = args
+        .document
+        .as_deref()
+        .map(|doc| cache.get(doc).map(|d| d.path.clone()))
+        .transpose()?;
+
+    let candidates = cache.describe(target.as_deref(), args.apply)?;
+    console::print_describe(output, &candidates)?;
+    Ok(0)
+}
+
+/// Suggest references a document is missing
+#[allow(clippy::unused_async)]
+async fn suggest(args: SuggestArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let target = cache.get(&args.document)?.path.clone();
+    let suggestions = cache.suggest(&target, args.limit, args.apply)?;
+    console::print_suggestions(output, &suggestions)?;
+    Ok(0)
+}
+
+/// Rank recently churned files lacking up-to-date documentation
+#[allow(clippy::unused_async)]
+async fn gaps(args: GapsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let mut report = cache.gaps(args.since)?;
+    report.gaps.truncate(args.limit);
+    console::print_gaps(output, &report)?;
+    Ok(0)
+}
+
+/// Bundle the documents relevant to the changes in a revision range
+#[allow(clippy::unused_async)]
+async fn pack(args: PackArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir.clone())?;
+    cache.load()?;
+    cache.set_include_drafts(args.include_drafts);
+
+    let pack = cache.pack_diff(&args.diff, args.hops)?;
+    console::print_pack(output, &pack, context_dir.parent().unwrap_or(&context_dir))?;
+    Ok(0)
+}
+
+/// Draft a new document from the commits in a revision range
+#[allow(clippy::unused_async)]
+async fn capture(args: CaptureArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let capture = cache.capture(
+        &args.from_git,
+        args.slug.as_deref(),
+        args.path.as_deref(),
+        args.dry_run,
+    )?;
+    console::print_capture(output, &capture)?;
+    Ok(0)
+}
+
+/// Scaffold a document and open it in the user's editor
+#[allow(clippy::unused_async)]
+async fn new(args: NewArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    let path = cache.new_document(args.kind, &args.slug)?;
+    console::print_new_document(output, &path, &args.slug)?;
+    if args.no_edit || matches!(output, OutputFormat::Json) {
+        return Ok(0);
+    }
+    let Some(editor) = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+    else {
+        return Ok(0);
+    };
+    // Editors are often configured with arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|e| ContextError::Other(format!("failed to run editor '{editor}': {e}")))?;
+    Ok(i32::from(!status.success()))
+}
+
+/// Report the documents covering a build target
+#[allow(clippy::unused_async)]
+async fn query(args: QueryArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    let query = cache.query_target(&args.files_for_target)?;
+    console::print_target_query(output, &query)?;
+    let has = |status| query.documents.iter().any(|d| d.status == status);
+    Ok(if has(Status::Orphaned) {
+        2
+    } else {
+        i32::from(has(Status::Stale))
+    })
+}
+
+/// Report which project files documents reference, failing below `--min`
+#[allow(clippy::unused_async)]
+async fn coverage(args: CoverageArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+    cache.set_include_drafts(args.include_drafts);
+
+    let coverage = cache.coverage(&args.paths)?;
+    console::print_coverage(output, &coverage)?;
+    Ok(i32::from(
+        args.min.is_some_and(|min| coverage.percent() < min),
+    ))
+}
+
+/// Print the review checklist for a revision range
+#[allow(clippy::unused_async)]
+async fn checklist(args: ChecklistArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    let checklist = cache.checklist(&args.diff)?;
+    let source_url = args
+        .source_url
+        .or_else(|| cache.config().preview.source_url.clone());
+    console::print_checklist(output, &checklist, source_url.as_deref())?;
+    Ok(0)
+}
+
+/// Summarize how the context tree changed between two revisions
+#[allow(clippy::unused_async)]
+async fn diff_docs(args: DiffDocsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let cache = open_cache(context_dir)?;
+
+    let diff = cache.diff_docs(&args.from, args.to.as_deref())?;
+    console::print_docs_diff(output, &diff)?;
+    Ok(0)
+}
+
+/// Validate documents whenever they or their referenced files change,
+/// reporting status transitions until interrupted
+async fn watch(args: WatchArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let project_root = context_dir.parent().unwrap_or(&context_dir).to_path_buf();
+    let debounce = std::time::Duration::from_millis(args.debounce_ms);
+    // Reloading picks up new and edited documents and config
+    let load = || -> Result<Vec<Validation>> {
+        let mut cache = open_cache(context_dir.clone())?;
+        cache.load_metadata()?;
+        cache.status()
+    };
+
+    let (notify, mut changes) = tokio::sync::mpsc::unbounded_channel();
+    let _watcher = watch::watch(&project_root, move || {
+        let _ = notify.send(());
+    })?;
+    let validations = load()?;
+    console::print_watch_started(output, &validations)?;
+    let mut statuses = watch::statuses(&validations);
+
+    // Waits wake up now and then to notice Ctrl-C
+    let tick = std::time::Duration::from_millis(250);
+    while !INTERRUPT.is_interrupted() {
+        if !matches!(
+            tokio::time::timeout(tick, changes.recv()).await,
+            Ok(Some(()))
+        ) {
+            continue;
+        }
+        // Let the burst settle, then validate once for all of it
+        while let Ok(Some(())) = tokio::time::timeout(debounce, changes.recv()).await {}
+        match load() {
+            Ok(validations) => {
+                // An interrupted status covers only some documents
+                if INTERRUPT.is_interrupted() {
+                    break;
+                }
+                console::print_transitions(output, &watch::transitions(&statuses, &validations))?;
+                statuses = watch::statuses(&validations);
+            }
+            Err(ContextError::Cancelled) => break,
+            // A document saved halfway through an edit; try again on the next change
+            Err(e) => console::print_warning(&Event::Warning {
+                document: None,
+                message: e.to_string(),
+            }),
+        }
+    }
+    Ok(0)
+}
+
+/// Install git hooks, or run one on git's behalf
+#[allow(clippy::unused_async)]
+async fn hooks(args: HooksArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let project_root = context_dir.parent().unwrap_or(&context_dir).to_path_buf();
+
+    match args.command {
+        HooksCommand::Install(install) => {
+            let installed = if install.commit_msg {
+                vec![hooks::install_commit_msg(&project_root, install.force)?]
+            } else {
+                Vec::new()
+            };
+            console::print_hooks_installed(output, &installed)?;
+        }
+        HooksCommand::CommitMsg(commit_msg) => {
+            let mut cache = open_cache(context_dir.clone())?;
+            let message = std::fs::read_to_string(&commit_msg.file)?;
+            let kind = hooks::conventional_type(hooks::subject(&message));
+            if kind.is_some_and(|k| cache.config().hooks.skip_types.iter().any(|s| s == k)) {
+                return Ok(0);
+            }
+            cache.load_metadata()?;
+
+            let staged = git::staged_files(&project_root)?;
+            let documents: Vec<String> = cache
+                .documents_referencing(&staged)
+                .iter()
+                .map(|d| {
+                    d.path
+                        .strip_prefix(&project_root)
+                        .unwrap_or(&d.path)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            hooks::add_trailers(&project_root, &commit_msg.file, &documents)?;
+            console::print_commit_reminder(output, &documents)?;
+        }
+    }
+    Ok(0)
+}
+
+/// Show the status of the current user's documents, optionally reviewing
+/// and syncing the stale ones
+#[allow(clippy::unused_async)]
+async fn mine(args: MineArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir.clone())?;
+    cache.load()?;
+
+    let project_root = context_dir.parent().unwrap_or(&context_dir);
+    let mut owners = args.owners;
+    if owners.is_empty() {
+        owners = git::identities(project_root);
+        owners.extend(std::env::var("USER").ok());
+    }
+    if owners.is_empty() {
+        return Err(ContextError::Other(
+            "could not determine who you are from git; pass --as <OWNER>".to_string(),
+        ));
+    }
+    let filter = StatusFilter {
+        owners,
+        ..StatusFilter::default()
+    };
+    let mut statuses = cache.status_filtered(&filter)?;
+
+    if args.fix {
+        let pending: Vec<_> = statuses
+            .iter()
+            .filter(|s| s.status != Status::Valid)
+            .cloned()
+            .collect();
+        if !pending.is_empty() {
+            if !std::io::stdin().is_terminal() {
+                return Err(ContextError::Other(
+                    "--fix needs a terminal; use `context sync --reviewed-by <NAME> <PATH>` instead".to_string(),
+                ));
+            }
+            if !console::confirm_review(&context_dir, &pending)? {
+                return Ok(1);
+            }
+            let reviewer = review::local_reviewer(project_root);
+            for validation in &pending {
+                match cache.sync_reviewed(Some(&validation.path), Some(&reviewer)) {
+                    Ok(_) => {}
+                    Err(ContextError::InvalidReferences { documents, .. }) => {
+                        console::print_invalid_references(output, &documents)?;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            statuses = cache.status_filtered(&filter)?;
+        }
+    }
+
+    console::print_mine(output, &context_dir, &filter.owners, &statuses)?;
+    if statuses.iter().any(|s| s.status == Status::Orphaned) {
+        Ok(2)
+    } else {
+        Ok(i32::from(
+            statuses.iter().any(|s| s.status == Status::Stale),
+        ))
+    }
+}
+
+/// Show when each of a document's references went out of date
+#[allow(clippy::unused_async)]
+async fn when_stale(args: WhenStaleArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    let report = cache.when_stale(&args.document)?;
+    console::print_when_stale(output, &report)?;
+    Ok(0)
+}
+
+/// Update, rebuild or verify the full-text search index
+#[allow(clippy::unused_async)]
+async fn index(args: IndexArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+
+    if args.verify {
+        let verification = cache.verify_index()?;
+        console::print_index_verification(output, &verification)?;
+        return Ok(i32::from(!verification.is_ok()));
+    }
+
+    if matches!(output, OutputFormat::Text) && !LOG_JSON.load(Ordering::Relaxed) {
+        cache.subscribe(console::print_index_progress);
+    }
+    let update = cache.index(args.rebuild)?;
+    console::print_index_update(output, &update)?;
+    Ok(0)
+}
+
+/// Report document quality problems
+#[allow(clippy::unused_async)]
+async fn lint(args: LintArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    if args.metrics {
+        console::print_metrics(output, &cache.metrics())?;
+        return Ok(0);
+    }
+
+    let issues = cache.lint();
+    console::print_lint(output, &issues)?;
+    Ok(i32::from(!issues.is_empty()))
+}
+
+/// Check the tree's structure without loading it, which broken trees fail
+#[allow(clippy::unused_async)]
+async fn doctor(args: DoctorArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let findings = doctor::examine(&context_dir, args.duplicate_threshold / 100.0);
+    console::print_doctor(output, &findings)?;
+    Ok(i32::from(
+        findings
+            .iter()
+            .any(|f| f.severity == doctor::Severity::Error),
+    ))
+}
+
+/// List documents in dependency order
+#[allow(clippy::unused_async)]
+async fn order(output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    console::print_order(output, &cache.dependency_order()?)?;
+    Ok(0)
+}
+
+/// Print the document/source graph
+#[allow(clippy::unused_async)]
+async fn graph(args: GraphArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load()?;
+
+    console::print_graph(output, &cache.graph()?, args.format)?;
+    Ok(0)
+}
+
+/// Fetch, update or check remote context trees
+#[allow(clippy::unused_async)]
+async fn remote(args: RemoteArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+
+    match args.command {
+        RemoteCommand::Fetch(args) => {
+            let fetched = cache.fetch_remotes(args.name.as_deref())?;
+            console::print_fetched(output, &fetched)?;
+        }
+        RemoteCommand::Update(args) => {
+            let updated = cache.update_remotes(args.name.as_deref())?;
+            console::print_updated(output, &updated)?;
+        }
+        RemoteCommand::Status => {
+            cache.load_metadata()?;
+            let statuses = cache.remote_status()?;
+            console::print_remote_status(output, &statuses)?;
+            // Non-zero when anything needs attention, like `status`
+            let clean = statuses
+                .iter()
+                .all(|s| s.up_to_date() && s.shadowed.is_empty());
+            return Ok(i32::from(!clean));
+        }
+    }
+    Ok(0)
+}
+
+/// Create, list or restore snapshots of the context directory
+#[allow(clippy::unused_async)]
+async fn snapshot(args: SnapshotArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+
+    match args.command {
+        SnapshotCommand::Create(args) => {
+            let taken = snapshot::create(&context_dir, args.label.as_deref())?;
+            console::print_snapshots(output, &[taken])?;
+        }
+        SnapshotCommand::List => {
+            console::print_snapshots(output, &snapshot::list(&context_dir)?)?;
+        }
+        SnapshotCommand::Restore(args) => {
+            let restored = snapshot::restore(&context_dir, &args.name)?;
+            console::print_restore(output, &restored)?;
+        }
+    }
+    Ok(0)
+}
+
+/// Check organization rules
+#[allow(clippy::unused_async)]
+async fn policy(args: PolicyArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+
+    match args.command {
+        PolicyCommand::Check => {
+            cache.load()?;
+            let report = cache.check_policy()?;
+            console::print_policy(output, &report)?;
+            Ok(i32::from(report.errors() > 0))
+        }
+    }
+}
+
+/// Compare documents with the templates they were created from
+#[allow(clippy::unused_async)]
+async fn templates(args: TemplatesArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    match args.command {
+        TemplatesCommand::Diff(args) => {
+            let drift = cache.template_drift(&args.document)?;
+            console::print_template_drift(output, &drift)?;
+            Ok(i32::from(
+                !drift.missing.is_empty() || !drift.extra.is_empty(),
+            ))
+        }
+        TemplatesCommand::Status => {
+            let drifts = cache.template_status()?;
+            console::print_template_status(output, &drifts)?;
+            Ok(i32::from(drifts.iter().any(TemplateDrift::outdated)))
+        }
+    }
+}
+
+/// Render the diagrams embedded in documents
+#[allow(clippy::unused_async)]
+async fn diagrams(args: DiagramsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    match args.command {
+        DiagramsCommand::Render(args) => {
+            let rendered = cache.render_diagrams(&args.output)?;
+            console::print_rendered_diagrams(output, &rendered)?;
+            Ok(i32::from(rendered.iter().any(|r| r.error.is_some())))
+        }
+    }
+}
+
+/// Check that every configured locale has an up-to-date translation
+#[allow(clippy::unused_async)]
+async fn i18n(output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+    let report = cache.i18n()?;
+    console::print_i18n(output, &report)?;
+    Ok(i32::from(!report.is_complete()))
+}
+
+/// Prune runtime caches and report how much was reclaimed
+#[allow(clippy::unused_async)]
+async fn gc(output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let cache = open_cache(context_dir)?;
+    console::print_gc(output, &cache.gc()?)?;
+    Ok(0)
+}
+
+/// Report statistics about the context tree
+#[allow(clippy::unused_async)]
+async fn stats(args: StatsArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let mut cache = open_cache(context_dir)?;
+    cache.load_metadata()?;
+
+    if args.usage {
+        console::print_usage(output, &cache.usage()?, args.limit)?;
+    } else if args.debt {
+        console::print_debt(output, &cache.debt(clock::today())?, args.limit)?;
+    } else if args.trend {
+        console::print_trend(output, &cache.trend(args.limit)?)?;
+    } else {
+        console::print_summary(output, &cache.summary()?, args.limit)?;
+    }
+    Ok(0)
+}
+
+/// Report version and environment details, even outside a context repository
+#[allow(clippy::unused_async)]
+async fn info(output: OutputFormat) -> Result<i32> {
+    let cwd = std::env::current_dir()?;
+    let root = find_context_root(&cwd).ok();
+
+    let summary = match &root {
+        Some(root) => {
+            let mut cache = open_cache(root.clone())?;
+            cache.load_metadata()?;
+            Some(cache.summary()?)
+        }
+        None => None,
+    };
+
+    let info = Info {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config: root
+            .as_ref()
+            .map(|r| r.join(CONFIG_FILE_NAME))
+            .filter(|p| p.exists()),
+        git: git::info(root.as_deref().and_then(Path::parent).unwrap_or(&cwd)),
+        root,
+        summary,
+    };
+    console::print_info(output, &info)?;
+    Ok(0)
+}
+
+/// Print the resolved context root and whether a marker pinned it
+#[allow(clippy::unused_async)]
+async fn root(output: OutputFormat) -> Result<i32> {
+    let cwd = std::env::current_dir()?;
+    let root = resolve_context_root(&cwd)?;
+    console::print_root(output, &root)?;
+    Ok(0)
+}
+
+/// Print shell exports for the resolved context root
+#[allow(clippy::unused_async)]
+async fn env(args: EnvArgs, output: OutputFormat) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let prompt = if args.prompt {
+        let mut cache = open_cache(context_dir.clone())?;
+        cache.load_metadata()?;
+        Some(cache.summary()?.health())
+    } else {
+        None
+    };
+    let env = ShellEnv {
+        project_root: context_dir
+            .parent()
+            .map_or_else(|| context_dir.clone(), Path::to_path_buf),
+        context_root: context_dir,
+        prompt,
+    };
+    console::print_env(output, args.shell, &env)?;
+    Ok(0)
+}
+
+/// Run the end-to-end self-test against this binary
+#[allow(clippy::unused_async)]
+async fn selftest(args: SelftestArgs, output: OutputFormat) -> Result<i32> {
+    let binary = std::env::current_exe()?;
+    let report = selftest::run(&binary, args.keep)?;
+    console::print_selftest(output, &report)?;
+    Ok(i32::from(!report.passed()))
+}
+
 /// Start the MCP server
 #[allow(clippy::unused_async)]
-async fn serve(_args: ServeArgs) -> Result<i32> {
-    crate::mcp::server::run_server()
+async fn serve(args: ServeArgs) -> Result<i32> {
+    let mut server = ContextServer::new().with_limits(Limits {
+        max_concurrent: args.max_concurrent,
+        calls_per_minute: args.calls_per_minute,
+        max_response_bytes: args.max_response_bytes,
+    });
+    if let Some(path) = args.audit_log {
+        server = server.with_audit_log(AuditLog::new(path, args.audit_max_bytes, DEFAULT_KEEP));
+    }
+    if args.roots.is_empty() {
+        if let Ok(root) = std::env::current_dir()
+            .map_err(ContextError::from)
+            .and_then(|cwd| resolve_context_root(&cwd))
+        {
+            server = server.with_root(root_name(&root), root);
+        }
+    } else {
+        let mut names: HashMap<String, PathBuf> = HashMap::new();
+        for dir in &args.roots {
+            let root = resolve_context_root(dir)?;
+            let name = root_name(&root);
+            if let Some(other) = names.insert(name.clone(), dir.clone()) {
+                return Err(ContextError::ConfigError(format!(
+                    "roots {} and {} are both named '{name}'",
+                    other.display(),
+                    dir.display()
+                )));
+            }
+            server = server.with_root(name, root);
+        }
+    }
+
+    crate::mcp::server::run_server(server)
         .await
         .map_err(|e| ContextError::Other(e.to_string()))?;
     Ok(0)
 }
 
+/// Name a served root after its project directory
+fn root_name(root: &ContextRoot) -> String {
+    root.context_dir
+        .parent()
+        .and_then(Path::file_name)
+        .map_or_else(
+            || root.context_dir.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        )
+}
+
+/// Serve the context tree as a local website
+async fn preview(args: PreviewArgs) -> Result<i32> {
+    let context_dir = find_context_root_from_cwd()?;
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", args.port)).await?;
+    outln!(
+        "Serving {} at http://{}/ (press Ctrl-C to stop)",
+        context_dir.display(),
+        listener.local_addr()?
+    );
+    crate::preview::server::run(listener, context_dir).await?;
+    Ok(0)
+}
+
 /// Map exit codes for different scenarios
 #[must_use]
 pub fn map_exit_code(success: bool, error: Option<&ContextError>) -> i32 {
@@ -121,6 +1285,7 @@ pub fn map_exit_code(success: bool, error: Option<&ContextError>) -> i32 {
     match error {
         Some(ContextError::NotARepository) => 128,
         Some(ContextError::NotInitialized(_)) => 3,
+        Some(ContextError::Cancelled) => INTERRUPTED_EXIT_CODE,
         _ => 1,
     }
 }
@@ -1,49 +1,157 @@
-use crate::core::models::{FindResult, Status, SyncResult, Validation};
+use super::args::{OutputFormat, Shell};
+use super::selftest::Report;
+use crate::core::archive::ArchiveResult;
+use crate::core::capture::Capture;
+use crate::core::checklist::{self, Checklist};
+use crate::core::clock;
+use crate::core::coverage::Coverage;
+use crate::core::debt::DebtReport;
+use crate::core::describe::Candidate;
+use crate::core::diagrams::RenderedDiagram;
+use crate::core::docdiff::{Change, DocsDiff};
+use crate::core::doctor::Finding;
+use crate::core::document::{self, Document};
+use crate::core::drift::StaleReport;
+use crate::core::events::{Event, ReferenceOutcome, Warning};
+use crate::core::gaps::{GapKind, GapReport};
+use crate::core::gc::GcReport;
+use crate::core::graph::{Graph, GraphFormat};
+use crate::core::hooks;
+use crate::core::i18n::I18nReport;
+use crate::core::lint::LintIssue;
+use crate::core::merge::MergeResult;
+use crate::core::models::{
+    DocumentState, Explanation, FindResult, ImportResult, Info, ListedDocument, Page,
+    ResolveResult, ShellEnv, ShownDocument, Status, Summary, SyncPlan, SyncResult, Validation,
+};
+use crate::core::order::DependencyOrder;
+use crate::core::pack::Pack;
+use crate::core::policy::{PolicyReport, Severity};
+use crate::core::query::TargetQuery;
+use crate::core::readability::DocumentMetrics;
+use crate::core::remote::{Fetched, RemoteStatus, Updated};
+use crate::core::search::SearchHit;
+use crate::core::search_index::SearchIndex;
+use crate::core::sections::{self, Section};
+use crate::core::snapshot::{Restored, Snapshot};
+use crate::core::split::SplitPlan;
+use crate::core::storage::{IndexUpdate, IndexVerification};
+use crate::core::suggest::Suggestions;
+use crate::core::template::TemplateDrift;
+use crate::core::trend::{self, Point};
+use crate::core::usage::{DocumentUsage, UsageReport};
+use crate::core::watch::Transition;
+use crate::core::{ContextRoot, CONTEXT_DIR_NAME};
 use crate::error::{ContextError, InvalidReference, Result};
 use serde_json::json;
-use std::path::PathBuf;
-use super::args::OutputFormat;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
 
 /// Print document status
-pub fn print_status(format: OutputFormat, statuses: &[Validation]) -> Result<()> {
+pub fn print_status(format: OutputFormat, page: &Page<Validation>, partial: bool) -> Result<()> {
     match format {
         OutputFormat::Text => {
-            for status in statuses {
-                if status.status != Status::Valid {
-                    println!("modified:  {}", status.path.display());
+            if partial {
+                outln!("Interrupted: partial results, not every document was checked");
+            }
+            for status in &page.items {
+                outln!("modified:  {}", status.path.display());
+                for section in &status.sections {
+                    let references: Vec<&str> = section
+                        .changed
+                        .iter()
+                        .chain(&section.missing)
+                        .map(String::as_str)
+                        .collect();
+                    outln!(
+                        "  section '{}' is {}: {}",
+                        section.title,
+                        section.status,
+                        references.join(", ")
+                    );
+                }
+                for unreadable in &status.unreadable {
+                    outln!(
+                        "  unreadable: {} ({})",
+                        unreadable.reference,
+                        unreadable.error
+                    );
                 }
             }
+            print_page_footer(page);
         }
         OutputFormat::Json => {
-            let json_statuses: Vec<_> = statuses
+            let json_statuses: Vec<_> = page
+                .items
                 .iter()
-                .filter(|s| s.status != Status::Valid)
                 .map(|s| {
                     json!({
                         "path": s.path.display().to_string(),
                         "status": s.status.to_string(),
                         "changed": s.changed,
                         "missing": s.missing,
+                        "sections": s.sections,
+                        "unreadable": s.unreadable,
                     })
                 })
                 .collect();
-            println!("{}", serde_json::to_string_pretty(&json_statuses)?);
+            let output = if page.limit.is_some() || partial {
+                let mut output = json!({
+                    "total": page.total,
+                    "offset": page.offset,
+                    "limit": page.limit,
+                    "items": json_statuses,
+                });
+                if partial {
+                    output["partial"] = json!(true);
+                }
+                output
+            } else {
+                json!(json_statuses)
+            };
+            outln!("{}", serde_json::to_string_pretty(&output)?);
         }
     }
     Ok(())
 }
 
+/// Print a "Showing X-Y of Z" line for paginated text output
+fn print_page_footer<T>(page: &Page<T>) {
+    if page.limit.is_some() && !page.items.is_empty() {
+        outln!(
+            "\nShowing {}-{} of {}",
+            page.offset + 1,
+            page.offset + page.items.len(),
+            page.total
+        );
+    }
+}
+
 /// Print find results
 pub fn print_find(format: OutputFormat, results: &[FindResult]) -> Result<()> {
     match format {
         OutputFormat::Text => {
             for result in results {
-                if result.matches.is_empty() {
-                    println!("{}: no references found", result.query);
+                if result.total == 0 {
+                    outln!("{}: no references found", result.query);
                 } else {
-                    println!("{}:", result.query);
+                    outln!("{}:", result.query);
                     for m in &result.matches {
-                        println!("  {} ({})", m.document.display(), m.status);
+                        let reference = match m.reference_status {
+                            Status::Valid => "unchanged",
+                            Status::Stale => "changed",
+                            Status::Orphaned => "missing",
+                        };
+                        outln!(
+                            "  {} ({}, reference {reference})",
+                            deep_link(&m.document, m.line, m.anchor.as_deref()),
+                            m.status
+                        );
+                    }
+                    if result.matches.len() < result.total {
+                        outln!("  ... {} of {} shown", result.matches.len(), result.total);
                     }
                 }
             }
@@ -54,113 +162,2063 @@ pub fn print_find(format: OutputFormat, results: &[FindResult]) -> Result<()> {
                 .map(|r| {
                     json!({
                         "query": r.query,
+                        "total": r.total,
                         "matches": r.matches.iter().map(|m| {
                             json!({
                                 "document": m.document.display().to_string(),
                                 "reference": m.reference,
                                 "status": m.status.to_string(),
+                                "reference_status": m.reference_status.to_string(),
+                                "line": m.line,
+                                "anchor": m.anchor,
                             })
                         }).collect::<Vec<_>>(),
                     })
                 })
                 .collect();
-            println!("{}", serde_json::to_string_pretty(&json_results)?);
+            outln!("{}", serde_json::to_string_pretty(&json_results)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print a document body, or just one section of it
+pub fn print_document(
+    format: OutputFormat,
+    doc: &Document,
+    section: Option<&Section>,
+) -> Result<()> {
+    let text = section.map_or_else(|| doc.body.clone(), |s| sections::extract(&doc.body, s));
+    match format {
+        OutputFormat::Text => out!("{text}"),
+        OutputFormat::Json => {
+            let json_doc = json!({
+                "path": doc.path.display().to_string(),
+                "slug": doc.slug,
+                "namespace": doc.namespace(),
+                "description": doc.description,
+                "section": section.map(|s| json!({
+                    "breadcrumb": s.breadcrumb,
+                    "start_line": s.start_line,
+                    "end_line": s.end_line,
+                })),
+                "body": text,
+            });
+            outln!("{}", serde_json::to_string_pretty(&json_doc)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print documents grouped by directory, or as a tree
+pub fn print_ls(format: OutputFormat, documents: &[ListedDocument], tree: bool) -> Result<()> {
+    match format {
+        OutputFormat::Text if tree => {
+            let mut root = TreeNode::default();
+            for doc in documents {
+                let mut node = &mut root;
+                for component in &doc.path {
+                    node = node
+                        .children
+                        .entry(component.to_string_lossy().into_owned())
+                        .or_default();
+                }
+                node.document = Some(doc);
+            }
+            outln!("{}", CONTEXT_DIR_NAME);
+            for line in root.lines("") {
+                outln!("{line}");
+            }
+        }
+        OutputFormat::Text => {
+            let width = documents
+                .iter()
+                .map(|d| d.slug.chars().count())
+                .max()
+                .unwrap_or(0);
+            let mut groups: BTreeMap<String, Vec<&ListedDocument>> = BTreeMap::new();
+            for doc in documents {
+                let dir = doc
+                    .path
+                    .parent()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                groups
+                    .entry(if dir.is_empty() { ".".to_string() } else { dir })
+                    .or_default()
+                    .push(doc);
+            }
+            for (dir, docs) in groups {
+                outln!("{dir}/");
+                for doc in docs {
+                    outln!("  {:<width$}  {}", doc.slug, listing_summary(doc));
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(documents)?);
+        }
+    }
+    Ok(())
+}
+
+/// Status badge, reference count and description of a listed document
+fn listing_summary(doc: &ListedDocument) -> String {
+    let mut summary = format!(
+        "[{}] {} ref{}",
+        doc.status,
+        doc.references,
+        if doc.references == 1 { "" } else { "s" }
+    );
+    if doc.state != DocumentState::Published {
+        summary.push_str(" (");
+        summary.push_str(&doc.state.to_string());
+        summary.push(')');
+    }
+    if !doc.description.is_empty() {
+        summary.push_str(" - ");
+        summary.push_str(&doc.description);
+    }
+    summary
+}
+
+/// A directory or document in `context ls --tree`
+#[derive(Default)]
+struct TreeNode<'a> {
+    children: BTreeMap<String, TreeNode<'a>>,
+    document: Option<&'a ListedDocument>,
+}
+
+impl TreeNode<'_> {
+    /// The lines drawing this node's children, each prefixed with `indent`
+    fn lines(&self, indent: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        let count = self.children.len();
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let label = match child.document {
+                Some(doc) => format!("{name}  {} {}", doc.slug, listing_summary(doc)),
+                None => name.clone(),
+            };
+            lines.push(format!(
+                "{indent}{}{label}",
+                if last { "└── " } else { "├── " }
+            ));
+            lines.extend(child.lines(&format!("{indent}{}", if last { "    " } else { "│   " })));
+        }
+        lines
+    }
+}
+
+/// Print a document with each reference annotated with its validity
+pub fn print_show(format: OutputFormat, shown: &ShownDocument) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "{} ({}) - {}, updated {}",
+                shown.path.display(),
+                shown.slug,
+                shown.status,
+                shown.updated
+            );
+            if !shown.description.is_empty() {
+                outln!("{}", shown.description);
+            }
+            outln!();
+            let labels = shown
+                .references
+                .iter()
+                .map(|(reference, outcome)| {
+                    let label = match outcome {
+                        ReferenceOutcome::Unchanged => "valid",
+                        ReferenceOutcome::Changed => "stale",
+                        ReferenceOutcome::Missing => "missing",
+                        ReferenceOutcome::Unreadable => "unreadable",
+                    };
+                    (reference.clone(), label)
+                })
+                .collect();
+            out!("{}", document::annotate_references(&shown.body, &labels));
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(shown)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print a single search hit.
+///
+/// Hits are printed one at a time as they are found, so JSON output is
+/// newline-delimited (one object per line) rather than a single array.
+pub fn print_search_hit(format: OutputFormat, hit: &SearchHit) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            // Full-text hits can match on the slug or description alone
+            if hit.matches.is_empty() {
+                outln!("{}", deep_link(&hit.document, None, None));
+            }
+            for m in &hit.matches {
+                let link = deep_link(&hit.document, Some(m.line), m.anchor.as_deref());
+                if m.breadcrumb.is_empty() {
+                    outln!("{link}: {}", m.text.trim());
+                } else {
+                    outln!("{link} ({}): {}", m.breadcrumb.join(" > "), m.text.trim());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string(hit)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print where a search index was written
+pub fn print_search_index(format: OutputFormat, index: &SearchIndex, path: &Path) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "Indexed {} documents ({} terms) into {}",
+                index.documents.len(),
+                index.terms.len(),
+                path.display()
+            );
+        }
+        OutputFormat::Json => {
+            let json = json!({
+                "path": path.display().to_string(),
+                "documents": index.documents.len(),
+                "terms": index.terms.len(),
+            });
+            outln!("{}", serde_json::to_string_pretty(&json)?);
         }
     }
     Ok(())
 }
 
+/// Format a `path:line#anchor` deep link into a document
+fn deep_link(path: &Path, line: Option<usize>, anchor: Option<&str>) -> String {
+    let line = line.map(|l| format!(":{l}")).unwrap_or_default();
+    let anchor = anchor.map(|a| format!("#{a}")).unwrap_or_default();
+    format!("{}{line}{anchor}", path.display())
+}
+
 /// Print sync results
 pub fn print_sync(format: OutputFormat, result: &SyncResult) -> Result<()> {
     match format {
         OutputFormat::Text => {
-            println!("Synced {} documents", result.count);
+            if result.partial {
+                outln!("Interrupted: partial results, remaining documents were not synced");
+            }
+            outln!(
+                "Synced {} documents ({} updated, {} unchanged)",
+                result.count,
+                result.updated.len(),
+                result.unchanged.len()
+            );
             if !result.updated.is_empty() {
-                println!("Updated:");
+                outln!("Updated:");
                 for path in &result.updated {
-                    println!("  {}", path.display());
+                    outln!("  {}", path.display());
                 }
             }
             if !result.failed.is_empty() {
-                println!("Failed:");
+                outln!("Failed:");
                 for error in &result.failed {
-                    println!("  {error}");
+                    outln!("  {error}");
+                }
+            }
+            if result.skipped > 0 {
+                outln!(
+                    "Skipped {} documents not matching the status filter",
+                    result.skipped
+                );
+            }
+            if !result.protected.is_empty() {
+                outln!("Skipped protected (sync each by path):");
+                for path in &result.protected {
+                    outln!("  {}", path.display());
+                }
+            }
+            if !result.pruned.is_empty() {
+                outln!("Pruned missing references (reword their mentions in the body):");
+                for (path, references) in &result.pruned {
+                    outln!("  {}: {}", path.display(), references.join(", "));
+                }
+            }
+            if !result.cleaned_references.is_empty() {
+                outln!("Cleaned references no longer mentioned:");
+                for (path, references) in &result.cleaned_references {
+                    outln!("  {}: {}", path.display(), references.join(", "));
+                }
+            }
+            if !result.cleaned_artifacts.is_empty() {
+                outln!("Cleaned stored data of deleted documents:");
+                for path in &result.cleaned_artifacts {
+                    outln!("  {}", path.display());
                 }
             }
         }
         OutputFormat::Json => {
+            let by_document = |map: &'_ BTreeMap<std::path::PathBuf, Vec<String>>| -> BTreeMap<String, Vec<String>> {
+                map.iter().map(|(p, refs)| (p.display().to_string(), refs.clone())).collect()
+            };
             let json_result = json!({
                 "count": result.count,
                 "updated": result.updated.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+                "unchanged": result.unchanged.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
                 "failed": result.failed,
+                "protected": result.protected.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+                "partial": result.partial,
+                "skipped": result.skipped,
+                "pruned": by_document(&result.pruned),
+                "cleaned_references": by_document(&result.cleaned_references),
+                "cleaned_artifacts": result.cleaned_artifacts.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
             });
-            println!("{}", serde_json::to_string_pretty(&json_result)?);
+            outln!("{}", serde_json::to_string_pretty(&json_result)?);
         }
     }
     Ok(())
 }
 
-/// Format a simple message
-pub fn format_message(format: OutputFormat, message: &str) -> String {
+/// Print what a sync would change, document by document
+pub fn print_sync_plan(format: OutputFormat, plan: &SyncPlan) -> Result<()> {
     match format {
-        OutputFormat::Text => message.to_string(),
-        OutputFormat::Json => serde_json::to_string(&json!({"message": message})).unwrap_or_default(),
+        OutputFormat::Text => {
+            outln!(
+                "Would sync {} documents ({} updated, {} unchanged)",
+                plan.changes.len() + plan.unchanged.len(),
+                plan.changes.len(),
+                plan.unchanged.len()
+            );
+            for change in &plan.changes {
+                outln!("{}", change.path.display());
+                for reference in &change.added {
+                    outln!("  + {reference}");
+                }
+                for reference in &change.removed {
+                    outln!("  - {reference}");
+                }
+                for (reference, hash) in &change.rehashed {
+                    outln!("  ~ {reference} ({} -> {})", hash.from, hash.to);
+                }
+                if let Some(hash) = &change.hash {
+                    outln!("  hash: {} -> {}", hash.from, hash.to);
+                }
+                if let Some(hash) = &change.body_hash {
+                    outln!("  body hash: {} -> {}", hash.from, hash.to);
+                }
+                if let Some(updated) = &change.updated {
+                    outln!("  updated: {updated}");
+                }
+            }
+            if !plan.protected.is_empty() {
+                outln!("Would skip protected (sync each by path):");
+                for path in &plan.protected {
+                    outln!("  {}", path.display());
+                }
+            }
+            if !plan.cleaned_references.is_empty() {
+                outln!("Would clean references no longer mentioned:");
+                for (path, references) in &plan.cleaned_references {
+                    outln!("  {}: {}", path.display(), references.join(", "));
+                }
+            }
+            if !plan.cleaned_artifacts.is_empty() {
+                outln!("Would clean stored data of deleted documents:");
+                for path in &plan.cleaned_artifacts {
+                    outln!("  {}", path.display());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(plan)?);
+        }
     }
+    Ok(())
 }
 
-/// Format an error message
-pub fn format_error(format: OutputFormat, error: &str) -> String {
+/// Print the documents produced (or planned) by a split
+pub fn print_split(format: OutputFormat, plan: &SplitPlan, dry_run: bool) -> Result<()> {
     match format {
-        OutputFormat::Text => format!("Error: {error}"),
-        OutputFormat::Json => serde_json::to_string(&json!({"error": error})).unwrap_or_default(),
+        OutputFormat::Text => {
+            let verb = if dry_run { "Would create" } else { "Created" };
+            for piece in &plan.pieces {
+                outln!(
+                    "{verb} {} ({} references)",
+                    piece.path.display(),
+                    piece.references.len()
+                );
+            }
+            let verb = if dry_run { "Would update" } else { "Updated" };
+            outln!("{verb} {}", plan.remainder.path.display());
+        }
+        OutputFormat::Json => {
+            let json_result = json!({
+                "dry_run": dry_run,
+                "updated": plan.remainder.path.display().to_string(),
+                "created": plan.pieces.iter().map(|p| json!({
+                    "path": p.path.display().to_string(),
+                    "slug": p.slug,
+                    "references": p.references.keys().collect::<BTreeSet<_>>(),
+                })).collect::<Vec<_>>(),
+            });
+            outln!("{}", serde_json::to_string_pretty(&json_result)?);
+        }
     }
+    Ok(())
 }
 
-/// Print invalid references error
-pub fn print_invalid_references(
-    format: OutputFormat,
-    documents: &[(PathBuf, Vec<InvalidReference>)],
-) -> Result<()> {
+/// Print everything documented about a source file
+pub fn print_explain(format: OutputFormat, explanation: &Explanation) -> Result<()> {
     match format {
         OutputFormat::Text => {
-            eprintln!(
-                "Error: Invalid references in {} document(s)",
-                documents.len()
-            );
-            eprintln!();
-            for (doc_path, invalid_refs) in documents {
-                eprintln!("  {}", doc_path.display());
-                for inv in invalid_refs {
-                    eprintln!("    - `{}`: {}", inv.path, inv.reason);
+            let missing = if explanation.exists { "" } else { " (missing)" };
+            outln!("{}{missing}", explanation.source);
+            if explanation.documents.is_empty() {
+                outln!("  Not referenced by any document");
+            }
+            for doc in &explanation.documents {
+                outln!();
+                let changed = if doc.changed {
+                    ", changed since sync"
+                } else {
+                    ""
+                };
+                outln!("{} [{}{changed}]", doc.document.display(), doc.status);
+                if !doc.description.is_empty() {
+                    outln!("  {}", doc.description);
+                }
+                if !doc.owners.is_empty() {
+                    outln!("  owners: {}", doc.owners.join(", "));
+                }
+                if !doc.updated.is_empty() {
+                    outln!("  updated: {}", doc.updated);
+                }
+                if let Some(excerpt) = &doc.excerpt {
+                    if let Some(line) = doc.line {
+                        outln!("  line {line}:");
+                    }
+                    for text in excerpt.lines() {
+                        outln!("    {text}");
+                    }
                 }
             }
         }
         OutputFormat::Json => {
-            let json_docs: Vec<_> = documents
+            outln!("{}", serde_json::to_string_pretty(explanation)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print generated description candidates
+pub fn print_describe(format: OutputFormat, candidates: &[Candidate]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if candidates.is_empty() {
+                outln!("No documents need a description");
+            }
+            for c in candidates {
+                let verb = if c.applied { "Applied" } else { "Suggested" };
+                match &c.candidate {
+                    Some(description) => outln!("{}\n  {verb}: {description}", c.path.display()),
+                    None => outln!("{}\n  No description could be derived", c.path.display()),
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json_result: Vec<_> = candidates
                 .iter()
-                .map(|(path, refs)| {
+                .map(|c| {
                     json!({
-                        "document": path.display().to_string(),
-                        "invalid": refs.iter().map(|r| {
-                            json!({
-                                "path": r.path,
-                                "reason": r.reason.to_string(),
-                            })
-                        }).collect::<Vec<_>>(),
+                        "path": c.path.display().to_string(),
+                        "current": c.current,
+                        "candidate": c.candidate,
+                        "applied": c.applied,
                     })
                 })
                 .collect();
-            let output = json!({
-                "error": "invalid_references",
-                "count": documents.len(),
-                "documents": json_docs,
-            });
-            eprintln!("{}", serde_json::to_string_pretty(&output)?);
+            outln!("{}", serde_json::to_string_pretty(&json_result)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print lint issues
+pub fn print_lint(format: OutputFormat, issues: &[LintIssue]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if issues.is_empty() {
+                outln!("No issues found");
+            }
+            for issue in issues {
+                outln!(
+                    "{}: [{}] {}",
+                    issue.path.display(),
+                    issue.rule,
+                    issue.message
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(issues)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print per-document length and readability metrics as a table
+pub fn print_metrics(format: OutputFormat, documents: &[DocumentMetrics]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if documents.is_empty() {
+                outln!("No documents found");
+                return Ok(());
+            }
+            outln!(
+                "{:>6}  {:>8}  {:>7}  {:>5}  DOCUMENT",
+                "WORDS",
+                "AVG SENT",
+                "PER HDG",
+                "CODE"
+            );
+            for doc in documents {
+                let m = &doc.metrics;
+                outln!(
+                    "{:>6}  {:>8.1}  {:>7.0}  {:>4.0}%  {}",
+                    m.words,
+                    m.sentence_words,
+                    m.words_per_heading,
+                    m.code_ratio * 100.0,
+                    doc.path.display()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(documents)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print structural findings, one per line
+pub fn print_doctor(format: OutputFormat, findings: &[Finding]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if findings.is_empty() {
+                outln!("No problems found");
+            }
+            for finding in findings {
+                outln!(
+                    "{:<7}  {}: [{}] {}",
+                    finding.severity.to_string(),
+                    finding.path.display(),
+                    finding.code,
+                    finding.message
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(findings)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print uncovered files and the coverage percentage
+pub fn print_coverage(format: OutputFormat, coverage: &Coverage) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for file in &coverage.uncovered {
+                outln!("uncovered  {file}");
+            }
+            outln!(
+                "Coverage: {}/{} files ({:.1}%)",
+                coverage.covered.len(),
+                coverage.files(),
+                coverage.percent()
+            );
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(coverage)?;
+            value["percent"] = serde_json::json!(coverage.percent());
+            outln!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the document graph rendered in `graph_format`, or its nodes and
+/// edges as JSON
+pub fn print_graph(format: OutputFormat, graph: &Graph, graph_format: GraphFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            out!("{}", graph.render(graph_format));
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(graph)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print documents in dependency order, with link cycles on stderr
+pub fn print_order(format: OutputFormat, order: &DependencyOrder) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for path in &order.order {
+                outln!("{}", path.display());
+            }
+            for cycle in &order.cycles {
+                let members: Vec<_> = cycle.iter().map(|p| p.display().to_string()).collect();
+                eprintln!("Warning: link cycle between {}", members.join(", "));
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(order)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the remotes that were fetched
+pub fn print_fetched(format: OutputFormat, fetched: &[Fetched]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if fetched.is_empty() {
+                outln!("No remotes configured");
+            }
+            for remote in fetched {
+                outln!(
+                    "Fetched {} ({} documents) into {}",
+                    remote.name,
+                    remote.documents,
+                    remote.context_dir.display()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(fetched)?);
         }
     }
     Ok(())
 }
 
+/// Print the remotes that were updated
+pub fn print_updated(format: OutputFormat, updated: &[Updated]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if updated.is_empty() {
+                outln!("No remotes configured");
+            }
+            for remote in updated {
+                let to = short_commit(&remote.to);
+                match remote.from.as_deref().map(short_commit) {
+                    Some(from) if from == to => {
+                        outln!("{} already up to date at {to}", remote.name);
+                    }
+                    Some(from) => outln!(
+                        "Updated {} {from}..{to} ({} documents)",
+                        remote.name,
+                        remote.documents
+                    ),
+                    None => outln!(
+                        "Fetched {} at {to} ({} documents)",
+                        remote.name,
+                        remote.documents
+                    ),
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(updated)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print how each remote compares with upstream, with any shadowed slugs
+pub fn print_remote_status(format: OutputFormat, statuses: &[RemoteStatus]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if statuses.is_empty() {
+                outln!("No remotes configured");
+            }
+            for status in statuses {
+                let state = match (status.commit.as_deref(), status.upstream.as_deref()) {
+                    (None, _) => "not fetched".to_string(),
+                    (Some(commit), None) => format!(
+                        "at {}, upstream unreachable: {}",
+                        short_commit(commit),
+                        status.error.as_deref().unwrap_or("unknown error")
+                    ),
+                    (Some(commit), Some(_)) if status.up_to_date() => {
+                        format!("up to date at {}", short_commit(commit))
+                    }
+                    (Some(commit), Some(upstream)) => {
+                        format!(
+                            "behind: {} -> {}",
+                            short_commit(commit),
+                            short_commit(upstream)
+                        )
+                    }
+                };
+                outln!("{}: {state}", status.name);
+                for shadow in &status.shadowed {
+                    outln!(
+                        "  conflict: '{}' in {} shadows {}",
+                        shadow.slug,
+                        shadow.local.display(),
+                        shadow.remote.display()
+                    );
+                }
+            }
+            if statuses
+                .iter()
+                .any(|s| s.commit.is_none() || (s.upstream.is_some() && !s.up_to_date()))
+            {
+                outln!("\nRun `context remote update` to pull the latest shared documents");
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(statuses)?);
+        }
+    }
+    Ok(())
+}
+
+fn short_commit(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
+
+/// Print snapshots, one per line
+pub fn print_snapshots(format: OutputFormat, snapshots: &[Snapshot]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if snapshots.is_empty() {
+                outln!("No snapshots");
+            }
+            for snapshot in snapshots {
+                outln!(
+                    "{}  {} bytes  {}",
+                    snapshot.name,
+                    snapshot.bytes,
+                    snapshot.path.display()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(snapshots)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print what garbage collection removed
+pub fn print_gc(format: OutputFormat, report: &GcReport) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for path in &report.removed {
+                outln!("removed: {}", path.display());
+            }
+            outln!(
+                "Reclaimed {} bytes from {} entries",
+                report.reclaimed,
+                report.removed.len()
+            );
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the outcome of restoring a snapshot
+pub fn print_restore(format: OutputFormat, restored: &Restored) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!("Restored snapshot {}", restored.restored.name);
+            outln!("Previous state saved as {}", restored.backup.name);
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(restored)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the outcome of a reference import
+pub fn print_import(format: OutputFormat, result: &ImportResult) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "Imported {} references into {} documents",
+                result.imported,
+                result.updated.len()
+            );
+            if !result.updated.is_empty() {
+                outln!("Updated:");
+                for path in &result.updated {
+                    outln!("  {}", path.display());
+                }
+            }
+            if !result.failed.is_empty() {
+                outln!("Failed:");
+                for error in &result.failed {
+                    outln!("  {error}");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(result)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the outcome of resolving merge conflicts
+pub fn print_resolve(format: OutputFormat, result: &ResolveResult) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if result.resolved.is_empty() && result.failed.is_empty() {
+                outln!("No merge conflicts found");
+            }
+            if !result.resolved.is_empty() {
+                outln!("Resolved:");
+                for path in &result.resolved {
+                    outln!("  {}", path.display());
+                }
+            }
+            if !result.failed.is_empty() {
+                outln!("Needs manual resolution:");
+                for error in &result.failed {
+                    outln!("  {error}");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json_result = json!({
+                "resolved": result.resolved.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+                "failed": result.failed,
+            });
+            outln!("{}", serde_json::to_string_pretty(&json_result)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print document counts by category and freshness
+pub fn print_summary(format: OutputFormat, summary: &Summary, limit: usize) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            print_summary_text(summary);
+            outln!(
+                "references: {} ({:.1} per document)",
+                summary.references,
+                summary.average_references()
+            );
+            if let Some(oldest) = &summary.oldest_updated {
+                outln!("oldest update: {oldest}");
+            }
+            if !summary.largest.is_empty() && limit > 0 {
+                outln!("largest documents:");
+                for doc in summary.largest.iter().take(limit) {
+                    outln!("  {:>8} bytes  {}", doc.bytes, doc.path.display());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(summary)?;
+            value["largest"] = json!(summary.largest.iter().take(limit).collect::<Vec<_>>());
+            value["average_references"] = json!(summary.average_references());
+            value["percent"] = json!({
+                "valid": summary.percent(summary.valid),
+                "stale": summary.percent(summary.stale),
+                "orphaned": summary.percent(summary.orphaned),
+            });
+            outln!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_summary_text(summary: &Summary) {
+    outln!("documents: {}", summary.documents);
+    for (category, count) in &summary.categories {
+        outln!("  {category}: {count}");
+    }
+    outln!(
+        "freshness: {} valid ({:.0}%), {} stale ({:.0}%), {} orphaned ({:.0}%)",
+        summary.valid,
+        summary.percent(summary.valid),
+        summary.stale,
+        summary.percent(summary.stale),
+        summary.orphaned,
+        summary.percent(summary.orphaned)
+    );
+    if let Some(last) = &summary.last_updated {
+        outln!("last updated: {last}");
+    }
+}
+
+/// Print the most- and least-read documents
+pub fn print_usage(format: OutputFormat, report: &UsageReport, limit: usize) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if !report.tracking {
+                outln!("Usage tracking is off; set `track = true` under [usage] in config.toml");
+            }
+            let since = report
+                .since
+                .as_deref()
+                .map(|s| format!(" since {s}"))
+                .unwrap_or_default();
+            outln!("{} reads recorded{since}", report.total);
+
+            let line = |d: &DocumentUsage| {
+                let last = d
+                    .last_read
+                    .as_deref()
+                    .map(|l| format!(", last {l}"))
+                    .unwrap_or_default();
+                outln!(
+                    "  {:>5}  {} ({}{last})",
+                    d.reads,
+                    d.slug,
+                    d.document.display()
+                );
+            };
+            let count = report.documents.len().min(limit);
+            outln!("\nMost read:");
+            report.documents.iter().take(count).for_each(line);
+            // Least read, starting with the least
+            outln!("\nLeast read:");
+            report.documents.iter().rev().take(count).for_each(line);
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the debt score of the tree, each directory and the most indebted
+/// documents
+pub fn print_debt(format: OutputFormat, report: &DebtReport, limit: usize) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "Doc debt: {:.1} (mean over {} documents, total {:.1})",
+                report.score,
+                report.documents.len(),
+                report.total
+            );
+
+            outln!("\nBy directory:");
+            let mut directories: Vec<_> = report.directories.iter().collect();
+            directories.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+            for (name, dir) in directories {
+                outln!("  {:>5.1}  {name} ({} documents)", dir.score, dir.documents);
+            }
+
+            let indebted: Vec<_> = report
+                .documents
+                .iter()
+                .filter(|d| d.score > 0.0)
+                .take(limit)
+                .collect();
+            if !indebted.is_empty() {
+                outln!("\nHighest debt:");
+            }
+            for doc in indebted {
+                outln!(
+                    "  {:>5.1}  {} ({}, {} days, {} commits, {:.0}% coverage, {} reads)",
+                    doc.score,
+                    doc.document.display(),
+                    doc.status,
+                    doc.stale_days,
+                    doc.commits,
+                    doc.coverage() * 100.0,
+                    doc.reads
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Chart recorded summary metrics, oldest point first
+pub fn print_trend(format: OutputFormat, points: &[Point]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let (Some(first), Some(last)) = (points.first(), points.last()) else {
+                outln!("No trend data yet: run `context sync` or `context stats` to record some");
+                return Ok(());
+            };
+            let date = |p: &Point| p.timestamp.get(..10).unwrap_or(&p.timestamp).to_string();
+            outln!(
+                "Last {} runs, {} to {}",
+                points.len(),
+                date(first),
+                date(last)
+            );
+
+            let counts = [
+                (
+                    "documents",
+                    points.iter().map(|p| p.documents).collect::<Vec<_>>(),
+                ),
+                ("valid", points.iter().map(|p| p.valid).collect()),
+                ("stale", points.iter().map(|p| p.stale).collect()),
+                ("orphaned", points.iter().map(|p| p.orphaned).collect()),
+            ];
+            for (name, counts) in counts {
+                #[allow(clippy::cast_precision_loss)]
+                let values: Vec<f64> = counts.iter().map(|&n| n as f64).collect();
+                let (start, end) = (counts[0], counts[counts.len() - 1]);
+                let change = end.cast_signed() - start.cast_signed();
+                outln!(
+                    "  {name:<10} {}  {end} ({change:+})",
+                    trend::sparkline(&values)
+                );
+            }
+            let coverage: Vec<f64> = points.iter().map(|p| p.coverage * 100.0).collect();
+            outln!(
+                "  {:<10} {}  {:.1}% ({:+.1})",
+                "coverage",
+                trend::sparkline(&coverage),
+                last.coverage * 100.0,
+                (last.coverage - first.coverage) * 100.0
+            );
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(points)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print suggested references as a ready-to-paste backtick list, with the
+/// reasons on stderr so the list can be piped
+pub fn print_suggestions(format: OutputFormat, suggestions: &Suggestions) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if suggestions.suggestions.is_empty() {
+                eprintln!(
+                    "No references to suggest for {}",
+                    suggestions.document.display()
+                );
+            }
+            for s in &suggestions.suggestions {
+                eprintln!("{}: {}", s.path, s.reasons.join(", "));
+            }
+            for s in &suggestions.suggestions {
+                outln!("- `{}`", s.path);
+            }
+            if suggestions.applied {
+                eprintln!("Added to {}", suggestions.document.display());
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(suggestions)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print churned files lacking documentation, most-changed first
+pub fn print_gaps(format: OutputFormat, report: &GapReport) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if report.gaps.is_empty() {
+                outln!(
+                    "No gaps: all {} files changed in the last {} days are documented",
+                    report.churned,
+                    report.days
+                );
+                return Ok(());
+            }
+            outln!("Commits  File (last {} days)", report.days);
+            for gap in &report.gaps {
+                let why = match gap.kind {
+                    GapKind::Undocumented => "undocumented".to_string(),
+                    GapKind::Stale => {
+                        let docs: Vec<_> = gap
+                            .documents
+                            .iter()
+                            .map(|d| d.display().to_string())
+                            .collect();
+                        format!("stale: {}", docs.join(", "))
+                    }
+                };
+                outln!("  {:>5}  {}  ({why})", gap.commits, gap.path);
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the documents owned by `owners` with their statuses
+pub fn print_mine(
+    format: OutputFormat,
+    root: &Path,
+    owners: &[String],
+    statuses: &[Validation],
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if statuses.is_empty() {
+                outln!("No documents owned by {}", owners.join(", "));
+                return Ok(());
+            }
+            for validation in statuses {
+                let path = validation
+                    .path
+                    .strip_prefix(root)
+                    .unwrap_or(&validation.path);
+                outln!("{:<9} {}", validation.status.to_string(), path.display());
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(statuses)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the commits that made a document's references out of date
+pub fn print_when_stale(format: OutputFormat, report: &StaleReport) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if report.references.is_empty() {
+                outln!("{} is up to date", report.document.display());
+                return Ok(());
+            }
+            outln!(
+                "{} ({}, updated {})",
+                report.document.display(),
+                report.status,
+                report.updated
+            );
+            for drift in &report.references {
+                let deleted = if drift.missing { " (deleted)" } else { "" };
+                outln!("  {}{deleted}", drift.reference);
+                match &drift.commit {
+                    Some(commit) => {
+                        let approximate = if drift.approximate { "  (by date)" } else { "" };
+                        outln!(
+                            "    {}  {}  {}  {}{approximate}",
+                            commit.hash.get(..7).unwrap_or(&commit.hash),
+                            commit.date.get(..10).unwrap_or(&commit.date),
+                            commit.author,
+                            commit.subject
+                        );
+                    }
+                    None => outln!("    not committed yet"),
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print policy violations, grouped by rule
+pub fn print_policy(format: OutputFormat, report: &PolicyReport) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for violation in &report.violations {
+                let severity = match violation.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                outln!(
+                    "{severity}[{}]: {}: {}",
+                    violation.rule,
+                    violation.path.display(),
+                    violation.message
+                );
+            }
+            let warnings = report.violations.len() - report.errors();
+            outln!(
+                "{} rules checked: {} errors, {warnings} warnings",
+                report.rules,
+                report.errors()
+            );
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print how a document's headings differ from its template's
+pub fn print_template_drift(format: OutputFormat, drift: &TemplateDrift) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "{} (template '{}')",
+                drift.document.display(),
+                drift.template
+            );
+            if drift.outdated() {
+                let version = drift.version.as_deref().unwrap_or("unrecorded");
+                outln!(
+                    "  created from version {version}, template is now {}",
+                    drift.current
+                );
+            }
+            for heading in &drift.missing {
+                outln!("- {heading}");
+            }
+            for heading in &drift.extra {
+                outln!("+ {heading}");
+            }
+            if drift.missing.is_empty() && drift.extra.is_empty() {
+                outln!("  headings match the template");
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(drift)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print every document created from a template, flagging outdated versions
+pub fn print_template_status(format: OutputFormat, drifts: &[TemplateDrift]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for drift in drifts {
+                let state = if drift.outdated() {
+                    "outdated"
+                } else {
+                    "current"
+                };
+                let changes = drift.missing.len() + drift.extra.len();
+                outln!(
+                    "{state:<9} {}  ({}, {changes} heading changes)",
+                    drift.document.display(),
+                    drift.template
+                );
+            }
+            let outdated = drifts.iter().filter(|d| d.outdated()).count();
+            outln!(
+                "{outdated} of {} templated documents use an outdated template version",
+                drifts.len()
+            );
+        }
+        OutputFormat::Json => {
+            let items: Vec<_> = drifts
+                .iter()
+                .map(|d| {
+                    json!({
+                        "document": d.document,
+                        "template": d.template,
+                        "version": d.version,
+                        "current": d.current,
+                        "outdated": d.outdated(),
+                        "missing": d.missing,
+                        "extra": d.extra,
+                    })
+                })
+                .collect();
+            outln!("{}", serde_json::to_string_pretty(&items)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print where each diagram was rendered, or why it wasn't
+pub fn print_rendered_diagrams(format: OutputFormat, rendered: &[RenderedDiagram]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for diagram in rendered {
+                let location = format!(
+                    "{}:{} ({})",
+                    diagram.document.display(),
+                    diagram.line,
+                    diagram.kind
+                );
+                match (&diagram.svg, &diagram.error) {
+                    (Some(svg), _) => outln!("rendered: {location} -> {}", svg.display()),
+                    (None, Some(error)) => outln!("failed:   {location}: {error}"),
+                    (None, None) => outln!(
+                        "skipped:  {location}: no [diagrams] {} command",
+                        diagram.kind
+                    ),
+                }
+            }
+            let svgs = rendered.iter().filter(|d| d.svg.is_some()).count();
+            outln!("Rendered {svgs} of {} diagrams", rendered.len());
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(rendered)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print shell exports, or the variables as JSON
+pub fn print_env(format: OutputFormat, shell: Shell, env: &ShellEnv) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let root = env.context_root.to_string_lossy();
+            let project = env.project_root.to_string_lossy();
+            let mut vars = vec![
+                ("CONTEXT_ROOT", root.as_ref()),
+                ("CONTEXT_PROJECT_ROOT", project.as_ref()),
+            ];
+            if let Some(prompt) = &env.prompt {
+                vars.push(("CONTEXT_PROMPT", prompt));
+            }
+            for (name, value) in vars {
+                match shell {
+                    Shell::Posix => outln!("export {name}={}", shell_quote(value)),
+                    Shell::Fish => outln!("set -gx {name} {}", shell_quote(value)),
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(env)?);
+        }
+    }
+    Ok(())
+}
+
+/// Single-quote a value for sh and fish
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Print a resolved context root and how it was found
+pub fn print_root(format: OutputFormat, root: &ContextRoot) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!("{}", root.context_dir.display());
+            match &root.marker {
+                Some(marker) => outln!("pinned by {}", marker.display()),
+                None => outln!(
+                    "found searching upward from {}",
+                    root.searched_from.display()
+                ),
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(root)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print a drafted document: the draft itself for a dry run, otherwise
+/// where it was written
+pub fn print_capture(format: OutputFormat, capture: &Capture) -> Result<()> {
+    match format {
+        OutputFormat::Text if capture.written => {
+            outln!(
+                "Created {} from {} commit(s) ({} references)",
+                capture.path.display(),
+                capture.commits.len(),
+                capture.references.len()
+            );
+            outln!("Replace the TODO comment with an explanation of the change.");
+        }
+        OutputFormat::Text => {
+            out!("{}", capture.body);
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(capture)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the documents covering a build target
+pub fn print_target_query(format: OutputFormat, query: &TargetQuery) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "{}: {} file(s), {} document(s)",
+                query.target,
+                query.files.len(),
+                query.documents.len()
+            );
+            for doc in &query.documents {
+                outln!(
+                    "  {:<8}  {}",
+                    doc.status.to_string(),
+                    doc.document.display()
+                );
+                for reference in &doc.references {
+                    outln!("            {reference}");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(query)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print a review checklist as Markdown
+pub fn print_checklist(
+    format: OutputFormat,
+    checklist: &Checklist,
+    source_url: Option<&str>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            out!("{}", checklist::markdown(checklist, source_url));
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(checklist)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print how the context tree changed between two revisions
+pub fn print_docs_diff(format: OutputFormat, diff: &DocsDiff) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let to = diff.to.as_deref().unwrap_or("the working tree");
+            if diff.documents.is_empty() {
+                outln!("No documentation changes between {} and {to}", diff.from);
+                return Ok(());
+            }
+            outln!("Documentation changes from {} to {to}:", diff.from);
+            for doc in &diff.documents {
+                let change = match doc.change {
+                    Change::Added => "added",
+                    Change::Removed => "removed",
+                    Change::Modified => "modified",
+                    Change::Unchanged => "unchanged",
+                };
+                outln!("  {change:<9} {} ({})", doc.path.display(), doc.slug);
+                if let Some(description) = &doc.description {
+                    outln!(
+                        "      description: {:?} -> {:?}",
+                        description.from,
+                        description.to
+                    );
+                }
+                for reference in &doc.references_added {
+                    outln!("      + {reference}");
+                }
+                for reference in &doc.references_dropped {
+                    outln!("      - {reference}");
+                }
+                if doc.body_changed && doc.change == Change::Modified {
+                    outln!("      body changed");
+                }
+                match (doc.status.from, doc.status.to) {
+                    (Some(from), Some(to)) if from != to => outln!("      status: {from} -> {to}"),
+                    (_, Some(s)) | (Some(s), None) if s != Status::Valid => {
+                        outln!("      status: {s}");
+                    }
+                    _ => {}
+                }
+            }
+            outln!("\n{} document(s) differ", diff.documents.len());
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(diff)?);
+        }
+    }
+    Ok(())
+}
+
+/// Report a scaffolded document
+pub fn print_new_document(format: OutputFormat, path: &Path, slug: &str) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!("Created {}", path.display());
+        }
+        OutputFormat::Json => {
+            let value = json!({ "path": path, "slug": slug });
+            outln!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
+    Ok(())
+}
+
+/// Announce that watching started, with the current status counts
+pub fn print_watch_started(format: OutputFormat, validations: &[Validation]) -> Result<()> {
+    let count = |status| validations.iter().filter(|v| v.status == status).count();
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "Watching {} documents ({} valid, {} stale, {} orphaned); press Ctrl-C to stop",
+                validations.len(),
+                count(Status::Valid),
+                count(Status::Stale),
+                count(Status::Orphaned)
+            );
+        }
+        OutputFormat::Json => {
+            let event = json!({
+                "event": "watching",
+                "timestamp": clock::now_utc().to_rfc3339(),
+                "documents": validations.len(),
+                "valid": count(Status::Valid),
+                "stale": count(Status::Stale),
+                "orphaned": count(Status::Orphaned),
+            });
+            outln!("{}", serde_json::to_string(&event)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print status transitions as they happen: one line each, or one JSON
+/// object per line
+pub fn print_transitions(format: OutputFormat, transitions: &[Transition]) -> Result<()> {
+    let name = |status: Option<Status>| status.map_or_else(|| "-".to_string(), |s| s.to_string());
+    for transition in transitions {
+        match format {
+            OutputFormat::Text => {
+                let mut notes = Vec::new();
+                if transition.from.is_none() {
+                    notes.push("added".to_string());
+                } else if transition.to.is_none() {
+                    notes.push("removed".to_string());
+                }
+                if !transition.changed.is_empty() {
+                    notes.push(format!("changed: {}", transition.changed.join(", ")));
+                }
+                if !transition.missing.is_empty() {
+                    notes.push(format!("missing: {}", transition.missing.join(", ")));
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", notes.join("; "))
+                };
+                outln!(
+                    "{} {}: {} -> {}{notes}",
+                    clock::now().format("%H:%M:%S"),
+                    transition.path.display(),
+                    name(transition.from),
+                    name(transition.to)
+                );
+            }
+            OutputFormat::Json => {
+                let mut event = serde_json::to_value(transition)?;
+                event["event"] = json!("transition");
+                event["timestamp"] = json!(clock::now_utc().to_rfc3339());
+                outln!("{}", serde_json::to_string(&event)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the git hooks that were installed
+pub fn print_hooks_installed(format: OutputFormat, installed: &[PathBuf]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for path in installed {
+                outln!("Installed {}", path.display());
+            }
+        }
+        OutputFormat::Json => {
+            outln!(
+                "{}",
+                serde_json::to_string_pretty(&json!({ "installed": installed }))?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print the documents a commit was reminded of
+pub fn print_commit_reminder(format: OutputFormat, documents: &[String]) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if !documents.is_empty() {
+                outln!("context: this commit changes files referenced by:");
+                for doc in documents {
+                    outln!("  {doc}");
+                }
+                outln!(
+                    "context: consider updating them in the same change (listed as {} trailers)",
+                    hooks::TRAILER
+                );
+            }
+        }
+        OutputFormat::Json => {
+            outln!(
+                "{}",
+                serde_json::to_string_pretty(&json!({ "documents": documents }))?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print a pack as one Markdown bundle, with paths relative to the project
+pub fn print_pack(format: OutputFormat, pack: &Pack, project_root: &Path) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let relative = |path: &Path| {
+                path.strip_prefix(project_root)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string()
+            };
+            outln!("# Context for {}", pack.range);
+            outln!();
+            outln!("Changed files:");
+            for file in &pack.changed {
+                outln!("- `{file}`");
+            }
+            for doc in &pack.documents {
+                outln!();
+                outln!("## {} ({}, {})", relative(&doc.path), doc.slug, doc.status);
+                if !doc.description.is_empty() {
+                    outln!();
+                    outln!("{}", doc.description);
+                }
+                outln!();
+                match &doc.linked_from {
+                    Some(from) => outln!("Linked from {} (hop {})", relative(from), doc.hops),
+                    None => outln!("References changed: {}", doc.references.join(", ")),
+                }
+                outln!();
+                outln!("{}", doc.body.trim_end());
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(pack)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print missing, out-of-date and orphaned translations
+pub fn print_i18n(format: OutputFormat, report: &I18nReport) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            if report.locales.is_empty() {
+                outln!("No locales configured; add `[i18n] locales` to config.toml");
+                return Ok(());
+            }
+            for (source, locale) in &report.missing {
+                outln!("missing:   {} ({locale})", source.display());
+            }
+            for outdated in &report.outdated {
+                let reason = if outdated.never_synced {
+                    "never synced"
+                } else {
+                    "source changed"
+                };
+                outln!(
+                    "outdated:  {} ({reason}: {})",
+                    outdated.translation.display(),
+                    outdated.source.display()
+                );
+            }
+            for translation in &report.orphaned {
+                outln!("orphaned:  {} (no source document)", translation.display());
+            }
+            outln!(
+                "{} source documents, {} locales: {} missing, {} outdated, {} orphaned translations",
+                report.sources,
+                report.locales.len(),
+                report.missing.len(),
+                report.outdated.len(),
+                report.orphaned.len()
+            );
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print each document as it is added to the full-text index
+pub fn print_index_progress(event: &Event) {
+    if let Event::DocumentIndexed { path } = event {
+        eprintln!("Indexing {}", path.display());
+    }
+}
+
+/// Print what an index update changed
+pub fn print_index_update(format: OutputFormat, update: &IndexUpdate) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for path in &update.removed {
+                outln!("Removed {}", path.display());
+            }
+            outln!(
+                "Indexed {} documents ({} unchanged, {} removed)",
+                update.indexed.len(),
+                update.unchanged,
+                update.removed.len()
+            );
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(update)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print how the full-text index compares with the documents
+pub fn print_index_verification(
+    format: OutputFormat,
+    verification: &IndexVerification,
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let groups = [
+                ("missing", &verification.missing),
+                ("outdated", &verification.outdated),
+                ("orphaned", &verification.orphaned),
+            ];
+            for (label, paths) in groups {
+                for path in paths {
+                    outln!("{label:<9} {}", path.display());
+                }
+            }
+            if let Some(corruption) = &verification.corruption {
+                outln!("corrupt   {corruption}");
+            }
+            if verification.is_ok() {
+                outln!("Index is up to date ({} documents)", verification.checked);
+            } else {
+                outln!("Index is out of date; run `context index` (or `--rebuild` if corrupt)");
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(verification)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print version and environment details
+pub fn print_info(format: OutputFormat, info: &Info) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!("context {}", info.version);
+            let root = info.root.as_ref().map_or_else(
+                || "(not in a context repository)".to_string(),
+                |r| r.display().to_string(),
+            );
+            outln!("root:    {root}");
+            let config = info
+                .config
+                .as_ref()
+                .map_or_else(|| "(defaults)".to_string(), |c| c.display().to_string());
+            outln!("config:  {config}");
+
+            if let Some(summary) = &info.summary {
+                print_summary_text(summary);
+            }
+
+            match &info.git {
+                Some(git) => {
+                    let branch = git.branch.as_deref().unwrap_or("(detached)");
+                    let commit = git
+                        .commit
+                        .as_deref()
+                        .map_or("(no commits)", |c| &c[..c.len().min(12)]);
+                    let dirty = if git.dirty { ", dirty" } else { "" };
+                    outln!("git:     {branch} @ {commit}{dirty}");
+                }
+                None => outln!("git:     (not a git repository)"),
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(info)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print a self-test report
+pub fn print_selftest(format: OutputFormat, report: &Report) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!("context {} ({}/{})", report.version, report.os, report.arch);
+            outln!("binary: {}", report.binary.display());
+            outln!("age: {}", report.age.as_deref().unwrap_or("not found"));
+            if let Some(project) = &report.project {
+                outln!("project: {}", project.display());
+            }
+            outln!();
+            for check in &report.checks {
+                let mark = if check.passed { "ok  " } else { "FAIL" };
+                match &check.detail {
+                    Some(detail) => outln!("{mark} {}: {detail}", check.name),
+                    None => outln!("{mark} {}", check.name),
+                }
+            }
+            let failed = report.checks.iter().filter(|c| !c.passed).count();
+            outln!();
+            if failed == 0 {
+                outln!("All {} checks passed", report.checks.len());
+            } else {
+                outln!("{failed} of {} checks failed", report.checks.len());
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(report)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the outcome of a merge
+pub fn print_merge(format: OutputFormat, result: &MergeResult) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            outln!(
+                "Merged {} into {}",
+                result.removed.display(),
+                result.merged.display()
+            );
+            let action = if result.deleted {
+                "Deleted"
+            } else {
+                "Superseded"
+            };
+            outln!("{action} {}", result.removed.display());
+            if !result.relinked.is_empty() {
+                outln!("Relinked:");
+                for path in &result.relinked {
+                    outln!("  {}", path.display());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json_result = json!({
+                "merged": result.merged.display().to_string(),
+                "removed": result.removed.display().to_string(),
+                "deleted": result.deleted,
+                "relinked": result.relinked.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+            });
+            outln!("{}", serde_json::to_string_pretty(&json_result)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print the documents archived (or to be, in a dry run) and those relinked
+pub fn print_archive(format: OutputFormat, result: &ArchiveResult) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let verb = if result.dry_run {
+                "Would archive"
+            } else {
+                "Archived"
+            };
+            if result.archived.is_empty() {
+                outln!("No deprecated documents are due for the archive");
+            }
+            for archived in &result.archived {
+                outln!(
+                    "{verb} {} -> {}",
+                    archived.from.display(),
+                    archived.to.display()
+                );
+            }
+            if !result.relinked.is_empty() {
+                outln!(
+                    "{}:",
+                    if result.dry_run {
+                        "Would relink"
+                    } else {
+                        "Relinked"
+                    }
+                );
+                for path in &result.relinked {
+                    outln!("  {}", path.display());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            outln!("{}", serde_json::to_string_pretty(result)?);
+        }
+    }
+    Ok(())
+}
+
+/// Format a simple message
+pub fn format_message(format: OutputFormat, message: &str) -> String {
+    match format {
+        OutputFormat::Text => message.to_string(),
+        OutputFormat::Json => {
+            serde_json::to_string(&json!({"message": message})).unwrap_or_default()
+        }
+    }
+}
+
+/// Format an error message
+pub fn format_error(format: OutputFormat, error: &str) -> String {
+    match format {
+        OutputFormat::Text => format!("Error: {error}"),
+        OutputFormat::Json => serde_json::to_string(&json!({"error": error})).unwrap_or_default(),
+    }
+}
+
+/// Print invalid references error
+pub fn print_invalid_references(
+    format: OutputFormat,
+    documents: &[(PathBuf, Vec<InvalidReference>)],
+) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            eprintln!(
+                "Error: Invalid references in {} document(s)",
+                documents.len()
+            );
+            eprintln!();
+            for (doc_path, invalid_refs) in documents {
+                eprintln!("  {}", doc_path.display());
+                for inv in invalid_refs {
+                    eprintln!("    - `{}`: {}", inv.path, inv.reason);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json_docs: Vec<_> = documents
+                .iter()
+                .map(|(path, refs)| {
+                    json!({
+                        "document": path.display().to_string(),
+                        "invalid": refs.iter().map(|r| {
+                            json!({
+                                "path": r.path,
+                                "reason": r.reason.to_string(),
+                            })
+                        }).collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            let output = json!({
+                "error": "invalid_references",
+                "count": documents.len(),
+                "documents": json_docs,
+            });
+            eprintln!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+    Ok(())
+}
+
+/// List the changes to stale documents and ask whether they were reviewed
+pub fn confirm_review(root: &Path, pending: &[Validation]) -> Result<bool> {
+    eprintln!("These documents' references changed since they were last synced:");
+    for validation in pending {
+        eprintln!(
+            "  {}",
+            validation
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&validation.path)
+                .display()
+        );
+        for reference in &validation.changed {
+            eprintln!("    changed: {reference}");
+        }
+        for reference in &validation.missing {
+            eprintln!("    missing: {reference}");
+        }
+    }
+    eprint!("Have you reviewed these changes against the documents? [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Print warnings raised while a command runs, ignoring other events
+pub fn print_warning(event: &Event) {
+    if let Event::Warning { document, message } = event {
+        match document {
+            Some(path) => eprintln!("Warning: {}: {message}", path.display()),
+            None => eprintln!("Warning: {message}"),
+        }
+    }
+}
+
+/// Output captured for `--output-file`, written out when the command finishes
+static CAPTURED: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Collect command output in memory instead of printing it, until
+/// `write_captured`
+pub fn capture_output() {
+    *CAPTURED.lock().unwrap_or_else(PoisonError::into_inner) = Some(Vec::new());
+}
+
+/// Write command output to stdout, or to the capture buffer
+pub fn write_output(args: std::fmt::Arguments) {
+    let mut captured = CAPTURED.lock().unwrap_or_else(PoisonError::into_inner);
+    match captured.as_mut() {
+        Some(buffer) => {
+            let _ = buffer.write_fmt(args);
+        }
+        None => print!("{args}"),
+    }
+}
+
+/// Write the captured output to `path` through a temporary file renamed
+/// into place, so readers never see a half-written report
+pub fn write_captured(path: &Path) -> Result<()> {
+    let output = CAPTURED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+        .unwrap_or_default();
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}.tmp", std::process::id()));
+    let temporary = PathBuf::from(temporary);
+    std::fs::write(&temporary, output)?;
+    std::fs::rename(&temporary, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temporary);
+    })?;
+    Ok(())
+}
+
+/// Write the warnings a command raised to stderr as one JSON object, so
+/// stdout keeps only the command's JSON result
+pub fn print_warnings(warnings: &[Warning]) -> Result<()> {
+    if !warnings.is_empty() {
+        eprintln!(
+            "{}",
+            serde_json::to_string_pretty(&json!({ "warnings": warnings }))?
+        );
+    }
+    Ok(())
+}
+
+/// Write an event to stderr as one line of JSON, for `--log-format json`
+pub fn log_event(event: &Event) {
+    if let Ok(line) = serde_json::to_string(event) {
+        eprintln!("{line}");
+    }
+}
+
 /// Handle a ContextError, printing appropriate output
 pub fn handle_error(format: OutputFormat, error: &ContextError) -> Result<()> {
     if let ContextError::InvalidReferences { documents, .. } = error {
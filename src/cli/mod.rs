@@ -1,6 +1,29 @@
+/// Like `print!`, to the command's output: stdout, or the `--output-file`
+/// buffer (see `console::write_output`)
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::cli::console::write_output(format_args!($($arg)*))
+    };
+}
+
+/// Like `println!`, to the command's output
+macro_rules! outln {
+    () => {
+        $crate::cli::console::write_output(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::cli::console::write_output(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
 pub mod args;
 pub mod commands;
 pub mod console;
+pub mod selftest;
 
-pub use args::{Cli, Commands, FindArgs, InitArgs, OutputFormat, ServeArgs, StatusArgs, SyncArgs};
+pub use args::{
+    Cli, Commands, DescribeArgs, ExplainArgs, FindArgs, GetArgs, InitArgs, LogFormat, MergeArgs,
+    OutputFormat, PageArgs, RefsArgs, RefsCommand, RefsImportArgs, ResolveArgs, SearchArgs,
+    SelftestArgs, ServeArgs, SplitArgs, StatusArgs, SyncArgs,
+};
 pub use commands::{execute, map_exit_code};
@@ -0,0 +1,199 @@
+//! End-to-end self-test of the running binary
+//!
+//! Builds a throwaway project in the system temp directory and drives the
+//! binary through a typical workflow as a subprocess, so packaging problems
+//! (wrong binary, broken runtime environment) show up the same way they
+//! would for a user. The report doubles as environment diagnostics for bug
+//! reports.
+
+use crate::core::crypto;
+use crate::error::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SOURCE: &str = "src/lib.rs";
+const DOCUMENT: &str = ".context/guides/lib.md";
+
+/// Outcome of one self-test step
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// What was checked
+    pub name: &'static str,
+    /// Whether the step behaved as expected
+    pub passed: bool,
+    /// Why the step failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Environment details and step results from a self-test run
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// Binary under test
+    pub binary: PathBuf,
+    /// Version of this build
+    pub version: &'static str,
+    /// Operating system
+    pub os: &'static str,
+    /// CPU architecture
+    pub arch: &'static str,
+    /// The age binary used for encrypted documents, if it could be run
+    pub age: Option<String>,
+    /// The temporary project, if it was kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<PathBuf>,
+    /// Results of each step, in order
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Whether every step passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+}
+
+/// Run the self-test against `binary`, removing the project unless `keep` is set
+pub fn run(binary: &Path, keep: bool) -> Result<Report> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let project =
+        std::env::temp_dir().join(format!("context-selftest-{}-{nanos}", std::process::id()));
+    std::fs::create_dir_all(project.join("src"))?;
+
+    let checks = run_steps(binary, &project);
+
+    let kept = if keep {
+        Some(project)
+    } else {
+        std::fs::remove_dir_all(&project)?;
+        None
+    };
+
+    Ok(Report {
+        binary: binary.to_path_buf(),
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        age: age_version(),
+        project: kept,
+        checks,
+    })
+}
+
+fn run_steps(binary: &Path, project: &Path) -> Vec<Check> {
+    let context = |args: &[&str]| {
+        Command::new(binary)
+            .args(args)
+            .current_dir(project)
+            .output()
+    };
+    let write = |relative: &str, content: &str| std::fs::write(project.join(relative), content);
+
+    let mut checks = Vec::new();
+
+    let init = context(&["init"]);
+    checks.push(expect_exit("init", &init, 0));
+    if !checks[0].passed {
+        return checks;
+    }
+
+    // There is no command for creating documents, so write one directly
+    let created = write(SOURCE, "pub fn answer() -> u32 { 42 }\n").and_then(|()| {
+        write(
+            DOCUMENT,
+            "---\nslug: lib\ndescription: Library entry point\n---\n\n# Library\n\nLives in `src/lib.rs`.\n",
+        )
+    });
+    checks.push(Check {
+        name: "create document",
+        passed: created.is_ok(),
+        detail: created.err().map(|e| e.to_string()),
+    });
+
+    checks.push(expect_exit("sync", &context(&["sync"]), 0));
+    let synced = std::fs::read_to_string(project.join(DOCUMENT)).unwrap_or_default();
+    let recorded = synced.contains(&format!("{SOURCE}: "));
+    checks.push(Check {
+        name: "sync records reference",
+        passed: recorded,
+        detail: (!recorded).then(|| "no reference in frontmatter".to_string()),
+    });
+
+    checks.push(expect_exit(
+        "status reports valid",
+        &context(&["status"]),
+        0,
+    ));
+
+    let find = context(&["find", SOURCE]);
+    let mut check = expect_exit("find locates document", &find, 0);
+    if check.passed && !stdout(&find).contains("lib.md") {
+        check.passed = false;
+        check.detail = Some("document missing from find output".to_string());
+    }
+    checks.push(check);
+
+    let modified = write(SOURCE, "pub fn answer() -> u32 { 43 }\n");
+    if modified.is_ok() {
+        checks.push(expect_exit(
+            "status reports stale",
+            &context(&["status"]),
+            1,
+        ));
+    }
+
+    let removed = std::fs::remove_file(project.join(SOURCE));
+    if removed.is_ok() {
+        checks.push(expect_exit(
+            "status reports orphaned",
+            &context(&["status"]),
+            2,
+        ));
+    }
+
+    checks
+}
+
+/// Check that a command ran and exited with the expected code
+fn expect_exit(name: &'static str, output: &std::io::Result<Output>, expected: i32) -> Check {
+    let detail = match output {
+        Err(e) => Some(format!("failed to run: {e}")),
+        Ok(output) if output.status.code() == Some(expected) => None,
+        Ok(output) => Some(format!(
+            "expected exit {expected}, got {}: {}",
+            output
+                .status
+                .code()
+                .map_or_else(|| "signal".to_string(), |c| c.to_string()),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    };
+    Check {
+        name,
+        passed: detail.is_none(),
+        detail,
+    }
+}
+
+fn stdout(output: &std::io::Result<Output>) -> String {
+    output
+        .as_ref()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+/// Version string of the configured age binary, if it runs
+fn age_version() -> Option<String> {
+    let output = Command::new(crypto::age_binary())
+        .arg("--version")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,102 @@
+//! Archiving deprecated documents out of the working tree
+//!
+//! `context archive` moves deprecated documents whose `updated` date is at
+//! least `[archive] after_days` old into `.context/archive/`, keeping their
+//! layout (`guides/auth.md` becomes `archive/guides/auth.md`). Links to and
+//! from a moved document are rewritten so they keep resolving. Archived
+//! documents are not loaded, so status, coverage, listings and packs ignore
+//! them; search finds them again with `--include-archived`. Each move is
+//! appended to `.context/.journal/archive.jsonl`, which is never rotated.
+
+use crate::core::journal::Journal;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Directory within the context root holding archived documents
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Archive journal path within the context root
+pub const ARCHIVE_JOURNAL: &str = ".journal/archive.jsonl";
+
+/// One archived document, written to the journal as one JSON line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// RFC 3339 timestamp of the move
+    pub timestamp: String,
+    /// Slug of the document
+    pub slug: String,
+    /// Where the document was, relative to the context root
+    pub from: PathBuf,
+    /// Where it is now, relative to the context root
+    pub to: PathBuf,
+}
+
+/// A document moved (or, in a dry run, to be moved) into the archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchivedDocument {
+    pub slug: String,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Outcome of `context archive`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArchiveResult {
+    /// Documents moved into the archive
+    pub archived: Vec<ArchivedDocument>,
+    /// Other documents whose links to a moved document were rewritten
+    pub relinked: Vec<PathBuf>,
+    /// Whether nothing was written
+    pub dry_run: bool,
+}
+
+/// The archive journal of the context directory at `root`
+pub fn journal(root: &Path) -> Journal {
+    Journal::new(root.join(ARCHIVE_JOURNAL), u64::MAX, 0)
+}
+
+/// Whether `path` lies in the archive of the context directory at `root`
+pub fn is_archived(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .is_ok_and(|relative| relative.starts_with(ARCHIVE_DIR))
+}
+
+/// Where the document at `path` goes when archived
+pub fn destination(root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(root.join(ARCHIVE_DIR).join(relative))
+}
+
+/// Whether a deprecated document last `updated` then is due for the
+/// archive `today`. Without a readable date it is kept, since its age is
+/// unknown.
+pub fn is_due(updated: &str, today: NaiveDate, after_days: u32) -> bool {
+    after_days == 0
+        || crate::core::clock::parse_updated(updated)
+            .is_some_and(|date| (today - date).num_days() >= i64::from(after_days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination_keeps_layout() {
+        let root = Path::new("/p/.context");
+        let to = destination(root, &root.join("guides/auth.md")).unwrap();
+        assert_eq!(to, root.join("archive/guides/auth.md"));
+        assert!(is_archived(root, &to));
+        assert!(!is_archived(root, &root.join("guides/archive.md")));
+        assert_eq!(destination(root, Path::new("/elsewhere/a.md")), None);
+    }
+
+    #[test]
+    fn test_due_after_configured_days() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert!(is_due("", today, 0));
+        assert!(is_due("2024-01-31", today, 30));
+        assert!(!is_due("2024-02-15", today, 30));
+        assert!(!is_due("", today, 30));
+    }
+}
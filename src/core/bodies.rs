@@ -0,0 +1,94 @@
+//! Bounded cache of document bodies for low-memory operation
+//!
+//! In low-memory mode `Cache` keeps only document metadata resident and
+//! reads bodies back from disk when an operation needs them. Recently used
+//! documents are kept here so repeated lookups don't hit the disk each time.
+
+use crate::core::document::Document;
+use crate::error::Result;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Number of bodies kept when no capacity is given
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Least-recently-used cache of fully loaded documents, keyed by path
+#[derive(Debug)]
+pub struct BodyCache {
+    capacity: usize,
+    /// Most recently used last
+    entries: VecDeque<Arc<Document>>,
+}
+
+impl BodyCache {
+    /// Create a cache holding at most `capacity` documents (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Get the fully loaded document at `path`, reading it from disk on a miss
+    pub fn get(&mut self, path: &Path) -> Result<Arc<Document>> {
+        if let Some(pos) = self.entries.iter().position(|d| d.path == path) {
+            let doc = self.entries.remove(pos).unwrap_or_else(|| unreachable!());
+            self.entries.push_back(Arc::clone(&doc));
+            return Ok(doc);
+        }
+
+        let doc = Arc::new(Document::load(path)?);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Arc::clone(&doc));
+        Ok(doc)
+    }
+
+    /// Forget a document, e.g. after it was rewritten on disk
+    pub fn invalidate(&mut self, path: &Path) {
+        self.entries.retain(|d| d.path != path);
+    }
+
+    /// Number of documents currently held
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no documents are held
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(format!("{name}.md"));
+                std::fs::write(&path, format!("---\nslug: {name}\n---\n\n{name} body\n")).unwrap();
+                path
+            })
+            .collect();
+
+        let mut cache = BodyCache::new(2);
+        cache.get(&paths[0]).unwrap();
+        cache.get(&paths[1]).unwrap();
+        cache.get(&paths[0]).unwrap();
+        cache.get(&paths[2]).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // b was least recently used, so it was evicted
+        std::fs::remove_file(&paths[1]).unwrap();
+        assert!(cache.get(&paths[1]).is_err());
+        assert_eq!(cache.get(&paths[0]).unwrap().body, "a body\n");
+    }
+}
@@ -1,8 +1,42 @@
-use crate::core::document::Document;
-use crate::core::models::{FindMatch, FindResult, SyncResult, Validation};
+use crate::core::archive::{self};
+use crate::core::bodies::{self, BodyCache};
+use crate::core::cancel::CancellationToken;
+use crate::core::clock;
+use crate::core::config::{Config, CONFIG_FILE_NAME};
+use crate::core::document::{self, Document, LOCAL_NAMESPACE};
+use crate::core::drift::{self, StaleReport};
+use crate::core::events::{Event, Subscriber, Subscribers, Warning, Warnings};
+use crate::core::fsio::ReadPolicy;
+use crate::core::gc::{self};
+use crate::core::git;
+use crate::core::i18n::{self};
+use crate::core::markdown_files;
+use crate::core::models::{
+    DocumentState, ExplainedDocument, Explanation, FindMatch, FindResult, HashChange,
+    ListedDocument, PlannedSync, ShownDocument, Status, StatusFilter, SyncPlan, SyncResult,
+    Validation,
+};
+use crate::core::presets::Preset;
+use crate::core::query::{self, TargetDocument, TargetQuery};
+use crate::core::redact::Redactor;
+use crate::core::refindex::{self, ReferenceIndex};
+use crate::core::review::{self, Review};
+use crate::core::sections;
+use crate::core::storage::{self, Storage};
+use crate::core::template::{self, TemplateSource};
 use crate::error::{ContextError, InvalidReference, Result};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::sync::{Arc, Mutex, PoisonError};
+
+mod authoring;
+mod cleanup;
+mod indexing;
+mod packs;
+mod quality;
+mod remotes;
+mod reports;
 
 // Create index.md files with empty frontmatter template
 const INDEX_TEMPLATE: &str = r#"---
@@ -16,6 +50,7 @@ updated: ""
 
 /// Cache for managing context documentation
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent settings, not a state machine
 pub struct Cache {
     /// Root directory (the context/ folder)
     root: PathBuf,
@@ -27,20 +62,181 @@ pub struct Cache {
     references: Option<Document>,
     /// All documents in the cache
     documents: Vec<Document>,
+    /// Read-only documents from fetched remotes
+    remote_documents: Vec<Document>,
+    /// Configuration loaded from config.toml
+    config: Config,
+    /// Compiled redaction rules from the configuration
+    redactor: Redactor,
+    /// Listeners notified of progress during operations
+    subscribers: Subscribers,
+    /// Checked between documents by long-running operations
+    cancellation: CancellationToken,
+    /// Non-fatal problems operations worked around
+    warnings: Warnings,
+    /// Recently used bodies, present only in low-memory mode
+    bodies: Option<Arc<Mutex<BodyCache>>>,
+    /// Where local documents' references and hashes are persisted
+    storage: Arc<dyn Storage>,
+    /// Whether packs, search indexes and coverage include drafts
+    include_drafts: bool,
+    /// Whether syncs drop references to deleted files instead of failing
+    prune_missing: bool,
+    /// Whether documents in .context/archive/ are loaded and searched
+    include_archived: bool,
+    /// Whether syncs drop unmentioned references from documents they don't
+    /// re-hash and purge stored data of deleted documents
+    cleanup: bool,
 }
 
 impl Cache {
     /// Create a new Cache for the given context directory
     pub fn create(root: PathBuf) -> Result<Self> {
+        let config = Config::load(&root)?;
+        Self::create_with_config(root, config)
+    }
+
+    /// Create a cache using an already loaded configuration instead of
+    /// reading `config.toml` (see `core::reload`)
+    pub fn create_with_config(root: PathBuf, config: Config) -> Result<Self> {
+        let redactor = Redactor::new(&config.redaction)?;
+        let storage = storage::open(&root, &config.storage)?;
         Ok(Self {
             root,
             index: None,
             guides: None,
             references: None,
             documents: Vec::new(),
+            remote_documents: Vec::new(),
+            config,
+            redactor,
+            subscribers: Subscribers::default(),
+            cancellation: CancellationToken::new(),
+            warnings: Warnings::new(),
+            bodies: None,
+            storage,
+            include_drafts: false,
+            prune_missing: false,
+            include_archived: false,
+            cleanup: false,
         })
     }
 
+    /// Keep only document metadata in memory, reading bodies on demand.
+    ///
+    /// Takes effect on the next `load`. Up to `capacity` recently used bodies
+    /// are kept; operations that rewrite documents load the bodies they touch
+    /// and drop them again afterwards.
+    pub fn set_low_memory(&mut self, capacity: usize) {
+        self.bodies = Some(Arc::new(Mutex::new(BodyCache::new(capacity))));
+    }
+
+    /// Whether bodies are loaded on demand rather than kept in memory
+    pub fn is_low_memory(&self) -> bool {
+        self.bodies.is_some()
+    }
+
+    /// Use a token to abort `load`, `status` and `sync` part way through.
+    ///
+    /// Once the token is cancelled those operations stop at the next document
+    /// boundary with `ContextError::Cancelled`.
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    /// Include draft documents in packs, search indexes and coverage, which
+    /// leave them out by default
+    pub fn set_include_drafts(&mut self, include: bool) {
+        self.include_drafts = include;
+    }
+
+    /// Make syncs drop references to files that no longer exist instead of
+    /// failing on them; absolute and traversal paths still fail
+    pub fn set_prune_missing(&mut self, prune: bool) {
+        self.prune_missing = prune;
+    }
+
+    /// Make syncs report the references they drop because bodies no longer
+    /// mention them. A bulk sync then also drops such references from the
+    /// unprotected documents it doesn't re-hash, and forgets stored metadata
+    /// and index entries of deleted documents.
+    pub fn set_cleanup(&mut self, cleanup: bool) {
+        self.cleanup = cleanup;
+    }
+
+    /// Load and search documents in `.context/archive/` too, which are left
+    /// out by default (see `core::archive`)
+    pub fn set_include_archived(&mut self, include: bool) {
+        self.include_archived = include;
+    }
+
+    /// Local document files, without archived ones unless included
+    fn local_paths(&self) -> impl Iterator<Item = PathBuf> + use<> {
+        let (root, include_archived) = (self.root.clone(), self.include_archived);
+        markdown_files(&self.root)
+            .filter(move |path| include_archived || !archive::is_archived(&root, path))
+    }
+
+    /// Whether `doc` belongs in packs, search indexes and coverage
+    fn publishable(&self, doc: &Document) -> bool {
+        self.include_drafts || doc.state != DocumentState::Draft
+    }
+
+    /// Warn that a deprecated document was surfaced, naming its successor
+    fn warn_if_deprecated(&self, doc: &Document) {
+        if doc.state != DocumentState::Deprecated {
+            return;
+        }
+        let message = match &doc.superseded_by {
+            Some(successor) => format!("'{}' is deprecated; see '{successor}'", doc.slug),
+            None => format!("'{}' is deprecated", doc.slug),
+        };
+        self.warn(Some(doc.path.clone()), message);
+    }
+
+    /// Collect warnings into a shared collection, e.g. one used by several caches
+    pub fn set_warnings(&mut self, warnings: Warnings) {
+        self.warnings = warnings;
+    }
+
+    /// Non-fatal problems operations on this cache worked around so far
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.warnings.to_vec()
+    }
+
+    /// Record a non-fatal problem and report it to subscribers
+    fn warn(&self, document: Option<PathBuf>, message: String) {
+        self.subscribers.emit(|| Event::Warning {
+            document: document.clone(),
+            message: message.clone(),
+        });
+        self.warnings.push(Warning { document, message });
+    }
+
+    /// Register a listener for progress events from this cache's operations
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Arc::new(subscriber));
+    }
+
+    /// The configuration for this context directory
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// A document's body with configured redaction rules applied.
+    ///
+    /// Use this whenever a body is about to leave the process (MCP responses,
+    /// exports) instead of reading `Document::body` directly.
+    pub fn redacted_body(&self, doc: &Document) -> String {
+        self.redact(&doc.path, &doc.body)
+    }
+
+    /// Apply configured redaction rules to text taken from the document at `doc_path`
+    pub fn redact(&self, doc_path: &Path, text: &str) -> String {
+        let relative = doc_path.strip_prefix(&self.root).unwrap_or(doc_path);
+        self.redactor.redact(relative, text)
+    }
+
     /// Initialize a new context directory with template index files
     pub fn init(root: PathBuf) -> Result<Self> {
         // Create directory structure
@@ -56,45 +252,495 @@ impl Cache {
         Self::create(root)
     }
 
-    /// Load all documents from the cache directory
+    /// Initialize a new context directory from a template repository or directory.
+    ///
+    /// The template contents are copied first, then any missing pieces of the
+    /// standard structure (guides/, references/, index files) are filled in
+    /// without overwriting what the template provided.
+    pub fn init_from(root: PathBuf, source: &TemplateSource) -> Result<Self> {
+        template::apply(source, &root)?;
+        Self::scaffold_missing(&root)?;
+        Self::create(root)
+    }
+
+    /// Initialize a new context directory from a built-in preset.
+    ///
+    /// Preset files are written on top of the standard structure; existing
+    /// files are left untouched so a preset can be applied to a live tree.
+    pub fn init_preset(root: PathBuf, preset: &Preset) -> Result<Self> {
+        for dir in preset.directories {
+            std::fs::create_dir_all(root.join(dir))?;
+        }
+        Self::scaffold_missing(&root)?;
+
+        let config = (CONFIG_FILE_NAME, preset.config);
+        for (relative, content) in preset.files.iter().chain([&config]) {
+            let path = root.join(relative);
+            if !path.exists() {
+                std::fs::write(path, content)?;
+            }
+        }
+
+        Self::create(root)
+    }
+
+    /// Create any missing directories and index files of the standard structure
+    fn scaffold_missing(root: &Path) -> Result<()> {
+        std::fs::create_dir_all(root.join("guides"))?;
+        std::fs::create_dir_all(root.join("references"))?;
+
+        for index in ["index.md", "guides/index.md", "references/index.md"] {
+            let path = root.join(index);
+            if !path.exists() {
+                std::fs::write(path, INDEX_TEMPLATE)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load all documents from the cache directory, in path order, along
+    /// with the documents of any fetched remotes.
+    ///
+    /// In low-memory mode only frontmatter is read (see `load_metadata`).
     pub fn load(&mut self) -> Result<()> {
         self.documents.clear();
+        self.remote_documents.clear();
 
-        // Walk the context directory and find all .md files
-        for entry in WalkDir::new(&self.root)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-        {
-            let path = entry.path();
-            if path.extension().is_some_and(|ext| ext == "md") {
-                let doc = Document::load(path)?;
-
-                // Track special index files
-                if path == self.root.join("index.md") {
-                    self.index = Some(doc.clone());
-                } else if path == self.root.join("guides/index.md") {
-                    self.guides = Some(doc.clone());
-                } else if path == self.root.join("references/index.md") {
-                    self.references = Some(doc.clone());
-                }
+        // Sorting by file name at each level yields documents sorted by path
+        for path in self.local_paths() {
+            self.cancellation.check()?;
+            let doc = self
+                .load_document(&path)
+                .and_then(|mut doc| self.storage.load(&mut doc).map(|()| doc))
+                .inspect_err(|e| {
+                    self.subscribers.emit(|| Event::Error {
+                        document: Some(path.clone()),
+                        message: e.to_string(),
+                    });
+                })?;
 
-                self.documents.push(doc);
+            // Track special index files
+            if path == self.root.join("index.md") {
+                self.index = Some(doc.clone());
+            } else if path == self.root.join("guides/index.md") {
+                self.guides = Some(doc.clone());
+            } else if path == self.root.join("references/index.md") {
+                self.references = Some(doc.clone());
+            }
+
+            self.documents.push(doc);
+        }
+        self.check_slugs()?;
+
+        // A broken remote shouldn't stop work on the local tree
+        for (name, dir) in self.remote_dirs() {
+            for path in markdown_files(&dir) {
+                self.cancellation.check()?;
+                match self.load_document(&path) {
+                    Ok(mut doc) => {
+                        doc.remote = Some(name.clone());
+                        self.remote_documents.push(doc);
+                    }
+                    Err(e) => self.warn(
+                        Some(path.clone()),
+                        format!("skipped document from remote '{name}': {e}"),
+                    ),
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Check the validity status of all documents
+    /// Fail if two local documents claim the same slug, counting both
+    /// frontmatter slugs and path-derived slugs such as `guides/auth/tokens`
+    fn check_slugs(&self) -> Result<()> {
+        let mut claims: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for doc in &self.documents {
+            for name in doc.slugs() {
+                claims.entry(name).or_default().push(doc.path.clone());
+            }
+        }
+
+        let collisions: Vec<_> = claims
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect();
+        if collisions.is_empty() {
+            Ok(())
+        } else {
+            Err(ContextError::DuplicateSlugs { collisions })
+        }
+    }
+
+    /// Load one document, reporting progress and encoding problems
+    fn load_document(&self, path: &Path) -> Result<Document> {
+        let mut doc = if self.is_low_memory() {
+            Document::load_metadata(path)?
+        } else {
+            Document::load(path)?
+        };
+        doc.read_policy = ReadPolicy::from(&self.config.io);
+        self.subscribers.emit(|| Event::DocumentLoaded {
+            path: path.to_path_buf(),
+        });
+        if let Some(offset) = doc.invalid_utf8_at {
+            self.warn(
+                Some(path.to_path_buf()),
+                format!("invalid UTF-8 at byte {offset}, replaced with U+FFFD"),
+            );
+        }
+        Ok(doc)
+    }
+
+    /// Load only document frontmatter, for operations that rarely need bodies.
+    ///
+    /// Switches the cache to low-memory mode if it isn't already, so any body
+    /// an operation does need is read from disk on demand.
+    pub fn load_metadata(&mut self) -> Result<()> {
+        if !self.is_low_memory() {
+            self.set_low_memory(bodies::DEFAULT_CAPACITY);
+        }
+        self.load()
+    }
+
+    /// Lazily walk the cache directory, loading one document at a time.
+    ///
+    /// Unlike `load()`, this never holds more than one document in memory,
+    /// and yields documents in a stable (file name) order.
+    pub fn walk(&self) -> impl Iterator<Item = Result<Document>> {
+        let local = self.local_paths().map(|path| (path, None));
+        self.load_each(local.chain(self.remote_paths()))
+    }
+
+    /// Load documents one at a time, local ones (without a remote) with
+    /// their stored metadata
+    fn load_each(
+        &self,
+        paths: impl Iterator<Item = (PathBuf, Option<String>)>,
+    ) -> impl Iterator<Item = Result<Document>> {
+        let storage = Arc::clone(&self.storage);
+        paths.map(move |(path, remote)| {
+            let mut doc = Document::load(path)?;
+            if remote.is_none() {
+                storage.load(&mut doc)?;
+            }
+            doc.remote = remote;
+            Ok(doc)
+        })
+    }
+
+    /// Stale and orphaned documents among those `sync(doc_path)` would sync,
+    /// whose changes a reviewer acknowledges by syncing them
+    pub fn pending_review(&self, doc_path: Option<&Path>) -> Result<Vec<Validation>> {
+        self.pending_review_matching(doc_path, &StatusFilter::default())
+    }
+
+    /// Like `pending_review`, for a sync of the documents matching `filter`
+    pub fn pending_review_matching(
+        &self,
+        doc_path: Option<&Path>,
+        filter: &StatusFilter,
+    ) -> Result<Vec<Validation>> {
+        let mut pending = Vec::new();
+        for idx in self.sync_indices(doc_path, filter)? {
+            let validation = self.documents[idx].validate()?;
+            if validation.status != Status::Valid {
+                pending.push(validation);
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Indices of the documents a sync of `doc_path` touches: that document
+    /// (even if protected), or all unprotected documents matching `filter`
+    /// if `None`
+    fn sync_indices(&self, doc_path: Option<&Path>, filter: &StatusFilter) -> Result<Vec<usize>> {
+        Ok(self.sync_selection(doc_path, filter)?.0)
+    }
+
+    /// `sync_indices`, along with how many documents matching every other
+    /// criterion of `filter` were left out for their status
+    fn sync_selection(
+        &self,
+        doc_path: Option<&Path>,
+        filter: &StatusFilter,
+    ) -> Result<(Vec<usize>, usize)> {
+        let mut indices = Vec::new();
+        let mut skipped = 0;
+        for (i, doc) in self.documents.iter().enumerate() {
+            let selected = match doc_path {
+                Some(p) => doc.path == p,
+                None if doc.protected || !self.matches_filter(doc, filter) => false,
+                // Validating first spares the documents already in sync a re-hash
+                None if !filter.statuses.is_empty() => {
+                    let selected = filter.statuses.contains(&doc.validate()?.status);
+                    skipped += usize::from(!selected);
+                    selected
+                }
+                None => true,
+            };
+            if selected {
+                indices.push(i);
+            }
+        }
+        // Translations copy their source's freshly synced references
+        indices.sort_by_key(|&i| self.translation_source(i).is_some());
+        Ok((indices, skipped))
+    }
+
+    /// Path of the local document a sync of `slug_or_path` targets, looked
+    /// up like `get`; documents of remotes are read-only
+    pub fn sync_target(&self, slug_or_path: &str) -> Result<PathBuf> {
+        let doc = self.get(slug_or_path)?;
+        if let Some(remote) = &doc.remote {
+            return Err(ContextError::RemoteError(format!(
+                "{}: documents from remote '{remote}' are read-only",
+                doc.path.display()
+            )));
+        }
+        Ok(doc.path.clone())
+    }
+
+    /// Index of the source document of the translation at `idx`, if it is
+    /// one and its source exists
+    fn translation_source(&self, idx: usize) -> Option<usize> {
+        let (source, _) = i18n::source_of(&self.documents[idx].path, &self.config.i18n.locales)?;
+        self.documents.iter().position(|doc| doc.path == source)
+    }
+
+    /// Find the commits that made a document's references out of date
+    pub fn when_stale(&self, slug_or_path: &str) -> Result<StaleReport> {
+        let doc = self.get(slug_or_path)?;
+        let project_root = doc
+            .project_root()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let validation = doc.validate()?;
+
+        let mut references = Vec::new();
+        for reference in validation.changed.iter().chain(&validation.missing) {
+            self.cancellation.check()?;
+            let missing = validation.missing.contains(reference);
+            let synced_hash = &doc.references[reference];
+            references.push(drift::first_change(
+                &project_root,
+                reference,
+                synced_hash,
+                &doc.updated,
+                missing,
+            )?);
+        }
+        references.sort_by(|a, b| a.reference.cmp(&b.reference));
+
+        Ok(StaleReport {
+            document: doc.path.clone(),
+            status: validation.status,
+            updated: doc.updated.clone(),
+            references,
+        })
+    }
+
+    /// Check the validity status of all documents. After an interrupt, only
+    /// the documents checked so far are returned.
     pub fn status(&self) -> Result<Vec<Validation>> {
         let mut results = Vec::new();
         for doc in &self.documents {
-            results.push(doc.validate()?);
+            if self.cancellation.is_interrupted() {
+                break;
+            }
+            self.cancellation.check()?;
+            results.push(self.validate(doc)?);
         }
         Ok(results)
     }
 
+    /// The documents matching a filter, in path order, with their status
+    pub fn list(&self, filter: &StatusFilter) -> Result<Vec<ListedDocument>> {
+        let mut listed = Vec::new();
+        for validation in self.status_filtered(filter)? {
+            let Some(doc) = self.documents.iter().find(|d| d.path == validation.path) else {
+                continue;
+            };
+            listed.push(ListedDocument {
+                path: doc
+                    .path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&doc.path)
+                    .to_path_buf(),
+                slug: doc.slug.clone(),
+                description: doc.description.clone(),
+                status: validation.status,
+                references: doc.references.len(),
+                state: doc.state,
+            });
+        }
+        Ok(listed)
+    }
+
+    /// A document by slug or path, with its body and the outcome of
+    /// validating each of its references
+    pub fn show(&self, slug_or_path: &str) -> Result<ShownDocument> {
+        let doc = self.get_with_body(slug_or_path)?;
+        let validation = self.validate(&doc)?;
+        Ok(ShownDocument {
+            path: doc.path.clone(),
+            slug: doc.slug.clone(),
+            description: doc.description.clone(),
+            status: validation.status,
+            updated: doc.updated.clone(),
+            references: doc
+                .references
+                .keys()
+                .map(|r| (r.clone(), validation.outcome(r)))
+                .collect(),
+            body: doc.body.clone(),
+        })
+    }
+
+    /// Check the validity status of documents matching a filter, stopping
+    /// early like `status` after an interrupt
+    pub fn status_filtered(&self, filter: &StatusFilter) -> Result<Vec<Validation>> {
+        self.status_where(filter, None)
+    }
+
+    /// Like `status_filtered`, but only changes introduced since the merge
+    /// base of `base` and `HEAD` count: a reference that differs from its
+    /// synced hash but is unchanged since the merge base (the base branch
+    /// moved, or a rebase rewrote history) doesn't make its document stale.
+    pub fn status_since_merge_base(
+        &self,
+        filter: &StatusFilter,
+        base: &str,
+    ) -> Result<Vec<Validation>> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let merge_base = git::merge_base(project_root, base)?;
+        let introduced: HashSet<String> = git::changed_files(project_root, &merge_base)?
+            .into_iter()
+            .collect();
+        self.status_where(filter, Some(&introduced))
+    }
+
+    fn status_where(
+        &self,
+        filter: &StatusFilter,
+        introduced: Option<&HashSet<String>>,
+    ) -> Result<Vec<Validation>> {
+        let mut results = Vec::new();
+        for doc in &self.documents {
+            if !self.matches_filter(doc, filter) {
+                continue;
+            }
+            if self.cancellation.is_interrupted() {
+                break;
+            }
+            self.cancellation.check()?;
+            let mut validation = self.validate(doc)?;
+            if let Some(introduced) = introduced {
+                validation.retain_references(|r| introduced.contains(refindex::normalize(r)));
+            }
+            if filter.statuses.is_empty() || filter.statuses.contains(&validation.status) {
+                results.push(validation);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Validate a document, reporting each reference's outcome to subscribers
+    fn validate(&self, doc: &Document) -> Result<Validation> {
+        let mut validation = doc.validate().inspect_err(|e| {
+            self.subscribers.emit(|| Event::Error {
+                document: Some(doc.path.clone()),
+                message: e.to_string(),
+            });
+        })?;
+        // Scoped sections need the body, which low-memory mode unloaded
+        if validation.status != Status::Valid && !doc.has_body() {
+            if let Ok(doc) = self.with_body(doc) {
+                validation.sections = doc.validate_sections(&validation);
+            }
+        }
+        for unreadable in &validation.unreadable {
+            self.warn(
+                Some(doc.path.clone()),
+                format!(
+                    "could not read {}: {}",
+                    unreadable.reference, unreadable.error
+                ),
+            );
+        }
+
+        let mut references: Vec<_> = doc.references.keys().collect();
+        references.sort();
+        for reference in references {
+            self.subscribers.emit(|| Event::ReferenceValidated {
+                document: doc.path.clone(),
+                reference: reference.clone(),
+                outcome: validation.outcome(reference),
+            });
+        }
+
+        Ok(validation)
+    }
+
+    /// Check the document-level (non-status) criteria of a filter
+    fn matches_filter(&self, doc: &Document, filter: &StatusFilter) -> bool {
+        if filter
+            .document
+            .as_ref()
+            .is_some_and(|path| *path != doc.path)
+        {
+            return false;
+        }
+
+        if let Some(dir) = &filter.directory {
+            let relative = doc.path.strip_prefix(&self.root).unwrap_or(&doc.path);
+            if !relative.starts_with(dir) {
+                return false;
+            }
+        }
+
+        if let Some(tag) = &filter.tag {
+            if !doc.tags.contains(tag) {
+                return false;
+            }
+        }
+
+        if !filter.owners.is_empty() {
+            let owned = doc.owners.iter().any(|owner| {
+                let owner = owner.trim_start_matches('@');
+                filter
+                    .owners
+                    .iter()
+                    .any(|o| o.trim_start_matches('@').eq_ignore_ascii_case(owner))
+            });
+            if !owned {
+                return false;
+            }
+        }
+
+        if !filter.states.is_empty() && !filter.states.contains(&doc.state) {
+            return false;
+        }
+
+        if !filter.paths.is_empty() {
+            let references_any = doc.references.keys().any(|r| {
+                filter.paths.iter().any(|p| {
+                    let p = p.trim_start_matches("./").trim_end_matches('/');
+                    Path::new(r).starts_with(p)
+                })
+            });
+            if !references_any {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Sync (update hashes) for all or a specific document.
     ///
     /// This uses a two-phase approach for atomicity:
@@ -103,90 +749,585 @@ impl Cache {
     ///
     /// If any document has invalid references, no documents are modified.
     pub fn sync(&mut self, doc_path: Option<&Path>) -> Result<SyncResult> {
-        // Determine which documents to sync
-        let doc_indices: Vec<usize> = match doc_path {
-            Some(p) => self
-                .documents
-                .iter()
-                .enumerate()
-                .filter(|(_, doc)| doc.path == p)
-                .map(|(i, _)| i)
-                .collect(),
-            None => (0..self.documents.len()).collect(),
-        };
+        self.sync_reviewed(doc_path, None)
+    }
+
+    /// Sync like `sync`, recording `reviewer` as having reviewed the changes
+    /// to every stale or orphaned document synced. With `[review] require`,
+    /// such documents are only synced when a reviewer is given.
+    pub fn sync_reviewed(
+        &mut self,
+        doc_path: Option<&Path>,
+        reviewer: Option<&str>,
+    ) -> Result<SyncResult> {
+        self.sync_matching(doc_path, &StatusFilter::default(), reviewer)
+    }
+
+    /// Sync like `sync_reviewed`, but without `doc_path` only the
+    /// unprotected documents matching `filter`, e.g. only stale ones
+    pub fn sync_matching(
+        &mut self,
+        doc_path: Option<&Path>,
+        filter: &StatusFilter,
+        reviewer: Option<&str>,
+    ) -> Result<SyncResult> {
+        let (doc_indices, skipped) = self.sync_selection(doc_path, filter)?;
 
         // Phase 1: Validate all documents, collect all errors
-        let mut all_invalid: Vec<(PathBuf, Vec<InvalidReference>)> = Vec::new();
+        self.check_sync_references(&doc_indices)?;
 
-        for &idx in &doc_indices {
-            let doc = &self.documents[idx];
-            let invalid = doc.prepare_sync();
-            if !invalid.is_empty() {
-                all_invalid.push((doc.path.clone(), invalid));
+        // Record the reviews before anything is synced, so no stale document
+        // is re-hashed without its review on file
+        if reviewer.is_some() || self.config.review.require {
+            let pending = self.pending_review_matching(doc_path, filter)?;
+            if let Some(reviewer) = reviewer {
+                let timestamp = clock::now_utc().to_rfc3339();
+                let journal = review::journal(&self.root);
+                for validation in &pending {
+                    let review = Review::new(&self.root, timestamp.clone(), reviewer, validation);
+                    journal.append(&serde_json::to_string(&review)?)?;
+                }
+            } else if !pending.is_empty() {
+                return Err(ContextError::ReviewRequired {
+                    documents: pending.into_iter().map(|v| v.path).collect(),
+                });
             }
         }
 
-        // If any documents have invalid references, fail the entire sync
-        if !all_invalid.is_empty() {
-            return Err(ContextError::InvalidReferences {
-                count: all_invalid.len(),
-                documents: all_invalid,
-            });
-        }
-
         // Phase 2: All documents valid, perform the actual sync
         let mut result = SyncResult::new();
+        result.skipped = skipped;
+        if doc_path.is_none() {
+            result.protected = self.protected_matching(filter);
+        }
 
         for &idx in &doc_indices {
-            let doc = &mut self.documents[idx];
-            match doc.sync() {
-                Ok(()) => {
+            // Documents synced before a cancellation stay synced
+            if self.cancellation.is_interrupted() {
+                result.partial = true;
+                break;
+            }
+            self.cancellation.check()?;
+            self.load_body(idx)?;
+            let before: Vec<String> = self.documents[idx].references.keys().cloned().collect();
+            let mut pruned = Vec::new();
+            let synced = self.sync_document(idx, &mut pruned);
+            if self.is_low_memory() {
+                self.documents[idx].unload_body();
+            }
+            let doc = &self.documents[idx];
+            if !pruned.is_empty() {
+                result.pruned.insert(doc.path.clone(), pruned);
+            }
+            if self.cleanup && synced.is_ok() {
+                let mut dropped: Vec<String> = before
+                    .into_iter()
+                    .filter(|r| {
+                        !doc.references.contains_key(r)
+                            && !result.pruned.get(&doc.path).is_some_and(|p| p.contains(r))
+                    })
+                    .collect();
+                dropped.sort();
+                if !dropped.is_empty() {
+                    result.cleaned_references.insert(doc.path.clone(), dropped);
+                }
+            }
+            match synced {
+                Ok(true) => {
+                    self.subscribers.emit(|| Event::SyncApplied {
+                        document: doc.path.clone(),
+                    });
                     result.count += 1;
                     result.updated.push(doc.path.clone());
                 }
+                Ok(false) => {
+                    result.count += 1;
+                    result.unchanged.push(doc.path.clone());
+                }
                 Err(e) => {
                     // This shouldn't happen since we validated, but handle it gracefully
+                    self.subscribers.emit(|| Event::Error {
+                        document: Some(doc.path.clone()),
+                        message: e.to_string(),
+                    });
                     result.failed.push(format!("{}: {}", doc.path.display(), e));
                 }
             }
         }
 
+        if self.cleanup && doc_path.is_none() && !result.partial {
+            self.clean_unsynced(&doc_indices, &mut result)?;
+        }
+
+        // Housekeeping must never fail a sync that already succeeded
+        if doc_path.is_none() && self.config.trend.record && !result.partial {
+            if let Err(e) = self
+                .summary()
+                .and_then(|summary| self.record_trend(&summary))
+            {
+                self.warn(None, format!("recording trend failed: {e}"));
+            }
+        }
+        if self.config.gc.after_sync && !result.partial {
+            if let Err(e) = gc::collect(&self.root, &self.config.gc, gc::Mode::Light) {
+                self.warn(None, format!("garbage collection failed: {e}"));
+            }
+        }
+
         Ok(result)
     }
 
+    /// What `sync_matching` would change, without writing anything: the
+    /// references each document would gain or lose and the hashes that
+    /// would change. Fails on invalid references just as a sync would.
+    pub fn sync_plan(&self, doc_path: Option<&Path>, filter: &StatusFilter) -> Result<SyncPlan> {
+        let doc_indices = self.sync_indices(doc_path, filter)?;
+        self.check_sync_references(&doc_indices)?;
+
+        let mut plan = SyncPlan::default();
+        if doc_path.is_none() {
+            plan.protected = self.protected_matching(filter);
+        }
+        for &idx in &doc_indices {
+            self.cancellation.check()?;
+            let source = self
+                .translation_source(idx)
+                .map(|s| self.source_state(s))
+                .transpose()?;
+            let before = self.with_body(&self.documents[idx])?;
+            let mut after = before.clone().into_owned();
+            let changed = after.refresh_sharing(
+                source,
+                self.config.updated,
+                self.prune_missing.then_some(&mut Vec::new()),
+            )?;
+            if !changed && after.external_metadata == self.storage.external() {
+                plan.unchanged.push(after.path);
+                continue;
+            }
+
+            let change = |from: &str, to: &str| {
+                (from != to).then(|| HashChange {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                })
+            };
+            let mut planned = PlannedSync {
+                path: after.path.clone(),
+                hash: change(&before.hash, &after.hash),
+                body_hash: change(
+                    before.body_hash.as_deref().unwrap_or_default(),
+                    after.body_hash.as_deref().unwrap_or_default(),
+                ),
+                updated: (after.updated != before.updated).then(|| after.updated.clone()),
+                ..PlannedSync::default()
+            };
+            for (reference, hash) in &after.references {
+                match before.references.get(reference) {
+                    None => planned.added.push(reference.clone()),
+                    Some(old) => {
+                        if let Some(rehashed) = change(old, hash) {
+                            planned.rehashed.insert(reference.clone(), rehashed);
+                        }
+                    }
+                }
+            }
+            planned.removed = before
+                .references
+                .keys()
+                .filter(|r| !after.references.contains_key(*r))
+                .cloned()
+                .collect();
+            planned.added.sort();
+            planned.removed.sort();
+            plan.changes.push(planned);
+        }
+        if self.cleanup && doc_path.is_none() {
+            self.plan_cleanup(&doc_indices, &mut plan)?;
+        }
+        Ok(plan)
+    }
+
+    /// Fail with every invalid reference among the documents at
+    /// `doc_indices`, so a sync writes nothing if any document can't sync
+    fn check_sync_references(&self, doc_indices: &[usize]) -> Result<()> {
+        let mut all_invalid: Vec<(PathBuf, Vec<InvalidReference>)> = Vec::new();
+        for &idx in doc_indices {
+            self.cancellation.check()?;
+            let doc = self.with_body(&self.documents[idx])?;
+            let invalid = doc.prepare_sync_pruning(self.prune_missing);
+            if !invalid.is_empty() {
+                all_invalid.push((doc.path.clone(), invalid));
+            }
+        }
+
+        if all_invalid.is_empty() {
+            return Ok(());
+        }
+        for (path, invalid) in &all_invalid {
+            for reference in invalid {
+                self.subscribers.emit(|| Event::Error {
+                    document: Some(path.clone()),
+                    message: format!("{}: {}", reference.path, reference.reason),
+                });
+            }
+        }
+        Err(ContextError::InvalidReferences {
+            count: all_invalid.len(),
+            documents: all_invalid,
+        })
+    }
+
+    /// Protected documents matching `filter`, which bulk syncs skip
+    fn protected_matching(&self, filter: &StatusFilter) -> Vec<PathBuf> {
+        self.documents
+            .iter()
+            .filter(|d| d.protected && self.matches_filter(d, filter))
+            .map(|d| d.path.clone())
+            .collect()
+    }
+
+    /// Index local and remote documents by the source paths they reference
+    pub fn reference_index(&self) -> ReferenceIndex<'_> {
+        ReferenceIndex::build(self.documents.iter().chain(&self.remote_documents))
+    }
+
     /// Find documents that reference the given source file path.
     ///
     /// The source_path should be relative to the project root (e.g., "src/core/models.rs").
     /// Returns a FindResult containing all documents that reference this file.
     pub fn find_by_reference(&self, source_path: &str) -> Result<FindResult> {
-        let mut matches = Vec::new();
+        self.find_in(&self.reference_index(), source_path)
+    }
 
-        // Normalize the search path (remove leading ./ if present)
-        let normalized = source_path.trim_start_matches("./");
+    /// Find the documents referencing each of `source_paths`, building the
+    /// reverse index once for all of them
+    pub fn find_by_references(&self, source_paths: &[String]) -> Result<Vec<FindResult>> {
+        let index = self.reference_index();
+        source_paths
+            .iter()
+            .map(|p| self.find_in(&index, p))
+            .collect()
+    }
 
-        for doc in &self.documents {
-            // Check if this document references the given path
-            for ref_path in doc.references.keys() {
-                let ref_normalized = ref_path.trim_start_matches("./");
-                if ref_normalized == normalized {
-                    // Get the validation status for this document
-                    let validation = doc.validate()?;
-                    matches.push(FindMatch {
-                        document: doc.path.clone(),
-                        reference: ref_path.clone(),
-                        status: validation.status,
-                    });
-                    break; // Only add each document once per query
-                }
+    /// The documents covering a build target, whose files come from the
+    /// `[query] resolver` command (see `core::query`)
+    pub fn query_target(&self, target: &str) -> Result<TargetQuery> {
+        let resolver = self.config.query.resolver.as_deref().ok_or_else(|| {
+            ContextError::ConfigError(
+                "no [query] resolver configured to map build targets to files".to_string(),
+            )
+        })?;
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let files = query::resolve(resolver, target, project_root)?;
+
+        let mut documents: BTreeMap<PathBuf, TargetDocument> = BTreeMap::new();
+        for found in self.find_by_references(&files)? {
+            for m in found.matches.into_iter().filter(|m| m.remote.is_none()) {
+                documents
+                    .entry(m.document.clone())
+                    .or_insert_with(|| TargetDocument {
+                        document: m.document,
+                        status: m.status,
+                        references: Vec::new(),
+                    })
+                    .references
+                    .push(m.reference);
             }
         }
+        Ok(TargetQuery {
+            target: target.to_string(),
+            files,
+            documents: documents.into_values().collect(),
+        })
+    }
+
+    fn find_in(&self, index: &ReferenceIndex<'_>, source_path: &str) -> Result<FindResult> {
+        let normalized = refindex::normalize(source_path);
+        let mut matches = Vec::new();
+        for doc in index.lookup(normalized) {
+            self.cancellation.check()?;
+            let Some(reference) = doc
+                .references
+                .keys()
+                .find(|r| refindex::normalize(r) == normalized)
+            else {
+                continue;
+            };
+            let validation = doc.validate()?;
+            let is = |list: &[String]| list.iter().any(|r| refindex::normalize(r) == normalized);
+            let reference_status = if is(&validation.missing) {
+                Status::Orphaned
+            } else if is(&validation.changed)
+                || validation
+                    .unreadable
+                    .iter()
+                    .any(|u| refindex::normalize(&u.reference) == normalized)
+            {
+                Status::Stale
+            } else {
+                Status::Valid
+            };
+            self.warn_if_deprecated(doc);
+            let location = self.with_body(doc)?.locate_reference(normalized);
+            matches.push(FindMatch {
+                document: doc.path.clone(),
+                reference: reference.clone(),
+                status: validation.status,
+                reference_status,
+                line: location.as_ref().map(|(line, _)| *line),
+                anchor: location.and_then(|(_, anchor)| anchor),
+                remote: doc.remote.clone(),
+            });
+        }
 
         Ok(FindResult {
             query: source_path.to_string(),
+            total: matches.len(),
             matches,
         })
     }
 
+    /// Gather everything documented about one source file.
+    ///
+    /// Each referencing document contributes its status, owners, last update
+    /// and the section around the first mention of the file (at most
+    /// `EXCERPT_LINES` lines of it).
+    pub fn explain(&self, source_path: &str) -> Result<Explanation> {
+        const EXCERPT_LINES: usize = 20;
+
+        let found = self.find_by_reference(source_path)?;
+        let mut documents = Vec::new();
+        for m in found.matches {
+            let Some(doc) = self.documents.iter().find(|d| d.path == m.document) else {
+                continue;
+            };
+            let doc = self.with_body(doc)?;
+            let validation = doc.validate()?;
+
+            let excerpt = m.line.map(|line| {
+                let index = sections::index(&doc.body);
+                let text = sections::section_at(&index, line).map_or_else(
+                    || {
+                        doc.body
+                            .lines()
+                            .nth(line - 1)
+                            .unwrap_or_default()
+                            .to_string()
+                    },
+                    |section| sections::extract(&doc.body, section),
+                );
+                let mut lines: Vec<&str> = text.lines().take(EXCERPT_LINES + 1).collect();
+                if lines.len() > EXCERPT_LINES {
+                    lines[EXCERPT_LINES] = "...";
+                }
+                lines.join("\n")
+            });
+
+            documents.push(ExplainedDocument {
+                document: doc.path.clone(),
+                slug: doc.slug.clone(),
+                description: doc.description.clone(),
+                status: validation.status,
+                changed: validation.changed.contains(&m.reference)
+                    || validation.missing.contains(&m.reference),
+                owners: doc.owners.clone(),
+                updated: doc.updated.clone(),
+                line: m.line,
+                anchor: m.anchor,
+                excerpt,
+            });
+        }
+
+        let normalized = source_path.trim_start_matches("./");
+        let exists = self
+            .root
+            .parent()
+            .is_some_and(|project| project.join(normalized).exists());
+
+        Ok(Explanation {
+            source: source_path.to_string(),
+            exists,
+            documents,
+        })
+    }
+
+    /// Local documents referencing any of `files` (project-relative paths),
+    /// leaving out documents that are among `files` themselves, since those
+    /// are being updated already
+    pub fn documents_referencing(&self, files: &[String]) -> Vec<&Document> {
+        let files: HashSet<&str> = files.iter().map(|f| f.trim_start_matches("./")).collect();
+        let project_root = self.root.parent().unwrap_or(&self.root);
+        self.documents
+            .iter()
+            .filter(|doc| {
+                let own = doc
+                    .path
+                    .strip_prefix(project_root)
+                    .unwrap_or(&doc.path)
+                    .to_string_lossy();
+                !files.contains(own.as_ref())
+                    && doc
+                        .references
+                        .keys()
+                        .any(|r| files.contains(r.trim_start_matches("./")))
+            })
+            .collect()
+    }
+
+    /// Re-derive one loaded document's references and hashes, saving it only
+    /// if they changed. Returns whether it was saved; references pruned as
+    /// missing (with `set_prune_missing`) are added to `pruned`.
+    fn sync_document(&mut self, idx: usize, pruned: &mut Vec<String>) -> Result<bool> {
+        let source = self
+            .translation_source(idx)
+            .map(|s| self.source_state(s))
+            .transpose()?;
+        let doc = &mut self.documents[idx];
+        let changed = doc.refresh_sharing(
+            source,
+            self.config.updated,
+            self.prune_missing.then_some(pruned),
+        )?;
+        // Leave documents whose hashes didn't change untouched on disk
+        if !changed && doc.external_metadata == self.storage.external() {
+            return Ok(false);
+        }
+        self.storage.save(doc)?;
+        Ok(true)
+    }
+
+    /// References and current body hash of a translation's source document,
+    /// which the translation records when synced
+    fn source_state(&self, idx: usize) -> Result<(HashMap<String, String>, String)> {
+        let source = self.with_body(&self.documents[idx])?;
+        Ok((
+            source.references.clone(),
+            document::hash(source.body.as_bytes()),
+        ))
+    }
+
+    /// A document with its body, reading the body from disk if it was unloaded
+    pub fn with_body<'a>(&self, doc: &'a Document) -> Result<Cow<'a, Document>> {
+        match &self.bodies {
+            Some(bodies) if !doc.has_body() => {
+                let mut bodies = bodies.lock().unwrap_or_else(PoisonError::into_inner);
+                Ok(Cow::Owned(doc.with_body_of(&*bodies.get(&doc.path)?)))
+            }
+            _ => Ok(Cow::Borrowed(doc)),
+        }
+    }
+
+    /// Look up a document like `get`, with its body loaded
+    pub fn get_with_body(&self, slug_or_path: &str) -> Result<Cow<'_, Document>> {
+        self.with_body(self.get(slug_or_path)?)
+    }
+
+    /// Bring an unloaded body back into memory before a document is modified
+    fn load_body(&mut self, idx: usize) -> Result<()> {
+        if let Some(bodies) = &self.bodies {
+            let doc = &mut self.documents[idx];
+            if !doc.has_body() {
+                let mut bodies = bodies.lock().unwrap_or_else(PoisonError::into_inner);
+                // The document is about to change, so don't serve a stale copy later
+                *doc = Document::load(&doc.path)?;
+                self.storage.load(doc)?;
+                bodies.invalidate(&doc.path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop a body again in low-memory mode once an operation is done with it
+    fn release_body(&mut self, idx: usize) {
+        if self.is_low_memory() {
+            if let Some(doc) = self.documents.get_mut(idx) {
+                doc.unload_body();
+            }
+        }
+    }
+
+    /// Look up a loaded document by slug or path.
+    ///
+    /// Paths may be given relative to the current directory or to the
+    /// context root (e.g. `guides/auth.md`). Anything else is matched
+    /// against document slugs, both the frontmatter slug and the slug derived
+    /// from the path (`guides/auth/tokens`), falling back to the file name
+    /// alone (`tokens`). A namespaced slug such as `platform/auth` or
+    /// `local/auth` only matches in that namespace; a bare slug is looked up
+    /// in the configured default namespace first, then in all the others.
+    pub fn get(&self, slug_or_path: &str) -> Result<&Document> {
+        let as_path = Path::new(slug_or_path);
+        for candidate in [as_path.to_path_buf(), self.root.join(as_path)] {
+            if let Ok(resolved) = self.resolve_doc_path(&candidate) {
+                let mut all = self.documents.iter().chain(&self.remote_documents);
+                if let Some(doc) = all.find(|d| d.path == resolved) {
+                    return Ok(doc);
+                }
+            }
+        }
+
+        let matches =
+            self.slug_matches(slug_or_path, |d, slug| d.slugs().iter().any(|s| s == slug));
+        let matches = if matches.is_empty() {
+            self.slug_matches(slug_or_path, |d, slug| d.leaf_slug() == slug)
+        } else {
+            matches
+        };
+
+        match matches.as_slice() {
+            [doc] => Ok(doc),
+            [first, second, ..] if first.namespace() == second.namespace() => {
+                Err(ContextError::InvalidDocument(format!(
+                    "ambiguous slug '{slug_or_path}' matches {} and {}; use a path instead",
+                    first.path.display(),
+                    second.path.display()
+                )))
+            }
+            [first, second, ..] => Err(ContextError::InvalidDocument(format!(
+                "ambiguous slug '{slug_or_path}' matches {} and {}; use a namespaced slug instead",
+                first.qualified_slug(),
+                second.qualified_slug()
+            ))),
+            [] => Err(ContextError::DocumentNotFound(slug_or_path.to_string())),
+        }
+    }
+
+    /// Documents whose slug satisfies `matches`, honouring namespace prefixes
+    /// and preferring the default namespace for bare slugs
+    fn slug_matches(
+        &self,
+        slug_or_path: &str,
+        matches: impl Fn(&Document, &str) -> bool,
+    ) -> Vec<&Document> {
+        let all = || self.documents.iter().chain(&self.remote_documents);
+        let namespaced = slug_or_path
+            .split_once('/')
+            .filter(|(namespace, _)| self.is_namespace(namespace));
+        if let Some((namespace, slug)) = namespaced {
+            return all()
+                .filter(|d| d.namespace() == namespace && matches(d, slug))
+                .collect();
+        }
+        let default = &self.config.namespaces.default;
+        let in_default: Vec<_> = all()
+            .filter(|d| d.namespace() == default && matches(d, slug_or_path))
+            .collect();
+        if in_default.is_empty() {
+            all().filter(|d| matches(d, slug_or_path)).collect()
+        } else {
+            in_default
+        }
+    }
+
+    /// Whether `name` is the local namespace or a configured remote
+    fn is_namespace(&self, name: &str) -> bool {
+        name == LOCAL_NAMESPACE || self.config.remotes.iter().any(|r| r.name == name)
+    }
+
     /// Resolve and validate a document path for selective sync.
     ///
     /// Returns the canonicalized path if valid, or an error if:
@@ -195,14 +1336,12 @@ impl Cache {
     /// - The path is not a markdown file
     pub fn resolve_doc_path(&self, user_path: &Path) -> Result<PathBuf> {
         // Canonicalize the user-provided path
-        let canonical = user_path.canonicalize().map_err(|_| {
-            ContextError::DocumentNotFound(user_path.display().to_string())
-        })?;
+        let canonical = user_path
+            .canonicalize()
+            .map_err(|_| ContextError::DocumentNotFound(user_path.display().to_string()))?;
 
         // Canonicalize the context root for comparison
-        let canonical_root = self.root.canonicalize().map_err(|e| {
-            ContextError::IoError(e)
-        })?;
+        let canonical_root = self.root.canonicalize().map_err(ContextError::IoError)?;
 
         // Verify the path is within the .context directory
         if !canonical.starts_with(&canonical_root) {
@@ -0,0 +1,580 @@
+//! Creating, splitting, merging and archiving documents
+
+use super::Cache;
+use crate::core::archive::{self, ArchiveEntry, ArchiveResult, ArchivedDocument};
+use crate::core::capture::{self, Capture};
+use crate::core::clock;
+use crate::core::conflict;
+use crate::core::describe::{self, Candidate};
+use crate::core::document::{self, Document};
+use crate::core::encoding;
+use crate::core::fsio::{self, ReadPolicy};
+use crate::core::git;
+use crate::core::import::{self, ReferenceDeclaration};
+use crate::core::links;
+use crate::core::markdown_files;
+use crate::core::merge::{self, MergeResult};
+use crate::core::models::{DocumentKind, DocumentState, ImportResult, ResolveResult};
+use crate::core::paths;
+use crate::core::split::{self, SplitPlan};
+use crate::error::{ContextError, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+impl Cache {
+    /// Merge externally declared references into documents.
+    ///
+    /// Each declaration is recorded in the document's references and in its
+    /// `external_references`, so later syncs keep (and re-hash) it even
+    /// though the body never mentions the file. Declarations naming unknown
+    /// documents, invalid paths or malformed hashes are reported and skipped.
+    pub fn import_references(
+        &mut self,
+        declarations: &[ReferenceDeclaration],
+    ) -> Result<ImportResult> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::SyncError("Could not determine project root".to_string()))?
+            .to_path_buf();
+
+        let mut result = ImportResult::default();
+        let mut changed = Vec::new();
+
+        for decl in declarations {
+            let idx = match self.get(&decl.doc) {
+                Ok(doc) => {
+                    let path = doc.path.clone();
+                    self.documents.iter().position(|d| d.path == path)
+                }
+                Err(e) => {
+                    result
+                        .failed
+                        .push(format!("{} -> {}: {e}", decl.path, decl.doc));
+                    continue;
+                }
+            };
+            let Some(idx) = idx else { continue };
+
+            let normalized = match paths::validate_path(&decl.path, &project_root) {
+                Ok(normalized) => normalized,
+                Err(reason) => {
+                    result
+                        .failed
+                        .push(format!("{} -> {}: {reason}", decl.path, decl.doc));
+                    continue;
+                }
+            };
+
+            let hash = match &decl.hash {
+                Some(hash) if import::is_short_hash(hash) => hash.clone(),
+                Some(hash) => {
+                    let e = ContextError::InvalidHashFormat(hash.clone());
+                    result
+                        .failed
+                        .push(format!("{} -> {}: {e}", decl.path, decl.doc));
+                    continue;
+                }
+                None => document::hash(&fsio::read(
+                    &project_root.join(&normalized),
+                    ReadPolicy::from(&self.config.io),
+                )?),
+            };
+
+            let doc = &mut self.documents[idx];
+            if !doc.external_references.contains(&normalized) {
+                doc.external_references.push(normalized.clone());
+            }
+            doc.references.insert(normalized, hash);
+            result.imported += 1;
+            if !changed.contains(&idx) {
+                changed.push(idx);
+            }
+        }
+
+        changed.sort_unstable();
+        for idx in changed {
+            let doc = &self.documents[idx];
+            self.storage.save(doc)?;
+            result.updated.push(doc.path.clone());
+        }
+
+        Ok(result)
+    }
+
+    /// Draft a new document from the commits in a git revision range (see
+    /// `core::capture`). The slug defaults to the name of the revision the
+    /// range ends at, or the current branch for `HEAD`, and the document is
+    /// written to `<slug>.md` unless `path` (relative to the context
+    /// directory) is given. Its references are synced right away.
+    ///
+    /// With `dry_run`, the draft is returned without writing anything.
+    /// Fails without writing if the target file exists.
+    pub fn capture(
+        &mut self,
+        range: &str,
+        slug: Option<&str>,
+        path: Option<&Path>,
+        dry_run: bool,
+    ) -> Result<Capture> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?
+            .to_path_buf();
+        let commits = git::commits(&project_root, range)?;
+        if commits.is_empty() {
+            return Err(ContextError::Other(format!("no commits in {range}")));
+        }
+        // Deleted files can't be referenced
+        let files: Vec<String> = git::changed_files(&project_root, range)?
+            .into_iter()
+            .filter(|f| !f.starts_with(".context/") && project_root.join(f).is_file())
+            .collect();
+
+        let end = capture::range_end(range);
+        let name = match end {
+            "" | "HEAD" => git::info(&project_root)
+                .and_then(|i| i.branch)
+                .unwrap_or_else(|| end.to_string()),
+            _ => end.to_string(),
+        };
+        let slug = slug.map_or_else(|| capture::slugify(&name), str::to_string);
+        if slug.is_empty() {
+            return Err(ContextError::InvalidDocument(format!(
+                "cannot derive a slug from '{name}', pass --slug"
+            )));
+        }
+        let path = self
+            .root
+            .join(path.map_or_else(|| PathBuf::from(format!("{slug}.md")), Path::to_path_buf));
+        if path.exists() {
+            return Err(ContextError::InvalidDocument(format!(
+                "refusing to overwrite existing document {}",
+                path.display()
+            )));
+        }
+
+        let body = capture::draft_body(&name, &commits, &files);
+        let mut result = Capture {
+            range: range.to_string(),
+            path: path.clone(),
+            slug: slug.clone(),
+            commits,
+            references: files,
+            body: body.clone(),
+            written: false,
+        };
+        if dry_run {
+            return Ok(result);
+        }
+
+        let description = result.commits[0].subject.clone();
+        let updated = self.config.updated.stamp();
+        let doc = Document::new(
+            path.clone(),
+            slug,
+            description,
+            HashMap::new(),
+            updated,
+            String::new(),
+            body,
+        );
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.storage.save(&doc)?;
+        self.documents.push(doc);
+        self.documents.sort_by(|a, b| a.path.cmp(&b.path));
+        self.sync(Some(&path))?;
+        result.written = true;
+        Ok(result)
+    }
+
+    /// Scaffold an empty document of `kind` at `<kind dir>/<slug>.md`, with
+    /// frontmatter for the slug, an empty description and no references.
+    /// Fails without writing if the file exists or the slug is taken.
+    pub fn new_document(&mut self, kind: DocumentKind, slug: &str) -> Result<PathBuf> {
+        if slug.is_empty() || slug.starts_with('.') || slug.contains(['/', '\\']) {
+            return Err(ContextError::InvalidDocument(format!(
+                "invalid slug '{slug}': use a single name such as 'auth-flow'"
+            )));
+        }
+        if let Some(doc) = self
+            .documents
+            .iter()
+            .find(|d| d.slugs().iter().any(|s| s == slug))
+        {
+            return Err(ContextError::InvalidDocument(format!(
+                "slug '{slug}' is already used by {}",
+                doc.path.display()
+            )));
+        }
+        let path = self.root.join(kind.directory()).join(format!("{slug}.md"));
+        if path.exists() {
+            return Err(ContextError::InvalidDocument(format!(
+                "refusing to overwrite existing document {}",
+                path.display()
+            )));
+        }
+
+        let updated = self.config.updated.stamp();
+        let body = format!("# {}\n", capture::title(slug));
+        let doc = Document::new(
+            path.clone(),
+            slug.to_string(),
+            String::new(),
+            HashMap::new(),
+            updated,
+            String::new(),
+            body,
+        );
+        std::fs::create_dir_all(self.root.join(kind.directory()))?;
+        self.storage.save(&doc)?;
+        self.documents.push(doc);
+        self.documents.sort_by(|a, b| a.path.cmp(&b.path));
+        self.sync(Some(&path))?;
+        Ok(path)
+    }
+
+    /// Split a document into one document per top-level section.
+    ///
+    /// With `dry_run`, the plan is returned without writing anything.
+    /// Otherwise the pieces are written next to the original, the original is
+    /// rewritten with links to them, and the sibling index.md (if any) gains
+    /// an entry per piece. Fails without writing if any target file exists.
+    pub fn split(&mut self, doc_path: &Path, dry_run: bool) -> Result<SplitPlan> {
+        let idx = self
+            .documents
+            .iter()
+            .position(|d| d.path == doc_path)
+            .ok_or_else(|| ContextError::DocumentNotFound(doc_path.display().to_string()))?;
+
+        self.load_body(idx)?;
+        let plan = split::plan(&self.documents[idx])?;
+        if let Some(existing) = plan.pieces.iter().find(|p| p.path.exists()) {
+            return Err(ContextError::InvalidDocument(format!(
+                "refusing to overwrite existing document {}",
+                existing.path.display()
+            )));
+        }
+        if dry_run {
+            return Ok(plan);
+        }
+
+        for piece in &plan.pieces {
+            self.storage.save(piece)?;
+        }
+        self.storage.save(&plan.remainder)?;
+
+        // List the new pieces in the sibling index, if there is one
+        let index_path = doc_path.with_file_name("index.md");
+        let index_idx = self.documents.iter().position(|d| d.path == index_path);
+        if let Some(index_idx) = index_idx.filter(|_| index_path != doc_path) {
+            self.load_body(index_idx)?;
+            {
+                let index = &mut self.documents[index_idx];
+                if !index.body.is_empty() && !index.body.ends_with('\n') {
+                    index.body.push('\n');
+                }
+                for piece in &plan.pieces {
+                    let file_name = piece.path.file_name().unwrap_or_default().to_string_lossy();
+                    let _ = writeln!(index.body, "- [{}]({file_name})", piece.description);
+                }
+                self.storage.save(index)?;
+            }
+            self.release_body(index_idx);
+        }
+
+        self.documents[idx] = plan.remainder.clone();
+        let len_before = self.documents.len();
+        self.documents.extend(plan.pieces.iter().cloned());
+        if self.is_low_memory() {
+            self.release_body(idx);
+            for doc in &mut self.documents[len_before..] {
+                doc.unload_body();
+            }
+        }
+        self.documents.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(plan)
+    }
+
+    /// Merge the document at `b_path` into the one at `a_path`.
+    ///
+    /// The merged-away document is deleted when `delete` is set, and
+    /// otherwise reduced to a stub marked `superseded_by`. Links in every
+    /// other document that pointed at it are re-pointed at the merged one.
+    pub fn merge(&mut self, a_path: &Path, b_path: &Path, delete: bool) -> Result<MergeResult> {
+        let find = |p: &Path| {
+            self.documents
+                .iter()
+                .position(|d| d.path == p)
+                .ok_or_else(|| ContextError::DocumentNotFound(p.display().to_string()))
+        };
+        let (a_idx, b_idx) = (find(a_path)?, find(b_path)?);
+        if a_idx == b_idx {
+            return Err(ContextError::InvalidDocument(
+                "cannot merge a document into itself".to_string(),
+            ));
+        }
+
+        self.load_body(a_idx)?;
+        self.load_body(b_idx)?;
+        let merged = merge::merge(&self.documents[a_idx], &self.documents[b_idx])?;
+        self.storage.save(&merged)?;
+
+        if delete {
+            std::fs::remove_file(b_path)?;
+            self.storage.remove(b_path)?;
+        } else {
+            let stub = merge::supersede(&self.documents[b_idx], &merged);
+            self.storage.save(&stub)?;
+            self.documents[b_idx] = stub;
+        }
+        self.documents[a_idx] = merged;
+
+        let mut relinked = Vec::new();
+        for idx in 0..self.documents.len() {
+            if idx == b_idx {
+                continue;
+            }
+            self.load_body(idx)?;
+            let doc = &mut self.documents[idx];
+            let (body, changed) = links::rewrite(&doc.body, &doc.path, b_path, a_path);
+            if changed > 0 {
+                doc.body = body;
+                self.storage.save(doc)?;
+                relinked.push(doc.path.clone());
+            }
+            self.release_body(idx);
+        }
+        self.release_body(b_idx);
+
+        if delete {
+            self.documents.remove(b_idx);
+        }
+
+        Ok(MergeResult {
+            merged: a_path.to_path_buf(),
+            removed: b_path.to_path_buf(),
+            deleted: delete,
+            relinked,
+        })
+    }
+
+    /// Move deprecated documents due under `[archive] after_days` (or
+    /// `older_than` days, if given) into `.context/archive/`, rewriting
+    /// links to and from them, and record each move in the archive journal.
+    /// With `dry_run`, report what would move without writing anything.
+    pub fn archive(&mut self, older_than: Option<u32>, dry_run: bool) -> Result<ArchiveResult> {
+        let after_days = older_than.unwrap_or(self.config.archive.after_days);
+        let today = clock::today();
+        let mut moves: HashMap<usize, PathBuf> = HashMap::new();
+        for (idx, doc) in self.documents.iter().enumerate() {
+            if doc.state != DocumentState::Deprecated
+                || archive::is_archived(&self.root, &doc.path)
+                || !archive::is_due(&doc.updated, today, after_days)
+            {
+                continue;
+            }
+            let Some(to) = archive::destination(&self.root, &doc.path) else {
+                continue;
+            };
+            if to.exists() {
+                return Err(ContextError::Other(format!(
+                    "{} is already archived at {}",
+                    doc.slug,
+                    to.display()
+                )));
+            }
+            moves.insert(idx, to);
+        }
+
+        let renames: Vec<(PathBuf, PathBuf)> = moves
+            .iter()
+            .map(|(idx, to)| (self.documents[*idx].path.clone(), to.clone()))
+            .collect();
+        let mut result = ArchiveResult {
+            dry_run,
+            ..ArchiveResult::default()
+        };
+        for idx in 0..self.documents.len() {
+            self.cancellation.check()?;
+            self.load_body(idx)?;
+            let doc = &self.documents[idx];
+            let from = doc.path.clone();
+            let at = moves.get(&idx).cloned().unwrap_or_else(|| from.clone());
+            let mut body = doc.body.clone();
+            if doc.is_locked() {
+                if at != from {
+                    self.warn(
+                        Some(from.clone()),
+                        "links in an encrypted document can't be rewritten when archiving it"
+                            .to_string(),
+                    );
+                }
+            } else {
+                // A moved body first keeps pointing at the same documents from
+                // its new place, then follows every document that moves
+                if at != from {
+                    body = links::rebase(&body, &from, &at);
+                }
+                for (old, new) in &renames {
+                    body = links::rewrite(&body, &at, old, new).0;
+                }
+            }
+
+            let relinked = body != doc.body;
+            if at != from {
+                result.archived.push(ArchivedDocument {
+                    slug: doc.slug.clone(),
+                    from: from.clone(),
+                    to: at.clone(),
+                });
+            } else if relinked {
+                result.relinked.push(from.clone());
+            }
+            if !dry_run && (relinked || at != from) {
+                let mut doc = doc.clone();
+                doc.body = body;
+                doc.path.clone_from(&at);
+                if at != from {
+                    if let Some(parent) = at.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                }
+                self.storage.save(&doc)?;
+                if at != from {
+                    std::fs::remove_file(&from)?;
+                    self.storage.remove(&from)?;
+                }
+                self.documents[idx] = doc;
+            }
+            self.release_body(idx);
+        }
+
+        if !dry_run {
+            self.journal_archived(&result.archived)?;
+            if !self.include_archived {
+                let root = self.root.clone();
+                self.documents
+                    .retain(|d| !archive::is_archived(&root, &d.path));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Record documents `archive` moved in the archive journal, with paths
+    /// relative to the context root
+    fn journal_archived(&self, archived: &[ArchivedDocument]) -> Result<()> {
+        let journal = archive::journal(&self.root);
+        let timestamp = clock::now_utc().to_rfc3339();
+        for archived in archived {
+            let entry = ArchiveEntry {
+                timestamp: timestamp.clone(),
+                slug: archived.slug.clone(),
+                from: archived
+                    .from
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&archived.from)
+                    .to_path_buf(),
+                to: archived
+                    .to
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&archived.to)
+                    .to_path_buf(),
+            };
+            journal.append(&serde_json::to_string(&entry)?)?;
+        }
+        Ok(())
+    }
+
+    /// Repair documents damaged by a conflicting git merge.
+    ///
+    /// Works on raw files rather than loaded documents, since conflicted
+    /// documents fail to load. Each damaged document has its frontmatter
+    /// repaired and its references re-derived from the body and re-hashed.
+    pub fn resolve(&self, doc_path: Option<&Path>) -> Result<ResolveResult> {
+        let paths: Vec<PathBuf> = match doc_path {
+            Some(path) => vec![path.to_path_buf()],
+            None => markdown_files(&self.root).collect(),
+        };
+
+        let mut result = ResolveResult::default();
+        for path in paths {
+            let content = encoding::read(&path)?.text;
+            if conflict::detect(&content).is_none() {
+                continue;
+            }
+
+            let resolved = conflict::repair(&path, &content)
+                .and_then(|repaired| Document::from_content(&path, &repaired))
+                .and_then(|mut doc| {
+                    self.storage.load(&mut doc)?;
+                    doc.refresh()?;
+                    self.storage.save(&doc)
+                });
+            match resolved {
+                Ok(()) => result.resolved.push(path),
+                Err(ContextError::InvalidReferences { documents, .. }) => {
+                    for (doc_path, refs) in documents {
+                        for r in refs {
+                            result.failed.push(format!(
+                                "{}: {} ({})",
+                                doc_path.display(),
+                                r.path,
+                                r.reason
+                            ));
+                        }
+                    }
+                }
+                Err(e) => result.failed.push(format!("{}: {e}", path.display())),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Generate candidate descriptions.
+    ///
+    /// With a path, describes that document; otherwise every document whose
+    /// description is empty. When `apply` is set, candidates are written to
+    /// frontmatter. Encrypted documents that can't be read are skipped.
+    pub fn describe(&mut self, doc_path: Option<&Path>, apply: bool) -> Result<Vec<Candidate>> {
+        let mut candidates = Vec::new();
+        for idx in 0..self.documents.len() {
+            let doc = &self.documents[idx];
+            let selected = match doc_path {
+                Some(path) => doc.path == path,
+                None => doc.description.trim().is_empty(),
+            };
+            if !selected {
+                continue;
+            }
+            self.load_body(idx)?;
+            let doc = &mut self.documents[idx];
+            if doc.is_locked() {
+                self.release_body(idx);
+                continue;
+            }
+
+            let candidate = describe::generate(&self.config.describe, doc)?;
+            let applied = apply && candidate.is_some();
+            let current = doc.description.clone();
+            if let Some(description) = candidate.as_ref().filter(|_| applied) {
+                doc.description.clone_from(description);
+                self.storage.save(doc)?;
+            }
+
+            candidates.push(Candidate {
+                path: doc.path.clone(),
+                current,
+                candidate,
+                applied,
+            });
+            self.release_body(idx);
+        }
+        Ok(candidates)
+    }
+}
@@ -0,0 +1,58 @@
+//! Dropping what syncs leave behind (`sync --cleanup`)
+
+use super::Cache;
+use crate::core::models::{SyncPlan, SyncResult};
+use crate::error::Result;
+
+impl Cache {
+    /// Drop unmentioned references from the unprotected local documents a
+    /// bulk sync didn't re-hash (translations take theirs from the source),
+    /// and forget stored data of deleted documents
+    pub(super) fn clean_unsynced(
+        &mut self,
+        synced: &[usize],
+        result: &mut SyncResult,
+    ) -> Result<()> {
+        for idx in 0..self.documents.len() {
+            if !self.is_cleaned(idx, synced) {
+                continue;
+            }
+            self.cancellation.check()?;
+            self.load_body(idx)?;
+            let doc = &mut self.documents[idx];
+            let dropped = doc.drop_unmentioned_references();
+            if !dropped.is_empty() {
+                self.storage.save(doc)?;
+                result.cleaned_references.insert(doc.path.clone(), dropped);
+            }
+            self.release_body(idx);
+        }
+        result.cleaned_artifacts = self.storage.cleanup(false)?;
+        Ok(())
+    }
+
+    /// Add to `plan` what `clean_unsynced` would drop, writing nothing
+    pub(super) fn plan_cleanup(&self, synced: &[usize], plan: &mut SyncPlan) -> Result<()> {
+        for idx in 0..self.documents.len() {
+            if !self.is_cleaned(idx, synced) {
+                continue;
+            }
+            self.cancellation.check()?;
+            let mut doc = self.with_body(&self.documents[idx])?.into_owned();
+            let dropped = doc.drop_unmentioned_references();
+            if !dropped.is_empty() {
+                plan.cleaned_references.insert(doc.path, dropped);
+            }
+        }
+        plan.cleaned_artifacts = self.storage.cleanup(true)?;
+        Ok(())
+    }
+
+    /// Whether `--cleanup` drops unmentioned references of the document at
+    /// `idx`, given the documents a bulk sync re-hashed
+    fn is_cleaned(&self, idx: usize, synced: &[usize]) -> bool {
+        !synced.contains(&idx)
+            && !self.documents[idx].protected
+            && self.translation_source(idx).is_none()
+    }
+}
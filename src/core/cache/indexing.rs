@@ -0,0 +1,146 @@
+//! Full-text search and the search index
+
+use super::Cache;
+use crate::core::archive::{self};
+use crate::core::clock;
+use crate::core::document::Document;
+use crate::core::events::Event;
+use crate::core::scoring;
+use crate::core::search::{self, SearchHit};
+use crate::core::search_index::SearchIndex;
+use crate::core::storage::{IndexUpdate, IndexVerification};
+use crate::error::{ContextError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl Cache {
+    /// Stream documents matching `query`.
+    ///
+    /// With a full-text index (the `sqlite` storage backend), `query` is an
+    /// FTS5 query of words, `"phrases"` and `prefix*`, and local hits come
+    /// best first by BM25, followed by scanned encrypted and remote
+    /// documents. Otherwise, or if the index can't run the query, bodies are
+    /// scanned for `query` as a case-insensitive substring while the tree is
+    /// walked, so the first hit is available before the whole tree is read.
+    pub fn search<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> Box<dyn Iterator<Item = Result<SearchHit>> + 'a> {
+        Box::new(self.search_documents(query).map(|found| {
+            found.map(|(doc, hit)| {
+                self.warn_if_deprecated(&doc);
+                hit
+            })
+        }))
+    }
+
+    /// Documents matching `query` along with their hits, as `search` finds them
+    fn search_documents<'a>(
+        &self,
+        query: &'a str,
+    ) -> Box<dyn Iterator<Item = Result<(Document, SearchHit)>> + 'a> {
+        let scan = move |doc: Result<Document>| match doc {
+            Ok(doc) => search::search_document(&doc, query).map(|hit| Ok((doc, hit))),
+            Err(e) => Some(Err(e)),
+        };
+        let full_text = match self.storage.search(query) {
+            Ok(Some(full_text)) => full_text,
+            Ok(None) => return Box::new(self.walk().filter_map(scan)),
+            Err(e) => {
+                self.warn(
+                    None,
+                    format!("full-text search failed, scanning instead: {e}"),
+                );
+                return Box::new(self.walk().filter_map(scan));
+            }
+        };
+
+        let storage = Arc::clone(&self.storage);
+        let (root, include_archived) = (self.root.clone(), self.include_archived);
+        let listed = move |path: &PathBuf| include_archived || !archive::is_archived(&root, path);
+        let unindexed: Vec<PathBuf> = full_text.unindexed.into_iter().filter(&listed).collect();
+        let indexed = full_text
+            .matches
+            .into_iter()
+            .filter(move |(path, _)| listed(path))
+            .map(move |(path, score)| {
+                let mut doc = Document::load(path)?;
+                storage.load(&mut doc)?;
+                let hit = search::full_text_hit(&doc, query, score);
+                Ok((doc, hit))
+            });
+        let unindexed = self
+            .load_each(unindexed.into_iter().map(|path| (path, None)))
+            .filter_map(scan);
+        let remote = self.load_each(self.remote_paths()).filter_map(scan);
+        Box::new(indexed.chain(unindexed).chain(remote))
+    }
+
+    /// Bring the full-text index up to date, or rebuild it from scratch,
+    /// emitting `DocumentIndexed` for each document indexed
+    pub fn index(&self, rebuild: bool) -> Result<IndexUpdate> {
+        let progress = |path: &Path| {
+            self.subscribers.emit(|| Event::DocumentIndexed {
+                path: path.to_path_buf(),
+            });
+        };
+        self.storage
+            .reindex(rebuild, &progress)?
+            .ok_or_else(|| self.no_index())
+    }
+
+    /// Check the full-text index against the documents on disk
+    pub fn verify_index(&self) -> Result<IndexVerification> {
+        self.storage.verify_index()?.ok_or_else(|| self.no_index())
+    }
+
+    fn no_index(&self) -> ContextError {
+        ContextError::StorageError(format!(
+            "the {} backend keeps no search index; set `backend = \"sqlite\"` under [storage] in config.toml",
+            self.storage.name()
+        ))
+    }
+
+    /// Search like `search`, but rank hits by freshness-weighted score.
+    ///
+    /// Valid, recently updated documents come first (see `core::scoring`),
+    /// weighted by the project's `[scoring]` config and, for full-text hits,
+    /// their BM25 relevance. Ranking needs every hit, so unlike `search` this
+    /// reads every match before returning.
+    pub fn search_ranked(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let today = clock::today();
+        let mut hits = Vec::new();
+        for found in self.search_documents(query) {
+            self.cancellation.check()?;
+            let (doc, mut hit) = found?;
+            self.warn_if_deprecated(&doc);
+            let status = doc.validate()?.status;
+            let freshness = scoring::freshness(&self.config.scoring, status, &doc.updated, today);
+            hit.score = Some(match hit.score {
+                Some(relevance) => freshness * relevance,
+                None => scoring::rank(freshness, hit.matches.len()),
+            });
+            hits.push(hit);
+        }
+
+        // Stable sort keeps search order among equal scores
+        hits.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+        Ok(hits)
+    }
+
+    /// Build a client-side search index over every document, including those
+    /// of fetched remotes. Bodies are redacted first, since the index is meant
+    /// to be published.
+    pub fn search_index(&self) -> Result<SearchIndex> {
+        let mut index = SearchIndex::new();
+        for doc in self.walk() {
+            self.cancellation.check()?;
+            let doc = doc?;
+            if !self.publishable(&doc) {
+                continue;
+            }
+            index.add(&self.root, &doc, &self.redacted_body(&doc));
+        }
+        Ok(index)
+    }
+}
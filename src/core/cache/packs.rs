@@ -0,0 +1,330 @@
+//! Context packs for a diff, files or a task
+
+use super::Cache;
+use crate::core::checklist::{ChangedReference, Checklist, ChecklistItem};
+use crate::core::clock;
+use crate::core::document::{self, Document};
+use crate::core::fsio::{self};
+use crate::core::git;
+use crate::core::links;
+use crate::core::models::{Status, Validation};
+use crate::core::pack::{self, Pack, PackedDocument};
+use crate::core::paths;
+use crate::core::refindex::{self};
+use crate::core::scoring;
+use crate::core::sections;
+use crate::core::suggest::{self, Suggestions};
+use crate::error::{ContextError, Result};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+impl Cache {
+    /// Suggest up to `limit` files the document at `doc_path` likely should
+    /// reference. With `apply`, they are added to the body's "Related files"
+    /// section and recorded as references right away.
+    pub fn suggest(&mut self, doc_path: &Path, limit: usize, apply: bool) -> Result<Suggestions> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::SyncError("Could not determine project root".to_string()))?
+            .to_path_buf();
+        let idx = self
+            .documents
+            .iter()
+            .position(|d| d.path == doc_path)
+            .ok_or_else(|| ContextError::DocumentNotFound(doc_path.display().to_string()))?;
+        self.load_body(idx)?;
+        let doc = &self.documents[idx];
+        if doc.is_locked() {
+            self.release_body(idx);
+            return Err(ContextError::InvalidDocument(format!(
+                "cannot read encrypted document {}",
+                doc_path.display()
+            )));
+        }
+
+        let mentions = suggest::mentions(&doc.body);
+        let existing: BTreeSet<String> = doc
+            .references
+            .keys()
+            .cloned()
+            .chain(paths::extract_paths(&doc.body))
+            .collect();
+        let files = suggest::candidate_files(&project_root);
+        let mut suggestions = suggest::suggest(&project_root, &mentions, &files, &existing);
+        suggestions.truncate(limit);
+
+        let applied = apply && !suggestions.is_empty();
+        if applied {
+            let doc = &mut self.documents[idx];
+            let added: Vec<String> = suggestions.iter().map(|s| s.path.clone()).collect();
+            doc.body = suggest::append_references(&doc.body, &added);
+            for path in added {
+                let hash = document::hash(&fsio::read(&project_root.join(&path), doc.read_policy)?);
+                doc.references.insert(path, hash);
+            }
+            self.storage.save(doc)?;
+        }
+        self.release_body(idx);
+
+        Ok(Suggestions {
+            document: doc_path.to_path_buf(),
+            suggestions,
+            applied,
+        })
+    }
+
+    /// Gather the documents relevant to the changes in a git revision range:
+    /// those referencing a changed file, then the documents they link to,
+    /// up to `hops` links away (see `core::pack`)
+    pub fn pack_diff(&self, range: &str, hops: usize) -> Result<Pack> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let changed = git::changed_files(project_root, range)?;
+        let documents = self.pack_files(&changed, hops)?;
+        Ok(Pack {
+            range: range.to_string(),
+            changed,
+            documents,
+        })
+    }
+
+    /// Gather the documents referencing any of `files` (project-relative
+    /// paths), those referencing more of them weighted by freshness first
+    /// (see `core::scoring`), then the documents they link to, up to `hops`
+    /// links away
+    pub fn pack_files(&self, files: &[String], hops: usize) -> Result<Vec<PackedDocument>> {
+        let files: HashSet<&str> = files.iter().map(|f| f.trim_start_matches("./")).collect();
+
+        let mut scored = Vec::new();
+        for doc in self.documents.iter().filter(|d| self.publishable(d)) {
+            self.cancellation.check()?;
+            let mut references: Vec<String> = doc
+                .references
+                .keys()
+                .map(|r| r.trim_start_matches("./"))
+                .filter(|r| files.contains(r))
+                .map(str::to_string)
+                .collect();
+            if references.is_empty() {
+                continue;
+            }
+            references.sort();
+            let packed = self.packed(doc, references, 0, None)?;
+            scored.push((
+                scoring::rank(packed.freshness, packed.references.len()),
+                doc,
+                packed,
+            ));
+        }
+        self.pack_ranked(scored, hops)
+    }
+
+    /// Gather the documents mentioning words of a free-text task, most
+    /// matched words weighted by freshness first (see `core::scoring`), then
+    /// the documents they link to, up to `hops` links away
+    pub fn pack_task(&self, task: &str, hops: usize) -> Result<Vec<PackedDocument>> {
+        let terms = pack::task_terms(task);
+        let mut scored = Vec::new();
+        for doc in self.documents.iter().filter(|d| self.publishable(d)) {
+            self.cancellation.check()?;
+            let full = self.with_body(doc)?;
+            let text = format!(
+                "{}\n{}\n{}",
+                doc.slug,
+                doc.description,
+                self.redacted_body(&full)
+            );
+            let matches = pack::matched_terms(&text, &terms);
+            if matches > 0 {
+                let packed = self.packed(doc, Vec::new(), 0, None)?;
+                scored.push((scoring::rank(packed.freshness, matches), doc, packed));
+            }
+        }
+        self.pack_ranked(scored, hops)
+    }
+
+    /// Order directly matched documents by score, highest first, then add
+    /// the documents they link to
+    fn pack_ranked<'a>(
+        &'a self,
+        mut scored: Vec<(f64, &'a Document, PackedDocument)>,
+        hops: usize,
+    ) -> Result<Vec<PackedDocument>> {
+        // Stable, so ties keep load order
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let (direct, documents) = scored
+            .into_iter()
+            .map(|(_, doc, packed)| (doc, packed))
+            .unzip();
+        self.pack_linked(direct, documents, hops)
+    }
+
+    /// Add the documents linked from `direct` ones, breadth-first up to
+    /// `hops` links away, to `documents`
+    fn pack_linked(
+        &self,
+        direct: Vec<&Document>,
+        mut documents: Vec<PackedDocument>,
+        hops: usize,
+    ) -> Result<Vec<PackedDocument>> {
+        let mut included: HashSet<PathBuf> = direct.iter().map(|d| d.path.clone()).collect();
+        let mut frontier = direct;
+        for hop in 1..=hops {
+            let mut next = Vec::new();
+            for doc in frontier {
+                self.cancellation.check()?;
+                let doc = self.with_body(doc)?;
+                for link in links::extract_links(&doc.body) {
+                    let target = links::resolve(&doc.path, &link.target);
+                    let Some(linked) = self
+                        .documents
+                        .iter()
+                        .find(|d| d.path == target && self.publishable(d))
+                    else {
+                        continue;
+                    };
+                    if included.insert(target) {
+                        next.push(linked);
+                        documents.push(self.packed(
+                            linked,
+                            Vec::new(),
+                            hop,
+                            Some(doc.path.clone()),
+                        )?);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        Ok(documents)
+    }
+
+    /// A document as it appears in a pack
+    fn packed(
+        &self,
+        doc: &Document,
+        references: Vec<String>,
+        hops: usize,
+        linked_from: Option<PathBuf>,
+    ) -> Result<PackedDocument> {
+        let full = self.with_body(doc)?;
+        let validation = full.validate()?;
+        let status = validation.status;
+        let body = match stale_sections(&full, &validation) {
+            Some(text) => self.redact(&doc.path, &text),
+            None => self.redacted_body(&full),
+        };
+        Ok(PackedDocument {
+            path: doc.path.clone(),
+            slug: doc.slug.clone(),
+            description: doc.description.clone(),
+            status,
+            freshness: scoring::freshness(
+                &self.config.scoring,
+                status,
+                &doc.updated,
+                clock::today(),
+            ),
+            references,
+            hops,
+            linked_from,
+            body,
+        })
+    }
+
+    /// The documents referencing a file changed in a git revision range,
+    /// for a review checklist (see `core::checklist`)
+    pub fn checklist(&self, range: &str) -> Result<Checklist> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let changed: HashSet<String> = git::changed_files(project_root, range)?
+            .into_iter()
+            .collect();
+
+        let mut items = Vec::new();
+        for doc in &self.documents {
+            let mut references: Vec<ChangedReference> = doc
+                .references
+                .keys()
+                .map(|r| refindex::normalize(r))
+                .filter(|r| changed.contains(*r))
+                .map(|r| ChangedReference {
+                    path: r.to_string(),
+                    deleted: !project_root.join(r).exists(),
+                })
+                .collect();
+            if references.is_empty() {
+                continue;
+            }
+            references.sort_by(|a, b| a.path.cmp(&b.path));
+            let document = doc
+                .path
+                .strip_prefix(project_root)
+                .unwrap_or(&doc.path)
+                .to_path_buf();
+            let updated = changed.contains(document.to_string_lossy().as_ref());
+            items.push(ChecklistItem {
+                document,
+                slug: doc.slug.clone(),
+                references,
+                updated,
+            });
+        }
+        items.sort_by(|a, b| a.document.cmp(&b.document));
+        Ok(Checklist {
+            range: range.to_string(),
+            items,
+        })
+    }
+}
+
+/// The sections of a stale document whose scoped references are out of
+/// date, as status reports them (see `Document::validate_sections`), or
+/// `None` to pack the whole body: when no scoped section is stale, or a
+/// reference outside them is out of date too
+fn stale_sections(doc: &Document, validation: &Validation) -> Option<String> {
+    if validation.status == Status::Valid
+        || validation.sections.is_empty()
+        || !validation.unreadable.is_empty()
+    {
+        return None;
+    }
+    let covered = |reference: &String| {
+        validation
+            .sections
+            .iter()
+            .any(|s| s.changed.contains(reference) || s.missing.contains(reference))
+    };
+    if !validation
+        .changed
+        .iter()
+        .chain(&validation.missing)
+        .all(covered)
+    {
+        return None;
+    }
+
+    let index = sections::index(&doc.body);
+    let mut kept: Vec<&sections::Section> = Vec::new();
+    for section in &index {
+        let stale = validation
+            .sections
+            .iter()
+            .any(|s| s.anchor == section.anchor);
+        // A stale section nested in a kept one is already part of it
+        let nested = kept.iter().any(|k| section.start_line <= k.end_line);
+        if stale && !nested {
+            kept.push(section);
+        }
+    }
+    let text: Vec<String> = kept
+        .iter()
+        .map(|s| sections::extract(&doc.body, s))
+        .collect();
+    Some(text.join("\n"))
+}
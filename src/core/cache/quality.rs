@@ -0,0 +1,162 @@
+//! Lint rules, readability metrics, diagrams and template drift
+
+use super::Cache;
+use crate::core::diagrams::{self, RenderedDiagram};
+use crate::core::lint::{self, LintIssue};
+use crate::core::policy::{self, Policy, PolicyReport};
+use crate::core::readability::{self, DocumentMetrics};
+use crate::core::suggest::{self};
+use crate::core::template::{self, TemplateDrift};
+use crate::error::{ContextError, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+impl Cache {
+    /// Check the rules of `policy.toml` against the local documents and
+    /// project files
+    pub fn check_policy(&self) -> Result<PolicyReport> {
+        let policy = Policy::load(&self.root)?.ok_or_else(|| {
+            ContextError::ConfigError(format!(
+                "no {} in {}",
+                policy::POLICY_FILE,
+                self.root.display()
+            ))
+        })?;
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let files: Vec<String> = suggest::candidate_files(project_root)
+            .into_iter()
+            .filter(|f| !f.starts_with(".context/"))
+            .collect();
+        let documents = self
+            .documents
+            .iter()
+            .map(|doc| self.with_body(doc).map(Cow::into_owned))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(policy.check(&self.root, &documents, &files))
+    }
+
+    /// Run lint rules over all loaded documents
+    pub fn lint(&self) -> Vec<LintIssue> {
+        let mut templates = HashMap::new();
+        let mut issues = Vec::new();
+        for doc in &self.documents {
+            issues.extend(lint::lint(doc));
+            let successor = doc
+                .superseded_by
+                .as_deref()
+                .and_then(|name| self.get(name).ok());
+            issues.extend(lint::lint_state(doc, successor));
+            // A body that can no longer be read has nothing left to check
+            let Ok(doc) = self.with_body(doc) else {
+                continue;
+            };
+            issues.extend(lint::lint_diagrams(&doc, &self.config.diagrams));
+            issues.extend(lint::lint_readability(&doc, &self.config.readability));
+            let Some(name) = &doc.template else {
+                continue;
+            };
+            let template = templates
+                .entry(name.clone())
+                .or_insert_with(|| template::document_template(&self.root, name));
+            issues.extend(lint::lint_template(&doc, name, template));
+        }
+        issues
+    }
+
+    /// Length and readability metrics of every loaded document with a
+    /// readable body, in path order
+    pub fn metrics(&self) -> Vec<DocumentMetrics> {
+        let mut metrics: Vec<_> = self
+            .documents
+            .iter()
+            .filter_map(|doc| self.with_body(doc).ok())
+            .filter(|doc| !doc.is_locked())
+            .map(|doc| DocumentMetrics {
+                path: doc
+                    .path
+                    .strip_prefix(&self.root)
+                    .unwrap_or(&doc.path)
+                    .to_path_buf(),
+                slug: doc.slug.clone(),
+                metrics: readability::measure(&doc.body),
+            })
+            .collect();
+        metrics.sort_by(|a, b| a.path.cmp(&b.path));
+        metrics
+    }
+
+    /// Render every diagram of the local documents to SVG files in `output`
+    /// with the `[diagrams]` commands. Diagrams without a configured command
+    /// are listed without an SVG.
+    pub fn render_diagrams(&self, output: &Path) -> Result<Vec<RenderedDiagram>> {
+        std::fs::create_dir_all(output)?;
+        let mut rendered = Vec::new();
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            let doc = self.with_body(doc)?;
+            if doc.is_locked() {
+                continue;
+            }
+            for (n, diagram) in diagrams::extract(&doc.body).iter().enumerate() {
+                let mut outcome = RenderedDiagram {
+                    document: doc.path.clone(),
+                    line: diagram.line,
+                    kind: diagram.kind,
+                    svg: None,
+                    error: None,
+                };
+                if let Some(command) = diagram.kind.command(&self.config.diagrams) {
+                    match diagrams::render(diagram, command) {
+                        Ok(svg) => {
+                            let path = output.join(diagrams::file_name(&doc.slug, n + 1));
+                            std::fs::write(&path, svg)?;
+                            outcome.svg = Some(path);
+                        }
+                        Err(e) => outcome.error = Some(e.to_string()),
+                    }
+                }
+                rendered.push(outcome);
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Compare a document's headings with its template's current version
+    pub fn template_drift(&self, slug_or_path: &str) -> Result<TemplateDrift> {
+        let doc = self.get_with_body(slug_or_path)?;
+        let name = doc.template.as_deref().ok_or_else(|| {
+            ContextError::TemplateError(format!("{} names no template", doc.path.display()))
+        })?;
+        let template = template::document_template(&self.root, name)?.ok_or_else(|| {
+            ContextError::TemplateError(format!("template '{name}' does not exist"))
+        })?;
+        Ok(template::drift(&doc, name, &template))
+    }
+
+    /// Compare every local document naming a template with that template,
+    /// skipping documents whose template no longer exists (see `lint`)
+    pub fn template_status(&self) -> Result<Vec<TemplateDrift>> {
+        let mut templates = HashMap::new();
+        let mut drifts = Vec::new();
+        for doc in &self.documents {
+            let Some(name) = &doc.template else {
+                continue;
+            };
+            if !templates.contains_key(name.as_str()) {
+                templates.insert(
+                    name.as_str(),
+                    template::document_template(&self.root, name)?,
+                );
+            }
+            if let Some(template) = &templates[name.as_str()] {
+                let doc = self.with_body(doc)?;
+                drifts.push(template::drift(&doc, name, template));
+            }
+        }
+        Ok(drifts)
+    }
+}
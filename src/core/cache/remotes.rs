@@ -0,0 +1,99 @@
+//! Shared context trees overlaid from other repositories
+
+use super::Cache;
+use crate::core::config::RemoteConfig;
+use crate::core::document::Document;
+use crate::core::markdown_files;
+use crate::core::remote;
+use crate::error::{ContextError, Result};
+use std::path::{Path, PathBuf};
+
+impl Cache {
+    /// Names and context directories of the configured remotes that have been fetched
+    pub(super) fn remote_dirs(&self) -> Vec<(String, PathBuf)> {
+        self.config
+            .remotes
+            .iter()
+            .map(|r| (r.name.clone(), remote::context_dir(&self.root, r)))
+            .filter(|(_, dir)| dir.is_dir())
+            .collect()
+    }
+
+    /// Fetch all configured remotes, or only the one called `name`.
+    ///
+    /// Call `load` afterwards to pick up the fetched documents.
+    pub fn fetch_remotes(&self, name: Option<&str>) -> Result<Vec<remote::Fetched>> {
+        let mut fetched = Vec::new();
+        for remote in self.selected_remotes(name)? {
+            self.cancellation.check()?;
+            fetched.push(remote::fetch(&self.root, remote)?);
+        }
+        Ok(fetched)
+    }
+
+    /// Pull the latest revision of all configured remotes, or only the one
+    /// called `name`, fetching any that haven't been fetched yet.
+    ///
+    /// Call `load` afterwards to pick up the updated documents.
+    pub fn update_remotes(&self, name: Option<&str>) -> Result<Vec<remote::Updated>> {
+        let mut updated = Vec::new();
+        for remote in self.selected_remotes(name)? {
+            self.cancellation.check()?;
+            updated.push(remote::update(&self.root, remote)?);
+        }
+        Ok(updated)
+    }
+
+    /// Compare each configured remote with upstream and report local
+    /// documents that shadow a remote document's slug. Requires `load`.
+    pub fn remote_status(&self) -> Result<Vec<remote::RemoteStatus>> {
+        let relative = |path: &Path| path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+        let mut statuses = Vec::new();
+        for config in &self.config.remotes {
+            self.cancellation.check()?;
+            let mut status = remote::status(&self.root, config);
+            for theirs in self
+                .remote_documents
+                .iter()
+                .filter(|d| d.remote.as_ref() == Some(&config.name))
+            {
+                for ours in self.documents.iter().filter(|d| d.slug == theirs.slug) {
+                    status.shadowed.push(remote::Shadow {
+                        slug: theirs.slug.clone(),
+                        local: relative(&ours.path),
+                        remote: relative(&theirs.path),
+                    });
+                }
+            }
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    fn selected_remotes(&self, name: Option<&str>) -> Result<Vec<&RemoteConfig>> {
+        let selected: Vec<_> = self
+            .config
+            .remotes
+            .iter()
+            .filter(|r| name.is_none_or(|n| r.name == n))
+            .collect();
+        if let (Some(name), true) = (name, selected.is_empty()) {
+            return Err(ContextError::RemoteError(format!(
+                "no remote named '{name}' in config"
+            )));
+        }
+        Ok(selected)
+    }
+
+    /// Documents overlaid read-only from fetched remotes, loaded by `load`
+    pub fn remote_documents(&self) -> &[Document] {
+        &self.remote_documents
+    }
+
+    /// Paths of the documents of fetched remotes, with the remote's name
+    pub(super) fn remote_paths(&self) -> impl Iterator<Item = (PathBuf, Option<String>)> {
+        self.remote_dirs().into_iter().flat_map(|(name, dir)| {
+            markdown_files(&dir).map(move |path| (path, Some(name.clone())))
+        })
+    }
+}
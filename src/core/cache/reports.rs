@@ -0,0 +1,366 @@
+//! Tree-wide reports: coverage, usage, trends, gaps, debt and more
+
+use super::Cache;
+use crate::core::clock;
+use crate::core::coverage::Coverage;
+use crate::core::debt::{self, DebtReport, DocumentDebt};
+use crate::core::docdiff::{self, DocsDiff, Side};
+use crate::core::document::Document;
+use crate::core::gaps::{self, GapReport};
+use crate::core::gc::{self, GcReport};
+use crate::core::graph::Graph;
+use crate::core::i18n::{self, I18nReport, OutdatedTranslation};
+use crate::core::links;
+use crate::core::models::{DocumentSize, Status, Summary};
+use crate::core::order::{self, DependencyOrder};
+use crate::core::paths;
+use crate::core::refindex::{self};
+use crate::core::suggest::{self};
+use crate::core::trend;
+use crate::core::usage::{self, UsageReport};
+use crate::error::{ContextError, Result};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+impl Cache {
+    /// Record that `doc` was read `via` some interface, if usage tracking is
+    /// enabled in config
+    pub fn record_read(&self, doc: &Document, via: &str) -> Result<()> {
+        if !self.config.usage.track {
+            return Ok(());
+        }
+        let read = usage::Read {
+            timestamp: clock::now_utc().to_rfc3339(),
+            document: doc
+                .path
+                .strip_prefix(&self.root)
+                .unwrap_or(&doc.path)
+                .to_path_buf(),
+            slug: doc.slug.clone(),
+            via: via.to_string(),
+        };
+        usage::journal(&self.root, &self.config.usage).append(&serde_json::to_string(&read)?)?;
+        Ok(())
+    }
+
+    /// Reads recorded in the usage journal for every loaded document
+    pub fn usage(&self) -> Result<UsageReport> {
+        let journal = usage::journal(&self.root, &self.config.usage);
+        let reads = usage::reads(&journal)?;
+        let documents = self
+            .documents
+            .iter()
+            .chain(&self.remote_documents)
+            .map(|doc| {
+                let path = doc.path.strip_prefix(&self.root).unwrap_or(&doc.path);
+                (path.to_path_buf(), doc.slug.clone())
+            });
+        Ok(usage::report(self.config.usage.track, &reads, documents))
+    }
+
+    /// Append a data point for `summary` to the trend journal, if recording
+    /// is enabled in config
+    pub fn record_trend(&self, summary: &Summary) -> Result<()> {
+        if !self.config.trend.record {
+            return Ok(());
+        }
+        let coverage = self.coverage(&[])?;
+        let point = trend::Point::new(
+            clock::now_utc().to_rfc3339(),
+            summary,
+            coverage.covered.len(),
+            coverage.files(),
+        );
+        trend::journal(&self.root, &self.config.trend).append(&serde_json::to_string(&point)?)?;
+        Ok(())
+    }
+
+    /// Which project files under `prefixes` (all if empty) local documents
+    /// reference (see `core::coverage`)
+    pub fn coverage(&self, prefixes: &[String]) -> Result<Coverage> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let referenced: HashSet<&str> = self
+            .documents
+            .iter()
+            .filter(|doc| self.publishable(doc))
+            .flat_map(|doc| doc.references.keys())
+            .map(|r| refindex::normalize(r))
+            .collect();
+        Ok(Coverage::compute(
+            suggest::candidate_files(project_root),
+            &referenced,
+            prefixes,
+        ))
+    }
+
+    /// The latest `limit` data points of the trend journal, oldest first
+    pub fn trend(&self, limit: usize) -> Result<Vec<trend::Point>> {
+        trend::points(&trend::journal(&self.root, &self.config.trend), limit)
+    }
+
+    /// Files changed in the last `days` days of git history that no document
+    /// references, or that only stale or orphaned documents reference
+    pub fn gaps(&self, days: u32) -> Result<GapReport> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let churn = gaps::churn(project_root, days)?;
+
+        let mut referencing: BTreeMap<String, Vec<(PathBuf, Status)>> = BTreeMap::new();
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            let status = doc.validate()?.status;
+            for reference in doc.references.keys() {
+                let reference = reference.trim_start_matches("./");
+                if churn.contains_key(reference) {
+                    referencing
+                        .entry(reference.to_string())
+                        .or_default()
+                        .push((doc.path.clone(), status));
+                }
+            }
+        }
+        Ok(gaps::find(days, &churn, &referencing))
+    }
+
+    /// How the context tree changed between revision `from` and revision
+    /// `to`, or the working tree (see `core::docdiff`)
+    pub fn diff_docs(&self, from: &str, to: Option<&str>) -> Result<DocsDiff> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        for rev in std::iter::once(from).chain(to) {
+            if rev.starts_with('-') {
+                return Err(ContextError::Other(format!("invalid revision: {rev}")));
+            }
+        }
+        let documents = docdiff::diff(
+            project_root,
+            Side::Revision(from),
+            to.map_or(Side::WorkingTree, Side::Revision),
+        )?;
+        Ok(DocsDiff {
+            from: from.to_string(),
+            to: to.map(str::to_string),
+            documents,
+        })
+    }
+
+    /// Order loaded documents so each comes after the documents it links to,
+    /// reporting any link cycles
+    pub fn dependency_order(&self) -> Result<DependencyOrder> {
+        let mut graph = BTreeMap::new();
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            let doc = self.with_body(doc)?;
+            let targets = links::extract_links(&doc.body)
+                .iter()
+                .map(|link| links::resolve(&doc.path, &link.target))
+                .collect();
+            graph.insert(doc.path.clone(), targets);
+        }
+        Ok(order::resolve(&graph))
+    }
+
+    /// The graph of documents, the files they reference and the documents
+    /// they link to, with document paths relative to the context root
+    pub fn graph(&self) -> Result<Graph> {
+        let relative = |path: &Path| {
+            path.strip_prefix(&self.root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        };
+        let mut graph = Graph::default();
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            let doc = self.with_body(doc)?;
+            let references: Vec<&str> = doc
+                .references
+                .keys()
+                .map(|r| refindex::normalize(r))
+                .collect();
+            let links = links::extract_links(&doc.body)
+                .iter()
+                .map(|link| relative(&links::resolve(&doc.path, &link.target)))
+                .collect();
+            graph.add(relative(&doc.path), doc.slug.clone(), &references, links);
+        }
+        graph.finish();
+        Ok(graph)
+    }
+
+    /// Prune runtime caches using the full `[gc]` retention policy
+    pub fn gc(&self) -> Result<GcReport> {
+        gc::collect(&self.root, &self.config.gc, gc::Mode::Full)
+    }
+
+    /// Count loaded documents by category and status
+    pub fn summary(&self) -> Result<Summary> {
+        let mut summary = Summary {
+            documents: self.documents.len(),
+            ..Summary::default()
+        };
+
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            *summary.categories.entry(self.category(doc)).or_default() += 1;
+
+            match doc.validate()?.status {
+                Status::Valid => summary.valid += 1,
+                Status::Stale => summary.stale += 1,
+                Status::Orphaned => summary.orphaned += 1,
+            }
+
+            // ISO dates compare correctly as strings
+            if !doc.updated.is_empty()
+                && summary
+                    .last_updated
+                    .as_ref()
+                    .is_none_or(|d| *d < doc.updated)
+            {
+                summary.last_updated = Some(doc.updated.clone());
+            }
+            if !doc.updated.is_empty()
+                && summary
+                    .oldest_updated
+                    .as_ref()
+                    .is_none_or(|d| *d > doc.updated)
+            {
+                summary.oldest_updated = Some(doc.updated.clone());
+            }
+            summary.references += doc.references.len();
+            if let Ok(metadata) = std::fs::metadata(&doc.path) {
+                summary.largest.push(DocumentSize {
+                    path: doc.path.clone(),
+                    bytes: metadata.len(),
+                });
+            }
+        }
+        // Ties broken by path so the ranking is stable
+        summary
+            .largest
+            .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+        summary.largest.truncate(Summary::LARGEST);
+
+        Ok(summary)
+    }
+
+    /// Score every document's documentation debt as of `today` (see `core::debt`)
+    pub fn debt(&self, today: NaiveDate) -> Result<DebtReport> {
+        let project_root = self
+            .root
+            .parent()
+            .ok_or_else(|| ContextError::Other("Could not determine project root".to_string()))?;
+        let usage = self.usage()?;
+        let reads: HashMap<&Path, usize> = usage
+            .documents
+            .iter()
+            .map(|u| (u.document.as_path(), u.reads))
+            .collect();
+        let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+
+        let mut documents = Vec::with_capacity(self.documents.len());
+        for doc in &self.documents {
+            self.cancellation.check()?;
+            let validation = doc.validate()?;
+            let relative = doc.path.strip_prefix(&self.root).unwrap_or(&doc.path);
+            // Assets document the context tree itself, not project sources
+            let broken: Vec<&str> = validation
+                .changed
+                .iter()
+                .chain(&validation.missing)
+                .map(String::as_str)
+                .filter(|r| !paths::is_asset(r))
+                .collect();
+            let (stale_days, commits) = if validation.status == Status::Valid {
+                (0, 0)
+            } else {
+                (
+                    debt::days_since(&doc.updated, today),
+                    debt::commits_since(project_root, &doc.updated, &broken),
+                )
+            };
+            documents.push((
+                self.category(doc),
+                DocumentDebt {
+                    document: relative.to_path_buf(),
+                    slug: doc.slug.clone(),
+                    status: validation.status,
+                    stale_days,
+                    commits,
+                    references: count(doc.source_references().count()),
+                    broken: count(broken.len()),
+                    reads: count(reads.get(relative).copied().unwrap_or(0)),
+                    score: 0.0,
+                },
+            ));
+        }
+        Ok(debt::report(&self.config.debt, documents))
+    }
+
+    /// Top-level directory of a document within the context root, `.` for
+    /// documents directly in it
+    fn category(&self, doc: &Document) -> String {
+        let relative = doc.path.strip_prefix(&self.root).unwrap_or(&doc.path);
+        match relative.components().count() {
+            0 | 1 => ".".to_string(),
+            _ => relative
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Check every configured locale has an up-to-date translation of each
+    /// source document
+    pub fn i18n(&self) -> Result<I18nReport> {
+        let locales = &self.config.i18n.locales;
+        let mut report = I18nReport {
+            locales: locales.clone(),
+            ..I18nReport::default()
+        };
+        if locales.is_empty() {
+            return Ok(report);
+        }
+
+        let paths: HashSet<&Path> = self
+            .documents
+            .iter()
+            .map(|doc| doc.path.as_path())
+            .collect();
+        for (idx, doc) in self.documents.iter().enumerate() {
+            self.cancellation.check()?;
+            if i18n::source_of(&doc.path, locales).is_none() {
+                report.sources += 1;
+                for locale in locales {
+                    if !paths.contains(i18n::translation_path(&doc.path, locale).as_path()) {
+                        report.missing.push((doc.path.clone(), locale.clone()));
+                    }
+                }
+                continue;
+            }
+
+            let Some(source) = self.translation_source(idx) else {
+                report.orphaned.push(doc.path.clone());
+                continue;
+            };
+            let (_, current) = self.source_state(source)?;
+            if doc.source_hash.as_deref() != Some(current.as_str()) {
+                report.outdated.push(OutdatedTranslation {
+                    translation: doc.path.clone(),
+                    source: self.documents[source].path.clone(),
+                    never_synced: doc.source_hash.is_none(),
+                });
+            }
+        }
+        Ok(report)
+    }
+}
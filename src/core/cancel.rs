@@ -0,0 +1,86 @@
+//! Cooperative cancellation for long-running cache operations
+//!
+//! A `CancellationToken` set on a `Cache` is checked between documents by
+//! `load`, `status` and `sync`, which return `ContextError::Cancelled` once
+//! it fires. Servers cancel the token when a client goes away so no one pays
+//! for hashing a large tree whose result will never be read.
+//!
+//! An interrupt (Ctrl-C in the CLI) stops work the same way, except that
+//! `status` and `sync` then return what they finished instead of failing:
+//! status results cover only the documents checked so far, and the sync
+//! result is marked `partial`.
+
+use crate::error::{ContextError, Result};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+const RUNNING: u8 = 0;
+const CANCELLED: u8 = 1;
+const INTERRUPTED: u8 = 2;
+
+/// A cheaply cloneable flag shared between a canceller and an operation
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicU8>);
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of every operation holding a clone of this token
+    pub fn cancel(&self) {
+        self.0.store(CANCELLED, Ordering::Relaxed);
+    }
+
+    /// Request that operations stop scheduling new work, keeping the
+    /// results they already have where they can
+    pub fn interrupt(&self) {
+        let _ = self
+            .0
+            .compare_exchange(RUNNING, INTERRUPTED, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    /// Whether cancellation or an interrupt has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != RUNNING
+    }
+
+    /// Whether an interrupt, rather than a cancellation, has been requested
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == INTERRUPTED
+    }
+
+    /// Fail with `ContextError::Cancelled` if cancellation has been requested
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(ContextError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(clone.check().is_ok());
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(matches!(clone.check(), Err(ContextError::Cancelled)));
+        assert!(!clone.is_interrupted());
+    }
+
+    #[test]
+    fn test_interrupt_also_fails_checks() {
+        let token = CancellationToken::new();
+        token.interrupt();
+        assert!(token.is_interrupted());
+        assert!(matches!(token.check(), Err(ContextError::Cancelled)));
+    }
+}
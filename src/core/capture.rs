@@ -0,0 +1,126 @@
+//! Drafting documents from the commits of a feature branch
+//!
+//! `context capture --from-git <range>` turns a revision range into a new
+//! document: the files the range touched become references, commit subjects
+//! become bullet points, and a placeholder marks where the author writes the
+//! prose. Documenting a change as it lands then starts from an outline
+//! rather than a blank page.
+
+use crate::core::git::Commit;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Placeholder left in drafts for the author to replace
+pub const PROSE_PLACEHOLDER: &str = "<!-- TODO: explain what changed and why -->";
+
+/// A document drafted from a revision range
+#[derive(Debug, Clone, Serialize)]
+pub struct Capture {
+    /// The revision range as given, e.g. `main..feature`
+    pub range: String,
+    /// Path to the drafted document
+    pub path: PathBuf,
+    /// Slug of the drafted document
+    pub slug: String,
+    /// Commits in the range, oldest first
+    pub commits: Vec<Commit>,
+    /// Files referenced by the draft: those changed in the range that still exist
+    pub references: Vec<String>,
+    /// Markdown body of the draft
+    pub body: String,
+    /// Whether the document was written, false for a dry run
+    pub written: bool,
+}
+
+/// The revision a range ends at: `feature` for `main..feature`, the
+/// revision itself for a single one
+pub fn range_end(range: &str) -> &str {
+    range
+        .rsplit("..")
+        .next()
+        .unwrap_or(range)
+        .trim_start_matches('.')
+}
+
+/// A slug from a branch or revision name: `feature/Token-Refresh` becomes
+/// `feature-token-refresh`
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A heading from a slug: `auth-flow` becomes `Auth flow`
+pub fn title(slug: &str) -> String {
+    let words = slug.replace(['-', '_'], " ");
+    let mut chars = words.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+/// The draft body: a title, the prose placeholder, one bullet per commit
+/// and one per referenced file
+pub fn draft_body(title: &str, commits: &[Commit], files: &[String]) -> String {
+    let mut body = format!("# {title}\n\n{PROSE_PLACEHOLDER}\n");
+    if !commits.is_empty() {
+        body.push_str("\n## Changes\n\n");
+        for commit in commits {
+            let _ = writeln!(body, "- {} ({})", commit.subject, commit.hash);
+        }
+    }
+    if !files.is_empty() {
+        body.push_str("\n## Files\n\n");
+        for file in files {
+            let _ = writeln!(body, "- `{file}`");
+        }
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_end() {
+        assert_eq!(range_end("main..feature"), "feature");
+        assert_eq!(range_end("main...feature"), "feature");
+        assert_eq!(range_end("HEAD~3"), "HEAD~3");
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("feature/Token-Refresh"), "feature-token-refresh");
+        assert_eq!(slugify("HEAD~3"), "head-3");
+        assert_eq!(slugify("--"), "");
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(title("auth-flow"), "Auth flow");
+        assert_eq!(title("ci_setup"), "Ci setup");
+        assert_eq!(title(""), "");
+    }
+
+    #[test]
+    fn test_draft_body() {
+        let commits = vec![Commit {
+            hash: "abc1234".to_string(),
+            author: "Ada".to_string(),
+            subject: "Add token refresh".to_string(),
+        }];
+        let body = draft_body("Token refresh", &commits, &["src/auth.rs".to_string()]);
+        assert_eq!(
+            body,
+            format!("# Token refresh\n\n{PROSE_PLACEHOLDER}\n\n## Changes\n\n- Add token refresh (abc1234)\n\n## Files\n\n- `src/auth.rs`\n")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Review checklists for pull requests
+//!
+//! `context checklist --diff main..HEAD` lists the documents referencing a
+//! file changed in the range, as a Markdown task list a CI job can post on
+//! the pull request. Documents are ordered by path and their references by
+//! path, so reposting after a new push only changes the lines that moved.
+//! A document edited in the same range is listed already checked. Links
+//! use a URL template with `{path}` (the `[preview] source_url` setting by
+//! default); without one, paths are printed as code.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A changed file a document references
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedReference {
+    /// Project-relative path
+    pub path: String,
+    /// Whether the range deleted the file
+    pub deleted: bool,
+}
+
+/// A document that likely needs review
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    /// Project-relative path, e.g. `.context/guides/auth.md`
+    pub document: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Its references changed in the range, in path order
+    pub references: Vec<ChangedReference>,
+    /// Whether the range also edits the document
+    pub updated: bool,
+}
+
+/// The documents to review for a revision range, in path order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checklist {
+    /// The revision range, e.g. `main..HEAD`
+    pub range: String,
+    pub items: Vec<ChecklistItem>,
+}
+
+/// Render a checklist as Markdown, linking paths through `source_url`
+pub fn markdown(checklist: &Checklist, source_url: Option<&str>) -> String {
+    let link = |path: &str| match source_url {
+        Some(template) => format!("[{path}]({})", template.replace("{path}", path)),
+        None => format!("`{path}`"),
+    };
+
+    let mut lines = vec![
+        format!("### Documentation to review ({})", checklist.range),
+        String::new(),
+    ];
+    if checklist.items.is_empty() {
+        lines.push("No documents reference the changed files.".to_string());
+    }
+    for item in &checklist.items {
+        let references: Vec<String> = item
+            .references
+            .iter()
+            .map(|r| {
+                format!(
+                    "{} ({})",
+                    link(&r.path),
+                    if r.deleted { "deleted" } else { "changed" }
+                )
+            })
+            .collect();
+        lines.push(format!(
+            "- [{}] {} — references {}",
+            if item.updated { "x" } else { " " },
+            link(&item.document.to_string_lossy()),
+            references.join(", ")
+        ));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checklist() -> Checklist {
+        Checklist {
+            range: "main..HEAD".to_string(),
+            items: vec![
+                ChecklistItem {
+                    document: PathBuf::from(".context/guides/auth.md"),
+                    slug: "auth".to_string(),
+                    references: vec![
+                        ChangedReference {
+                            path: "src/auth/jwt.rs".to_string(),
+                            deleted: false,
+                        },
+                        ChangedReference {
+                            path: "src/auth/old.rs".to_string(),
+                            deleted: true,
+                        },
+                    ],
+                    updated: false,
+                },
+                ChecklistItem {
+                    document: PathBuf::from(".context/billing.md"),
+                    slug: "billing".to_string(),
+                    references: vec![ChangedReference {
+                        path: "src/billing.rs".to_string(),
+                        deleted: false,
+                    }],
+                    updated: true,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_markdown_without_links() {
+        assert_eq!(
+            markdown(&checklist(), None),
+            "### Documentation to review (main..HEAD)\n\n\
+             - [ ] `.context/guides/auth.md` — references `src/auth/jwt.rs` (changed), `src/auth/old.rs` (deleted)\n\
+             - [x] `.context/billing.md` — references `src/billing.rs` (changed)\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_links_through_template() {
+        let rendered = markdown(&checklist(), Some("https://example.com/blob/main/{path}"));
+        assert!(rendered
+            .contains("[src/billing.rs](https://example.com/blob/main/src/billing.rs) (changed)"));
+        assert!(rendered.contains(
+            "- [x] [.context/billing.md](https://example.com/blob/main/.context/billing.md)"
+        ));
+    }
+}
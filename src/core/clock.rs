@@ -0,0 +1,139 @@
+//! The current time, which can be pinned for reproducible output
+//!
+//! Everything that stamps or ages documents (`updated` dates, journal
+//! timestamps, debt ages) asks this module instead of chrono directly.
+//! The system clock is used unless another [`Clock`] is installed with
+//! [`set`]; the CLI installs a [`FixedClock`] from the hidden
+//! `--fixed-time` flag or the `CONTEXT_FAKE_TIME` environment variable, so
+//! tests and rebuilds of a tree write the same dates every time.
+
+use crate::error::{ContextError, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Environment variable pinning the clock, as RFC 3339 or `YYYY-MM-DD`
+pub const FAKE_TIME_ENV: &str = "CONTEXT_FAKE_TIME";
+
+/// A source of the current time
+pub trait Clock: Send + Sync {
+    /// The current local time
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The operating system's clock, in the local time zone
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+}
+
+/// A clock stopped at one instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<FixedOffset>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0
+    }
+}
+
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Install `clock` for the whole process, or go back to the system clock
+/// with `None`
+pub fn set(clock: Option<Arc<dyn Clock>>) {
+    *CLOCK.write().unwrap_or_else(PoisonError::into_inner) = clock;
+}
+
+/// Pin the clock to `at`, or let it run again with `None`
+pub fn fix(at: Option<DateTime<FixedOffset>>) {
+    set(at.map(|at| Arc::new(FixedClock(at)) as Arc<dyn Clock>));
+}
+
+/// Parse a pinned time: RFC 3339, or a date meaning its midnight UTC
+pub fn parse(s: &str) -> Result<DateTime<FixedOffset>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(s) {
+        return Ok(at);
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| {
+            date.and_time(chrono::NaiveTime::MIN)
+                .and_utc()
+                .fixed_offset()
+        })
+        .map_err(|_| {
+            ContextError::Other(format!(
+                "Invalid time '{s}': expected RFC 3339 or YYYY-MM-DD"
+            ))
+        })
+}
+
+/// The time `CONTEXT_FAKE_TIME` pins, if set
+pub fn from_env() -> Result<Option<DateTime<FixedOffset>>> {
+    match std::env::var(FAKE_TIME_ENV) {
+        Ok(value) if !value.trim().is_empty() => parse(value.trim())
+            .map(Some)
+            .map_err(|e| ContextError::Other(format!("{FAKE_TIME_ENV}: {e}"))),
+        _ => Ok(None),
+    }
+}
+
+/// The date of an `updated` value, which is a plain date or, with
+/// `[updated] format = "timestamp"`, an RFC 3339 timestamp
+pub fn parse_updated(updated: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(updated, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(updated)
+                .ok()
+                .map(|at| at.date_naive())
+        })
+}
+
+/// The current local time (a pinned time keeps its own offset)
+pub fn now() -> DateTime<FixedOffset> {
+    match &*CLOCK.read().unwrap_or_else(PoisonError::into_inner) {
+        Some(clock) => clock.now(),
+        None => SystemClock.now(),
+    }
+}
+
+/// The current UTC time
+pub fn now_utc() -> DateTime<Utc> {
+    now().with_timezone(&Utc)
+}
+
+/// Today's local date
+pub fn today() -> NaiveDate {
+    now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepts_rfc3339_and_dates() {
+        let at = parse("2024-01-15T23:30:00-05:00").unwrap();
+        assert_eq!(
+            FixedClock(at).now().date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert_eq!(
+            parse("2024-01-15").unwrap().to_rfc3339(),
+            "2024-01-15T00:00:00+00:00"
+        );
+        assert!(parse("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_updated_accepts_dates_and_timestamps() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15);
+        assert_eq!(parse_updated("2024-01-15"), date);
+        assert_eq!(parse_updated("2024-01-15T23:30:00-05:00"), date);
+        assert_eq!(parse_updated(""), None);
+    }
+}
@@ -0,0 +1,573 @@
+//! Project configuration loaded from `.context/config.toml`
+
+use crate::core::clock;
+use crate::core::document::LOCAL_NAMESPACE;
+use crate::error::{ContextError, Result};
+use chrono::SecondsFormat;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file within the context directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Newest document schema version this binary understands
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Configuration for a context directory. Every section is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Document schema version the tree is written for, if declared
+    pub schema_version: Option<u32>,
+    /// Redaction rules applied to bodies leaving the process
+    pub redaction: RedactionConfig,
+    /// Description generation for `context describe`
+    pub describe: DescribeConfig,
+    /// Freshness weights for ranking documents
+    pub scoring: ScoringConfig,
+    /// Retention policy for runtime caches such as snapshots
+    pub gc: GcConfig,
+    /// Shared context trees overlaid read-only from other repositories
+    pub remotes: Vec<RemoteConfig>,
+    /// How un-prefixed slugs are resolved across namespaces
+    pub namespaces: NamespaceConfig,
+    /// Settings for the `context preview` website
+    pub preview: PreviewConfig,
+    /// Opt-in tracking of document reads
+    pub usage: UsageConfig,
+    /// Weights for the documentation debt score
+    pub debt: DebtConfig,
+    /// Summary metrics recorded per run for `context stats --trend`
+    pub trend: TrendConfig,
+    /// Where reference hashes and sync metadata are persisted
+    pub storage: StorageConfig,
+    /// Review requirements for syncing stale documents
+    pub review: ReviewConfig,
+    /// Locales that documents are translated into
+    pub i18n: I18nConfig,
+    /// Commands parsing and rendering diagrams in document bodies
+    pub diagrams: DiagramsConfig,
+    /// Retries and timeouts for reading referenced files
+    pub io: IoConfig,
+    /// Behavior of the git hooks installed by `context hooks install`
+    pub hooks: HooksConfig,
+    /// How `context query` maps build targets to files
+    pub query: QueryConfig,
+    /// How syncs stamp the `updated` field
+    pub updated: UpdatedConfig,
+    /// When `context archive` moves deprecated documents away
+    pub archive: ArchiveConfig,
+    /// Length and readability bounds `context lint` checks
+    pub readability: ReadabilityConfig,
+}
+
+/// Bounds on document length and readability for `context lint` (see
+/// `core::readability`); 0, the default, turns a bound off
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadabilityConfig {
+    /// Words in a body, code included
+    pub max_words: usize,
+    /// Average words per sentence
+    pub max_sentence_words: usize,
+    /// Words per heading
+    pub max_words_per_heading: usize,
+    /// Percentage of non-blank lines inside fenced code blocks
+    pub max_code_percent: u32,
+}
+
+/// When deprecated documents are due for `context archive` (see
+/// `core::archive`)
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArchiveConfig {
+    /// Days since a deprecated document was last `updated` before it is
+    /// archived (0 archives it on the next run)
+    pub after_days: u32,
+}
+
+/// How syncs stamp the `updated` field. Either form is read back, so a
+/// tree can switch format without rewriting every document.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdatedConfig {
+    /// Whose calendar dates the stamp: the syncing machine's or UTC's
+    pub timezone: UpdatedZone,
+    /// A plain date or a full timestamp
+    pub format: UpdatedFormat,
+}
+
+/// Time zone of the `updated` stamp
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdatedZone {
+    /// The local time of whoever syncs
+    #[default]
+    Local,
+    /// UTC, so syncs around midnight agree across time zones
+    Utc,
+}
+
+/// Form of the `updated` stamp
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdatedFormat {
+    /// `YYYY-MM-DD`
+    #[default]
+    Date,
+    /// RFC 3339 to the second, e.g. `2024-01-15T09:30:00Z`
+    Timestamp,
+}
+
+impl UpdatedConfig {
+    /// The `updated` value for a document synced now
+    pub fn stamp(self) -> String {
+        let now = match self.timezone {
+            UpdatedZone::Local => clock::now(),
+            UpdatedZone::Utc => clock::now_utc().fixed_offset(),
+        };
+        match self.format {
+            UpdatedFormat::Date => now.date_naive().to_string(),
+            UpdatedFormat::Timestamp => {
+                now.to_rfc3339_opts(SecondsFormat::Secs, self.timezone == UpdatedZone::Utc)
+            }
+        }
+    }
+}
+
+/// How `context query` maps build targets to files (see `core::query`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueryConfig {
+    /// Shell command printing the files of the build target given as `$1`,
+    /// one path or label per line
+    pub resolver: Option<String>,
+}
+
+/// Behavior of the git hooks installed by `context hooks install` (see `core::hooks`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Conventional-commit types (`docs` in `docs: fix typo`) whose commits
+    /// get no reminder of affected documents
+    pub skip_types: Vec<String>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            skip_types: vec!["docs".to_string()],
+        }
+    }
+}
+
+/// Retries and timeouts for reading referenced files (see `core::fsio`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IoConfig {
+    /// Extra attempts after a read fails with a transient error
+    pub retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for each further one
+    pub backoff_ms: u64,
+    /// Give up on a single read after this many milliseconds; 0 waits forever
+    pub timeout_ms: u64,
+}
+
+impl Default for IoConfig {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            backoff_ms: 50,
+            timeout_ms: 0,
+        }
+    }
+}
+
+/// Commands checking and rendering Mermaid and PlantUML blocks (see `core::diagrams`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiagramsConfig {
+    /// Shell command reading a Mermaid diagram on stdin and writing SVG to
+    /// stdout; only the built-in structural check runs when unset
+    pub mermaid: Option<String>,
+    /// Shell command reading a PlantUML diagram on stdin and writing SVG to stdout
+    pub plantuml: Option<String>,
+}
+
+/// Locale-suffixed translations of documents (see `core::i18n`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct I18nConfig {
+    /// Locales every document should be translated into, e.g. `["de", "fr"]`.
+    /// `auth.de.md` is only treated as a translation when `de` is listed.
+    pub locales: Vec<String>,
+}
+
+/// Evidence of review required before stale documents are re-synced (see `core::review`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReviewConfig {
+    /// Refuse to sync stale or orphaned documents without a named reviewer
+    pub require: bool,
+}
+
+/// Where reference hashes and sync metadata are persisted (see `core::storage`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Backend keeping references and hashes
+    pub backend: StorageBackend,
+}
+
+/// Storage backends for reference hashes and sync metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// In each document's YAML frontmatter
+    #[default]
+    Frontmatter,
+    /// In a SQLite database at `.context/.cache/context.db`
+    Sqlite,
+}
+
+/// Recording of summary metrics for `context stats --trend` (see `core::trend`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrendConfig {
+    /// Append a data point after each full sync
+    pub record: bool,
+    /// Rotate the trend journal once it reaches this many bytes
+    pub max_bytes: u64,
+    /// Rotated trend journals kept
+    pub keep: usize,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            record: true,
+            max_bytes: 1024 * 1024,
+            keep: 5,
+        }
+    }
+}
+
+/// Weights for `context stats --debt` (see `core::debt`). Each factor adds
+/// up to its weight, so with the defaults scores range from 0 to 100.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebtConfig {
+    /// Weight of how long an out-of-date document has gone unsynced
+    pub staleness: f64,
+    /// Weight of commits to a document's broken references since it was updated
+    pub churn: f64,
+    /// Weight of the share of a document's references that no longer match
+    pub coverage: f64,
+    /// Weight of how often an out-of-date document is read, relative to the most-read one
+    pub usage: f64,
+    /// Days unsynced at which the staleness factor is full
+    pub stale_days: u32,
+    /// Commits at which the churn factor is full
+    pub commits: u32,
+}
+
+impl Default for DebtConfig {
+    fn default() -> Self {
+        Self {
+            staleness: 40.0,
+            churn: 30.0,
+            coverage: 20.0,
+            usage: 10.0,
+            stale_days: 90,
+            commits: 10,
+        }
+    }
+}
+
+/// Opt-in read tracking for `context stats --usage` (see `core::usage`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageConfig {
+    /// Record every document read through MCP `context_get`
+    pub track: bool,
+    /// Rotate the usage journal once it reaches this many bytes
+    pub max_bytes: u64,
+    /// Rotated usage journals kept
+    pub keep: usize,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            track: false,
+            max_bytes: 1024 * 1024,
+            keep: 5,
+        }
+    }
+}
+
+/// Settings for `context preview` (see `preview::server`)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreviewConfig {
+    /// URL for viewing a referenced file, with `{path}` replaced by its
+    /// project-relative path (e.g. a GitHub blob URL). Without it, files are
+    /// shown by the preview server itself.
+    pub source_url: Option<String>,
+}
+
+/// Slug namespaces: `local` for the project's own documents and one per remote
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamespaceConfig {
+    /// Namespace searched first for slugs without a `namespace/` prefix
+    pub default: String,
+}
+
+impl Default for NamespaceConfig {
+    fn default() -> Self {
+        Self {
+            default: LOCAL_NAMESPACE.to_string(),
+        }
+    }
+}
+
+/// A context tree published in another git repository (see `core::remote`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// Name of the checkout under `.context/.remote/`
+    pub name: String,
+    /// Git URL to clone
+    pub url: String,
+    /// Directory within the repository containing its `.context`
+    #[serde(default)]
+    pub path: PathBuf,
+    /// Branch or tag to fetch instead of the default branch
+    pub rev: Option<String>,
+}
+
+/// Retention limits applied by `context gc` (see `core::gc`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GcConfig {
+    /// Entries older than this many days are removed (0 disables the limit)
+    pub max_age_days: u32,
+    /// Oldest entries are removed until each runtime directory fits in this
+    /// many bytes (0 disables the limit)
+    pub max_bytes: u64,
+    /// Newest entries of each runtime directory that are never removed
+    pub keep: usize,
+    /// Whether sync finishes with a light, age-only collection
+    pub after_sync: bool,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            max_bytes: 256 * 1024 * 1024,
+            keep: 5,
+            after_sync: true,
+        }
+    }
+}
+
+/// Weights used to rank documents by trustworthiness (see `core::scoring`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoringConfig {
+    /// Weight of a valid document
+    pub valid: f64,
+    /// Weight of a stale document
+    pub stale: f64,
+    /// Weight of an orphaned document
+    pub orphaned: f64,
+    /// Share of the score (0 to 1) that depends on how recently a document was updated
+    pub recency: f64,
+    /// Days after which the recency share of the score halves
+    pub half_life_days: f64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            valid: 1.0,
+            stale: 0.4,
+            orphaned: 0.1,
+            recency: 0.5,
+            half_life_days: 180.0,
+        }
+    }
+}
+
+/// How `context describe` generates candidate descriptions
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DescribeConfig {
+    /// Shell command that reads a document body on stdin and prints a
+    /// description; the built-in heuristic is used when unset
+    pub command: Option<String>,
+}
+
+/// Redaction rules for document bodies surfaced over MCP or exported
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedactionConfig {
+    /// Regular expressions whose matches are replaced in document bodies
+    pub patterns: Vec<String>,
+    /// Globs (relative to .context/) of documents whose entire body is withheld
+    pub paths: Vec<String>,
+    /// Text substituted for each redacted match
+    pub replacement: String,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            paths: Vec::new(),
+            replacement: "[REDACTED]".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration for a context directory, using defaults if absent
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        Self::parse(&content)
+    }
+
+    /// Parse configuration from TOML content
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self =
+            toml::from_str(content).map_err(|e| ContextError::ConfigError(e.to_string()))?;
+        config.validate_namespaces()?;
+        Ok(config)
+    }
+
+    /// Every remote must name a distinct namespace, and the default
+    /// namespace must exist
+    fn validate_namespaces(&self) -> Result<()> {
+        let mut names = vec![LOCAL_NAMESPACE];
+        for remote in &self.remotes {
+            if names.contains(&remote.name.as_str()) {
+                return Err(ContextError::ConfigError(format!(
+                    "remote name '{}' is already used as a namespace",
+                    remote.name
+                )));
+            }
+            names.push(&remote.name);
+        }
+        if !names.contains(&self.namespaces.default.as_str()) {
+            return Err(ContextError::ConfigError(format!(
+                "default namespace '{}' is neither '{LOCAL_NAMESPACE}' nor a configured remote",
+                self.namespaces.default
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(config.redaction.patterns.is_empty());
+        assert_eq!(config.redaction.replacement, "[REDACTED]");
+    }
+
+    #[test]
+    fn test_parse_schema_version() {
+        assert!(Config::parse("").unwrap().schema_version.is_none());
+        let config = Config::parse("schema_version = 2\n\n[io]\nretries = 0\n").unwrap();
+        assert_eq!(config.schema_version, Some(2));
+    }
+
+    #[test]
+    fn test_parse_redaction_config() {
+        let config = Config::parse(
+            r#"
+[redaction]
+patterns = ["AKIA[0-9A-Z]{16}"]
+paths = ["runbooks/**"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.redaction.patterns, vec!["AKIA[0-9A-Z]{16}"]);
+        assert_eq!(config.redaction.paths, vec!["runbooks/**"]);
+    }
+
+    #[test]
+    fn test_parse_describe_config() {
+        assert!(Config::parse("").unwrap().describe.command.is_none());
+        let config = Config::parse("[describe]\ncommand = \"llm -s 'Summarize'\"\n").unwrap();
+        assert_eq!(
+            config.describe.command.as_deref(),
+            Some("llm -s 'Summarize'")
+        );
+    }
+
+    #[test]
+    fn test_parse_scoring_config() {
+        let config = Config::parse("[scoring]\nstale = 0.2\n").unwrap();
+        assert!((config.scoring.stale - 0.2).abs() < f64::EPSILON);
+        assert!((config.scoring.valid - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_parse_gc_config() {
+        let config = Config::parse("[gc]\nmax_age_days = 7\nafter_sync = false\n").unwrap();
+        assert_eq!(config.gc.max_age_days, 7);
+        assert!(!config.gc.after_sync);
+        assert_eq!(config.gc.keep, 5);
+    }
+
+    #[test]
+    fn test_parse_remotes() {
+        let config = Config::parse(
+            r#"
+[[remotes]]
+name = "platform"
+url = "https://example.com/platform.git"
+rev = "v2"
+
+[[remotes]]
+name = "security"
+url = "https://example.com/security.git"
+path = "docs"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.remotes.len(), 2);
+        assert_eq!(config.remotes[0].path, PathBuf::new());
+        assert_eq!(config.remotes[0].rev.as_deref(), Some("v2"));
+        assert_eq!(config.remotes[1].path, PathBuf::from("docs"));
+    }
+
+    #[test]
+    fn test_namespaces_validated() {
+        let remote = "[[remotes]]\nname = \"platform\"\nurl = \"u\"\n";
+        let config =
+            Config::parse(&format!("{remote}[namespaces]\ndefault = \"platform\"\n")).unwrap();
+        assert_eq!(config.namespaces.default, "platform");
+        assert_eq!(Config::parse("").unwrap().namespaces.default, "local");
+
+        assert!(Config::parse("[namespaces]\ndefault = \"platform\"\n").is_err());
+        assert!(Config::parse(&format!("{remote}{remote}")).is_err());
+        assert!(Config::parse("[[remotes]]\nname = \"local\"\nurl = \"u\"\n").is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        assert!(Config::parse("[redaction]\npaterns = []\n").is_err());
+    }
+}
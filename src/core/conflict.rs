@@ -0,0 +1,244 @@
+use crate::error::{ContextError, Result};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Damage left in a document by a conflicting git merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// Unresolved conflict markers starting at the given 1-based line
+    Markers {
+        /// Line of the opening `<<<<<<<` marker
+        line: usize,
+        /// Whether the conflict is inside the frontmatter
+        in_frontmatter: bool,
+    },
+    /// A frontmatter key that appears more than once (`references.<path>` for
+    /// keys inside the references map)
+    DuplicateKey(String),
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Markers {
+                line,
+                in_frontmatter: true,
+            } => write!(f, "conflict markers in frontmatter at line {line}"),
+            Self::Markers { line, .. } => write!(f, "conflict markers in body at line {line}"),
+            Self::DuplicateKey(key) => write!(f, "duplicate frontmatter key '{key}'"),
+        }
+    }
+}
+
+/// Check raw document content for merge conflict damage.
+///
+/// Conflict markers only count when an opening `<<<<<<<` is followed by a
+/// closing `>>>>>>>`, so a setext heading underline is never mistaken for a
+/// `=======` separator.
+pub fn detect(content: &str) -> Option<Conflict> {
+    let frontmatter_end = frontmatter_end(content);
+
+    let lines: Vec<&str> = content.lines().collect();
+    if let Some(open) = lines.iter().position(|l| l.starts_with("<<<<<<<")) {
+        if lines[open..].iter().any(|l| l.starts_with(">>>>>>>")) {
+            return Some(Conflict::Markers {
+                line: open + 1,
+                in_frontmatter: frontmatter_end.is_some_and(|end| open < end),
+            });
+        }
+    }
+
+    let end = frontmatter_end?;
+    duplicate_key(&lines[1..end]).map(Conflict::DuplicateKey)
+}
+
+/// Repair the frontmatter of a document damaged by a merge.
+///
+/// Conflict markers in the frontmatter are dropped, keeping both sides, and
+/// where a key then appears twice the first ("ours") wins. The `references`
+/// map is emptied since it is re-derived from the body on the next sync.
+/// Conflicts in the body need a human, so they are reported as an error.
+pub fn repair(path: &Path, content: &str) -> Result<String> {
+    if let Some(
+        conflict @ Conflict::Markers {
+            in_frontmatter: false,
+            ..
+        },
+    ) = detect(content)
+    {
+        return Err(ContextError::MergeConflict {
+            path: path.to_path_buf(),
+            reason: conflict.to_string(),
+        });
+    }
+
+    let Some(end) = frontmatter_end(content) else {
+        return Ok(content.to_string());
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut seen = HashSet::new();
+    let mut skipping = false;
+    let mut repaired = vec!["---"];
+
+    for line in &lines[1..end] {
+        if is_marker(line) {
+            continue;
+        }
+        if let Some(key) = top_level_key(line) {
+            skipping = !seen.insert(key);
+            if !skipping && key == "references" {
+                repaired.push("references: {}");
+                skipping = true;
+                continue;
+            }
+        }
+        if !skipping {
+            repaired.push(line);
+        }
+    }
+
+    repaired.push("---");
+    repaired.extend(&lines[end + 1..]);
+
+    let mut out = repaired.join("\n");
+    if content.ends_with('\n') {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Index of the closing `---` line of the frontmatter, if there is one
+fn frontmatter_end(content: &str) -> Option<usize> {
+    if !content.starts_with("---\n") {
+        return None;
+    }
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .find(|(_, line)| *line == "---")
+        .map(|(idx, _)| idx)
+}
+
+fn is_marker(line: &str) -> bool {
+    line.starts_with("<<<<<<<")
+        || line.starts_with(">>>>>>>")
+        || line.starts_with("|||||||")
+        || line == "======="
+}
+
+/// The key of an unindented `key: value` line
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    mapping_key(line)
+}
+
+fn mapping_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let key = match trimmed.split_once(": ") {
+        Some((key, _)) => key,
+        None => trimmed.strip_suffix(':')?,
+    };
+    Some(key.trim_matches(['"', '\'']))
+}
+
+/// First key repeated at the top level or within the references map
+fn duplicate_key(frontmatter: &[&str]) -> Option<String> {
+    let mut top = HashSet::new();
+    let mut refs = HashSet::new();
+    let mut in_references = false;
+
+    for line in frontmatter {
+        if let Some(key) = top_level_key(line) {
+            if !top.insert(key) {
+                return Some(key.to_string());
+            }
+            in_references = key == "references";
+        } else if in_references && line.starts_with([' ', '\t']) {
+            if let Some(key) = mapping_key(line) {
+                if !refs.insert(key) {
+                    return Some(format!("references.{key}"));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFLICTED: &str = "---
+slug: auth
+description: Auth
+references:
+<<<<<<< HEAD
+  src/a.rs: 1111111
+=======
+  src/a.rs: 2222222
+  src/b.rs: 3333333
+>>>>>>> feature
+updated: 2025-01-01
+hash: abcdef0
+---
+
+# Auth
+
+Uses `src/a.rs`.
+";
+
+    #[test]
+    fn test_detect_markers_in_frontmatter() {
+        assert_eq!(
+            detect(CONFLICTED),
+            Some(Conflict::Markers {
+                line: 5,
+                in_frontmatter: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect_duplicate_keys() {
+        let content = "---\nslug: a\nreferences:\n  src/a.rs: 1\n  src/a.rs: 2\n---\n\nBody\n";
+        assert_eq!(
+            detect(content),
+            Some(Conflict::DuplicateKey("references.src/a.rs".to_string()))
+        );
+
+        let content = "---\nslug: a\nslug: b\n---\n\nBody\n";
+        assert_eq!(
+            detect(content),
+            Some(Conflict::DuplicateKey("slug".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_detect_ignores_setext_headings() {
+        let content = "---\nslug: a\n---\n\nTitle\n=======\n\nBody\n";
+        assert_eq!(detect(content), None);
+    }
+
+    #[test]
+    fn test_repair_frontmatter() {
+        let repaired = repair(Path::new("auth.md"), CONFLICTED).unwrap();
+        assert_eq!(detect(&repaired), None);
+        assert!(repaired.contains("references: {}"));
+        assert!(!repaired.contains("src/b.rs: "));
+        assert!(repaired.ends_with("Uses `src/a.rs`.\n"));
+    }
+
+    #[test]
+    fn test_repair_rejects_body_conflicts() {
+        let content = "---\nslug: a\n---\n\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> x\n";
+        assert!(matches!(
+            repair(Path::new("a.md"), content),
+            Err(ContextError::MergeConflict { .. })
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+//! Source coverage: which project files some document references
+//!
+//! The project's files come from git (tracked files plus untracked ones
+//! `.gitignore` doesn't exclude), or from walking the tree outside a
+//! repository. A file is covered when a local document references it;
+//! `.context` itself is never counted.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Covered and uncovered files, each in path order
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Coverage {
+    /// Files referenced by at least one document
+    pub covered: Vec<String>,
+    /// Files no document references
+    pub uncovered: Vec<String>,
+}
+
+impl Coverage {
+    /// Split `files` (project-relative) by whether `referenced` contains
+    /// them, keeping those under one of `prefixes` (all if empty)
+    pub fn compute(files: Vec<String>, referenced: &HashSet<&str>, prefixes: &[String]) -> Self {
+        let prefixes: Vec<&str> = prefixes
+            .iter()
+            .map(|p| p.trim_start_matches("./").trim_end_matches('/'))
+            .collect();
+        let under = |file: &str| {
+            prefixes.is_empty()
+                || prefixes.iter().any(|p| {
+                    p.is_empty()
+                        || file == *p
+                        || file
+                            .strip_prefix(p)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+        };
+
+        let mut coverage = Self::default();
+        for file in files {
+            if file.starts_with(".context/") || !under(&file) {
+                continue;
+            }
+            if referenced.contains(file.as_str()) {
+                coverage.covered.push(file);
+            } else {
+                coverage.uncovered.push(file);
+            }
+        }
+        coverage.covered.sort();
+        coverage.uncovered.sort();
+        coverage
+    }
+
+    /// Number of files considered
+    pub fn files(&self) -> usize {
+        self.covered.len() + self.uncovered.len()
+    }
+
+    /// Covered files as a percentage, 100 when there are no files
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.files() == 0 {
+            100.0
+        } else {
+            self.covered.len() as f64 * 100.0 / self.files() as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<String> {
+        [
+            "src/lib.rs",
+            "src/auth/jwt.rs",
+            "src/authz.rs",
+            ".context/index.md",
+            "build.rs",
+        ]
+        .iter()
+        .map(|f| (*f).to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_compute_splits_and_skips_context() {
+        let referenced = HashSet::from(["src/lib.rs", "src/auth/jwt.rs"]);
+        let coverage = Coverage::compute(files(), &referenced, &[]);
+        assert_eq!(coverage.covered, vec!["src/auth/jwt.rs", "src/lib.rs"]);
+        assert_eq!(coverage.uncovered, vec!["build.rs", "src/authz.rs"]);
+        assert!((coverage.percent() - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_prefixes_match_whole_components() {
+        let referenced = HashSet::from(["src/auth/jwt.rs"]);
+        let coverage = Coverage::compute(files(), &referenced, &["./src/auth/".to_string()]);
+        assert_eq!(coverage.files(), 1);
+        assert!((coverage.percent() - 100.0).abs() < f64::EPSILON);
+        assert!((Coverage::default().percent() - 100.0).abs() < f64::EPSILON);
+    }
+}
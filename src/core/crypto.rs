@@ -0,0 +1,131 @@
+//! Optional age encryption for document bodies marked `encrypted: true`
+//!
+//! Encryption shells out to an age-compatible binary (`age` by default, or
+//! `rage` via `CONTEXT_AGE_BIN`) so the key material never passes through
+//! this process's configuration. Keys are located through the environment:
+//!
+//! - `CONTEXT_AGE_IDENTITY`: identity file used to decrypt bodies on load
+//! - `CONTEXT_AGE_RECIPIENTS`: recipients file used to encrypt bodies on save
+
+use crate::error::{ContextError, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Header line of an ASCII-armored age file
+pub const ARMOR_HEADER: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Body shown in place of an encrypted document when no key is available
+pub const REDACTED_BODY: &str =
+    "[encrypted: this document's body is unavailable without the decryption key]\n";
+
+/// Check whether a document body is age ciphertext
+pub fn is_ciphertext(body: &str) -> bool {
+    body.trim_start().starts_with(ARMOR_HEADER)
+}
+
+/// Identity file for decryption, if configured
+pub fn identity_from_env() -> Option<PathBuf> {
+    std::env::var_os("CONTEXT_AGE_IDENTITY").map(PathBuf::from)
+}
+
+/// Recipients file for encryption, if configured
+pub fn recipients_from_env() -> Option<PathBuf> {
+    std::env::var_os("CONTEXT_AGE_RECIPIENTS").map(PathBuf::from)
+}
+
+/// The age-compatible binary to run
+pub fn age_binary() -> String {
+    std::env::var("CONTEXT_AGE_BIN").unwrap_or_else(|_| "age".to_string())
+}
+
+/// Decrypt an armored body, returning `None` when no identity is configured
+pub fn decrypt(ciphertext: &str) -> Result<Option<String>> {
+    let Some(identity) = identity_from_env() else {
+        return Ok(None);
+    };
+
+    let mut args = vec!["--decrypt".into(), "--identity".into()];
+    args.push(identity.into_os_string());
+    let plaintext = run_age(&args, ciphertext)?;
+    Ok(Some(plaintext))
+}
+
+/// Encrypt a body to the configured recipients as ASCII-armored ciphertext
+pub fn encrypt(plaintext: &str) -> Result<String> {
+    let recipients = recipients_from_env().ok_or_else(|| {
+        ContextError::EncryptionError(
+            "CONTEXT_AGE_RECIPIENTS must be set to save encrypted documents".to_string(),
+        )
+    })?;
+
+    let mut args = vec![
+        "--encrypt".into(),
+        "--armor".into(),
+        "--recipients-file".into(),
+    ];
+    args.push(recipients.into_os_string());
+    run_age(&args, plaintext)
+}
+
+/// Run the age binary with the given arguments, piping `input` through it
+fn run_age(args: &[std::ffi::OsString], input: &str) -> Result<String> {
+    pipe(&age_binary(), args, input)
+}
+
+/// Pipe `input` through `binary`. age streams its output, so stdin is fed
+/// from another thread while stdout is drained; writing it all first would
+/// block both processes once a body outgrows the pipe buffer.
+fn pipe(binary: &str, args: &[std::ffi::OsString], input: &str) -> Result<String> {
+    let mut child = Command::new(binary)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ContextError::EncryptionError(format!("failed to run {binary}: {e}")))?;
+
+    let stdin = child.stdin.take();
+    let output = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || match stdin {
+            // Dropping stdin at the end closes it, so the binary sees EOF
+            Some(mut stdin) => stdin.write_all(input.as_bytes()),
+            None => Ok(()),
+        });
+        let output = child.wait_with_output();
+        // A binary failing early closes its end of the pipe; its exit
+        // status and stderr explain more than the broken pipe would
+        let written = writer.join().unwrap_or(Ok(()));
+        output.and_then(|output| match written {
+            Err(e) if output.status.success() => Err(e),
+            _ => Ok(output),
+        })
+    })?;
+    if !output.status.success() {
+        return Err(ContextError::EncryptionError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| ContextError::EncryptionError(format!("invalid UTF-8 plaintext: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ciphertext() {
+        assert!(is_ciphertext("-----BEGIN AGE ENCRYPTED FILE-----\nYWdl\n"));
+        assert!(!is_ciphertext("# Runbook\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_streams_bodies_larger_than_the_pipe_buffer() {
+        let body = "The rotation runbook, line after line.\n".repeat(4096);
+        assert!(body.len() > 64 * 1024);
+        assert_eq!(pipe("cat", &[], &body).unwrap(), body);
+    }
+}
@@ -0,0 +1,235 @@
+//! Documentation debt: how much work out-of-date documents represent
+//!
+//! Each document gets a score from four factors weighted by `[debt]`:
+//! how long it has been out of date (days since `updated`), how much its
+//! broken references have churned since (commits touching them), the share
+//! of its references that no longer match, and how often it is read. Up to
+//! date documents owe nothing, so usage only raises the score of documents
+//! already in debt. Scores roll up per top-level directory and into a mean
+//! for the whole tree, which dashboards can track over time.
+
+use crate::core::clock;
+use crate::core::config::DebtConfig;
+use crate::core::git;
+use crate::core::models::Status;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// What a document's debt is computed from
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDebt {
+    /// Path of the document relative to the context root
+    pub document: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Validity status
+    pub status: Status,
+    /// Days since the document was updated, if it is out of date
+    pub stale_days: u32,
+    /// Commits touching its changed or missing references since it was updated
+    pub commits: u32,
+    /// References recorded
+    pub references: u32,
+    /// References that changed or went missing
+    pub broken: u32,
+    /// Recorded reads from the usage journal
+    pub reads: u32,
+    /// The debt score
+    pub score: f64,
+}
+
+impl DocumentDebt {
+    /// Share of references that still match (1 when there are none)
+    pub fn coverage(&self) -> f64 {
+        if self.references == 0 {
+            return 1.0;
+        }
+        1.0 - f64::from(self.broken) / f64::from(self.references)
+    }
+}
+
+/// Debt of the documents in one top-level directory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirectoryDebt {
+    /// Documents in the directory
+    pub documents: usize,
+    /// Sum of their scores
+    pub total: f64,
+    /// Mean of their scores
+    pub score: f64,
+}
+
+/// Debt across the context tree, highest first
+#[derive(Debug, Clone, Serialize)]
+pub struct DebtReport {
+    /// Mean score across all documents
+    pub score: f64,
+    /// Sum of all document scores
+    pub total: f64,
+    /// Debt per top-level directory (`.` for the context root itself)
+    pub directories: BTreeMap<String, DirectoryDebt>,
+    /// Per-document debt, highest first
+    pub documents: Vec<DocumentDebt>,
+}
+
+/// Days between `updated` and `today`, or 0 if the date is unparseable
+pub fn days_since(updated: &str, today: NaiveDate) -> u32 {
+    clock::parse_updated(updated).map_or(0, |date| {
+        u32::try_from((today - date).num_days().max(0)).unwrap_or(u32::MAX)
+    })
+}
+
+/// Commits touching any of `paths` since the `updated` date. Without git
+/// history, each path counts as one change.
+pub fn commits_since(project_root: &Path, updated: &str, paths: &[&str]) -> u32 {
+    if paths.is_empty() {
+        return 0;
+    }
+    let mut args = vec!["log".to_string(), "--format=%H".to_string()];
+    if !updated.is_empty() {
+        args.push(format!("--since={updated}"));
+    }
+    args.push("--".to_string());
+    args.extend(paths.iter().map(ToString::to_string));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let commits = git::run(project_root, &args).map_or(paths.len(), |log| log.lines().count());
+    u32::try_from(commits).unwrap_or(u32::MAX)
+}
+
+/// Score one document, given the most reads of any document
+pub fn score(config: &DebtConfig, doc: &DocumentDebt, max_reads: u32) -> f64 {
+    if doc.status == Status::Valid {
+        return 0.0;
+    }
+    let saturate = |value: u32, full: u32| (f64::from(value) / f64::from(full.max(1))).min(1.0);
+    let usage = if max_reads == 0 {
+        0.0
+    } else {
+        f64::from(doc.reads) / f64::from(max_reads)
+    };
+    let score = config.staleness * saturate(doc.stale_days, config.stale_days)
+        + config.churn * saturate(doc.commits, config.commits)
+        + config.coverage * (1.0 - doc.coverage())
+        + config.usage * usage;
+    round(score)
+}
+
+/// Score documents (paired with their top-level directory) and roll them up
+pub fn report(config: &DebtConfig, documents: Vec<(String, DocumentDebt)>) -> DebtReport {
+    let max_reads = documents.iter().map(|(_, d)| d.reads).max().unwrap_or(0);
+    let mut directories: BTreeMap<String, DirectoryDebt> = BTreeMap::new();
+    let mut scored = Vec::with_capacity(documents.len());
+    for (directory, mut doc) in documents {
+        doc.score = score(config, &doc, max_reads);
+        let entry = directories.entry(directory).or_default();
+        entry.documents += 1;
+        entry.total += doc.score;
+        scored.push(doc);
+    }
+    for entry in directories.values_mut() {
+        entry.total = round(entry.total);
+        entry.score = mean(entry.total, entry.documents);
+    }
+
+    scored.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.document.cmp(&b.document))
+    });
+    let total = round(scored.iter().map(|d| d.score).sum());
+    DebtReport {
+        score: mean(total, scored.len()),
+        total,
+        directories,
+        documents: scored,
+    }
+}
+
+fn mean(total: f64, count: usize) -> f64 {
+    match u32::try_from(count) {
+        Ok(0) | Err(_) => 0.0,
+        Ok(count) => round(total / f64::from(count)),
+    }
+}
+
+/// Round to one decimal so scores stay readable and stable in JSON
+fn round(score: f64) -> f64 {
+    (score * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(
+        name: &str,
+        status: Status,
+        stale_days: u32,
+        commits: u32,
+        broken: u32,
+        reads: u32,
+    ) -> DocumentDebt {
+        DocumentDebt {
+            document: PathBuf::from(name),
+            slug: name.to_string(),
+            status,
+            stale_days,
+            commits,
+            references: 4,
+            broken,
+            reads,
+            score: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_days_since() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(days_since("2025-05-02", today), 30);
+        assert_eq!(days_since("2025-07-01", today), 0);
+        assert_eq!(days_since("", today), 0);
+    }
+
+    #[test]
+    fn test_score_factors() {
+        let config = DebtConfig::default();
+        // Valid documents owe nothing, however often they're read
+        assert!(score(&config, &doc("a", Status::Valid, 0, 0, 0, 50), 50).abs() < 1e-9);
+        // Fully saturated on every factor
+        let worst = doc("b", Status::Orphaned, 365, 40, 4, 50);
+        assert!((score(&config, &worst, 50) - 100.0).abs() < 1e-9);
+        // Half the stale window, a fifth of the commits, one of four references, unread
+        let partial = doc("c", Status::Stale, 45, 2, 1, 0);
+        assert!((score(&config, &partial, 50) - 31.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_rolls_up_directories() {
+        let config = DebtConfig::default();
+        let report = report(
+            &config,
+            vec![
+                (
+                    "guides".to_string(),
+                    doc("guides/a.md", Status::Stale, 90, 0, 0, 0),
+                ),
+                (
+                    "guides".to_string(),
+                    doc("guides/b.md", Status::Valid, 0, 0, 0, 0),
+                ),
+                (".".to_string(), doc("c.md", Status::Stale, 45, 0, 0, 0)),
+            ],
+        );
+        let order: Vec<_> = report
+            .documents
+            .iter()
+            .map(|d| d.document.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(order, vec!["guides/a.md", "c.md", "guides/b.md"]);
+        assert!((report.directories["guides"].score - 20.0).abs() < 1e-9);
+        assert!((report.total - 60.0).abs() < 1e-9);
+        assert!((report.score - 20.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,184 @@
+//! Candidate descriptions for documents with an empty `description`
+//!
+//! The default generator is a heuristic over the body: the first sentence of
+//! the first prose paragraph, falling back to the first heading. A project
+//! can plug in its own generator (typically a script calling an LLM) with
+//! `[describe] command` in config.toml. The command runs through `sh -c` with
+//! the document body on stdin, and its trimmed stdout is the description.
+
+use crate::core::config::DescribeConfig;
+use crate::core::document::Document;
+use crate::error::{ContextError, Result};
+use regex::Regex;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+/// Longest description the heuristic will produce, in characters
+pub const MAX_LEN: usize = 160;
+
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").expect("valid regex"));
+static EMPHASIS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\*\*|__|\*|`").expect("valid regex"));
+
+/// A generated description for one document
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// The document's current description
+    pub current: String,
+    /// The generated description, if one could be derived
+    pub candidate: Option<String>,
+    /// Whether the candidate was written to the document's frontmatter
+    pub applied: bool,
+}
+
+/// Generate a description for a document with the configured generator
+pub fn generate(config: &DescribeConfig, doc: &Document) -> Result<Option<String>> {
+    match &config.command {
+        Some(command) => run_command(command, &doc.body),
+        None => Ok(heuristic(&doc.body)),
+    }
+}
+
+/// Derive a description from the body's first paragraph or heading
+pub fn heuristic(body: &str) -> Option<String> {
+    let mut in_code_block = false;
+    let mut first_heading = None;
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            if paragraph.is_empty() {
+                continue;
+            }
+            break;
+        }
+
+        if let Some(title) = trimmed.strip_prefix('#') {
+            if first_heading.is_none() {
+                first_heading = Some(title.trim_start_matches('#').trim());
+            }
+            if paragraph.is_empty() {
+                continue;
+            }
+            break;
+        }
+
+        let is_prose = !(trimmed.starts_with(['|', '<', '-', '*', '+', '>'])
+            || trimmed.chars().next().is_some_and(|c| c.is_ascii_digit()));
+        if is_prose {
+            paragraph.push(trimmed);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+
+    let text = if paragraph.is_empty() {
+        first_heading?.to_string()
+    } else {
+        first_sentence(&plain_text(&paragraph.join(" ")))
+    };
+
+    let text = truncate(text.trim());
+    (!text.is_empty()).then_some(text)
+}
+
+/// Strip inline markdown (links, emphasis, code spans) down to plain text
+fn plain_text(markdown: &str) -> String {
+    let unlinked = LINK.replace_all(markdown, "$1");
+    EMPHASIS.replace_all(&unlinked, "").into_owned()
+}
+
+fn first_sentence(text: &str) -> String {
+    match text.find(". ") {
+        Some(end) => text[..=end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// Cut text to `MAX_LEN` characters at a word boundary
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_LEN {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_LEN - 3).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{}...", cut.trim_end_matches([',', ';', ':']))
+}
+
+/// Run a configured generator command with the body on stdin
+fn run_command(command: &str, body: &str) -> Result<Option<String>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ContextError::Other(format!("failed to run describe command: {e}")))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(ContextError::Other(format!(
+            "describe command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let description = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!description.is_empty()).then_some(description))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_first_sentence() {
+        let body = "# Auth\n\nThe [auth](auth.md) module validates **JWT** tokens. It also refreshes them.\n";
+        assert_eq!(
+            heuristic(body).as_deref(),
+            Some("The auth module validates JWT tokens.")
+        );
+    }
+
+    #[test]
+    fn test_heuristic_skips_code_and_lists() {
+        let body = "# Build\n\n```sh\ncargo build\n```\n\n- step one\n\nRun the build with cargo\n";
+        assert_eq!(heuristic(body).as_deref(), Some("Run the build with cargo"));
+    }
+
+    #[test]
+    fn test_heuristic_falls_back_to_heading() {
+        assert_eq!(
+            heuristic("# Release Process\n\n- tag\n- publish\n").as_deref(),
+            Some("Release Process")
+        );
+        assert_eq!(heuristic(""), None);
+    }
+
+    #[test]
+    fn test_heuristic_truncates() {
+        let body = "word ".repeat(100);
+        let description = heuristic(&body).unwrap();
+        assert!(description.chars().count() <= MAX_LEN);
+        assert!(description.ends_with("word..."));
+    }
+}
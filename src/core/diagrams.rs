@@ -0,0 +1,355 @@
+//! Mermaid and PlantUML diagrams embedded in document bodies
+//!
+//! Fenced ` ```mermaid ` and ` ```plantuml ` (or ` ```puml `) blocks are
+//! checked by `context lint`. The built-in check is structural: a Mermaid
+//! block must open with a known diagram type and close its flowchart node
+//! shapes, and a PlantUML block must pair `@startuml` with `@enduml`. For a
+//! full parse, `[diagrams] mermaid` and `[diagrams] plantuml` name shell
+//! commands that read a diagram on stdin and write SVG to stdout, e.g.
+//! `mmdc -i - -o - -e svg -q` or `plantuml -tsvg -pipe`. A command exiting
+//! non-zero marks the diagram as broken, and `context diagrams render` uses
+//! the same commands to export every diagram as SVG.
+
+use crate::core::config::DiagramsConfig;
+use crate::error::{ContextError, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Diagram types Mermaid accepts as the first statement of a diagram
+const MERMAID_TYPES: &[&str] = &[
+    "graph",
+    "flowchart",
+    "sequenceDiagram",
+    "classDiagram",
+    "stateDiagram",
+    "stateDiagram-v2",
+    "erDiagram",
+    "journey",
+    "gantt",
+    "pie",
+    "quadrantChart",
+    "requirementDiagram",
+    "gitGraph",
+    "C4Context",
+    "C4Container",
+    "C4Component",
+    "C4Dynamic",
+    "C4Deployment",
+    "mindmap",
+    "timeline",
+    "zenuml",
+    "sankey-beta",
+    "xychart-beta",
+    "block-beta",
+    "packet-beta",
+    "architecture-beta",
+    "kanban",
+    "radar-beta",
+];
+
+/// Diagram languages that can be checked and rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramKind {
+    /// A ` ```mermaid ` block
+    Mermaid,
+    /// A ` ```plantuml ` or ` ```puml ` block
+    PlantUml,
+}
+
+impl DiagramKind {
+    fn from_info(info: &str) -> Option<Self> {
+        match info.split_whitespace().next()? {
+            "mermaid" => Some(Self::Mermaid),
+            "plantuml" | "puml" => Some(Self::PlantUml),
+            _ => None,
+        }
+    }
+
+    /// The command configured to parse and render this kind of diagram
+    pub fn command(self, config: &DiagramsConfig) -> Option<&str> {
+        match self {
+            Self::Mermaid => config.mermaid.as_deref(),
+            Self::PlantUml => config.plantuml.as_deref(),
+        }
+    }
+}
+
+impl std::fmt::Display for DiagramKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mermaid => write!(f, "mermaid"),
+            Self::PlantUml => write!(f, "plantuml"),
+        }
+    }
+}
+
+/// A diagram block within a document body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagram {
+    /// Language of the block
+    pub kind: DiagramKind,
+    /// 1-based body line of the opening fence
+    pub line: usize,
+    /// Content between the fences
+    pub source: String,
+}
+
+/// The outcome of rendering one diagram
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedDiagram {
+    /// Document containing the diagram
+    pub document: PathBuf,
+    /// 1-based body line of the opening fence
+    pub line: usize,
+    /// Language of the diagram
+    pub kind: DiagramKind,
+    /// SVG file written, `None` if rendering failed or no command is configured
+    pub svg: Option<PathBuf>,
+    /// Why rendering failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// File name for the `n`th (1-based) diagram of the document with `slug`
+pub fn file_name(slug: &str, n: usize) -> String {
+    format!("{}-{n}.svg", slug.replace('/', "-"))
+}
+
+/// Every diagram block in a body, in order. An unclosed block runs to the
+/// end of the body, as in Markdown.
+pub fn extract(body: &str) -> Vec<Diagram> {
+    let mut diagrams = Vec::new();
+    let mut open: Option<(Option<DiagramKind>, usize, String)> = None;
+    for (idx, line) in body.lines().enumerate() {
+        let trimmed = line.trim_start();
+        match &mut open {
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    open = Some((DiagramKind::from_info(info), idx + 1, String::new()));
+                }
+            }
+            Some((_, _, _)) if trimmed.starts_with("```") => {
+                if let Some((Some(kind), line, source)) = open.take() {
+                    diagrams.push(Diagram { kind, line, source });
+                }
+            }
+            Some((_, _, source)) => {
+                source.push_str(line);
+                source.push('\n');
+            }
+        }
+    }
+    if let Some((Some(kind), line, source)) = open {
+        diagrams.push(Diagram { kind, line, source });
+    }
+    diagrams
+}
+
+/// Why a diagram is broken, or `None` if it passes the built-in check and
+/// the configured command (if any)
+pub fn check(diagram: &Diagram, config: &DiagramsConfig) -> Option<String> {
+    let problem = match diagram.kind {
+        DiagramKind::Mermaid => check_mermaid(&diagram.source),
+        DiagramKind::PlantUml => check_plantuml(&diagram.source),
+    };
+    if problem.is_some() {
+        return problem;
+    }
+    let command = diagram.kind.command(config)?;
+    render(diagram, command).err().map(|e| e.to_string())
+}
+
+/// Render a diagram to SVG with a configured command
+pub fn render(diagram: &Diagram, command: &str) -> Result<Vec<u8>> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ContextError::Other(format!("failed to run {} command: {e}", diagram.kind)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(diagram.source.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(ContextError::Other(format!(
+            "{} command failed: {}",
+            diagram.kind,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+fn check_mermaid(source: &str) -> Option<String> {
+    // Skip `%%` comments or directives and an optional `---` config block
+    let mut lines = source
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("%%"));
+    let mut first = lines.next();
+    if first == Some("---") {
+        first = lines
+            .by_ref()
+            .find(|l| *l == "---")
+            .and_then(|_| lines.next());
+    }
+    let Some(first) = first else {
+        return Some("diagram is empty".to_string());
+    };
+    let keyword = first.split_whitespace().next().unwrap_or_default();
+    if !MERMAID_TYPES.contains(&keyword) {
+        return Some(format!("unknown diagram type '{keyword}'"));
+    }
+    // Other diagram types use brackets freely, e.g. `||--o{` in ER diagrams
+    if matches!(keyword, "graph" | "flowchart") {
+        return unclosed_shape(source);
+    }
+    None
+}
+
+fn check_plantuml(source: &str) -> Option<String> {
+    let mut open: Option<&str> = None;
+    for line in source.lines().map(str::trim) {
+        if let Some(kind) = line.strip_prefix("@start") {
+            if let Some(kind) = open {
+                return Some(format!("@start{kind} is not closed before the next @start"));
+            }
+            open = Some(kind.split_whitespace().next().unwrap_or_default());
+        } else if let Some(kind) = line.strip_prefix("@end") {
+            match open.take() {
+                Some(start) if start == kind.trim() => {}
+                Some(start) => {
+                    return Some(format!("@start{start} is closed by @end{}", kind.trim()))
+                }
+                None => return Some(format!("@end{} without @start", kind.trim())),
+            }
+        }
+    }
+    match open {
+        Some(kind) => Some(format!("@start{kind} is never closed")),
+        None if !source.contains("@start") => Some("no @startuml".to_string()),
+        None => None,
+    }
+}
+
+/// The first flowchart line opening a node shape it doesn't close. Only
+/// openers are checked: `>` nodes close a bracket they never open.
+fn unclosed_shape(source: &str) -> Option<String> {
+    for (idx, line) in source.lines().enumerate() {
+        let mut depth = 0usize;
+        let mut quoted = false;
+        for c in line.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                _ if quoted => {}
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        if depth > 0 {
+            return Some(format!("unclosed node shape on diagram line {}", idx + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_diagram_blocks() {
+        let body = "# Flow\n\n```mermaid\ngraph TD\n  A --> B\n```\n\n```rust\nfn main() {}\n```\n\n```puml\n@startuml\n@enduml\n```\n";
+        let diagrams = extract(body);
+        assert_eq!(diagrams.len(), 2);
+        assert_eq!(diagrams[0].kind, DiagramKind::Mermaid);
+        assert_eq!(diagrams[0].line, 3);
+        assert_eq!(diagrams[0].source, "graph TD\n  A --> B\n");
+        assert_eq!(diagrams[1].kind, DiagramKind::PlantUml);
+    }
+
+    #[test]
+    fn test_builtin_checks() {
+        let config = DiagramsConfig::default();
+        let diagram = |kind, source: &str| Diagram {
+            kind,
+            line: 1,
+            source: source.to_string(),
+        };
+        assert_eq!(
+            check(
+                &diagram(
+                    DiagramKind::Mermaid,
+                    "%% note\nflowchart LR\n  A[Start] --> B(\"x)\")\n"
+                ),
+                &config
+            ),
+            None
+        );
+        assert_eq!(
+            check(
+                &diagram(
+                    DiagramKind::Mermaid,
+                    "---\ntitle: x\n---\nsequenceDiagram\n"
+                ),
+                &config
+            ),
+            None
+        );
+        assert!(check(&diagram(DiagramKind::Mermaid, "grpah TD\n"), &config)
+            .unwrap()
+            .contains("grpah"));
+        assert!(check(
+            &diagram(DiagramKind::Mermaid, "graph TD\n  A[Start --> B\n"),
+            &config
+        )
+        .is_some());
+        assert!(check(&diagram(DiagramKind::Mermaid, ""), &config).is_some());
+        assert_eq!(
+            check(
+                &diagram(DiagramKind::PlantUml, "@startuml\nA -> B\n@enduml\n"),
+                &config
+            ),
+            None
+        );
+        assert!(check(
+            &diagram(DiagramKind::PlantUml, "@startuml\nA -> B\n"),
+            &config
+        )
+        .is_some());
+        assert!(check(&diagram(DiagramKind::PlantUml, "A -> B\n"), &config).is_some());
+    }
+
+    #[test]
+    fn test_configured_command_decides() {
+        let diagram = Diagram {
+            kind: DiagramKind::Mermaid,
+            line: 1,
+            source: "graph TD\n".to_string(),
+        };
+        let failing = DiagramsConfig {
+            mermaid: Some("echo 'Parse error on line 1' >&2; exit 1".to_string()),
+            plantuml: None,
+        };
+        assert!(check(&diagram, &failing).unwrap().contains("Parse error"));
+
+        let rendering = DiagramsConfig {
+            mermaid: Some("cat >/dev/null; echo '<svg/>'".to_string()),
+            plantuml: None,
+        };
+        assert_eq!(check(&diagram, &rendering), None);
+        assert_eq!(
+            render(&diagram, "cat >/dev/null; echo '<svg/>'").unwrap(),
+            b"<svg/>\n"
+        );
+    }
+}
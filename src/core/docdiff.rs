@@ -0,0 +1,229 @@
+//! How the context tree itself changed between two revisions
+//!
+//! Documents are read as of a git revision (or from the working tree) and
+//! matched by path. Each side's status is computed against the referenced
+//! files as of that same side, so a reviewer sees both what was edited in
+//! the documents and what the code change did to their validity.
+
+use crate::core::document::{hash, Document};
+use crate::core::frontmatter;
+use crate::core::git;
+use crate::core::models::Status;
+use crate::core::refindex;
+use crate::core::{markdown_files, CONTEXT_DIR_NAME};
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Where to read a side of the comparison from
+#[derive(Debug, Clone, Copy)]
+pub enum Side<'a> {
+    /// A git revision
+    Revision(&'a str),
+    /// The files on disk
+    WorkingTree,
+}
+
+impl Side<'_> {
+    /// Contents of a file relative to the project root, `None` if absent
+    fn read(self, project_root: &Path, path: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Revision(rev) => git::show(project_root, rev, path),
+            Self::WorkingTree => std::fs::read(project_root.join(path)).ok(),
+        }
+    }
+}
+
+/// What happened to a document's file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Modified,
+    /// The file is identical but its status moved with the code
+    Unchanged,
+}
+
+/// A value before and after
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Delta<T> {
+    pub from: T,
+    pub to: T,
+}
+
+/// How one document changed
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDiff {
+    /// Path relative to the project root, e.g. `.context/guides/auth.md`
+    pub path: PathBuf,
+    /// Slug on the newer side, or the older one for removed documents
+    pub slug: String,
+    pub change: Change,
+    /// Old and new description, if it changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Delta<String>>,
+    pub references_added: Vec<String>,
+    pub references_dropped: Vec<String>,
+    /// Whether the body text changed
+    pub body_changed: bool,
+    /// Status on each side; `None` where the document doesn't exist
+    pub status: Delta<Option<Status>>,
+}
+
+/// The documents that differ between two sides, in path order
+#[derive(Debug, Clone, Serialize)]
+pub struct DocsDiff {
+    pub from: String,
+    /// The newer revision, or `None` for the working tree
+    pub to: Option<String>,
+    pub documents: Vec<DocumentDiff>,
+}
+
+/// The documents of the context tree as of `side`, keyed by path relative
+/// to the project root. Dot directories such as `.templates` are skipped,
+/// as when loading.
+pub fn documents(project_root: &Path, side: Side) -> Result<BTreeMap<PathBuf, Document>> {
+    let paths: Vec<String> = match side {
+        Side::Revision(rev) => git::run(
+            project_root,
+            &["ls-tree", "-r", "--name-only", rev, "--", CONTEXT_DIR_NAME],
+        )?
+        .lines()
+        .filter(|p| {
+            let inner = p.strip_prefix(CONTEXT_DIR_NAME).unwrap_or(p);
+            Path::new(p).extension().is_some_and(|ext| ext == "md")
+                && !inner.split('/').any(|c| c.starts_with('.'))
+        })
+        .map(str::to_string)
+        .collect(),
+        Side::WorkingTree => markdown_files(&project_root.join(CONTEXT_DIR_NAME))
+            .filter_map(|p| {
+                p.strip_prefix(project_root)
+                    .ok()
+                    .map(|p| p.to_string_lossy().into_owned())
+            })
+            .collect(),
+    };
+
+    let mut documents = BTreeMap::new();
+    for path in paths {
+        let Some(content) = side.read(project_root, &path) else {
+            continue;
+        };
+        let doc = frontmatter::parse(PathBuf::from(&path), &String::from_utf8_lossy(&content))?;
+        documents.insert(PathBuf::from(path), doc);
+    }
+    Ok(documents)
+}
+
+/// A document's status against the referenced files as of `side`
+pub fn status(doc: &Document, project_root: &Path, side: Side) -> Status {
+    let mut status = Status::Valid;
+    for (reference, stored) in &doc.references {
+        match side.read(project_root, refindex::normalize(reference)) {
+            None => return Status::Orphaned,
+            Some(content) if hash(&content) != *stored => status = Status::Stale,
+            Some(_) => {}
+        }
+    }
+    status
+}
+
+/// Compare the documents on two sides, keeping those that were added,
+/// removed or edited, or whose status moved
+pub fn diff(project_root: &Path, from: Side, to: Side) -> Result<Vec<DocumentDiff>> {
+    let before = documents(project_root, from)?;
+    let after = documents(project_root, to)?;
+    let paths: BTreeSet<&PathBuf> = before.keys().chain(after.keys()).collect();
+
+    let mut diffs = Vec::new();
+    for path in paths {
+        let old = before.get(path);
+        let new = after.get(path);
+        let references = |doc: Option<&Document>| -> BTreeSet<String> {
+            doc.map(|d| d.references.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+        let (old_refs, new_refs) = (references(old), references(new));
+        let status = Delta {
+            from: old.map(|d| status(d, project_root, from)),
+            to: new.map(|d| status(d, project_root, to)),
+        };
+        let description = match (old, new) {
+            (Some(o), Some(n)) if o.description != n.description => Some(Delta {
+                from: o.description.clone(),
+                to: n.description.clone(),
+            }),
+            _ => None,
+        };
+        let body_changed = old.map(|d| &d.body) != new.map(|d| &d.body);
+        let change = match (old, new) {
+            (None, _) => Change::Added,
+            (_, None) => Change::Removed,
+            _ if description.is_some() || old_refs != new_refs || body_changed => Change::Modified,
+            _ if status.from != status.to => Change::Unchanged,
+            _ => continue,
+        };
+        let slug = new.or(old).map(|d| d.slug.clone()).unwrap_or_default();
+        diffs.push(DocumentDiff {
+            path: path.clone(),
+            slug,
+            change,
+            description,
+            references_added: new_refs.difference(&old_refs).cloned().collect(),
+            references_dropped: old_refs.difference(&new_refs).cloned().collect(),
+            body_changed,
+            status,
+        });
+    }
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_working_tree_documents_skip_dot_directories() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            ".context/guides/auth.md",
+            "---\nslug: auth\n---\n\nAuth.\n",
+        );
+        write(dir.path(), ".context/.templates/adr.md", "# ADR\n");
+        let docs = documents(dir.path(), Side::WorkingTree).unwrap();
+        let paths: Vec<_> = docs.keys().cloned().collect();
+        assert_eq!(paths, vec![PathBuf::from(".context/guides/auth.md")]);
+    }
+
+    #[test]
+    fn test_status_against_working_tree() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "src/lib.rs", "// lib");
+        let mut doc = frontmatter::parse(
+            PathBuf::from(".context/a.md"),
+            "---\nslug: a\n---\n\nBody\n",
+        )
+        .unwrap();
+        doc.references
+            .insert("src/lib.rs".to_string(), hash(b"// lib"));
+        assert_eq!(status(&doc, dir.path(), Side::WorkingTree), Status::Valid);
+        write(dir.path(), "src/lib.rs", "// edited");
+        assert_eq!(status(&doc, dir.path(), Side::WorkingTree), Status::Stale);
+        doc.references.insert("src/gone.rs".to_string(), hash(b""));
+        assert_eq!(
+            status(&doc, dir.path(), Side::WorkingTree),
+            Status::Orphaned
+        );
+    }
+}
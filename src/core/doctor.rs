@@ -0,0 +1,275 @@
+//! Structural health checks for a context tree
+//!
+//! `context doctor` reads every document on its own, so it keeps working
+//! when a tree is too broken to load (duplicate slugs or unparseable
+//! frontmatter make loading fail). Each finding carries a stable code for
+//! scripts and a severity; only errors fail the check. Pairs of documents
+//! whose bodies overlap heavily are reported too (see `core::similarity`).
+
+use crate::core::archive;
+use crate::core::document::Document;
+use crate::core::similarity;
+use crate::core::{encoding, frontmatter, markdown_files};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The tree can't be loaded or is ambiguous
+    Error,
+    /// Likely a mistake
+    Warning,
+    /// Worth a look
+    Info,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// One problem found in the tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Machine-readable code, e.g. `duplicate-slug`
+    pub code: &'static str,
+    pub severity: Severity,
+    /// The document or directory concerned
+    pub path: PathBuf,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl Finding {
+    fn new(
+        code: &'static str,
+        severity: Severity,
+        path: &Path,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            code,
+            severity,
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Check every document under `context_dir`, returning the findings in
+/// path order. Bodies at least `duplicate_threshold` similar (0 to 1) are
+/// reported as duplicated.
+pub fn examine(context_dir: &Path, duplicate_threshold: f64) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut documents = Vec::new();
+    for path in markdown_files(context_dir) {
+        let parsed = encoding::read(&path)
+            .and_then(|decoded| frontmatter::parse(path.clone(), &decoded.text));
+        match parsed {
+            Ok(doc) => documents.push(doc),
+            Err(e) => findings.push(Finding::new(
+                "unparseable-frontmatter",
+                Severity::Error,
+                &path,
+                e.to_string(),
+            )),
+        }
+    }
+
+    let mut directories: BTreeSet<PathBuf> = BTreeSet::from([context_dir.to_path_buf()]);
+    let mut claims: BTreeMap<String, Vec<&Path>> = BTreeMap::new();
+    for doc in &documents {
+        if let Some(dir) = doc.path.parent() {
+            directories.insert(dir.to_path_buf());
+        }
+        for slug in doc.slugs() {
+            claims.entry(slug).or_default().push(&doc.path);
+        }
+        findings.extend(examine_document(doc));
+    }
+
+    for dir in directories {
+        if !dir.join("index.md").is_file() {
+            findings.push(Finding::new(
+                "missing-index",
+                Severity::Warning,
+                &dir,
+                "directory has no index.md",
+            ));
+        }
+    }
+    for (slug, paths) in claims.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        for path in &paths {
+            let others: Vec<String> = paths
+                .iter()
+                .filter(|p| *p != path)
+                .map(|p| p.display().to_string())
+                .collect();
+            findings.push(Finding::new(
+                "duplicate-slug",
+                Severity::Error,
+                path,
+                format!("slug '{slug}' is also claimed by {}", others.join(", ")),
+            ));
+        }
+    }
+
+    // Archived documents are expected to resemble their successors, and
+    // encrypted bodies can't be compared
+    let bodies: Vec<(PathBuf, &str)> = documents
+        .iter()
+        .filter(|doc| !doc.encrypted && !archive::is_archived(context_dir, &doc.path))
+        .map(|doc| (doc.path.clone(), doc.body.as_str()))
+        .collect();
+    for pair in similarity::similar_pairs(&bodies, duplicate_threshold) {
+        findings.push(Finding::new(
+            "duplicate-content",
+            Severity::Warning,
+            &pair.a,
+            format!(
+                "body is {:.0}% similar to {}",
+                pair.similarity * 100.0,
+                pair.b.display()
+            ),
+        ));
+    }
+
+    findings.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.severity.cmp(&b.severity))
+            .then(a.code.cmp(b.code))
+    });
+    findings
+}
+
+/// Checks that need only the document itself
+fn examine_document(doc: &Document) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let stem = doc
+        .path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let leaf = doc.slug.rsplit('/').next().unwrap_or_default();
+    if !doc.slug.is_empty() && stem != "index" && leaf != stem {
+        findings.push(Finding::new(
+            "slug-mismatch",
+            Severity::Warning,
+            &doc.path,
+            format!("slug '{}' doesn't match the file name '{stem}'", doc.slug),
+        ));
+    }
+    if doc.description.trim().is_empty() {
+        findings.push(Finding::new(
+            "empty-description",
+            Severity::Warning,
+            &doc.path,
+            "description is empty",
+        ));
+    }
+    if doc.references.is_empty() && stem != "index" {
+        findings.push(Finding::new(
+            "no-references",
+            Severity::Info,
+            &doc.path,
+            "document references no files, so it can never go stale",
+        ));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn codes(findings: &[Finding], root: &Path) -> Vec<(String, &'static str)> {
+        findings
+            .iter()
+            .map(|f| {
+                (
+                    f.path.strip_prefix(root).unwrap().display().to_string(),
+                    f.code,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_examine_reports_structural_problems() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join(".context");
+        write(
+            &root,
+            "index.md",
+            "---\nslug: index\ndescription: Docs\n---\n\n# Docs\n",
+        );
+        write(&root, "guides/auth.md", "---\nslug: login\ndescription: Login\nreferences:\n  src/a.rs: abc\n---\n\nSee `src/a.rs`.\n");
+        write(
+            &root,
+            "guides/login.md",
+            "---\nslug: login\ndescription: ''\nreferences:\n  src/a.rs: abc\n---\n\nBody\n",
+        );
+        write(
+            &root,
+            "guides/broken.md",
+            "---\nslug: [unclosed\n---\n\nBody\n",
+        );
+        write(
+            &root,
+            "notes.md",
+            "---\nslug: notes\ndescription: Notes\n---\n\nNo references.\n",
+        );
+        let body = "\n\nRefresh tokens rotate hourly and are checked against the signing key on every request.\n";
+        write(
+            &root,
+            "references/tokens.md",
+            &format!("---\nslug: tokens\ndescription: Tokens\n---{body}"),
+        );
+        write(
+            &root,
+            "references/index.md",
+            &format!("---\nslug: references\ndescription: Copied\n---{body}"),
+        );
+
+        let findings = examine(&root, similarity::DEFAULT_THRESHOLD);
+        assert_eq!(
+            codes(&findings, &root),
+            vec![
+                ("guides".to_string(), "missing-index"),
+                ("guides/auth.md".to_string(), "duplicate-slug"),
+                ("guides/auth.md".to_string(), "slug-mismatch"),
+                ("guides/broken.md".to_string(), "unparseable-frontmatter"),
+                ("guides/login.md".to_string(), "duplicate-slug"),
+                ("guides/login.md".to_string(), "empty-description"),
+                ("notes.md".to_string(), "no-references"),
+                ("references/index.md".to_string(), "duplicate-content"),
+                ("references/tokens.md".to_string(), "no-references"),
+            ]
+        );
+        assert_eq!(findings[1].severity, Severity::Error);
+        assert_eq!(findings[6].severity, Severity::Info);
+        assert_eq!(
+            findings[7].message,
+            format!(
+                "body is 100% similar to {}",
+                root.join("references/tokens.md").display()
+            )
+        );
+    }
+}
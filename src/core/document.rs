@@ -1,13 +1,27 @@
-use crate::core::frontmatter;
-use crate::core::models::{Status, Validation};
-use crate::core::paths::{extract_paths, validate_path, PathError};
+use crate::core::config::UpdatedConfig;
+use crate::core::encoding::{self, BOM};
+use crate::core::fsio::{self, ReadPolicy};
+use crate::core::models::{
+    DocumentState, SectionValidation, Status, UnreadableReference, Validation,
+};
+use crate::core::paths::{self, extract_paths, validate_path, PathError};
+use crate::core::{conflict, crypto, frontmatter, sections};
 use crate::error::{InvalidReference, Result};
-use chrono::Local;
 use sha2::{Digest, Sha256};
-use std::{collections::HashMap, path::{Path, PathBuf}};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// Namespace of documents in the project's own context tree. Remote
+/// documents are namespaced by the name of their remote.
+pub const LOCAL_NAMESPACE: &str = "local";
 
 /// A document in the context cache
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent frontmatter flags, not a state machine
 pub struct Document {
     /// File path of this document within the context directory
     pub path: PathBuf,
@@ -19,10 +33,58 @@ pub struct Document {
     pub references: HashMap<String, String>,
     /// Last update date (ISO 8601 format: YYYY-MM-DD)
     pub updated: String,
-    /// Content hash of the document body (excluding frontmatter)
+    /// Content hash of the document body (excluding frontmatter). Empty for
+    /// encrypted documents, where it would fingerprint the plaintext, so
+    /// only their references decide when they were last `updated`.
     pub hash: String,
+    /// Full SHA-256 of the body as `sha256:<hex>`, recorded at sync time so
+    /// external tools can detect document changes without reading bodies.
+    /// `hash` covers the same bytes but keeps only 7 hex digits (28 bits),
+    /// enough to notice an edit to one document but not to key embeddings
+    /// or exports across many documents without collisions, and it doesn't
+    /// name its algorithm. Not recorded for encrypted documents, like `hash`.
+    pub body_hash: Option<String>,
     /// Document body content (after frontmatter)
     pub body: String,
+    /// Free-form labels for grouping and filtering documents
+    pub tags: Vec<String>,
+    /// People or teams responsible for keeping the document accurate
+    pub owners: Vec<String>,
+    /// Reference paths contributed by external tools (`context refs import`)
+    /// rather than mentioned in the body; sync keeps and re-hashes them
+    pub external_references: Vec<String>,
+    /// Lifecycle state (draft, published or deprecated)
+    pub state: DocumentState,
+    /// Slug of the document this one was merged into or replaced by, if any
+    pub superseded_by: Option<String>,
+    /// Name of the document template (`.context/.templates/<name>.md`) this
+    /// document was created from
+    pub template: Option<String>,
+    /// Hash of the template file when this document was created from it
+    pub template_version: Option<String>,
+    /// Body hash of the source document when this translation was last
+    /// synced (see `core::i18n`)
+    pub source_hash: Option<String>,
+    /// Whether the body is stored age-encrypted on disk
+    pub encrypted: bool,
+    /// Whether only a sync naming this document may refresh its hashes;
+    /// bulk syncs skip it
+    pub protected: bool,
+    /// Original ciphertext of an encrypted body that could not be decrypted.
+    /// When set, `body` holds a redacted placeholder.
+    pub ciphertext: Option<String>,
+    /// Name of the remote context this read-only document was fetched from
+    pub remote: Option<String>,
+    /// Byte offset of the first invalid UTF-8 sequence in the file, if any.
+    /// Invalid bytes were replaced with U+FFFD, so saving rewrites them.
+    pub invalid_utf8_at: Option<usize>,
+    /// Whether references and hashes are kept by a storage backend (see
+    /// `core::storage`) rather than in frontmatter, which then omits them
+    pub external_metadata: bool,
+    /// How referenced files are read, from the `[io]` config section
+    pub read_policy: ReadPolicy,
+    /// False when the body was dropped to save memory (see `unload_body`)
+    body_loaded: bool,
 }
 
 impl Document {
@@ -43,28 +105,206 @@ impl Document {
             references,
             updated,
             hash,
+            body_hash: None,
             body,
+            tags: Vec::new(),
+            owners: Vec::new(),
+            external_references: Vec::new(),
+            state: DocumentState::default(),
+            superseded_by: None,
+            template: None,
+            template_version: None,
+            source_hash: None,
+            encrypted: false,
+            protected: false,
+            ciphertext: None,
+            remote: None,
+            invalid_utf8_at: None,
+            external_metadata: false,
+            read_policy: ReadPolicy::default(),
+            body_loaded: true,
+        }
+    }
+
+    /// Namespace the document's slug lives in: its remote, or `local`
+    pub fn namespace(&self) -> &str {
+        self.remote.as_deref().unwrap_or(LOCAL_NAMESPACE)
+    }
+
+    /// Slug prefixed with its namespace, e.g. `platform/auth`, which
+    /// identifies the document even when other namespaces share its slug
+    pub fn qualified_slug(&self) -> String {
+        format!("{}/{}", self.namespace(), self.slug)
+    }
+
+    /// Slug derived from the document's path within its context directory,
+    /// without the extension, e.g. `guides/auth/tokens`
+    pub fn path_slug(&self) -> String {
+        let context_dir = self.project_root().map(|root| root.join(".context"));
+        let relative = context_dir
+            .as_deref()
+            .and_then(|dir| self.path.strip_prefix(dir).ok())
+            .unwrap_or(&self.path);
+        relative
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Last component of the path slug, e.g. `tokens` for `guides/auth/tokens`
+    pub fn leaf_slug(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Slugs this document answers to: its frontmatter slug and its path
+    /// slug. Index files share the `index` slug by default, so they only
+    /// answer to their path slug (`index`, `guides/index`), as do documents
+    /// without a frontmatter slug.
+    pub fn slugs(&self) -> Vec<String> {
+        let path_slug = self.path_slug();
+        if self.slug.is_empty() || self.slug == path_slug || self.leaf_slug() == "index" {
+            vec![path_slug]
+        } else {
+            vec![self.slug.clone(), path_slug]
         }
     }
+
+    /// Whether this is an encrypted document that could not be decrypted
+    pub fn is_locked(&self) -> bool {
+        self.ciphertext.is_some()
+    }
+
+    /// Whether `body` holds the document's body, rather than having been unloaded
+    pub fn has_body(&self) -> bool {
+        self.body_loaded
+    }
+
+    /// A copy of this document's metadata with the body of another copy of it
+    pub(crate) fn with_body_of(&self, loaded: &Document) -> Document {
+        let mut doc = self.clone();
+        doc.body.clone_from(&loaded.body);
+        doc.ciphertext.clone_from(&loaded.ciphertext);
+        doc.body_loaded = true;
+        doc
+    }
+
+    /// Drop the body to save memory, keeping only frontmatter metadata.
+    ///
+    /// Saving an unloaded document keeps the body currently on disk.
+    pub fn unload_body(&mut self) {
+        self.body = String::new();
+        self.ciphertext = None;
+        self.body_loaded = false;
+    }
 }
 
 impl Document {
-    /// Load a document from the given path
+    /// Load a document from the given path, decoding invalid UTF-8 lossily
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
-        let content = std::fs::read_to_string(path)?;
-        frontmatter::parse(path.to_path_buf(), &content)
+        let decoded = encoding::read(path)?;
+        let mut doc = Self::from_content(path, &decoded.text)?;
+        doc.invalid_utf8_at = decoded.invalid_at;
+        Ok(doc)
     }
 
-    /// Save the document to disk
+    /// Load only a document's frontmatter, leaving the body unloaded.
+    ///
+    /// Reading stops at the closing `---`, so the cost no longer grows with
+    /// body size. `invalid_utf8_at` only covers the frontmatter. Files
+    /// without complete frontmatter are loaded in full and then unloaded.
+    pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut head = Vec::new();
+        let mut line = Vec::new();
+
+        reader.read_until(b'\n', &mut line)?;
+        let opening = line.strip_prefix(BOM).unwrap_or(&line);
+        let closed = opening == b"---\n"
+            && loop {
+                head.append(&mut line);
+                if reader.read_until(b'\n', &mut line)? == 0 {
+                    break false;
+                }
+                if line == b"---\n" {
+                    head.append(&mut line);
+                    break true;
+                }
+            };
+
+        let mut doc = if closed {
+            let decoded = encoding::decode(&head);
+            let mut doc = Self::from_content(path, &decoded.text)?;
+            doc.invalid_utf8_at = decoded.invalid_at;
+            doc
+        } else {
+            Self::load(path)?
+        };
+        doc.unload_body();
+        Ok(doc)
+    }
+
+    /// Parse a document from raw file content, as `load` would
+    pub fn from_content(path: &Path, content: &str) -> Result<Self> {
+        // Merge damage would otherwise surface as a confusing YAML error
+        if let Some(conflict) = conflict::detect(content) {
+            return Err(crate::error::ContextError::MergeConflict {
+                path: path.to_path_buf(),
+                reason: conflict.to_string(),
+            });
+        }
+
+        let mut doc = frontmatter::parse(path.to_path_buf(), content)?;
+
+        // Decrypt encrypted bodies, or redact them when no key is available
+        if doc.encrypted && crypto::is_ciphertext(&doc.body) {
+            if let Some(plaintext) = crypto::decrypt(&doc.body)? {
+                doc.body = plaintext;
+            } else {
+                doc.ciphertext = Some(std::mem::take(&mut doc.body));
+                doc.body = crypto::REDACTED_BODY.to_string();
+            }
+        }
+
+        Ok(doc)
+    }
+
+    /// Save the document to disk, encrypting the body if required
     pub fn save(&self) -> Result<()> {
-        let content = frontmatter::serialize(self)?;
+        if let Some(remote) = &self.remote {
+            return Err(crate::error::ContextError::RemoteError(format!(
+                "{}: documents from remote '{remote}' are read-only",
+                self.path.display()
+            )));
+        }
+
+        if !self.body_loaded {
+            // Rewrite the frontmatter around the stored (possibly encrypted) body
+            let on_disk = frontmatter::parse(self.path.clone(), &encoding::read(&self.path)?.text)?;
+            std::fs::write(
+                &self.path,
+                frontmatter::serialize_with_body(self, &on_disk.body)?,
+            )?;
+            return Ok(());
+        }
+
+        let content = match (&self.ciphertext, self.encrypted) {
+            (Some(ciphertext), _) => frontmatter::serialize_with_body(self, ciphertext)?,
+            (None, true) => frontmatter::serialize_with_body(self, &crypto::encrypt(&self.body)?)?,
+            (None, false) => frontmatter::serialize(self)?,
+        };
         std::fs::write(&self.path, content)?;
         Ok(())
     }
 
     /// Get the project root directory (parent of .context/)
-    fn project_root(&self) -> Option<PathBuf> {
+    pub(crate) fn project_root(&self) -> Option<PathBuf> {
         // Walk up the path to find the ".context" directory
         let mut current = self.path.parent();
         while let Some(dir) = current {
@@ -77,7 +317,7 @@ impl Document {
     }
 
     /// Resolve a reference path relative to the project root
-    fn resolve_ref_path(&self, ref_path: &str) -> PathBuf {
+    pub fn resolve_ref_path(&self, ref_path: &str) -> PathBuf {
         if let Some(root) = self.project_root() {
             root.join(ref_path)
         } else {
@@ -85,11 +325,97 @@ impl Document {
         }
     }
 
+    /// Locate the first body line mentioning a reference path in backticks.
+    ///
+    /// Returns the 1-based line number and the anchor of the nearest
+    /// enclosing heading, skipping fenced code blocks like `extract_paths`.
+    pub fn locate_reference(&self, ref_path: &str) -> Option<(usize, Option<String>)> {
+        let ref_path = paths::asset_path(ref_path).unwrap_or(ref_path);
+        let plain = format!("`{ref_path}`");
+        let dotted = format!("`./{ref_path}`");
+        let mut in_code_block = false;
+
+        let line = self.body.lines().enumerate().find_map(|(idx, line)| {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                return None;
+            }
+            (!in_code_block && (line.contains(&plain) || line.contains(&dotted))).then_some(idx + 1)
+        })?;
+
+        let index = sections::index(&self.body);
+        let anchor = sections::section_at(&index, line).map(|s| s.anchor.clone());
+        Some((line, anchor))
+    }
+
+    /// Paths a sync derives references from: those mentioned in the body,
+    /// followed by any external references not already mentioned. Assets
+    /// are given as their `.context/assets/...` reference.
+    fn sync_paths(&self, project_root: &Path) -> Vec<String> {
+        let mut paths = extract_paths(&self.body);
+        for path in &self.external_references {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        for path in &mut paths {
+            if let Some(asset) = paths::asset_reference(path, project_root) {
+                *path = asset;
+            }
+        }
+        paths
+    }
+
+    /// Drop recorded references the body (or the external declarations) no
+    /// longer mentions, without re-hashing anything else. Returns the
+    /// dropped references; nothing is dropped from an unreadable body.
+    pub fn drop_unmentioned_references(&mut self) -> Vec<String> {
+        let Some(project_root) = self.project_root() else {
+            return Vec::new();
+        };
+        if !self.body_loaded || self.is_locked() {
+            return Vec::new();
+        }
+        let mentioned: HashSet<String> = self
+            .sync_paths(&project_root)
+            .iter()
+            .map(|path| paths::normalize_path(path))
+            .collect();
+        let mut dropped: Vec<String> = self
+            .references
+            .keys()
+            .filter(|r| !mentioned.contains(*r))
+            .cloned()
+            .collect();
+        dropped.sort();
+        for reference in &dropped {
+            self.references.remove(reference);
+        }
+        dropped
+    }
+
+    /// References to files in the project, excluding assets
+    pub fn source_references(&self) -> impl Iterator<Item = &String> {
+        self.references.keys().filter(|r| !paths::is_asset(r))
+    }
+
+    /// References to assets within the context directory (see `core::paths`)
+    pub fn asset_references(&self) -> impl Iterator<Item = &String> {
+        self.references.keys().filter(|r| paths::is_asset(r))
+    }
+
     /// Validate paths extracted from the document body.
     ///
     /// Returns a list of invalid references, or an empty vec if all are valid.
     /// This is the first phase of a two-phase sync for atomicity.
     pub fn prepare_sync(&self) -> Vec<InvalidReference> {
+        self.prepare_sync_pruning(false)
+    }
+
+    /// Validate like `prepare_sync`; with `prune_missing`, paths that don't
+    /// exist are not invalid, since a pruning sync drops them. Absolute and
+    /// traversal paths always are.
+    pub fn prepare_sync_pruning(&self, prune_missing: bool) -> Vec<InvalidReference> {
         let Some(project_root) = self.project_root() else {
             return vec![InvalidReference::new(
                 "<unknown>".to_string(),
@@ -97,15 +423,18 @@ impl Document {
             )];
         };
 
-        let paths = extract_paths(&self.body);
+        let paths = self.sync_paths(&project_root);
         let mut invalid = Vec::new();
 
         for path in paths {
-            if let Err(reason) = validate_path(&path, &project_root) {
-                invalid.push(InvalidReference::new(path, reason));
+            match validate_path(&path, &project_root) {
+                Err(PathError::NotFound) if prune_missing => {}
+                Err(reason) => invalid.push(InvalidReference::new(path, reason)),
+                Ok(_) => {}
             }
         }
 
+        invalid.sort_by(|a, b| a.path.cmp(&b.path));
         invalid
     }
 
@@ -113,16 +442,89 @@ impl Document {
     ///
     /// This replaces all existing references with paths discovered from the body.
     /// Call `prepare_sync()` first to validate paths if atomic behavior is needed.
-    /// The `updated` date is only changed if the document body has changed.
+    /// The `updated` date is only changed if the references or body changed,
+    /// and the file is left untouched if nothing did.
     pub fn sync(&mut self) -> Result<()> {
+        if self.refresh()? {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Sync like `sync`, but drop references to files that no longer exist
+    /// instead of failing on them. Returns the pruned paths; mentions of
+    /// them stay in the body for the author to reword.
+    pub fn sync_pruning_missing(&mut self) -> Result<Vec<String>> {
+        let mut pruned = Vec::new();
+        if self.refresh_sharing(None, UpdatedConfig::default(), Some(&mut pruned))? {
+            self.save()?;
+        }
+        Ok(pruned)
+    }
+
+    /// Re-derive references and hashes as `sync` does, without saving.
+    /// Returns whether any of them changed.
+    pub fn refresh(&mut self) -> Result<bool> {
+        self.refresh_sharing(None, UpdatedConfig::default(), None)
+    }
+
+    /// Refresh like `refresh`, but for a translation take the references
+    /// and body hash of its source, if given (see `core::i18n`), and stamp
+    /// `updated` as configured. Given `pruned`, references to missing files
+    /// are dropped and collected there instead of failing the refresh.
+    pub(crate) fn refresh_sharing(
+        &mut self,
+        source: Option<(HashMap<String, String>, String)>,
+        stamp: UpdatedConfig,
+        pruned: Option<&mut Vec<String>>,
+    ) -> Result<bool> {
+        let reviewed = (
+            self.references.clone(),
+            self.hash.clone(),
+            self.source_hash.clone(),
+        );
+        let body_hash = self.body_hash.clone();
+        self.rederive(pruned)?;
+        if let Some((references, source_hash)) = source {
+            self.references = references;
+            self.source_hash = Some(source_hash);
+        }
+
+        // Only a change to what was reviewed makes the document newly updated
+        let changed = reviewed
+            != (
+                self.references.clone(),
+                self.hash.clone(),
+                self.source_hash.clone(),
+            );
+        if changed {
+            self.updated = stamp.stamp();
+        }
+        Ok(changed || body_hash != self.body_hash)
+    }
+
+    /// Replace references and hashes with those derived from the body now,
+    /// pruning missing files into `pruned` if given
+    fn rederive(&mut self, mut pruned: Option<&mut Vec<String>>) -> Result<()> {
         let project_root = self.project_root().ok_or_else(|| {
-            crate::error::ContextError::SyncError(
-                "Could not determine project root".to_string(),
-            )
+            crate::error::ContextError::SyncError("Could not determine project root".to_string())
         })?;
 
-        // Extract paths from the document body
-        let paths = extract_paths(&self.body);
+        if !self.body_loaded {
+            return Err(crate::error::ContextError::SyncError(
+                "cannot sync a document whose body is not loaded".to_string(),
+            ));
+        }
+
+        // References can't be re-derived from a body we can't read
+        if self.is_locked() {
+            return Err(crate::error::ContextError::EncryptionError(
+                "cannot sync an encrypted document without the decryption key".to_string(),
+            ));
+        }
+
+        // Extract paths from the document body, plus externally declared ones
+        let paths = self.sync_paths(&project_root);
 
         // Validate and hash each path
         let mut new_references: HashMap<String, String> = HashMap::new();
@@ -132,10 +534,20 @@ impl Document {
             match validate_path(&path, &project_root) {
                 Ok(normalized) => {
                     let full_path = project_root.join(&normalized);
-                    let content = std::fs::read(&full_path)?;
+                    let content = fsio::read(&full_path, self.read_policy).map_err(|e| {
+                        crate::error::ContextError::SyncError(format!(
+                            "cannot read {normalized}: {e}"
+                        ))
+                    })?;
                     let file_hash = hash(&content);
                     new_references.insert(normalized, file_hash);
                 }
+                Err(PathError::NotFound) if pruned.is_some() => {
+                    self.external_references.retain(|r| *r != path);
+                    if let Some(pruned) = pruned.as_deref_mut() {
+                        pruned.push(path);
+                    }
+                }
                 Err(reason) => {
                     invalid.push(InvalidReference::new(path, reason));
                 }
@@ -144,6 +556,7 @@ impl Document {
 
         // If any paths are invalid, return error
         if !invalid.is_empty() {
+            invalid.sort_by(|a, b| a.path.cmp(&b.path));
             return Err(crate::error::ContextError::InvalidReferences {
                 count: 1,
                 documents: vec![(self.path.clone(), invalid)],
@@ -153,51 +566,138 @@ impl Document {
         // Replace all references with newly discovered paths
         self.references = new_references;
 
-        // Compute hash of the document body
-        let new_hash = hash(self.body.as_bytes());
-
-        // Only update the date if the body has changed
-        if self.hash != new_hash {
-            self.updated = Local::now().format("%Y-%m-%d").to_string();
-        }
-
-        // Always update the hash
-        self.hash = new_hash;
-
-        // Save to disk
-        self.save()
+        // Always update the hashes; either would fingerprint the plaintext of
+        // an encrypted body
+        self.hash = if self.encrypted {
+            String::new()
+        } else {
+            hash(self.body.as_bytes())
+        };
+        self.body_hash = (!self.encrypted).then(|| body_hash(&self.body));
+        Ok(())
     }
 
     /// Validate the document's references
     pub fn validate(&self) -> Result<Validation> {
         let mut validation = Validation::new(self.path.clone(), Status::Valid);
 
-        for (ref_path, stored_hash) in &self.references {
-            let resolved_path = self.resolve_ref_path(ref_path);
+        // Sorted so changed/missing lists come out in a stable order
+        let mut references: Vec<_> = self.references.iter().collect();
+        references.sort();
 
-            if resolved_path.exists() {
-                let content = std::fs::read(&resolved_path)?;
-                let current_hash = hash(&content);
+        for (ref_path, stored_hash) in references {
+            let resolved_path = self.resolve_ref_path(ref_path);
 
-                if current_hash != *stored_hash {
-                    validation.add_changed(ref_path.clone());
+            match fsio::read(&resolved_path, self.read_policy) {
+                Ok(content) => {
+                    if hash(&content) != *stored_hash {
+                        validation.add_changed(ref_path.clone());
+                        if validation.status != Status::Orphaned {
+                            validation.status = Status::Stale;
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    validation.add_missing(ref_path.clone());
+                    validation.status = Status::Orphaned;
+                }
+                // Unconfirmed references count as stale, like changed ones
+                Err(e) => {
+                    validation.unreadable.push(UnreadableReference {
+                        reference: ref_path.clone(),
+                        error: e.to_string(),
+                    });
                     if validation.status != Status::Orphaned {
                         validation.status = Status::Stale;
                     }
                 }
-            } else {
-                validation.add_missing(ref_path.clone());
-                validation.status = Status::Orphaned;
             }
         }
 
+        if validation.status != Status::Valid && self.body_loaded {
+            validation.sections = self.validate_sections(&validation);
+        }
         Ok(validation)
     }
+
+    /// Status of each scoped section mentioning a changed or missing reference
+    pub(crate) fn validate_sections(&self, validation: &Validation) -> Vec<SectionValidation> {
+        let index = sections::index(&self.body);
+        let mut stale = Vec::new();
+        for section in sections::scoped(&self.body, &index) {
+            let mentioned = extract_paths(&sections::extract(&self.body, section));
+            let mentions = |reference: &&String| {
+                let written = paths::asset_path(reference).unwrap_or(reference);
+                mentioned.iter().any(|m| m == written)
+            };
+            let changed: Vec<String> = validation
+                .changed
+                .iter()
+                .filter(mentions)
+                .cloned()
+                .collect();
+            let missing: Vec<String> = validation
+                .missing
+                .iter()
+                .filter(mentions)
+                .cloned()
+                .collect();
+            if changed.is_empty() && missing.is_empty() {
+                continue;
+            }
+            stale.push(SectionValidation {
+                title: section.title.clone(),
+                anchor: section.anchor.clone(),
+                status: if missing.is_empty() {
+                    Status::Stale
+                } else {
+                    Status::Orphaned
+                },
+                changed,
+                missing,
+            });
+        }
+        stale
+    }
+}
+
+/// Content address of a document body, as recorded in `body_hash`: unlike
+/// the short `hash`, the full digest, labelled with its algorithm
+pub fn body_hash(body: &str) -> String {
+    format!("sha256:{:x}", Sha256::digest(body.as_bytes()))
 }
 
+/// `body` with ` [label]` after each backticked mention of a reference in
+/// `labels`, skipping fenced code blocks like `extract_paths`
+pub fn annotate_references(body: &str, labels: &BTreeMap<String, &str>) -> String {
+    let mut mentions: Vec<(String, &str)> = Vec::new();
+    for (reference, label) in labels {
+        let written = paths::asset_path(reference).unwrap_or(reference);
+        mentions.push((format!("`{written}`"), label));
+        mentions.push((format!("`./{written}`"), label));
+    }
+
+    let mut in_code_block = false;
+    let mut annotated = String::with_capacity(body.len());
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            annotated.push_str(line);
+            continue;
+        }
+        let mut line = line.to_string();
+        for (mention, label) in &mentions {
+            line = line.replace(mention.as_str(), &format!("{mention} [{label}]"));
+        }
+        annotated.push_str(&line);
+    }
+    annotated
+}
 
 /// Compute SHA-256 hash of content, returning the first 7 characters of the hash
-fn hash(content: &[u8]) -> String {
+pub(crate) fn hash(content: &[u8]) -> String {
     let hash = Sha256::digest(content);
     format!("{hash:x}")[..7].to_string()
 }
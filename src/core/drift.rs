@@ -0,0 +1,118 @@
+//! When a document went stale, found from the history of its references
+//!
+//! Each reference records the hash its file had when the document was last
+//! synced. Walking a changed reference's git history from newest to oldest,
+//! the newest commit whose version of the file still has that hash is the
+//! synced version, and the commit after it is the one that made the
+//! document stale. When no committed version matches (the document was
+//! synced against uncommitted edits, or history is shallow), the first
+//! commit since the document's `updated` date is reported instead and
+//! marked approximate.
+
+use crate::core::document::hash;
+use crate::core::git;
+use crate::core::models::Status;
+use crate::error::Result;
+use chrono::DateTime;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A commit in a reference's history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Commit {
+    /// Full commit hash
+    pub hash: String,
+    /// Author name
+    pub author: String,
+    /// Author date, RFC 3339
+    pub date: String,
+    /// First line of the commit message
+    pub subject: String,
+}
+
+/// The change that made one reference out of date
+#[derive(Debug, Clone, Serialize)]
+pub struct Drift {
+    /// Reference path relative to the project root
+    pub reference: String,
+    /// Whether the file has since been deleted
+    pub missing: bool,
+    /// First commit changing the file after the sync, or `None` if the
+    /// change hasn't been committed
+    pub commit: Option<Commit>,
+    /// Whether the commit was found by date rather than by content
+    pub approximate: bool,
+}
+
+/// When each out-of-date reference of a document changed
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleReport {
+    /// Path to the document file
+    pub document: PathBuf,
+    /// The document's status
+    pub status: Status,
+    /// Date the document was last updated
+    pub updated: String,
+    /// Changed and missing references, in path order
+    pub references: Vec<Drift>,
+}
+
+/// Find the first commit changing `reference` after it had `synced_hash`,
+/// falling back to the first commit since `updated`
+pub fn first_change(
+    project_root: &Path,
+    reference: &str,
+    synced_hash: &str,
+    updated: &str,
+    missing: bool,
+) -> Result<Drift> {
+    let commits = history(project_root, reference)?;
+
+    // Newest first, so the commit before the match is the change after it
+    let synced = commits.iter().position(|c| {
+        git::show(project_root, &c.hash, reference)
+            .is_some_and(|content| hash(&content) == synced_hash)
+    });
+    let (commit, approximate) = match synced {
+        Some(i) => (i.checked_sub(1).map(|i| commits[i].clone()), false),
+        None if updated.is_empty() => (None, true),
+        None => {
+            // RFC 3339 dates compare correctly as strings against a plain
+            // date; a timestamp may be in another offset
+            let since = match DateTime::parse_from_rfc3339(updated) {
+                Ok(updated) => commits.iter().rev().find(|c| {
+                    DateTime::parse_from_rfc3339(&c.date).is_ok_and(|date| date >= updated)
+                }),
+                Err(_) => commits.iter().rev().find(|c| c.date.as_str() >= updated),
+            };
+            (since.cloned(), true)
+        }
+    };
+
+    Ok(Drift {
+        reference: reference.to_string(),
+        missing,
+        commit,
+        approximate,
+    })
+}
+
+/// Commits touching `path`, newest first
+fn history(project_root: &Path, path: &str) -> Result<Vec<Commit>> {
+    let log = git::run(
+        project_root,
+        &["log", "--format=%H%x1f%an%x1f%aI%x1f%s", "--", path],
+    )?;
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,74 @@
+//! Tolerant decoding of document files
+//!
+//! A single invalid byte (typically from a bad paste) must not stop every
+//! command from loading the tree, so documents are decoded lossily: invalid
+//! sequences become U+FFFD and the problem is reported as a warning and by
+//! the `non-utf8` lint rule. A leading UTF-8 byte order mark is dropped.
+
+use crate::error::Result;
+use std::path::Path;
+
+/// UTF-8 byte order mark, dropped when decoding
+pub const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Text decoded from a document file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// The decoded text, without any byte order mark
+    pub text: String,
+    /// Byte offset in the file of the first invalid UTF-8 sequence, if any
+    pub invalid_at: Option<usize>,
+}
+
+/// Decode bytes as UTF-8, replacing invalid sequences
+pub fn decode(bytes: &[u8]) -> Decoded {
+    let (skipped, bytes) = match bytes.strip_prefix(BOM) {
+        Some(rest) => (BOM.len(), rest),
+        None => (0, bytes),
+    };
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Decoded {
+            text: text.to_string(),
+            invalid_at: None,
+        },
+        Err(e) => Decoded {
+            text: String::from_utf8_lossy(bytes).into_owned(),
+            invalid_at: Some(skipped + e.valid_up_to()),
+        },
+    }
+}
+
+/// Read and decode a file
+pub fn read(path: &Path) -> Result<Decoded> {
+    Ok(decode(&std::fs::read(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_utf8_unchanged() {
+        let decoded = decode("# Título\n".as_bytes());
+        assert_eq!(decoded.text, "# Título\n");
+        assert_eq!(decoded.invalid_at, None);
+    }
+
+    #[test]
+    fn test_bom_stripped() {
+        let decoded = decode(b"\xEF\xBB\xBF---\nslug: a\n---\n");
+        assert_eq!(decoded.text, "---\nslug: a\n---\n");
+        assert_eq!(decoded.invalid_at, None);
+    }
+
+    #[test]
+    fn test_invalid_bytes_replaced() {
+        let decoded = decode(b"abc\xFFdef");
+        assert_eq!(decoded.text, "abc\u{FFFD}def");
+        assert_eq!(decoded.invalid_at, Some(3));
+
+        let decoded = decode(b"\xEF\xBB\xBFabc\xFF");
+        assert_eq!(decoded.text, "abc\u{FFFD}");
+        assert_eq!(decoded.invalid_at, Some(6));
+    }
+}
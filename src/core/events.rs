@@ -0,0 +1,166 @@
+//! Progress events for applications embedding `Cache`
+//!
+//! GUIs and bots can subscribe to a cache to surface progress and partial
+//! results as operations run, instead of waiting for the final result or
+//! parsing CLI output:
+//!
+//! ```no_run
+//! # use context::core::{Cache, events::Event};
+//! # fn main() -> context::error::Result<()> {
+//! let mut cache = Cache::create(".context".into())?;
+//! cache.subscribe(|event: &Event| eprintln!("{event:?}"));
+//! cache.load()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Something that happened during a cache operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A document was read and parsed
+    DocumentLoaded {
+        /// Path to the document file
+        path: PathBuf,
+    },
+    /// One of a document's references was checked against the source tree
+    ReferenceValidated {
+        /// Path to the document file
+        document: PathBuf,
+        /// The reference path as stored in the document
+        reference: String,
+        /// What the check found
+        outcome: ReferenceOutcome,
+    },
+    /// A document's body was added to the full-text index
+    DocumentIndexed {
+        /// Path to the document file
+        path: PathBuf,
+    },
+    /// A document's references and hash were updated and saved
+    SyncApplied {
+        /// Path to the document file
+        document: PathBuf,
+    },
+    /// Something needs attention but the operation continued
+    Warning {
+        /// The document involved, if the warning concerns one
+        document: Option<PathBuf>,
+        /// Description of the problem
+        message: String,
+    },
+    /// An operation failed, possibly for a single document
+    Error {
+        /// The document involved, if the error concerns one
+        document: Option<PathBuf>,
+        /// Description of the error
+        message: String,
+    },
+}
+
+/// A non-fatal problem an operation worked around
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// The document involved, if the warning concerns one
+    pub document: Option<PathBuf>,
+    /// Description of the problem
+    pub message: String,
+}
+
+/// Warnings collected across operations, shareable between caches so a
+/// caller can report them alongside results or fail on them
+#[derive(Debug, Clone, Default)]
+pub struct Warnings(Arc<Mutex<Vec<Warning>>>);
+
+impl Warnings {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a warning
+    pub fn push(&self, warning: Warning) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(warning);
+    }
+
+    /// The warnings recorded so far, oldest first
+    pub fn to_vec(&self) -> Vec<Warning> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+}
+
+/// Result of validating a single reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceOutcome {
+    /// The file exists and its hash matches
+    Unchanged,
+    /// The file's hash no longer matches
+    Changed,
+    /// The file no longer exists
+    Missing,
+    /// The file exists but could not be read
+    Unreadable,
+}
+
+/// Receives events from a `Cache`.
+///
+/// Implemented for any `Fn(&Event)` closure. Subscribers are called
+/// synchronously on the thread running the operation, so they should return
+/// quickly.
+pub trait Subscriber: Send + Sync {
+    /// Handle one event
+    fn on_event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> Subscriber for F {
+    fn on_event(&self, event: &Event) {
+        self(event);
+    }
+}
+
+/// The subscribers registered on a cache
+#[derive(Clone, Default)]
+pub(crate) struct Subscribers(Vec<Arc<dyn Subscriber>>);
+
+impl Subscribers {
+    pub(crate) fn push(&mut self, subscriber: Arc<dyn Subscriber>) {
+        self.0.push(subscriber);
+    }
+
+    /// Deliver an event, only building it if someone is listening
+    pub(crate) fn emit(&self, event: impl FnOnce() -> Event) {
+        if self.0.is_empty() {
+            return;
+        }
+        let event = event();
+        for subscriber in &self.0 {
+            subscriber.on_event(&event);
+        }
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Subscribers({})", self.0.len())
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::document::Document;
+use crate::core::models::DocumentState;
 use crate::error::Result;
 use serde_yaml::{self, Value};
 use std::collections::HashMap;
@@ -67,15 +68,81 @@ fn parse_with_frontmatter(path: PathBuf, frontmatter_str: &str, body: String) ->
         .unwrap_or("")
         .to_string();
 
-    Ok(Document::new(
-        path,
-        slug,
-        description,
-        references,
-        updated,
-        hash,
-        body,
-    ))
+    let body_hash = fm
+        .get(Value::String("body_hash".to_string()))
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+
+    let tags = string_list(fm, "tags");
+    let owners = string_list(fm, "owners");
+    let external_references = string_list(fm, "external_references");
+
+    let state = match fm
+        .get(Value::String("state".to_string()))
+        .and_then(Value::as_str)
+    {
+        Some(state) => state
+            .parse()
+            .map_err(crate::error::ContextError::InvalidDocument)?,
+        None => DocumentState::default(),
+    };
+
+    let superseded_by = fm
+        .get(Value::String("superseded_by".to_string()))
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+
+    let template = fm
+        .get(Value::String("template".to_string()))
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+
+    let template_version = fm
+        .get(Value::String("template_version".to_string()))
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+
+    let source_hash = fm
+        .get(Value::String("source_hash".to_string()))
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+
+    let encrypted = fm
+        .get(Value::String("encrypted".to_string()))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let protected = fm
+        .get(Value::String("protected".to_string()))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut doc = Document::new(path, slug, description, references, updated, hash, body);
+    doc.body_hash = body_hash;
+    doc.tags = tags;
+    doc.owners = owners;
+    doc.external_references = external_references;
+    doc.state = state;
+    doc.superseded_by = superseded_by;
+    doc.template = template;
+    doc.template_version = template_version;
+    doc.source_hash = source_hash;
+    doc.encrypted = encrypted;
+    doc.protected = protected;
+    Ok(doc)
+}
+
+/// Read a sequence of strings from frontmatter, empty if absent
+fn string_list(fm: &serde_yaml::Mapping, key: &str) -> Vec<String> {
+    fm.get(Value::String(key.to_string()))
+        .and_then(Value::as_sequence)
+        .map(|seq| {
+            seq.iter()
+                .filter_map(Value::as_str)
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Parse a document without frontmatter, generating default values
@@ -100,6 +167,11 @@ fn parse_without_frontmatter(path: PathBuf, content: &str) -> Document {
 
 /// Serialize Document back to complete file content with YAML frontmatter
 pub fn serialize(document: &Document) -> Result<String> {
+    serialize_with_body(document, &document.body)
+}
+
+/// Serialize Document frontmatter followed by the given on-disk body
+pub fn serialize_with_body(document: &Document, body: &str) -> Result<String> {
     let mut fm_map = serde_yaml::Mapping::new();
 
     fm_map.insert(
@@ -112,32 +184,117 @@ pub fn serialize(document: &Document) -> Result<String> {
         Value::String(document.description.clone()),
     );
 
-    let mut refs_map = serde_yaml::Mapping::new();
-    for (path, hash) in &document.references {
-        refs_map.insert(Value::String(path.clone()), Value::String(hash.clone()));
+    // A storage backend keeps these instead
+    if !document.external_metadata {
+        // Sorted so rewriting a document never reorders its references
+        let mut refs: Vec<_> = document.references.iter().collect();
+        refs.sort();
+        let mut refs_map = serde_yaml::Mapping::new();
+        for (path, hash) in refs {
+            refs_map.insert(Value::String(path.clone()), Value::String(hash.clone()));
+        }
+        fm_map.insert(
+            Value::String("references".to_string()),
+            Value::Mapping(refs_map),
+        );
     }
-    fm_map.insert(
-        Value::String("references".to_string()),
-        Value::Mapping(refs_map),
-    );
 
     fm_map.insert(
         Value::String("updated".to_string()),
         Value::String(document.updated.clone()),
     );
 
-    fm_map.insert(
-        Value::String("hash".to_string()),
-        Value::String(document.hash.clone()),
-    );
+    if !document.external_metadata && !document.encrypted {
+        fm_map.insert(
+            Value::String("hash".to_string()),
+            Value::String(document.hash.clone()),
+        );
+    }
+
+    if let Some(body_hash) = document
+        .body_hash
+        .as_ref()
+        .filter(|_| !document.external_metadata)
+    {
+        fm_map.insert(
+            Value::String("body_hash".to_string()),
+            Value::String(body_hash.clone()),
+        );
+    }
+
+    if !document.tags.is_empty() {
+        fm_map.insert(Value::String("tags".to_string()), sequence(&document.tags));
+    }
+
+    if !document.owners.is_empty() {
+        fm_map.insert(
+            Value::String("owners".to_string()),
+            sequence(&document.owners),
+        );
+    }
+
+    if !document.external_references.is_empty() {
+        fm_map.insert(
+            Value::String("external_references".to_string()),
+            sequence(&document.external_references),
+        );
+    }
+
+    if document.state != DocumentState::Published {
+        fm_map.insert(
+            Value::String("state".to_string()),
+            Value::String(document.state.to_string()),
+        );
+    }
+
+    if let Some(successor) = &document.superseded_by {
+        fm_map.insert(
+            Value::String("superseded_by".to_string()),
+            Value::String(successor.clone()),
+        );
+    }
+
+    if let Some(template) = &document.template {
+        fm_map.insert(
+            Value::String("template".to_string()),
+            Value::String(template.clone()),
+        );
+    }
+
+    if let Some(version) = &document.template_version {
+        fm_map.insert(
+            Value::String("template_version".to_string()),
+            Value::String(version.clone()),
+        );
+    }
+
+    if let Some(source_hash) = &document.source_hash {
+        fm_map.insert(
+            Value::String("source_hash".to_string()),
+            Value::String(source_hash.clone()),
+        );
+    }
+
+    if document.encrypted {
+        fm_map.insert(Value::String("encrypted".to_string()), Value::Bool(true));
+    }
+
+    if document.protected {
+        fm_map.insert(Value::String("protected".to_string()), Value::Bool(true));
+    }
 
     let frontmatter = serde_yaml::to_string(&fm_map)?;
-    Ok(format!("---\n{}---\n\n{}", frontmatter, document.body))
+    Ok(format!("---\n{frontmatter}---\n\n{body}"))
+}
+
+/// A YAML sequence of strings
+fn sequence(values: &[String]) -> Value {
+    Value::Sequence(values.iter().cloned().map(Value::String).collect())
 }
 
 /// Extract YAML frontmatter from content
 /// Returns (frontmatter_str, body) or None if no frontmatter found
-fn extract_frontmatter(content: &str) -> Option<(String, String)> {
+pub(crate) fn extract_frontmatter(content: &str) -> Option<(String, String)> {
     if !content.starts_with("---\n") {
         return None;
     }
@@ -193,6 +350,75 @@ This is the body.
         assert!(doc.body.contains("# Authentication"));
     }
 
+    #[test]
+    fn test_serialize_sorts_references() {
+        let references = ["src/c.rs", "src/a.rs", "src/b.rs"]
+            .iter()
+            .map(|p| ((*p).to_string(), "0000000".to_string()))
+            .collect();
+        let doc = Document::new(
+            PathBuf::from("a.md"),
+            "a".to_string(),
+            String::new(),
+            references,
+            String::new(),
+            String::new(),
+            String::new(),
+        );
+        let serialized = serialize(&doc).unwrap();
+        let a = serialized.find("src/a.rs").unwrap();
+        let b = serialized.find("src/b.rs").unwrap();
+        let c = serialized.find("src/c.rs").unwrap();
+        assert!(a < b && b < c);
+    }
+
+    #[test]
+    fn test_owners_round_trip() {
+        let content = "---\nslug: auth\nowners:\n- alice\n- team-identity\n---\n\nBody\n";
+        let doc = parse(PathBuf::from("auth.md"), content).unwrap();
+        assert_eq!(doc.owners, vec!["alice", "team-identity"]);
+
+        let serialized = serialize(&doc).unwrap();
+        assert!(serialized.contains("owners:\n- alice\n- team-identity\n"));
+    }
+
+    #[test]
+    fn test_template_round_trip() {
+        let content = "---\nslug: adr-1\ntemplate: adr\ntemplate_version: 1a2b3c4\n---\n\nBody\n";
+        let doc = parse(PathBuf::from("adr-1.md"), content).unwrap();
+        assert_eq!(doc.template.as_deref(), Some("adr"));
+        assert_eq!(doc.template_version.as_deref(), Some("1a2b3c4"));
+        assert!(serialize(&doc)
+            .unwrap()
+            .contains("template: adr\ntemplate_version: 1a2b3c4\n"));
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let content = "---\nslug: old\nstate: deprecated\nsuperseded_by: new\n---\n\nBody\n";
+        let mut doc = parse(PathBuf::from("old.md"), content).unwrap();
+        assert_eq!(doc.state, DocumentState::Deprecated);
+        assert!(serialize(&doc).unwrap().contains("state: deprecated\n"));
+        doc.state = DocumentState::Published;
+        assert!(!serialize(&doc).unwrap().contains("state"));
+        assert!(parse(
+            PathBuf::from("x.md"),
+            "---\nslug: x\nstate: archived\n---\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_protected_round_trip() {
+        let content = "---\nslug: runbook\nprotected: true\n---\n\nBody\n";
+        let doc = parse(PathBuf::from("runbook.md"), content).unwrap();
+        assert!(doc.protected);
+        assert!(serialize(&doc).unwrap().contains("protected: true\n"));
+
+        let doc = parse(PathBuf::from("guide.md"), "---\nslug: guide\n---\n\nBody\n").unwrap();
+        assert!(!serialize(&doc).unwrap().contains("protected"));
+    }
+
     #[test]
     fn test_parse_without_frontmatter() {
         let content = "# Just a document\n\nNo frontmatter here.";
@@ -0,0 +1,138 @@
+//! Reading referenced files on unreliable filesystems
+//!
+//! Network mounts (NFS, SMB) fail reads sporadically and can hang on a
+//! single file. Reads of referenced files go through `read`, which retries
+//! transient errors with exponential backoff and, when `[io] timeout_ms` is
+//! set, gives up on a file that doesn't answer in time. Validation reports
+//! a reference that still can't be read as unreadable rather than aborting
+//! the whole run; sync fails only the document that references it.
+
+use crate::core::config::IoConfig;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How hard to try reading a referenced file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadPolicy {
+    /// Extra attempts after a failed read
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub backoff: Duration,
+    /// Longest a single attempt may take, if limited
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ReadPolicy {
+    fn default() -> Self {
+        Self::from(&IoConfig::default())
+    }
+}
+
+impl From<&IoConfig> for ReadPolicy {
+    fn from(config: &IoConfig) -> Self {
+        Self {
+            retries: config.retries,
+            backoff: Duration::from_millis(config.backoff_ms),
+            timeout: (config.timeout_ms > 0).then(|| Duration::from_millis(config.timeout_ms)),
+        }
+    }
+}
+
+/// Read a whole file, retrying transient failures. A missing file or a
+/// permission error is reported at once, since retrying can't fix it.
+pub fn read(path: &Path, policy: ReadPolicy) -> io::Result<Vec<u8>> {
+    let mut delay = policy.backoff;
+    let mut attempt = 0;
+    loop {
+        match read_once(path, policy.timeout) {
+            Err(e) if attempt < policy.retries && is_transient(&e) => {
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn read_once(path: &Path, timeout: Option<Duration>) -> io::Result<Vec<u8>> {
+    let Some(timeout) = timeout else {
+        return std::fs::read(path);
+    };
+
+    // A read stuck in the kernel can't be cancelled, so it runs on its own
+    // thread and is abandoned if it doesn't finish in time
+    let (tx, rx) = mpsc::channel();
+    let owned = path.to_path_buf();
+    std::thread::spawn(move || {
+        let _ = tx.send(std::fs::read(owned));
+    });
+    rx.recv_timeout(timeout).unwrap_or_else(|_| {
+        Err(io::Error::new(
+            ErrorKind::TimedOut,
+            format!("read timed out after {}ms", timeout.as_millis()),
+        ))
+    })
+}
+
+fn is_transient(error: &io::Error) -> bool {
+    !matches!(
+        error.kind(),
+        ErrorKind::NotFound
+            | ErrorKind::PermissionDenied
+            | ErrorKind::IsADirectory
+            | ErrorKind::InvalidInput
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_with_timeout_returns_contents() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("a.rs");
+        std::fs::write(&path, "fn a() {}").unwrap();
+        let policy = ReadPolicy {
+            retries: 0,
+            backoff: Duration::ZERO,
+            timeout: Some(Duration::from_secs(5)),
+        };
+        assert_eq!(read(&path, policy).unwrap(), b"fn a() {}");
+    }
+
+    #[test]
+    fn test_missing_file_fails_without_retrying() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let policy = ReadPolicy {
+            retries: 3,
+            backoff: Duration::from_mins(1),
+            timeout: None,
+        };
+        let error = read(&dir.path().join("missing.rs"), policy).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hanging_read_times_out() {
+        // Opening a FIFO for reading blocks until a writer appears
+        let dir = tempfile::TempDir::new().unwrap();
+        let fifo = dir.path().join("fifo");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let policy = ReadPolicy {
+            retries: 1,
+            backoff: Duration::from_millis(1),
+            timeout: Some(Duration::from_millis(50)),
+        };
+        let error = read(&fifo, policy).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,180 @@
+//! Documentation gaps: frequently changed code nobody documents
+//!
+//! Files that churn the most in recent git history are where documentation
+//! matters most and rots fastest. A churned file is a gap when no document
+//! references it, or when every document referencing it is stale or
+//! orphaned. Gaps are ranked by the number of commits touching the file.
+
+use crate::core::git;
+use crate::core::models::Status;
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Why a churned file counts as a gap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GapKind {
+    /// No document references the file
+    Undocumented,
+    /// Every document referencing the file is stale or orphaned
+    Stale,
+}
+
+/// A frequently changed file lacking up-to-date documentation
+#[derive(Debug, Clone, Serialize)]
+pub struct Gap {
+    /// Path relative to the project root
+    pub path: String,
+    /// Commits touching the file in the window
+    pub commits: usize,
+    /// Why the file is a gap
+    pub kind: GapKind,
+    /// The out-of-date documents referencing the file, if any
+    pub documents: Vec<PathBuf>,
+}
+
+/// Gaps found over a window of history, most-churned first
+#[derive(Debug, Clone, Serialize)]
+pub struct GapReport {
+    /// Length of the window in days
+    pub days: u32,
+    /// Files changed in the window, documented or not
+    pub churned: usize,
+    /// Files lacking up-to-date documentation
+    pub gaps: Vec<Gap>,
+}
+
+/// Parse a window such as `30d`, `2w`, `6m` (30-day months) or `1y` into
+/// days. A bare number is days.
+pub fn parse_days(spec: &str) -> std::result::Result<u32, String> {
+    let spec = spec.trim();
+    let (number, unit) = match spec.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&spec[..i], c.to_ascii_lowercase()),
+        _ => (spec, 'd'),
+    };
+    let multiplier = match unit {
+        'd' => 1,
+        'w' => 7,
+        'm' => 30,
+        'y' => 365,
+        _ => {
+            return Err(format!(
+                "unknown unit '{unit}' in '{spec}': use d, w, m or y"
+            ))
+        }
+    };
+    number
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|days| *days > 0)
+        .ok_or_else(|| format!("invalid window '{spec}': expected e.g. 30d, 2w, 6m or 1y"))
+}
+
+/// Number of commits in the last `days` days touching each file that still
+/// exists, relative to (and limited to) `project_root`
+pub fn churn(project_root: &Path, days: u32) -> Result<BTreeMap<String, usize>> {
+    let since = format!("--since={days} days ago");
+    let log = git::run(
+        project_root,
+        &["log", &since, "--relative", "--name-only", "--format="],
+    )?;
+    let mut commits: BTreeMap<String, usize> = BTreeMap::new();
+    for file in log.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *commits.entry(file.to_string()).or_default() += 1;
+    }
+    commits.retain(|file, _| !file.starts_with(".context/") && project_root.join(file).is_file());
+    Ok(commits)
+}
+
+/// Rank churned files lacking documentation, given the statuses of the
+/// documents referencing each file
+pub fn find(
+    days: u32,
+    churn: &BTreeMap<String, usize>,
+    referencing: &BTreeMap<String, Vec<(PathBuf, Status)>>,
+) -> GapReport {
+    let mut gaps: Vec<Gap> = churn
+        .iter()
+        .filter_map(|(path, &commits)| {
+            let docs = referencing.get(path).map(Vec::as_slice).unwrap_or_default();
+            let kind = if docs.is_empty() {
+                GapKind::Undocumented
+            } else if docs.iter().all(|(_, status)| *status != Status::Valid) {
+                GapKind::Stale
+            } else {
+                return None;
+            };
+            Some(Gap {
+                path: path.clone(),
+                commits,
+                kind,
+                documents: docs.iter().map(|(doc, _)| doc.clone()).collect(),
+            })
+        })
+        .collect();
+    // Stable, so ties stay in path order
+    gaps.sort_by_key(|g| std::cmp::Reverse(g.commits));
+
+    GapReport {
+        days,
+        churned: churn.len(),
+        gaps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("30d"), Ok(30));
+        assert_eq!(parse_days("2w"), Ok(14));
+        assert_eq!(parse_days("6M"), Ok(180));
+        assert_eq!(parse_days("1y"), Ok(365));
+        assert_eq!(parse_days("45"), Ok(45));
+        assert!(parse_days("0d").is_err());
+        assert!(parse_days("3h").is_err());
+        assert!(parse_days("d").is_err());
+    }
+
+    #[test]
+    fn test_find_ranks_undocumented_and_stale() {
+        let churn = BTreeMap::from([
+            ("src/a.rs".to_string(), 2),
+            ("src/b.rs".to_string(), 5),
+            ("src/c.rs".to_string(), 9),
+        ]);
+        let referencing = BTreeMap::from([
+            (
+                "src/a.rs".to_string(),
+                vec![(PathBuf::from("a.md"), Status::Stale)],
+            ),
+            (
+                "src/c.rs".to_string(),
+                vec![
+                    (PathBuf::from("c.md"), Status::Valid),
+                    (PathBuf::from("d.md"), Status::Stale),
+                ],
+            ),
+        ]);
+
+        let report = find(30, &churn, &referencing);
+        let gaps: Vec<_> = report
+            .gaps
+            .iter()
+            .map(|g| (g.path.as_str(), g.commits, g.kind))
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                ("src/b.rs", 5, GapKind::Undocumented),
+                ("src/a.rs", 2, GapKind::Stale)
+            ]
+        );
+        assert_eq!(report.churned, 3);
+    }
+}
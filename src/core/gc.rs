@@ -0,0 +1,204 @@
+//! Garbage collection for runtime caches under the context directory
+//!
+//! Snapshots (and any future persisted indexes or journals) accumulate
+//! without bound. Collection prunes each runtime directory according to the
+//! `[gc]` retention policy, always keeping the newest `keep` entries of
+//! each. A light collection, run automatically after sync, only applies the
+//! age limit, so it never removes recent entries just because space is
+//! tight.
+
+use crate::core::config::GcConfig;
+use crate::core::snapshot::SNAPSHOT_DIR;
+use crate::error::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Directories within the context root holding disposable runtime data
+pub const RUNTIME_DIRS: &[&str] = &[SNAPSHOT_DIR];
+
+const DAY: Duration = Duration::from_hours(24);
+
+/// Which limits a collection applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Age and size limits
+    Full,
+    /// Age limit only
+    Light,
+}
+
+/// What a collection removed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    /// Entries that were deleted
+    pub removed: Vec<PathBuf>,
+    /// Bytes freed
+    pub reclaimed: u64,
+}
+
+struct Entry {
+    path: PathBuf,
+    modified: SystemTime,
+    bytes: u64,
+}
+
+/// Prune the runtime directories of the context directory at `root`
+pub fn collect(root: &Path, config: &GcConfig, mode: Mode) -> Result<GcReport> {
+    let mut report = GcReport::default();
+    let now = SystemTime::now();
+    let max_age = (config.max_age_days > 0).then(|| DAY * config.max_age_days);
+
+    for dir in RUNTIME_DIRS {
+        let mut entries = entries(&root.join(dir))?;
+        // Newest first, so the protected entries come first
+        entries.sort_by_key(|e| std::cmp::Reverse(e.modified));
+        let mut total: u64 = entries.iter().map(|e| e.bytes).sum();
+
+        let mut doomed = Vec::new();
+        for (i, entry) in entries.iter().enumerate().skip(config.keep) {
+            let expired = max_age
+                .is_some_and(|max| now.duration_since(entry.modified).unwrap_or_default() > max);
+            if expired {
+                doomed.push(i);
+                total -= entry.bytes;
+            }
+        }
+
+        if mode == Mode::Full && config.max_bytes > 0 {
+            // Oldest first until the directory fits
+            for i in (config.keep..entries.len()).rev() {
+                if total <= config.max_bytes {
+                    break;
+                }
+                if !doomed.contains(&i) {
+                    doomed.push(i);
+                    total -= entries[i].bytes;
+                }
+            }
+        }
+
+        doomed.sort_unstable();
+        for i in doomed {
+            let entry = &entries[i];
+            if entry.path.is_dir() {
+                std::fs::remove_dir_all(&entry.path)?;
+            } else {
+                std::fs::remove_file(&entry.path)?;
+            }
+            report.removed.push(entry.path.clone());
+            report.reclaimed += entry.bytes;
+        }
+    }
+
+    report.removed.sort();
+    Ok(report)
+}
+
+/// Entries of a runtime directory, skipping dotfiles such as `.gitignore`
+fn entries(dir: &Path) -> Result<Vec<Entry>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        let bytes = if metadata.is_dir() {
+            WalkDir::new(entry.path())
+                .into_iter()
+                .filter_map(std::result::Result::ok)
+                .filter_map(|e| e.metadata().ok())
+                .filter(std::fs::Metadata::is_file)
+                .map(|m| m.len())
+                .sum()
+        } else {
+            metadata.len()
+        };
+        entries.push(Entry {
+            path: entry.path(),
+            modified: metadata.modified()?,
+            bytes,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    /// Write a snapshot-like file of `bytes` bytes, last modified `days` ago
+    fn write(root: &Path, name: &str, bytes: usize, days: u32) {
+        let path = root.join(SNAPSHOT_DIR).join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, vec![0u8; bytes]).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - DAY * days)
+            .unwrap();
+    }
+
+    fn names(report: &GcReport) -> Vec<String> {
+        report
+            .removed
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn config(max_age_days: u32, max_bytes: u64, keep: usize) -> GcConfig {
+        GcConfig {
+            max_age_days,
+            max_bytes,
+            keep,
+            after_sync: true,
+        }
+    }
+
+    #[test]
+    fn test_age_limit_keeps_newest() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "old.tar.gz", 10, 40);
+        write(dir.path(), "older.tar.gz", 10, 50);
+        write(dir.path(), "new.tar.gz", 10, 1);
+        write(dir.path(), ".gitignore", 2, 90);
+
+        let report = collect(dir.path(), &config(30, 0, 1), Mode::Light).unwrap();
+        assert_eq!(names(&report), vec!["old.tar.gz", "older.tar.gz"]);
+        assert_eq!(report.reclaimed, 20);
+
+        // The newest entry survives even when everything has expired
+        write(dir.path(), "new.tar.gz", 10, 60);
+        assert!(collect(dir.path(), &config(30, 0, 1), Mode::Full)
+            .unwrap()
+            .removed
+            .is_empty());
+    }
+
+    #[test]
+    fn test_size_limit_only_in_full_mode() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "a.tar.gz", 100, 3);
+        write(dir.path(), "b.tar.gz", 100, 2);
+        write(dir.path(), "c.tar.gz", 100, 1);
+
+        let cfg = config(0, 150, 0);
+        assert!(collect(dir.path(), &cfg, Mode::Light)
+            .unwrap()
+            .removed
+            .is_empty());
+        let report = collect(dir.path(), &cfg, Mode::Full).unwrap();
+        assert_eq!(names(&report), vec!["a.tar.gz", "b.tar.gz"]);
+        assert_eq!(report.reclaimed, 200);
+    }
+}
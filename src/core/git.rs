@@ -0,0 +1,176 @@
+//! Read-only queries against the git repository containing a project
+//!
+//! Git is invoked as a subprocess so no git library is linked in. Every
+//! query returns `None` (or an error) rather than panicking when git is not
+//! installed or the directory is not inside a repository.
+
+use crate::error::{ContextError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Summary of the repository state, for diagnostics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitInfo {
+    /// Current branch, or `None` with a detached HEAD
+    pub branch: Option<String>,
+    /// Full hash of the HEAD commit, or `None` before the first commit
+    pub commit: Option<String>,
+    /// Whether the working tree has uncommitted changes
+    pub dirty: bool,
+}
+
+/// Run git in `dir`, returning trimmed stdout
+pub fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| ContextError::Other(format!("failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(ContextError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Describe the repository containing `dir`, or `None` if there isn't one
+pub fn info(dir: &Path) -> Option<GitInfo> {
+    run(dir, &["rev-parse", "--is-inside-work-tree"]).ok()?;
+    let branch = run(dir, &["symbolic-ref", "--short", "-q", "HEAD"]).ok();
+    let commit = run(dir, &["rev-parse", "-q", "--verify", "HEAD"]).ok();
+    let dirty = run(dir, &["status", "--porcelain"]).is_ok_and(|s| !s.is_empty());
+    Some(GitInfo {
+        branch,
+        commit,
+        dirty,
+    })
+}
+
+/// Names the current git user goes by: `user.name`, `user.email` and the
+/// email's local part, as configured for `dir`
+pub fn identities(dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    for key in ["user.name", "user.email"] {
+        if let Ok(value) = run(dir, &["config", key]).map(|v| v.trim().to_string()) {
+            if !value.is_empty() {
+                names.push(value);
+            }
+        }
+    }
+    if let Some((local, _)) = names.iter().find_map(|n| n.split_once('@')) {
+        names.push(local.to_string());
+    }
+    names
+}
+
+/// A commit as listed by `git log`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Commit {
+    /// Abbreviated commit hash
+    pub hash: String,
+    /// Author name
+    pub author: String,
+    /// First line of the commit message
+    pub subject: String,
+}
+
+/// Non-merge commits in a revision range such as `main..HEAD`, oldest first
+pub fn commits(dir: &Path, range: &str) -> Result<Vec<Commit>> {
+    if range.starts_with('-') {
+        return Err(ContextError::Other(format!(
+            "invalid revision range: {range}"
+        )));
+    }
+    let output = run(
+        dir,
+        &[
+            "log",
+            "--reverse",
+            "--no-merges",
+            "--format=%h%x1f%an%x1f%s",
+            range,
+            "--",
+        ],
+    )?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Files changed in a revision range such as `main..HEAD`, relative to
+/// `dir`. A single revision compares it with the working tree, as `git
+/// diff` does. Deleted files are included.
+pub fn changed_files(dir: &Path, range: &str) -> Result<Vec<String>> {
+    if range.starts_with('-') {
+        return Err(ContextError::Other(format!(
+            "invalid revision range: {range}"
+        )));
+    }
+    let output = run(
+        dir,
+        &[
+            "diff",
+            "--name-only",
+            "--relative",
+            "--no-renames",
+            range,
+            "--",
+        ],
+    )?;
+    Ok(output
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// The best common ancestor of `rev` and `HEAD`, as a full hash
+pub fn merge_base(dir: &Path, rev: &str) -> Result<String> {
+    if rev.starts_with('-') {
+        return Err(ContextError::Other(format!("invalid revision: {rev}")));
+    }
+    run(dir, &["merge-base", rev, "HEAD"])
+}
+
+/// Files staged for the next commit, relative to `dir`
+pub fn staged_files(dir: &Path) -> Result<Vec<String>> {
+    let output = run(
+        dir,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "--relative",
+            "--no-renames",
+            "--",
+        ],
+    )?;
+    Ok(output
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Contents of `path` (relative to `dir`) as of `rev`, or `None` if the
+/// file didn't exist there
+pub fn show(dir: &Path, rev: &str, path: &str) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .args(["show", &format!("{rev}:./{path}")])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    output.status.success().then_some(output.stdout)
+}
@@ -0,0 +1,260 @@
+//! The graph of documents and the source files they reference
+//!
+//! Documents and files form a bipartite graph through references; links
+//! between documents add document-to-document edges. `context graph`
+//! renders it as Graphviz DOT or a Mermaid flowchart, so documented areas
+//! of the codebase and clusters of related documents can be seen at a
+//! glance.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Rendering accepted by `context graph --format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            _ => Err(format!("Unknown graph format: {s}")),
+        }
+    }
+}
+
+/// A directed edge between two nodes
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Documents, referenced files and the edges between them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Graph {
+    /// Slug of each document, by path relative to `.context`
+    pub documents: BTreeMap<String, String>,
+    /// Referenced files, relative to the project root
+    pub files: BTreeSet<String>,
+    /// Document to each file it references
+    pub references: BTreeSet<Edge>,
+    /// Document to each document it links to
+    pub links: BTreeSet<Edge>,
+}
+
+impl Graph {
+    /// Add a document with the files it references and the documents it
+    /// links to; links to anything but a document of the graph are
+    /// dropped by `finish`
+    pub fn add(&mut self, path: String, slug: String, references: &[&str], links: Vec<String>) {
+        for file in references {
+            self.files.insert((*file).to_string());
+            self.references.insert(Edge {
+                from: path.clone(),
+                to: (*file).to_string(),
+            });
+        }
+        for target in links {
+            self.links.insert(Edge {
+                from: path.clone(),
+                to: target,
+            });
+        }
+        self.documents.insert(path, slug);
+    }
+
+    /// Drop links to unknown documents and from a document to itself
+    pub fn finish(&mut self) {
+        let documents = &self.documents;
+        self.links
+            .retain(|link| link.from != link.to && documents.contains_key(&link.to));
+    }
+
+    /// Render in `format`
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+        }
+    }
+
+    /// Graphviz DOT: documents as boxes labelled by slug, files as
+    /// ellipses, links as dashed edges
+    pub fn dot(&self) -> String {
+        let mut out = String::from("digraph context {\n    rankdir=LR;\n");
+        for (path, slug) in &self.documents {
+            let _ = writeln!(
+                out,
+                "    {} [shape=box, label={}];",
+                quote(&document_id(path)),
+                quote(slug)
+            );
+        }
+        for file in &self.files {
+            let _ = writeln!(
+                out,
+                "    {} [shape=ellipse, label={}];",
+                quote(&file_id(file)),
+                quote(file)
+            );
+        }
+        for edge in &self.references {
+            let _ = writeln!(
+                out,
+                "    {} -> {};",
+                quote(&document_id(&edge.from)),
+                quote(&file_id(&edge.to))
+            );
+        }
+        for edge in &self.links {
+            let _ = writeln!(
+                out,
+                "    {} -> {} [style=dashed];",
+                quote(&document_id(&edge.from)),
+                quote(&document_id(&edge.to))
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart: documents as rectangles labelled by slug, files
+    /// as rounded nodes, links as dotted edges. Mermaid ids can't contain
+    /// paths, so nodes are numbered in path order
+    pub fn mermaid(&self) -> String {
+        let documents: BTreeMap<&str, String> = self
+            .documents
+            .keys()
+            .enumerate()
+            .map(|(i, path)| (path.as_str(), format!("d{i}")))
+            .collect();
+        let files: BTreeMap<&str, String> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (file.as_str(), format!("f{i}")))
+            .collect();
+
+        let mut out = String::from("flowchart LR\n");
+        for (path, slug) in &self.documents {
+            let _ = writeln!(
+                out,
+                "    {}[\"{}\"]",
+                documents[path.as_str()],
+                escape_mermaid(slug)
+            );
+        }
+        for file in &self.files {
+            let _ = writeln!(
+                out,
+                "    {}(\"{}\")",
+                files[file.as_str()],
+                escape_mermaid(file)
+            );
+        }
+        for edge in &self.references {
+            let _ = writeln!(
+                out,
+                "    {} --> {}",
+                documents[edge.from.as_str()],
+                files[edge.to.as_str()]
+            );
+        }
+        for edge in &self.links {
+            let _ = writeln!(
+                out,
+                "    {} -.-> {}",
+                documents[edge.from.as_str()],
+                documents[edge.to.as_str()]
+            );
+        }
+        out
+    }
+}
+
+fn document_id(path: &str) -> String {
+    format!("doc:{path}")
+}
+
+fn file_id(path: &str) -> String {
+    format!("file:{path}")
+}
+
+/// A DOT string literal
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Mermaid labels are quoted; quotes inside use an entity
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        let mut graph = Graph::default();
+        graph.add(
+            "guides/auth.md".to_string(),
+            "auth".to_string(),
+            &["src/auth.rs", "src/lib.rs"],
+            vec![
+                "references/lib.md".to_string(),
+                "guides/auth.md".to_string(),
+                "missing.md".to_string(),
+            ],
+        );
+        graph.add(
+            "references/lib.md".to_string(),
+            "lib".to_string(),
+            &["src/lib.rs"],
+            Vec::new(),
+        );
+        graph.finish();
+        graph
+    }
+
+    #[test]
+    fn test_links_only_between_known_documents() {
+        let graph = graph();
+        assert_eq!(graph.files.len(), 2);
+        assert_eq!(graph.references.len(), 3);
+        assert_eq!(
+            graph.links.iter().collect::<Vec<_>>(),
+            vec![&Edge {
+                from: "guides/auth.md".to_string(),
+                to: "references/lib.md".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = graph().dot();
+        assert!(dot.starts_with("digraph context {\n"));
+        assert!(dot.contains("    \"doc:guides/auth.md\" [shape=box, label=\"auth\"];\n"));
+        assert!(dot.contains("    \"doc:guides/auth.md\" -> \"file:src/lib.rs\";\n"));
+        assert!(dot
+            .contains("    \"doc:guides/auth.md\" -> \"doc:references/lib.md\" [style=dashed];\n"));
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        assert_eq!(
+            graph().mermaid(),
+            "flowchart LR\n    d0[\"auth\"]\n    d1[\"lib\"]\n    f0(\"src/auth.rs\")\n    f1(\"src/lib.rs\")\n    \
+             d0 --> f0\n    d0 --> f1\n    d1 --> f1\n    d0 -.-> d1\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Git hooks reminding committers of the documents their changes affect
+//!
+//! `context hooks install --commit-msg` installs a `commit-msg` hook that
+//! runs `context hooks commit-msg`. When the staged files are referenced by
+//! documents that aren't staged themselves, each such document is added to
+//! the message as a `Context-Docs:` trailer, so the author sees what to
+//! update in the same change and reviewers see what may have gone stale.
+//! The hook never blocks a commit.
+
+use crate::core::git;
+use crate::error::{ContextError, Result};
+use std::path::{Path, PathBuf};
+
+/// Trailer naming a document affected by a commit
+pub const TRAILER: &str = "Context-Docs";
+
+/// Line identifying hooks written by `context hooks install`
+const MARKER: &str = "# Installed by `context hooks install`";
+
+/// The `commit-msg` hook script; it exits 0 even if `context` is missing or fails
+pub const COMMIT_MSG_SCRIPT: &str = "#!/bin/sh
+# Installed by `context hooks install`
+# Adds a Context-Docs trailer for each document referencing the staged files
+command -v context >/dev/null 2>&1 || exit 0
+context hooks commit-msg \"$1\" || true
+";
+
+/// Install the `commit-msg` hook in the repository containing
+/// `project_root`, honoring `core.hooksPath`. An existing hook not written
+/// by `context` is only replaced with `force`.
+pub fn install_commit_msg(project_root: &Path, force: bool) -> Result<PathBuf> {
+    let path = project_root.join(git::run(
+        project_root,
+        &["rev-parse", "--git-path", "hooks/commit-msg"],
+    )?);
+    if !force {
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            if !existing.contains(MARKER) {
+                return Err(ContextError::Other(format!(
+                    "{} already exists; pass --force to replace it",
+                    path.display()
+                )));
+            }
+        }
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, COMMIT_MSG_SCRIPT)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// The subject of a commit message: its first line that is neither blank
+/// nor a git comment
+pub fn subject(message: &str) -> &str {
+    message
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+        .unwrap_or_default()
+}
+
+/// The conventional-commit type of a subject: `feat` for
+/// `feat(auth)!: add refresh`, `None` if it doesn't follow the convention
+pub fn conventional_type(subject: &str) -> Option<&str> {
+    let (prefix, _) = subject.split_once(':')?;
+    let prefix = prefix.trim_end_matches('!');
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then_some(kind)
+}
+
+/// Add a `Context-Docs` trailer per document to the message in
+/// `message_file`, skipping ones already present
+pub fn add_trailers(project_root: &Path, message_file: &Path, documents: &[String]) -> Result<()> {
+    if documents.is_empty() {
+        return Ok(());
+    }
+    let trailers: Vec<String> = documents
+        .iter()
+        .map(|d| format!("{TRAILER}: {d}"))
+        .collect();
+    let file = message_file.display().to_string();
+    let mut args = vec![
+        "interpret-trailers",
+        "--in-place",
+        "--if-exists",
+        "addIfDifferent",
+    ];
+    for trailer in &trailers {
+        args.extend(["--trailer", trailer]);
+    }
+    args.push(&file);
+    git::run(project_root, &args)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_skips_comments() {
+        assert_eq!(
+            subject("\n# Please enter a message\nfix: typo\n\nbody\n"),
+            "fix: typo"
+        );
+        assert_eq!(subject("# only comments\n"), "");
+    }
+
+    #[test]
+    fn test_conventional_type() {
+        assert_eq!(conventional_type("feat(auth)!: add refresh"), Some("feat"));
+        assert_eq!(conventional_type("docs: fix typo"), Some("docs"));
+        assert_eq!(conventional_type("Fix the thing"), None);
+        assert_eq!(
+            conventional_type("Fix: the thing with spaces: here"),
+            Some("Fix")
+        );
+        assert_eq!(conventional_type("Refactor parser (again): cleanup"), None);
+    }
+}
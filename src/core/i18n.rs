@@ -0,0 +1,100 @@
+//! Translated variants of documents
+//!
+//! With `[i18n] locales = ["de", "fr"]`, a document named `auth.de.md` is
+//! the German translation of `auth.md` in the same directory. Translations
+//! share their source's reference set: syncing a translation copies the
+//! source's references and hashes, so it goes stale together with the
+//! source, and records the source's body hash as `source_hash`. A
+//! translation whose source body has changed since is out of date even if
+//! no referenced file changed. `context i18n` reports missing translations
+//! for each configured locale, out-of-date translations, and translations
+//! whose source no longer exists.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A translation whose source document changed after it was last synced
+#[derive(Debug, Clone, Serialize)]
+pub struct OutdatedTranslation {
+    /// Path to the translation
+    pub translation: PathBuf,
+    /// Path to its source document
+    pub source: PathBuf,
+    /// Whether the translation was never synced against its source
+    pub never_synced: bool,
+}
+
+/// Translation coverage across the configured locales
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct I18nReport {
+    /// Configured locales
+    pub locales: Vec<String>,
+    /// Number of source (untranslated) documents
+    pub sources: usize,
+    /// Source documents lacking a translation, with the locale
+    pub missing: Vec<(PathBuf, String)>,
+    /// Translations whose source changed since they were synced
+    pub outdated: Vec<OutdatedTranslation>,
+    /// Translations without a source document
+    pub orphaned: Vec<PathBuf>,
+}
+
+impl I18nReport {
+    /// Whether every translation exists and is up to date
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.outdated.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// If `path` is a translation (`name.<locale>.md` for a configured locale),
+/// the path of its source document and the locale
+pub fn source_of(path: &Path, locales: &[String]) -> Option<(PathBuf, String)> {
+    let stem = path.file_stem()?.to_str()?;
+    let (name, locale) = stem.rsplit_once('.')?;
+    if name.is_empty() || !locales.iter().any(|l| l == locale) {
+        return None;
+    }
+    let extension = path.extension()?.to_str()?;
+    Some((
+        path.with_file_name(format!("{name}.{extension}")),
+        locale.to_string(),
+    ))
+}
+
+/// Path of the `locale` translation of the source document at `path`
+pub fn translation_path(path: &Path, locale: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}.{locale}.{extension}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_of_only_matches_configured_locales() {
+        let locales = vec!["de".to_string(), "pt-BR".to_string()];
+        assert_eq!(
+            source_of(Path::new("guides/auth.de.md"), &locales),
+            Some((PathBuf::from("guides/auth.md"), "de".to_string()))
+        );
+        assert_eq!(
+            source_of(Path::new("auth.pt-BR.md"), &locales),
+            Some((PathBuf::from("auth.md"), "pt-BR".to_string()))
+        );
+        assert_eq!(source_of(Path::new("guides/auth.md"), &locales), None);
+        assert_eq!(source_of(Path::new("release.v1.md"), &locales), None);
+        assert_eq!(source_of(Path::new(".de.md"), &locales), None);
+        assert_eq!(
+            translation_path(Path::new("guides/auth.md"), "de"),
+            PathBuf::from("guides/auth.de.md")
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Reference declarations produced outside the context tree
+//!
+//! Build systems and code generators can contribute references without
+//! editing document bodies by emitting one JSON object per line:
+//!
+//! ```text
+//! {"path": "src/gen/schema.rs", "doc": "schema"}
+//! {"path": "proto/api.proto", "doc": "references/api.md", "hash": "8a3b2c1"}
+//! ```
+//!
+//! `doc` is a slug or path as accepted by `Cache::get`. When `hash` is
+//! omitted the file's current hash is used.
+
+use crate::error::{ContextError, Result};
+use serde::Deserialize;
+use std::io::BufRead;
+
+/// One externally declared reference
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReferenceDeclaration {
+    /// Source file path, relative to the project root
+    pub path: String,
+    /// Slug or path of the document that references the file
+    pub doc: String,
+    /// Short hash to record instead of hashing the file now
+    pub hash: Option<String>,
+}
+
+/// Input formats accepted by `context refs import`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            _ => Err(format!("Unknown import format: {s}")),
+        }
+    }
+}
+
+/// Parse JSON Lines declarations, skipping blank lines.
+///
+/// Fails on the first malformed line so nothing is imported from a
+/// truncated or corrupt stream.
+pub fn parse_jsonl<R: BufRead>(reader: R) -> Result<Vec<ReferenceDeclaration>> {
+    let mut declarations = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let declaration = serde_json::from_str(&line)
+            .map_err(|e| ContextError::Other(format!("line {}: {e}", idx + 1)))?;
+        declarations.push(declaration);
+    }
+    Ok(declarations)
+}
+
+/// Whether a hash has the short form stored in frontmatter (7 lowercase hex digits)
+pub fn is_short_hash(hash: &str) -> bool {
+    hash.len() == 7 && hash.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jsonl() {
+        let input = "{\"path\": \"src/a.rs\", \"doc\": \"auth\"}\n\n{\"path\": \"b.proto\", \"doc\": \"api\", \"hash\": \"8a3b2c1\"}\n";
+        let declarations = parse_jsonl(input.as_bytes()).unwrap();
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(declarations[0].hash, None);
+        assert_eq!(declarations[1].hash.as_deref(), Some("8a3b2c1"));
+    }
+
+    #[test]
+    fn test_parse_jsonl_reports_line() {
+        let input = "{\"path\": \"src/a.rs\", \"doc\": \"auth\"}\n{\"path\": 1}\n";
+        let err = parse_jsonl(input.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+    }
+
+    #[test]
+    fn test_is_short_hash() {
+        assert!(is_short_hash("8a3b2c1"));
+        assert!(!is_short_hash("8A3B2C1"));
+        assert!(!is_short_hash("8a3b2c"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Append-only line journals shared between processes
+//!
+//! The CLI, MCP servers and other tools may append to the same journal at
+//! once. Every append and rotation happens under an exclusive advisory lock
+//! on a `<journal>.lock` file next to the journal, and each line is written
+//! with a single call, so concurrent writers never interleave or lose
+//! lines, and a rotation never races with an append. The lock file is kept
+//! separate because rotation renames the journal itself.
+
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// A line-oriented journal with size-based rotation
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl Journal {
+    /// Create a journal writing to `path`, rotating once it exceeds
+    /// `max_bytes` and keeping `keep` rotated files (`path.1` is the newest)
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            keep,
+        }
+    }
+
+    /// Path of the active journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the `n`th rotated file
+    pub fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    /// Append one line (a trailing newline is added), rotating first if the
+    /// journal has grown too large
+    pub fn append(&self, line: &str) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let mut lock_path = self.path.clone().into_os_string();
+        lock_path.push(".lock");
+        let lock = File::create(lock_path)?;
+        // Released when `lock` is dropped, including on error
+        lock.lock()?;
+
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.len() >= self.max_bytes) {
+            self.rotate()?;
+        }
+
+        let mut buf = String::with_capacity(line.len() + 1);
+        buf.push_str(line);
+        buf.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(buf.as_bytes())
+    }
+
+    /// Every line still kept, across rotated files, oldest first
+    pub fn lines(&self) -> std::io::Result<Vec<String>> {
+        let files = (1..=self.keep)
+            .rev()
+            .map(|n| self.rotated(n))
+            .chain([self.path.clone()]);
+        let mut lines = Vec::new();
+        for file in files {
+            match std::fs::read_to_string(&file) {
+                Ok(content) => lines.extend(content.lines().map(str::to_string)),
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Shift `path.N` to `path.N+1`, dropping the oldest, and move the active
+    /// journal to `path.1`. Must be called with the lock held.
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        let _ = std::fs::remove_file(self.rotated(self.keep));
+        for n in (1..self.keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                std::fs::rename(from, self.rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))
+    }
+}
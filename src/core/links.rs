@@ -0,0 +1,213 @@
+//! Markdown links between context documents
+
+use std::path::{Component, Path, PathBuf};
+
+/// A markdown link to another markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Link text between the brackets
+    pub text: String,
+    /// Link target as written, without any `#anchor`
+    pub target: String,
+    /// 1-based body line of the link
+    pub line: usize,
+}
+
+/// Extract links to local `.md` files from a body, skipping fenced code blocks
+pub fn extract_links(body: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(open) = rest.find('[') {
+            let after_open = &rest[open + 1..];
+            let Some(close) = after_open.find("](") else {
+                break;
+            };
+            let text = &after_open[..close];
+            let after_paren = &after_open[close + 2..];
+            let Some(end) = after_paren.find(')') else {
+                break;
+            };
+            let target = after_paren[..end].split('#').next().unwrap_or_default();
+            if is_local_markdown(target) {
+                links.push(Link {
+                    text: text.to_string(),
+                    target: target.to_string(),
+                    line: idx + 1,
+                });
+            }
+            rest = &after_paren[end + 1..];
+        }
+    }
+
+    links
+}
+
+fn is_local_markdown(target: &str) -> bool {
+    !target.contains("://")
+        && !target.starts_with('/')
+        && Path::new(target).extension().is_some_and(|ext| ext == "md")
+}
+
+/// Lexically normalize a path, resolving `.` and `..` without touching the filesystem
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Resolve a link target written in `from_doc` to a document path
+pub fn resolve(from_doc: &Path, target: &str) -> PathBuf {
+    let dir = from_doc.parent().unwrap_or_else(|| Path::new(""));
+    normalize(&dir.join(target))
+}
+
+/// Relative link target from `from_doc` to `to_doc` (e.g. `../references/api.md`)
+pub fn relative(from_doc: &Path, to_doc: &Path) -> String {
+    let from_dir = normalize(from_doc.parent().unwrap_or_else(|| Path::new("")));
+    let to = normalize(to_doc);
+    let from_parts: Vec<_> = from_dir.components().collect();
+    let to_parts: Vec<_> = to.components().collect();
+
+    let common = from_parts
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_parts.len() - common];
+    parts.extend(
+        to_parts[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    parts.join("/")
+}
+
+/// Point every link in `body` (written in `from_doc`) that resolves to `old`
+/// at `new` instead. Returns the rewritten body and the number of links changed.
+pub fn rewrite(body: &str, from_doc: &Path, old: &Path, new: &Path) -> (String, usize) {
+    let old = normalize(old);
+    let replacement = relative(from_doc, new);
+    map_targets(body, |target| {
+        (resolve(from_doc, target) == old).then(|| replacement.clone())
+    })
+}
+
+/// Rewrite links in a body written at `from_doc` so they resolve to the same
+/// documents when the body is moved to `to_doc`
+pub fn rebase(body: &str, from_doc: &Path, to_doc: &Path) -> String {
+    map_targets(body, |target| {
+        let relinked = relative(to_doc, &resolve(from_doc, target));
+        (relinked != target).then_some(relinked)
+    })
+    .0
+}
+
+/// Apply `f` to every local markdown link target in `body` outside code
+/// blocks, replacing the target (but keeping any `#anchor`) when `f` returns
+/// `Some`. Returns the new body and the number of targets replaced.
+fn map_targets(body: &str, f: impl Fn(&str) -> Option<String>) -> (String, usize) {
+    let mut changed = 0;
+    let mut in_code_block = false;
+    let mut out: Vec<String> = Vec::new();
+
+    for line in body.split('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || !line.contains("](") {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let mut new_line = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(pos) = rest.find("](") {
+            let (before, after) = rest.split_at(pos + 2);
+            new_line.push_str(before);
+            let end = after.find(')').unwrap_or(after.len());
+            let full_target = &after[..end];
+            let (target, anchor) = full_target
+                .split_once('#')
+                .map_or((full_target, None), |(t, a)| (t, Some(a)));
+            match is_local_markdown(target).then(|| f(target)).flatten() {
+                Some(replacement) => {
+                    changed += 1;
+                    new_line.push_str(&replacement);
+                    if let Some(anchor) = anchor {
+                        new_line.push('#');
+                        new_line.push_str(anchor);
+                    }
+                }
+                None => new_line.push_str(full_target),
+            }
+            rest = &after[end..];
+        }
+        new_line.push_str(rest);
+        out.push(new_line);
+    }
+
+    (out.join("\n"), changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_links() {
+        let body = "See [API](../references/api.md#auth) and [site](https://x.io/a.md).\n```\n[no](b.md)\n```\n";
+        let links = extract_links(body);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "../references/api.md");
+        assert_eq!(links[0].line, 1);
+    }
+
+    #[test]
+    fn test_resolve_and_relative() {
+        let from = Path::new(".context/guides/auth.md");
+        let to = Path::new(".context/references/api.md");
+        assert_eq!(resolve(from, "../references/api.md"), to);
+        assert_eq!(relative(from, to), "../references/api.md");
+        assert_eq!(
+            relative(from, Path::new(".context/guides/jwt.md")),
+            "jwt.md"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let from = Path::new(".context/guides/auth.md");
+        let body = "See [old](old.md#intro) and [other](other.md).";
+        let (out, changed) = rewrite(
+            body,
+            from,
+            Path::new(".context/guides/old.md"),
+            Path::new(".context/references/new.md"),
+        );
+        assert_eq!(changed, 1);
+        assert_eq!(
+            out,
+            "See [old](../references/new.md#intro) and [other](other.md)."
+        );
+    }
+}
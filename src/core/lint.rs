@@ -0,0 +1,245 @@
+//! Document quality checks that don't affect reference validity
+
+use crate::core::config::{DiagramsConfig, ReadabilityConfig};
+use crate::core::diagrams;
+use crate::core::document::Document;
+use crate::core::models::DocumentState;
+use crate::core::readability;
+use crate::core::sections;
+use crate::core::template::DocumentTemplate;
+use crate::error::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A quality problem found in a document
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Name of the rule that flagged the issue
+    pub rule: &'static str,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl LintIssue {
+    fn new(doc: &Document, rule: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            path: doc.path.clone(),
+            rule,
+            message: message.into(),
+        }
+    }
+}
+
+/// Run every lint rule against a document
+pub fn lint(doc: &Document) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    if doc.description.trim().is_empty() {
+        issues.push(LintIssue::new(
+            doc,
+            "empty-description",
+            "description is empty (see `context describe`)",
+        ));
+    }
+
+    if let Some(offset) = doc.invalid_utf8_at {
+        issues.push(LintIssue::new(
+            doc,
+            "non-utf8",
+            format!("file is not valid UTF-8 (first invalid byte at offset {offset})"),
+        ));
+    }
+
+    issues
+}
+
+/// Check a document's lifecycle state: a deprecated document must name,
+/// in `superseded_by`, a successor that exists (`successor`) and is not
+/// itself a draft
+pub fn lint_state(doc: &Document, successor: Option<&Document>) -> Vec<LintIssue> {
+    if doc.state != DocumentState::Deprecated {
+        return Vec::new();
+    }
+    let issue = match (&doc.superseded_by, successor) {
+        (None, _) => LintIssue::new(
+            doc,
+            "deprecated-without-successor",
+            "deprecated document does not name its successor in `superseded_by`",
+        ),
+        (Some(name), None) => LintIssue::new(
+            doc,
+            "unknown-successor",
+            format!("successor '{name}' does not exist"),
+        ),
+        (Some(name), Some(successor)) if successor.state == DocumentState::Draft => LintIssue::new(
+            doc,
+            "draft-successor",
+            format!("successor '{name}' is still a draft"),
+        ),
+        (Some(_), Some(_)) => return Vec::new(),
+    };
+    vec![issue]
+}
+
+/// Check a document against the template it names: the template must exist
+/// and each required section must be present and not empty
+pub fn lint_template(
+    doc: &Document,
+    name: &str,
+    template: &Result<Option<DocumentTemplate>>,
+) -> Vec<LintIssue> {
+    let template = match template {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            return vec![LintIssue::new(
+                doc,
+                "unknown-template",
+                format!("template '{name}' does not exist"),
+            )]
+        }
+        Err(e) => return vec![LintIssue::new(doc, "invalid-template", e.to_string())],
+    };
+
+    let index = sections::index(&doc.body);
+    let mut issues = Vec::new();
+    for heading in &template.required_sections {
+        match sections::find(&index, heading) {
+            None => issues.push(LintIssue::new(
+                doc,
+                "missing-section",
+                format!("section \"{heading}\" required by template '{name}' is missing"),
+            )),
+            Some(section)
+                if sections::extract(&doc.body, section)
+                    .lines()
+                    .skip(1)
+                    .all(|l| l.trim().is_empty()) =>
+            {
+                issues.push(LintIssue::new(
+                    doc,
+                    "empty-section",
+                    format!("section \"{heading}\" required by template '{name}' is empty"),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    issues
+}
+
+/// Check each Mermaid and PlantUML block in the body (see `core::diagrams`)
+pub fn lint_diagrams(doc: &Document, config: &DiagramsConfig) -> Vec<LintIssue> {
+    if doc.is_locked() {
+        return Vec::new();
+    }
+    diagrams::extract(&doc.body)
+        .iter()
+        .filter_map(|diagram| {
+            let problem = diagrams::check(diagram, config)?;
+            Some(LintIssue::new(
+                doc,
+                "broken-diagram",
+                format!(
+                    "{} diagram at body line {}: {problem}",
+                    diagram.kind, diagram.line
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Check the body's length and readability against `config` (see
+/// `core::readability`)
+pub fn lint_readability(doc: &Document, config: &ReadabilityConfig) -> Vec<LintIssue> {
+    if doc.is_locked() {
+        return Vec::new();
+    }
+    readability::check(&readability::measure(&doc.body), config)
+        .into_iter()
+        .map(|violation| LintIssue::new(doc, violation.rule, violation.message))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(description: &str) -> Document {
+        Document::new(
+            PathBuf::from("a.md"),
+            "a".to_string(),
+            description.to_string(),
+            HashMap::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_empty_description_flagged() {
+        let issues = lint(&doc("  "));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule, "empty-description");
+        assert!(lint(&doc("Auth flow")).is_empty());
+    }
+
+    #[test]
+    fn test_template_sections_flagged() {
+        let template = Ok(Some(DocumentTemplate {
+            required_sections: vec![
+                "## Overview".to_string(),
+                "Gotchas".to_string(),
+                "## Usage".to_string(),
+            ],
+            ..DocumentTemplate::default()
+        }));
+        let mut doc = doc("Auth flow");
+        doc.body = "# Auth\n\n## Overview\n\nTokens.\n\n### Gotchas\n\n## Setup\n".to_string();
+
+        let issues = lint_template(&doc, "guide", &template);
+        let rules: Vec<_> = issues.iter().map(|i| i.rule).collect();
+        assert_eq!(rules, vec!["empty-section", "missing-section"]);
+        assert!(issues[1].message.contains("## Usage"));
+
+        assert_eq!(
+            lint_template(&doc, "gone", &Ok(None))[0].rule,
+            "unknown-template"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_needs_existing_successor() {
+        let mut deprecated = doc("Old auth");
+        deprecated.state = DocumentState::Deprecated;
+        assert_eq!(
+            lint_state(&deprecated, None)[0].rule,
+            "deprecated-without-successor"
+        );
+
+        deprecated.superseded_by = Some("auth".to_string());
+        assert_eq!(lint_state(&deprecated, None)[0].rule, "unknown-successor");
+
+        let mut successor = doc("Auth");
+        successor.state = DocumentState::Draft;
+        assert_eq!(
+            lint_state(&deprecated, Some(&successor))[0].rule,
+            "draft-successor"
+        );
+        successor.state = DocumentState::Published;
+        assert!(lint_state(&deprecated, Some(&successor)).is_empty());
+        assert!(lint_state(&doc("Auth"), None).is_empty());
+    }
+
+    #[test]
+    fn test_non_utf8_flagged() {
+        let mut doc = doc("Auth flow");
+        doc.invalid_utf8_at = Some(12);
+        let issues = lint(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].rule, "non-utf8");
+    }
+}
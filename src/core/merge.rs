@@ -0,0 +1,175 @@
+//! Merging two documents into one
+
+use crate::core::document::Document;
+use crate::core::links;
+use crate::core::models::DocumentState;
+use crate::error::{ContextError, Result};
+use std::path::PathBuf;
+
+/// Outcome of merging two documents
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The document that now holds the combined content
+    pub merged: PathBuf,
+    /// The document that was merged away
+    pub removed: PathBuf,
+    /// Whether the merged-away document was deleted (rather than superseded)
+    pub deleted: bool,
+    /// Documents whose links were re-pointed at the merged document
+    pub relinked: Vec<PathBuf>,
+}
+
+/// Increase the level of every heading in a body by one (`#` -> `##`),
+/// leaving fenced code blocks untouched
+fn demote_headings(body: &str) -> String {
+    let mut in_code_block = false;
+    let mut out: Vec<String> = Vec::new();
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if !in_code_block && line.starts_with('#') {
+            out.push(format!("#{line}"));
+        } else {
+            out.push(line.to_string());
+        }
+    }
+    let mut joined = out.join("\n");
+    joined.push('\n');
+    joined
+}
+
+/// Combine `b` into `a`, returning the merged document (written at `a`'s path).
+///
+/// `b`'s body is appended under its own heading with its headings demoted one
+/// level, references, tags and owners are unioned (`a`'s stored hash wins when both
+/// reference the same file), and links from `b` are re-pointed so they still
+/// resolve from `a`'s location.
+pub fn merge(a: &Document, b: &Document) -> Result<Document> {
+    if a.is_locked() || b.is_locked() {
+        return Err(ContextError::EncryptionError(
+            "cannot merge an encrypted document without the decryption key".to_string(),
+        ));
+    }
+
+    let title = if b.description.is_empty() {
+        b.slug.clone()
+    } else {
+        b.description.clone()
+    };
+
+    // Links in b's body are relative to b; re-point them relative to a
+    let b_body = links::rebase(&b.body, &b.path, &a.path);
+
+    let mut merged = a.clone();
+    merged.body = format!(
+        "{}\n\n## {title}\n\n{}",
+        a.body.trim_end(),
+        demote_headings(b_body.trim())
+    );
+
+    for (path, hash) in &b.references {
+        merged
+            .references
+            .entry(path.clone())
+            .or_insert_with(|| hash.clone());
+    }
+    for tag in &b.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    for path in &b.external_references {
+        if !merged.external_references.contains(path) {
+            merged.external_references.push(path.clone());
+        }
+    }
+    for owner in &b.owners {
+        if !merged.owners.contains(owner) {
+            merged.owners.push(owner.clone());
+        }
+    }
+    merged.encrypted = a.encrypted || b.encrypted;
+
+    Ok(merged)
+}
+
+/// Reduce `b` to a stub pointing readers at the document it was merged into
+pub fn supersede(b: &Document, into: &Document) -> Document {
+    let mut stub = b.clone();
+    stub.superseded_by = Some(into.slug.clone());
+    stub.state = DocumentState::Deprecated;
+    stub.references.clear();
+    stub.external_references.clear();
+    stub.body = format!(
+        "This document was merged into [{}]({}).\n",
+        into.slug,
+        links::relative(&b.path, &into.path)
+    );
+    stub
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(path: &str, slug: &str, refs: &[(&str, &str)], body: &str) -> Document {
+        Document::new(
+            PathBuf::from(path),
+            slug.to_string(),
+            String::new(),
+            refs.iter()
+                .map(|(p, h)| ((*p).to_string(), (*h).to_string()))
+                .collect::<HashMap<_, _>>(),
+            String::new(),
+            String::new(),
+            body.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_merge_unions_references_and_demotes_headings() {
+        let a = doc(
+            ".context/guides/a.md",
+            "a",
+            &[("src/a.rs", "1111111")],
+            "# A\n\nAlpha.\n",
+        );
+        let b = doc(
+            ".context/guides/b.md",
+            "b",
+            &[("src/a.rs", "2222222"), ("src/b.rs", "3333333")],
+            "# B\n\n## Detail\n\nBeta.\n",
+        );
+        let merged = merge(&a, &b).unwrap();
+        assert_eq!(merged.references.len(), 2);
+        assert_eq!(merged.references["src/a.rs"], "1111111");
+        assert!(merged.body.contains("## b\n\n## B\n\n### Detail"));
+        assert_eq!(merged.path, PathBuf::from(".context/guides/a.md"));
+    }
+
+    #[test]
+    fn test_merge_repoints_links_from_b() {
+        let a = doc(".context/guides/a.md", "a", &[], "# A\n");
+        let b = doc(".context/references/b.md", "b", &[], "See [api](api.md).\n");
+        let merged = merge(&a, &b).unwrap();
+        assert!(merged.body.contains("[api](../references/api.md)"));
+    }
+
+    #[test]
+    fn test_supersede_stub() {
+        let a = doc(".context/guides/a.md", "a", &[], "# A\n");
+        let b = doc(
+            ".context/guides/b.md",
+            "b",
+            &[("src/b.rs", "3333333")],
+            "# B\n",
+        );
+        let stub = supersede(&b, &a);
+        assert_eq!(stub.superseded_by.as_deref(), Some("a"));
+        assert_eq!(stub.state, DocumentState::Deprecated);
+        assert!(stub.references.is_empty());
+        assert!(stub.body.contains("[a](a.md)"));
+    }
+}
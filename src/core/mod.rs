@@ -1,25 +1,133 @@
+pub mod archive;
+pub mod bodies;
 pub mod cache;
+pub mod cancel;
+pub mod capture;
+pub mod checklist;
+pub mod clock;
+pub mod config;
+pub mod conflict;
+pub mod coverage;
+pub mod crypto;
+pub mod debt;
+pub mod describe;
+pub mod diagrams;
+pub mod docdiff;
+pub mod doctor;
 pub mod document;
+pub mod drift;
+pub mod encoding;
+pub mod events;
 pub mod frontmatter;
+pub mod fsio;
+pub mod gaps;
+pub mod gc;
+pub mod git;
+pub mod graph;
+pub mod hooks;
+pub mod i18n;
+pub mod import;
+pub mod journal;
+pub mod links;
+pub mod lint;
+pub mod merge;
 pub mod models;
+pub mod order;
+pub mod pack;
 pub mod paths;
+pub mod policy;
+pub mod presets;
+pub mod query;
+pub mod readability;
+pub mod redact;
+pub mod refindex;
+pub mod reload;
+pub mod remote;
+pub mod review;
+pub mod scoring;
+pub mod search;
+pub mod search_index;
+pub mod sections;
+pub mod similarity;
+pub mod snapshot;
+pub mod split;
+pub mod storage;
+pub mod suggest;
+pub mod template;
+pub mod trend;
+pub mod usage;
+pub mod watch;
 
 pub use cache::Cache;
 pub use models::*;
 
 use crate::error::{ContextError, Result};
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 /// The name of the context directory should always be .context
 pub const CONTEXT_DIR_NAME: &str = ".context";
 
+/// Marker file that pins context root discovery to its directory.
+///
+/// Upward search stops at the first directory containing a `.context-root`
+/// file, so a project nested inside another project never picks up the
+/// outer `.context`. The file may be empty, meaning the `.context` next to
+/// it, or name the project directory whose `.context` to use, relative to
+/// the marker.
+pub const ROOT_MARKER_NAME: &str = ".context-root";
+
+/// A discovered context directory and how it was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextRoot {
+    /// The context directory
+    pub context_dir: PathBuf,
+    /// The `.context-root` marker that pinned it, if any
+    pub marker: Option<PathBuf>,
+    /// Directory the upward search started from
+    pub searched_from: PathBuf,
+}
+
 /// Find .context by searching upward from the given path
 pub fn find_context_root(from: &Path) -> Result<PathBuf> {
-    let mut current = from.canonicalize().ok();
+    resolve_context_root(from).map(|root| root.context_dir)
+}
+
+/// Find .context by searching upward from the given path, stopping at the
+/// first `.context` directory or `.context-root` marker
+pub fn resolve_context_root(from: &Path) -> Result<ContextRoot> {
+    let Ok(searched_from) = from.canonicalize() else {
+        return Err(ContextError::NotARepository);
+    };
+    let mut current = Some(searched_from.clone());
     while let Some(dir) = current {
+        let marker = dir.join(ROOT_MARKER_NAME);
+        if marker.is_file() {
+            let pinned = std::fs::read_to_string(&marker)?;
+            let pinned = pinned.trim();
+            let context_dir = dir.join(pinned).join(CONTEXT_DIR_NAME);
+            if !context_dir.is_dir() {
+                return Err(ContextError::NotInitialized(format!(
+                    "{} pins the context root to {}, which does not exist",
+                    marker.display(),
+                    context_dir.display()
+                )));
+            }
+            return Ok(ContextRoot {
+                context_dir,
+                marker: Some(marker),
+                searched_from,
+            });
+        }
+
         let candidate = dir.join(CONTEXT_DIR_NAME);
         if candidate.is_dir() {
-            return Ok(candidate);
+            return Ok(ContextRoot {
+                context_dir: candidate,
+                marker: None,
+                searched_from,
+            });
         }
         current = dir.parent().map(Path::to_path_buf);
     }
@@ -30,4 +138,68 @@ pub fn find_context_root(from: &Path) -> Result<PathBuf> {
 pub fn find_context_root_from_cwd() -> Result<PathBuf> {
     let cwd = std::env::current_dir()?;
     find_context_root(&cwd)
-}
\ No newline at end of file
+}
+
+/// Markdown files under a context directory in path order, skipping hidden
+/// directories (runtime data such as snapshots and remote checkouts)
+pub fn markdown_files(context_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(context_dir)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !(entry.file_type().is_dir()
+                    && entry.file_name().to_string_lossy().starts_with('.'))
+        })
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "md"))
+        .map(walkdir::DirEntry::into_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_marker_pins_discovery() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let outer = dir.path().canonicalize().unwrap();
+        let nested = outer.join("vendor/inner/src");
+        std::fs::create_dir_all(outer.join(".context")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let found = resolve_context_root(&nested).unwrap();
+        assert_eq!(found.context_dir, outer.join(".context"));
+        assert_eq!(found.marker, None);
+
+        // An empty marker without a .context next to it stops the search
+        std::fs::write(outer.join("vendor/inner/.context-root"), "").unwrap();
+        assert!(matches!(
+            resolve_context_root(&nested),
+            Err(ContextError::NotInitialized(_))
+        ));
+
+        std::fs::create_dir_all(outer.join("vendor/inner/docs/.context")).unwrap();
+        std::fs::write(outer.join("vendor/inner/.context-root"), "docs\n").unwrap();
+        let pinned = resolve_context_root(&nested).unwrap();
+        assert_eq!(pinned.context_dir, outer.join("vendor/inner/docs/.context"));
+        assert_eq!(
+            pinned.marker,
+            Some(outer.join("vendor/inner/.context-root"))
+        );
+        assert_eq!(pinned.searched_from, nested);
+    }
+
+    #[test]
+    fn test_markdown_files_skip_hidden_directories() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("guides")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".remote/platform/.context")).unwrap();
+        std::fs::write(dir.path().join("guides/a.md"), "").unwrap();
+        std::fs::write(dir.path().join(".remote/platform/.context/b.md"), "").unwrap();
+
+        let files: Vec<_> = markdown_files(dir.path()).collect();
+        assert_eq!(files, vec![dir.path().join("guides/a.md")]);
+    }
+}
@@ -1,8 +1,9 @@
+use crate::core::events::ReferenceOutcome;
+use crate::core::git::GitInfo;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
-
 /// Validity status of a document relative to its source file references
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -15,6 +16,12 @@ pub enum Status {
     Orphaned,
 }
 
+impl Status {
+    /// The statuses of documents a sync would change, as selected by
+    /// `context sync --only-stale`
+    pub const OUT_OF_DATE: [Self; 2] = [Self::Stale, Self::Orphaned];
+}
+
 impl std::fmt::Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -25,6 +32,111 @@ impl std::fmt::Display for Status {
     }
 }
 
+impl std::str::FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "valid" => Ok(Self::Valid),
+            "stale" => Ok(Self::Stale),
+            "orphaned" => Ok(Self::Orphaned),
+            _ => Err(format!("Unknown status: {s}")),
+        }
+    }
+}
+
+/// Lifecycle state of a document, from its `state` frontmatter field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentState {
+    /// Work in progress, left out of packs, search indexes and coverage
+    Draft,
+    /// Current documentation (the default)
+    #[default]
+    Published,
+    /// Kept for history; names its successor in `superseded_by`
+    Deprecated,
+}
+
+impl std::fmt::Display for DocumentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Draft => write!(f, "draft"),
+            Self::Published => write!(f, "published"),
+            Self::Deprecated => write!(f, "deprecated"),
+        }
+    }
+}
+
+impl std::str::FromStr for DocumentState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "draft" => Ok(Self::Draft),
+            "published" => Ok(Self::Published),
+            "deprecated" => Ok(Self::Deprecated),
+            _ => Err(format!(
+                "Unknown state: {s} (expected draft, published or deprecated)"
+            )),
+        }
+    }
+}
+
+/// Kind of document `context new` scaffolds, deciding its directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentKind {
+    /// How-to material, under `guides/`
+    Guide,
+    /// Reference material, under `references/`
+    Reference,
+}
+
+impl DocumentKind {
+    /// Directory of this kind of document, relative to the context root
+    pub fn directory(self) -> &'static str {
+        match self {
+            Self::Guide => "guides",
+            Self::Reference => "references",
+        }
+    }
+}
+
+impl std::str::FromStr for DocumentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "guide" | "guides" => Ok(Self::Guide),
+            "reference" | "references" => Ok(Self::Reference),
+            _ => Err(format!(
+                "Unknown document kind: {s} (expected guide or reference)"
+            )),
+        }
+    }
+}
+
+/// Criteria for narrowing status results. Empty criteria match everything.
+#[derive(Debug, Clone, Default)]
+pub struct StatusFilter {
+    /// Only documents under this directory, relative to the context root
+    pub directory: Option<PathBuf>,
+    /// Only documents carrying this tag
+    pub tag: Option<String>,
+    /// Only documents with one of these statuses
+    pub statuses: Vec<Status>,
+    /// Only documents referencing one of these source paths (or files beneath them)
+    pub paths: Vec<String>,
+    /// Only documents owned by one of these owners (case-insensitive,
+    /// ignoring a leading `@`)
+    pub owners: Vec<String>,
+    /// Only documents in one of these lifecycle states
+    pub states: Vec<DocumentState>,
+    /// Only the document at this path, as resolved by `Cache::get`
+    pub document: Option<PathBuf>,
+}
+
 /// Status information for a document
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Validation {
@@ -36,6 +148,38 @@ pub struct Validation {
     pub changed: Vec<String>,
     /// Files that are missing
     pub missing: Vec<String>,
+    /// Scoped sections (see `core::sections`) whose references changed or
+    /// went missing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<SectionValidation>,
+    /// Referenced files that exist but could not be read, e.g. after
+    /// retries and timeouts on a network mount (see `core::fsio`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unreadable: Vec<UnreadableReference>,
+}
+
+/// A reference whose file could not be read during validation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnreadableReference {
+    /// The reference path as stored in the document
+    pub reference: String,
+    /// Why reading failed
+    pub error: String,
+}
+
+/// Status of a section scoping its own references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionValidation {
+    /// Heading text of the section
+    pub title: String,
+    /// Anchor of the heading
+    pub anchor: String,
+    /// Stale or orphaned, like a document
+    pub status: Status,
+    /// The section's references that changed
+    pub changed: Vec<String>,
+    /// The section's references that are missing
+    pub missing: Vec<String>,
 }
 
 impl Validation {
@@ -46,6 +190,8 @@ impl Validation {
             status,
             changed: vec![],
             missing: vec![],
+            sections: vec![],
+            unreadable: vec![],
         }
     }
 
@@ -58,17 +204,123 @@ impl Validation {
     pub fn add_missing(&mut self, file: String) {
         self.missing.push(file);
     }
+
+    /// How validation found one of the document's references
+    pub fn outcome(&self, reference: &str) -> ReferenceOutcome {
+        if self.missing.iter().any(|r| r == reference) {
+            ReferenceOutcome::Missing
+        } else if self.changed.iter().any(|r| r == reference) {
+            ReferenceOutcome::Changed
+        } else if self.unreadable.iter().any(|u| u.reference == reference) {
+            ReferenceOutcome::Unreadable
+        } else {
+            ReferenceOutcome::Unchanged
+        }
+    }
+
+    /// Forget changed and missing references that `keep` rejects, then
+    /// recompute the status of the document and of its sections
+    pub fn retain_references(&mut self, keep: impl Fn(&str) -> bool) {
+        self.changed.retain(|r| keep(r));
+        self.missing.retain(|r| keep(r));
+        self.status = derive_status(&self.changed, &self.missing, !self.unreadable.is_empty());
+        for section in &mut self.sections {
+            section.changed.retain(|r| keep(r));
+            section.missing.retain(|r| keep(r));
+            section.status = derive_status(&section.changed, &section.missing, false);
+        }
+        self.sections.retain(|s| s.status != Status::Valid);
+    }
+}
+
+/// Orphaned if anything is missing, stale if anything else is wrong
+fn derive_status(changed: &[String], missing: &[String], unreadable: bool) -> Status {
+    if !missing.is_empty() {
+        Status::Orphaned
+    } else if !changed.is_empty() || unreadable {
+        Status::Stale
+    } else {
+        Status::Valid
+    }
 }
 
 /// Result of a sync operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResult {
-    /// Number of documents synced
+    /// Number of documents synced, updated or unchanged
     pub count: usize,
-    /// Documents that were updated
+    /// Documents whose hashes changed and were rewritten
     pub updated: Vec<PathBuf>,
+    /// Documents whose hashes already matched, left untouched on disk
+    #[serde(default)]
+    pub unchanged: Vec<PathBuf>,
     /// Documents that failed (orphaned or had errors)
     pub failed: Vec<String>,
+    /// Protected documents a bulk sync left alone
+    #[serde(default)]
+    pub protected: Vec<PathBuf>,
+    /// Whether an interrupt stopped the sync before every document was done
+    #[serde(default)]
+    pub partial: bool,
+    /// References to deleted files dropped by `--prune-missing`, by document
+    #[serde(default)]
+    pub pruned: BTreeMap<PathBuf, Vec<String>>,
+    /// References dropped by `--cleanup` because bodies no longer mention
+    /// them, by document
+    #[serde(default)]
+    pub cleaned_references: BTreeMap<PathBuf, Vec<String>>,
+    /// Stored metadata and index entries of deleted documents removed by
+    /// `--cleanup`, relative to the context root
+    #[serde(default)]
+    pub cleaned_artifacts: Vec<PathBuf>,
+    /// Documents a bulk sync filtered by status left alone because their
+    /// status didn't match, e.g. valid ones under `--only-stale`
+    #[serde(default)]
+    pub skipped: usize,
+}
+
+/// A hash as recorded and as a sync would record it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// What syncing one document would change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlannedSync {
+    pub path: PathBuf,
+    /// References the body mentions that aren't recorded yet
+    pub added: Vec<String>,
+    /// Recorded references the body no longer mentions
+    pub removed: Vec<String>,
+    /// Kept references whose file changed, with their old and new hashes
+    pub rehashed: BTreeMap<String, HashChange>,
+    /// The document's content hash, if it would change
+    pub hash: Option<HashChange>,
+    /// The full body hash, if it would change
+    pub body_hash: Option<HashChange>,
+    /// The `updated` date the document would get, if it changes
+    pub updated: Option<String>,
+}
+
+/// Result of `sync --dry-run`: what a sync would write, with nothing written
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPlan {
+    /// Documents a sync would rewrite
+    pub changes: Vec<PlannedSync>,
+    /// Documents a sync would leave untouched
+    pub unchanged: Vec<PathBuf>,
+    /// Protected documents a bulk sync would skip
+    pub protected: Vec<PathBuf>,
+    /// References `--cleanup` would drop from documents the sync leaves
+    /// alone, by document
+    #[serde(default)]
+    pub cleaned_references: BTreeMap<PathBuf, Vec<String>>,
+    /// Stored data of deleted documents `--cleanup` would remove, relative
+    /// to the context root
+    #[serde(default)]
+    pub cleaned_artifacts: Vec<PathBuf>,
 }
 
 impl SyncResult {
@@ -77,11 +329,38 @@ impl SyncResult {
         Self {
             count: 0,
             updated: vec![],
+            unchanged: vec![],
             failed: vec![],
+            protected: vec![],
+            partial: false,
+            pruned: BTreeMap::new(),
+            cleaned_references: BTreeMap::new(),
+            cleaned_artifacts: vec![],
+            skipped: 0,
         }
     }
 }
 
+/// Result of importing external reference declarations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportResult {
+    /// Number of declarations recorded
+    pub imported: usize,
+    /// Documents whose references changed
+    pub updated: Vec<PathBuf>,
+    /// Declarations that could not be applied
+    pub failed: Vec<String>,
+}
+
+/// Result of resolving merge conflicts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolveResult {
+    /// Documents whose frontmatter was repaired and re-synced
+    pub resolved: Vec<PathBuf>,
+    /// Documents that still need manual attention
+    pub failed: Vec<String>,
+}
+
 impl Default for SyncResult {
     fn default() -> Self {
         Self::new()
@@ -106,6 +385,16 @@ pub struct FindMatch {
     pub reference: String,
     /// Validation status of the document
     pub status: Status,
+    /// Status of this reference alone: valid if unchanged, stale if the
+    /// file changed (or couldn't be read), orphaned if it is missing
+    pub reference_status: Status,
+    /// 1-based body line where the reference is first mentioned
+    pub line: Option<usize>,
+    /// Anchor of the heading nearest the mention, for deep links
+    pub anchor: Option<String>,
+    /// Remote context the document comes from, if it isn't local
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
 }
 
 /// Result of a find operation for a single query path
@@ -115,4 +404,291 @@ pub struct FindResult {
     pub query: String,
     /// Documents that reference this file
     pub matches: Vec<FindMatch>,
+    /// Total number of matching documents, before any pagination
+    pub total: usize,
+}
+
+impl FindResult {
+    /// Keep only the matches within `[offset, offset + limit)`, preserving `total`
+    #[must_use]
+    pub fn paginate(mut self, offset: usize, limit: Option<usize>) -> Self {
+        self.matches = Page::new(self.matches, offset, limit).items;
+        self
+    }
+}
+
+/// Everything the context tree knows about one source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    /// The source file path that was queried
+    pub source: String,
+    /// Whether the source file currently exists
+    pub exists: bool,
+    /// Documents that reference the file
+    pub documents: Vec<ExplainedDocument>,
+}
+
+/// One line of `context ls`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListedDocument {
+    /// Path relative to the context root, e.g. `guides/auth.md`
+    pub path: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Brief summary of the document
+    pub description: String,
+    /// Validation status of the document
+    pub status: Status,
+    /// Number of files the document references
+    pub references: usize,
+    /// Lifecycle state of the document
+    #[serde(default)]
+    pub state: DocumentState,
+}
+
+/// A document with the outcome of validating each of its references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShownDocument {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Brief summary of the document
+    pub description: String,
+    /// Validation status of the document as a whole
+    pub status: Status,
+    /// Date the document was last synced with a body change
+    pub updated: String,
+    /// Each reference and how validation found it
+    pub references: BTreeMap<String, ReferenceOutcome>,
+    /// The document body
+    pub body: String,
+}
+
+/// A document referencing the explained source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedDocument {
+    /// Path to the document file
+    pub document: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Brief summary of the document
+    pub description: String,
+    /// Validation status of the document as a whole
+    pub status: Status,
+    /// Whether this particular file changed since the document was last synced
+    pub changed: bool,
+    /// People or teams responsible for the document
+    pub owners: Vec<String>,
+    /// Date the document was last synced with a body change
+    pub updated: String,
+    /// 1-based body line where the file is first mentioned
+    pub line: Option<usize>,
+    /// Anchor of the heading nearest the mention
+    pub anchor: Option<String>,
+    /// The section surrounding the mention
+    pub excerpt: Option<String>,
+}
+
+/// Counts describing the documents in a context tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Summary {
+    /// Total number of documents
+    pub documents: usize,
+    /// Documents per top-level directory (`.` for the context root itself)
+    pub categories: BTreeMap<String, usize>,
+    /// Documents whose references all match
+    pub valid: usize,
+    /// Documents with changed references
+    pub stale: usize,
+    /// Documents with missing references
+    pub orphaned: usize,
+    /// Most recent `updated` date across documents
+    pub last_updated: Option<String>,
+    /// Least recent `updated` date across documents
+    #[serde(default)]
+    pub oldest_updated: Option<String>,
+    /// References across all documents
+    #[serde(default)]
+    pub references: usize,
+    /// The largest document files, largest first
+    #[serde(default)]
+    pub largest: Vec<DocumentSize>,
+}
+
+/// Size of a document file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentSize {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Size of the file in bytes
+    pub bytes: u64,
+}
+
+impl Summary {
+    /// How many documents `largest` keeps
+    pub const LARGEST: usize = 10;
+
+    /// `count` as a percentage of all documents
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self, count: usize) -> f64 {
+        if self.documents == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.documents as f64
+        }
+    }
+
+    /// Mean number of references per document
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_references(&self) -> f64 {
+        if self.documents == 0 {
+            0.0
+        } else {
+            self.references as f64 / self.documents as f64
+        }
+    }
+
+    /// Short doc-health line for shell prompts, e.g. `docs: 3 stale`
+    pub fn health(&self) -> String {
+        let mut problems = Vec::new();
+        if self.stale > 0 {
+            problems.push(format!("{} stale", self.stale));
+        }
+        if self.orphaned > 0 {
+            problems.push(format!("{} orphaned", self.orphaned));
+        }
+        if problems.is_empty() {
+            "docs: ok".to_string()
+        } else {
+            format!("docs: {}", problems.join(", "))
+        }
+    }
+}
+
+/// Variables exported to the shell by `context env`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellEnv {
+    /// Resolved context directory (`CONTEXT_ROOT`)
+    pub context_root: PathBuf,
+    /// Project directory containing it (`CONTEXT_PROJECT_ROOT`)
+    pub project_root: PathBuf,
+    /// Doc-health line for prompts (`CONTEXT_PROMPT`), if requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+/// Version and environment details for bug reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Info {
+    /// Crate version
+    pub version: String,
+    /// Resolved context directory, if inside a context repository
+    pub root: Option<PathBuf>,
+    /// Configuration file in use, if one exists
+    pub config: Option<PathBuf>,
+    /// Document counts and freshness, if inside a context repository
+    pub summary: Option<Summary>,
+    /// Repository state, if inside a git repository
+    pub git: Option<GitInfo>,
+}
+
+/// One page of a larger result set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// Total number of items across all pages
+    pub total: usize,
+    /// Index of the first item on this page
+    pub offset: usize,
+    /// Maximum number of items per page (`None` means unpaginated)
+    pub limit: Option<usize>,
+}
+
+impl<T> Page<T> {
+    /// Slice `items` down to the page starting at `offset`
+    pub fn new(items: Vec<T>, offset: usize, limit: Option<usize>) -> Self {
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Self {
+            items,
+            total,
+            offset,
+            limit,
+        }
+    }
+
+    /// Offset of the next page, if there are more items
+    pub fn next_offset(&self) -> Option<usize> {
+        let next = self.offset + self.items.len();
+        (self.limit.is_some() && next < self.total).then_some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_kind_parses_singular_and_plural() {
+        assert_eq!(
+            "guide".parse::<DocumentKind>().unwrap(),
+            DocumentKind::Guide
+        );
+        assert_eq!(
+            "References".parse::<DocumentKind>().unwrap(),
+            DocumentKind::Reference
+        );
+        assert_eq!(DocumentKind::Reference.directory(), "references");
+        assert!("adr".parse::<DocumentKind>().is_err());
+    }
+
+    #[test]
+    fn test_page_slices_items() {
+        let page = Page::new((0..10).collect(), 4, Some(3));
+        assert_eq!(page.items, vec![4, 5, 6]);
+        assert_eq!(page.total, 10);
+        assert_eq!(page.next_offset(), Some(7));
+    }
+
+    #[test]
+    fn test_last_page_has_no_next() {
+        let page = Page::new((0..10).collect(), 8, Some(3));
+        assert_eq!(page.items, vec![8, 9]);
+        assert_eq!(page.next_offset(), None);
+    }
+
+    #[test]
+    fn test_unpaginated_has_no_next() {
+        let page = Page::new((0..10).collect::<Vec<_>>(), 0, None);
+        assert_eq!(page.items.len(), 10);
+        assert_eq!(page.next_offset(), None);
+    }
+
+    #[test]
+    fn test_summary_health_line() {
+        let mut summary = Summary {
+            documents: 5,
+            categories: BTreeMap::new(),
+            valid: 5,
+            stale: 0,
+            orphaned: 0,
+            last_updated: None,
+            oldest_updated: None,
+            references: 10,
+            largest: Vec::new(),
+        };
+        assert_eq!(summary.health(), "docs: ok");
+        assert!((summary.average_references() - 2.0).abs() < f64::EPSILON);
+        assert!((summary.percent(1) - 20.0).abs() < f64::EPSILON);
+        summary.stale = 3;
+        assert_eq!(summary.health(), "docs: 3 stale");
+        summary.orphaned = 1;
+        assert_eq!(summary.health(), "docs: 3 stale, 1 orphaned");
+    }
 }
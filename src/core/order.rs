@@ -0,0 +1,204 @@
+//! Dependency ordering of documents by their cross-document links
+//!
+//! A document that links to another builds on it, so the linked document
+//! comes first: foundational references precede the guides that cite them.
+//! Documents that link to each other in a cycle can't be ordered; they are
+//! reported and emitted together, in path order, where the cycle as a
+//! whole would go.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Documents in dependency order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependencyOrder {
+    /// Every document, each after the documents it links to
+    pub order: Vec<PathBuf>,
+    /// Groups of documents that link to each other, each sorted by path
+    pub cycles: Vec<Vec<PathBuf>>,
+}
+
+/// Order the documents of a link graph mapping each document to those it links to.
+///
+/// Ties are broken by path so the result is deterministic. Links to
+/// documents outside the graph and links from a document to itself are
+/// ignored.
+pub fn resolve(graph: &BTreeMap<PathBuf, BTreeSet<PathBuf>>) -> DependencyOrder {
+    let nodes: Vec<&PathBuf> = graph.keys().collect();
+    let index: BTreeMap<&PathBuf, usize> = nodes.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+    let edges: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| {
+            graph[*node]
+                .iter()
+                .filter_map(|target| index.get(target).copied())
+                .collect()
+        })
+        .collect();
+
+    // Collapse cycles so the remaining graph is acyclic. Nodes are sorted,
+    // so each component's first member is its smallest path.
+    let components = strongly_connected(&edges);
+    let mut component_of = vec![0; nodes.len()];
+    for (c, members) in components.iter().enumerate() {
+        for &node in members {
+            component_of[node] = c;
+        }
+    }
+
+    // dependents[c] are the components linking into c; pending[c] counts
+    // the components c still waits for
+    let mut dependents: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); components.len()];
+    let mut pending = vec![0; components.len()];
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            let (from, to) = (component_of[from], component_of[to]);
+            if from != to && dependents[to].insert(from) {
+                pending[from] += 1;
+            }
+        }
+    }
+
+    // Kahn's algorithm, always taking the ready component with the smallest path
+    let mut ready: BTreeSet<(usize, usize)> = (0..components.len())
+        .filter(|&c| pending[c] == 0)
+        .map(|c| (components[c][0], c))
+        .collect();
+    let mut result = DependencyOrder::default();
+    while let Some((_, c)) = ready.pop_first() {
+        let members: Vec<PathBuf> = components[c].iter().map(|&n| nodes[n].clone()).collect();
+        if members.len() > 1 {
+            result.cycles.push(members.clone());
+        }
+        result.order.extend(members);
+
+        for &dependent in &dependents[c] {
+            pending[dependent] -= 1;
+            if pending[dependent] == 0 {
+                ready.insert((components[dependent][0], dependent));
+            }
+        }
+    }
+
+    result.cycles.sort();
+    result
+}
+
+/// Tarjan's algorithm; each component's members are sorted
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        edges: &'a [Vec<usize>],
+        next: usize,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        components: Vec<Vec<usize>>,
+    }
+
+    fn visit(s: &mut State<'_>, node: usize) {
+        s.index[node] = Some(s.next);
+        s.low[node] = s.next;
+        s.next += 1;
+        s.stack.push(node);
+        s.on_stack[node] = true;
+
+        for &target in &s.edges[node] {
+            match s.index[target] {
+                None => {
+                    visit(s, target);
+                    s.low[node] = s.low[node].min(s.low[target]);
+                }
+                Some(target_index) if s.on_stack[target] => {
+                    s.low[node] = s.low[node].min(target_index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(s.low[node]) == s.index[node] {
+            let mut component = Vec::new();
+            while let Some(member) = s.stack.pop() {
+                s.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            s.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        edges,
+        next: 0,
+        index: vec![None; edges.len()],
+        low: vec![0; edges.len()],
+        stack: Vec::new(),
+        on_stack: vec![false; edges.len()],
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if state.index[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+    state.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> BTreeMap<PathBuf, BTreeSet<PathBuf>> {
+        edges
+            .iter()
+            .map(|(from, to)| (PathBuf::from(from), to.iter().map(PathBuf::from).collect()))
+            .collect()
+    }
+
+    fn paths(order: &[PathBuf]) -> Vec<&str> {
+        order.iter().map(|p| p.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_references_precede_guides() {
+        let order = resolve(&graph(&[
+            ("a-guide.md", &["z-reference.md"]),
+            ("b-guide.md", &["a-guide.md", "z-reference.md"]),
+            ("z-reference.md", &[]),
+        ]));
+        assert_eq!(
+            paths(&order.order),
+            vec!["z-reference.md", "a-guide.md", "b-guide.md"]
+        );
+        assert!(order.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_independent_documents_keep_path_order() {
+        let order = resolve(&graph(&[("b.md", &[]), ("a.md", &["a.md", "missing.md"])]));
+        assert_eq!(paths(&order.order), vec!["a.md", "b.md"]);
+        assert!(order.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_cycles_reported_and_emitted_together() {
+        let order = resolve(&graph(&[
+            ("guide.md", &["x.md"]),
+            ("x.md", &["y.md"]),
+            ("y.md", &["x.md", "base.md"]),
+            ("base.md", &[]),
+        ]));
+        assert_eq!(
+            paths(&order.order),
+            vec!["base.md", "x.md", "y.md", "guide.md"]
+        );
+        assert_eq!(
+            order.cycles,
+            vec![vec![PathBuf::from("x.md"), PathBuf::from("y.md")]]
+        );
+    }
+}
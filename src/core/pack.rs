@@ -0,0 +1,263 @@
+//! Bundles of the documents relevant to a change
+//!
+//! `context pack --diff <range>` finds the source files changed in a git
+//! revision range, the documents referencing them, and the documents those
+//! link to, up to a number of hops. The bundle carries each document's
+//! redacted body and why it was included, so an AI reviewer of a pull
+//! request gets exactly the context the change touches.
+//!
+//! The MCP `context_pack` tool seeds a pack from file paths or a free-text
+//! task instead, and cuts the documents into section excerpts that fit a
+//! token budget, for assembling an agent's prompt.
+
+use crate::core::models::Status;
+use crate::core::{scoring, sections};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Hops followed from directly affected documents by default
+pub const DEFAULT_HOPS: usize = 1;
+
+/// Token budget for excerpts when none is given
+pub const DEFAULT_MAX_TOKENS: usize = 8000;
+
+/// Words too common in task descriptions to say anything about relevance
+const STOP_WORDS: &[&str] = &[
+    "about", "add", "all", "and", "are", "but", "can", "change", "fix", "for", "from", "has",
+    "have", "how", "into", "its", "make", "new", "not", "should", "that", "the", "this", "use",
+    "was", "what", "when", "where", "which", "with", "would",
+];
+
+/// Documents gathered for a revision range
+#[derive(Debug, Clone, Serialize)]
+pub struct Pack {
+    /// The revision range as given, e.g. `main..HEAD`
+    pub range: String,
+    /// Project files changed in the range
+    pub changed: Vec<String>,
+    /// Documents in the bundle: directly affected ones first, then linked
+    /// ones in order of distance
+    pub documents: Vec<PackedDocument>,
+}
+
+/// One document in a pack
+#[derive(Debug, Clone, Serialize)]
+pub struct PackedDocument {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Brief summary of the document
+    pub description: String,
+    /// Validity status of the document
+    pub status: Status,
+    /// Freshness weight of the document (see `core::scoring`), ranking its
+    /// excerpts
+    #[serde(skip)]
+    pub freshness: f64,
+    /// Changed files the document references; empty for linked documents
+    pub references: Vec<String>,
+    /// Links followed from a directly affected document, 0 if it is one
+    pub hops: usize,
+    /// The document that links here, for linked documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_from: Option<PathBuf>,
+    /// Document body with redaction rules applied; only the out-of-date
+    /// sections of a stale document whose stale references are all scoped
+    /// to sections (see `core::sections`)
+    pub body: String,
+}
+
+/// A section of a packed document, with where it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct Excerpt {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Heading titles leading to the section; empty for text before the
+    /// first heading
+    pub section: Vec<String>,
+    /// Anchor of the section heading, for deep-linking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// Validity status of the document
+    pub status: Status,
+    /// Links followed to reach the document, 0 if it was matched directly
+    pub hops: usize,
+    /// Estimated size of `text` in tokens
+    pub tokens: usize,
+    /// Section text, including its heading
+    pub text: String,
+}
+
+/// Excerpts of a pack cut to a token budget
+#[derive(Debug, Clone, Serialize)]
+pub struct Excerpts {
+    /// Excerpts that fit the budget, most relevant first
+    pub excerpts: Vec<Excerpt>,
+    /// Estimated tokens used by `excerpts`
+    pub tokens: usize,
+    /// Excerpts left out because they did not fit
+    pub omitted: usize,
+}
+
+/// Rough token count of text, at about four characters per token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// The words of a task description worth matching against documents:
+/// lowercased, at least three characters, without common words
+pub fn task_terms(task: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in task.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let word = word.to_lowercase();
+        if word.chars().count() >= 3
+            && !STOP_WORDS.contains(&word.as_str())
+            && !terms.contains(&word)
+        {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Number of `terms` occurring in `text`
+pub fn matched_terms(text: &str, terms: &[String]) -> usize {
+    let text = text.to_lowercase();
+    terms.iter().filter(|t| text.contains(t.as_str())).count()
+}
+
+/// Cut packed documents into one excerpt per section and keep as many as fit
+/// in `max_tokens`. Sections matching task `terms` come first, ranked by
+/// matches weighted by their document's freshness (see `scoring::rank`),
+/// then the rest by freshness alone, so the budget goes to fresh content
+/// first. Ties keep the documents' order, then body order. Excerpts that
+/// don't fit are skipped so smaller ones later on can still be used.
+pub fn excerpts(documents: &[PackedDocument], terms: &[String], max_tokens: usize) -> Excerpts {
+    let mut candidates: Vec<(bool, f64, Excerpt)> = Vec::new();
+    for doc in documents {
+        for excerpt in split(doc) {
+            let matches = matched_terms(&excerpt.text, terms);
+            candidates.push((matches > 0, scoring::rank(doc.freshness, matches), excerpt));
+        }
+    }
+    // Stable, so ties keep document and body order
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)));
+
+    let mut result = Excerpts {
+        excerpts: Vec::new(),
+        tokens: 0,
+        omitted: 0,
+    };
+    for (_, _, excerpt) in candidates {
+        if result.tokens + excerpt.tokens <= max_tokens {
+            result.tokens += excerpt.tokens;
+            result.excerpts.push(excerpt);
+        } else {
+            result.omitted += 1;
+        }
+    }
+    result
+}
+
+/// Split a document body at every heading; blank stretches are dropped
+fn split(doc: &PackedDocument) -> Vec<Excerpt> {
+    let lines: Vec<&str> = doc.body.lines().collect();
+    let index = sections::index(&doc.body);
+    let mut starts: Vec<(usize, Option<&sections::Section>)> = vec![(1, None)];
+    starts.extend(index.iter().map(|s| (s.start_line, Some(s))));
+
+    let mut excerpts = Vec::new();
+    for (i, (start, section)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(lines.len(), |(next, _)| next - 1);
+        if *start > end {
+            continue;
+        }
+        let text = lines[start - 1..end].join("\n").trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        excerpts.push(Excerpt {
+            path: doc.path.clone(),
+            slug: doc.slug.clone(),
+            section: section.map(|s| s.breadcrumb.clone()).unwrap_or_default(),
+            anchor: section.map(|s| s.anchor.clone()),
+            status: doc.status,
+            hops: doc.hops,
+            tokens: estimate_tokens(&text),
+            text,
+        });
+    }
+    excerpts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packed(slug: &str, body: &str) -> PackedDocument {
+        PackedDocument {
+            path: PathBuf::from(format!("{slug}.md")),
+            slug: slug.to_string(),
+            description: String::new(),
+            status: Status::Valid,
+            freshness: 1.0,
+            references: Vec::new(),
+            hops: 0,
+            linked_from: None,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_task_terms() {
+        assert_eq!(
+            task_terms("Fix the token refresh for OAuth, and the token_cache"),
+            vec!["token", "refresh", "oauth", "token_cache"]
+        );
+    }
+
+    #[test]
+    fn test_excerpts_split_at_headings_with_provenance() {
+        let docs = [packed("auth", "Intro.\n\n# Auth\n\n## Tokens\n\nJWTs.\n")];
+        let result = excerpts(&docs, &[], DEFAULT_MAX_TOKENS);
+        let sections: Vec<_> = result.excerpts.iter().map(|e| e.section.clone()).collect();
+        assert_eq!(
+            sections,
+            vec![
+                vec![],
+                vec!["Auth".to_string()],
+                vec!["Auth".to_string(), "Tokens".to_string()]
+            ]
+        );
+        assert_eq!(result.excerpts[2].anchor.as_deref(), Some("tokens"));
+        assert_eq!(result.excerpts[2].text, "## Tokens\n\nJWTs.");
+        assert_eq!(result.omitted, 0);
+    }
+
+    #[test]
+    fn test_excerpts_rank_by_terms_within_budget() {
+        let docs = [
+            packed("billing", "# Billing\n\nCharges.\n"),
+            packed("auth", "# Auth\n\nToken refresh runs hourly.\n\n# Sessions\n\nA very long section about sessions that will not fit.\n"),
+        ];
+        let terms = task_terms("token refresh");
+        let result = excerpts(&docs, &terms, 15);
+        let slugs: Vec<_> = result
+            .excerpts
+            .iter()
+            .map(|e| (e.slug.as_str(), e.section.join("/")))
+            .collect();
+        assert_eq!(
+            slugs,
+            vec![
+                ("auth", "Auth".to_string()),
+                ("billing", "Billing".to_string())
+            ]
+        );
+        assert_eq!(result.omitted, 1);
+        assert!(result.tokens <= 15);
+    }
+}
@@ -1,5 +1,12 @@
 //! Path extraction and validation from markdown content
-
+//!
+//! References are relative to the project root, except assets: files such
+//! as diagrams kept in `.context/assets/` and mentioned as `assets/...`. An
+//! asset reference is recorded as `.context/assets/...`, so it validates
+//! and goes stale like any other reference, but it documents the context
+//! tree rather than the project and never counts towards source coverage.
+
+use crate::core::CONTEXT_DIR_NAME;
 use std::collections::HashSet;
 use std::fmt;
 use std::path::Path;
@@ -123,7 +130,7 @@ fn is_path_like(s: &str) -> bool {
 }
 
 /// Normalize a path by stripping leading `./`
-fn normalize_path(path: &str) -> String {
+pub(crate) fn normalize_path(path: &str) -> String {
     path.strip_prefix("./").unwrap_or(path).to_string()
 }
 
@@ -164,6 +171,34 @@ pub fn validate_path(path: &str, project_root: &Path) -> Result<String, PathErro
     Ok(normalized)
 }
 
+/// Directory within the context root holding assets such as diagrams
+pub const ASSET_DIR: &str = "assets";
+
+/// The reference recorded for `path` if it names an existing asset, e.g.
+/// `.context/assets/architecture.png` for `assets/architecture.png`.
+/// Project files take precedence only when no such asset exists.
+pub fn asset_reference(path: &str, project_root: &Path) -> Option<String> {
+    let normalized = normalize_path(path);
+    if !normalized.starts_with(&format!("{ASSET_DIR}/")) || normalized.contains("..") {
+        return None;
+    }
+    let reference = format!("{CONTEXT_DIR_NAME}/{normalized}");
+    project_root.join(&reference).is_file().then_some(reference)
+}
+
+/// Whether a recorded reference is an asset within the context directory
+pub fn is_asset(reference: &str) -> bool {
+    reference
+        .strip_prefix(CONTEXT_DIR_NAME)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|rest| rest.starts_with(&format!("{ASSET_DIR}/")))
+}
+
+/// How an asset reference is written in a document body (`assets/...`)
+pub fn asset_path(reference: &str) -> Option<&str> {
+    is_asset(reference).then(|| &reference[CONTEXT_DIR_NAME.len() + 1..])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,4 +338,24 @@ Also see `src/config.rs` and `grep` command.
             Ok("src/exists.rs".to_string())
         );
     }
+
+    #[test]
+    fn test_asset_references_resolve_inside_context() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".context/assets")).unwrap();
+        fs::write(
+            dir.path().join(".context/assets/arch.png"),
+            [0x89, b'P', b'N', b'G'],
+        )
+        .unwrap();
+
+        let reference = asset_reference("./assets/arch.png", dir.path()).unwrap();
+        assert_eq!(reference, ".context/assets/arch.png");
+        assert!(is_asset(&reference));
+        assert_eq!(asset_path(&reference), Some("assets/arch.png"));
+        assert_eq!(asset_reference("assets/missing.png", dir.path()), None);
+        assert_eq!(asset_reference("src/arch.png", dir.path()), None);
+        assert!(!is_asset("assets/arch.png"));
+        assert!(!is_asset(".context/guides/auth.md"));
+    }
 }
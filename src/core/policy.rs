@@ -0,0 +1,345 @@
+//! Organization rules declared in `.context/policy.toml`
+//!
+//! Each `[[rule]]` names a check and the files or documents it applies to:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "api-documented"
+//! check = "referenced"        # every matching project file has a document
+//! files = ["src/api/**"]
+//!
+//! [[rule]]
+//! name = "adrs-frozen"
+//! check = "immutable"         # bodies may not change once tagged
+//! documents = ["decisions/**"]
+//! tag = "accepted"
+//!
+//! [[rule]]
+//! name = "described"
+//! check = "description"       # also "owners"
+//! severity = "warning"
+//! ```
+//!
+//! Document globs are relative to the context root and default to every
+//! local document. `context policy check` reports each violation with its
+//! rule and fails when any rule of `error` severity is violated. The
+//! `immutable` check compares a document's body with the first committed
+//! version carrying the tag, so it needs git history; encrypted documents
+//! are skipped since their ciphertext changes on every save.
+
+use crate::core::document::Document;
+use crate::core::{frontmatter, git};
+use crate::error::{ContextError, Result};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Policy file name within the context root
+pub const POLICY_FILE: &str = "policy.toml";
+
+/// What a rule requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Check {
+    /// Every matching project file is referenced by at least one document
+    Referenced,
+    /// Every matching document has a description
+    Description,
+    /// Every matching document names an owner
+    Owners,
+    /// Matching documents carrying the rule's tag keep the body they had
+    /// when the tag was first committed
+    Immutable,
+}
+
+/// Whether a violation fails `context policy check`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Reported and fails the check
+    #[default]
+    Error,
+    /// Reported only
+    Warning,
+}
+
+/// A rule as written in the policy file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Name reported with violations
+    pub name: String,
+    /// What the rule requires
+    pub check: Check,
+    /// Project file globs, for `referenced`
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Document globs relative to the context root (all documents if empty)
+    #[serde(default)]
+    pub documents: Vec<String>,
+    /// Tag marking documents as final, for `immutable`
+    pub tag: Option<String>,
+    /// Message replacing the default explanation of violations
+    pub message: Option<String>,
+    /// Whether violations fail the check
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    rule: Vec<Rule>,
+}
+
+/// A file or document breaking a rule
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// Name of the rule
+    pub rule: String,
+    /// What the rule requires
+    pub check: Check,
+    /// Severity of the rule
+    pub severity: Severity,
+    /// Project file (relative to the project root) or document (relative to
+    /// the context root) breaking the rule
+    pub path: PathBuf,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+/// Outcome of checking every rule
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyReport {
+    /// Number of rules checked
+    pub rules: usize,
+    /// Violations, grouped by rule in policy order
+    pub violations: Vec<Violation>,
+}
+
+impl PolicyReport {
+    /// Number of violations of `error` rules
+    pub fn errors(&self) -> usize {
+        self.violations
+            .iter()
+            .filter(|v| v.severity == Severity::Error)
+            .count()
+    }
+}
+
+/// A parsed policy with compiled globs
+#[derive(Debug, Clone)]
+pub struct Policy {
+    rules: Vec<(Rule, Vec<Pattern>, Vec<Pattern>)>,
+}
+
+impl Policy {
+    /// Load `policy.toml` from the context root, `None` if there is none
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let path = root.join(POLICY_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::parse(&std::fs::read_to_string(path)?).map(Some)
+    }
+
+    /// Parse and validate policy file content
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: PolicyFile = toml::from_str(content).map_err(|e| policy_error(&e.to_string()))?;
+        let compile = |rule: &Rule, globs: &[String]| {
+            globs
+                .iter()
+                .map(|g| {
+                    Pattern::new(g).map_err(|e| {
+                        policy_error(&format!("rule '{}': invalid glob '{g}': {e}", rule.name))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+
+        let mut rules = Vec::with_capacity(file.rule.len());
+        for rule in file.rule {
+            match rule.check {
+                Check::Referenced if rule.files.is_empty() => {
+                    return Err(policy_error(&format!(
+                        "rule '{}': `referenced` needs `files`",
+                        rule.name
+                    )));
+                }
+                Check::Immutable if rule.tag.is_none() => {
+                    return Err(policy_error(&format!(
+                        "rule '{}': `immutable` needs a `tag`",
+                        rule.name
+                    )));
+                }
+                _ => {}
+            }
+            let files = compile(&rule, &rule.files)?;
+            let documents = compile(&rule, &rule.documents)?;
+            rules.push((rule, files, documents));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Check every rule against the local `documents` of the context
+    /// directory at `root` and the project `files` (relative to its root)
+    pub fn check(&self, root: &Path, documents: &[Document], files: &[String]) -> PolicyReport {
+        let project_root = root.parent().unwrap_or(root);
+        let referenced: HashSet<&str> = documents
+            .iter()
+            .flat_map(|doc| doc.references.keys())
+            .map(|r| r.trim_start_matches("./"))
+            .collect();
+
+        let mut violations = Vec::new();
+        for (rule, file_globs, doc_globs) in &self.rules {
+            let mut violation = |path: PathBuf, default: &str| {
+                violations.push(Violation {
+                    rule: rule.name.clone(),
+                    check: rule.check,
+                    severity: rule.severity,
+                    path,
+                    message: rule.message.clone().unwrap_or_else(|| default.to_string()),
+                });
+            };
+
+            if rule.check == Check::Referenced {
+                for file in files {
+                    if file_globs.iter().any(|g| g.matches(file))
+                        && !referenced.contains(file.as_str())
+                    {
+                        violation(PathBuf::from(file), "not referenced by any document");
+                    }
+                }
+                continue;
+            }
+
+            for doc in documents {
+                let relative = doc.path.strip_prefix(root).unwrap_or(&doc.path);
+                if !doc_globs.is_empty() && !doc_globs.iter().any(|g| g.matches_path(relative)) {
+                    continue;
+                }
+                match rule.check {
+                    Check::Description if doc.description.trim().is_empty() => {
+                        violation(relative.to_path_buf(), "description is empty");
+                    }
+                    Check::Owners if doc.owners.is_empty() => {
+                        violation(relative.to_path_buf(), "no owners");
+                    }
+                    Check::Immutable => {
+                        let tag = rule.tag.as_deref().unwrap_or_default();
+                        if !doc.encrypted && doc.tags.iter().any(|t| t == tag) {
+                            if let Some(commit) = edited_since_tagged(project_root, doc, tag) {
+                                let default =
+                                    format!("body changed after it was tagged '{tag}' in {commit}");
+                                violation(relative.to_path_buf(), &default);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        PolicyReport {
+            rules: self.rules.len(),
+            violations,
+        }
+    }
+}
+
+/// The short hash of the first commit in which `doc` carried `tag`, if its
+/// body has changed since. `None` without such a commit or git history.
+fn edited_since_tagged(project_root: &Path, doc: &Document, tag: &str) -> Option<String> {
+    let relative = doc.path.strip_prefix(project_root).unwrap_or(&doc.path);
+    let relative = relative.to_string_lossy();
+    let Ok(log) = git::run(
+        project_root,
+        &["log", "--reverse", "--format=%h", "--", &relative],
+    ) else {
+        return None;
+    };
+
+    for commit in log.lines() {
+        let Some(content) = git::show(project_root, commit, &relative) else {
+            continue;
+        };
+        let Ok(tagged) = frontmatter::parse(doc.path.clone(), &String::from_utf8_lossy(&content))
+        else {
+            continue;
+        };
+        if tagged.tags.iter().any(|t| t == tag) {
+            return (tagged.body != doc.body).then(|| commit.to_string());
+        }
+    }
+    None
+}
+
+fn policy_error(reason: &str) -> ContextError {
+    ContextError::ConfigError(format!("{POLICY_FILE}: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(root: &Path, path: &str, description: &str, references: &[&str]) -> Document {
+        Document::new(
+            root.join(path),
+            path.trim_end_matches(".md").to_string(),
+            description.to_string(),
+            references
+                .iter()
+                .map(|r| ((*r).to_string(), "abc".to_string()))
+                .collect::<HashMap<_, _>>(),
+            String::new(),
+            String::new(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_parse_rejects_incomplete_rules() {
+        assert!(Policy::parse("[[rule]]\nname = \"a\"\ncheck = \"referenced\"\n").is_err());
+        assert!(Policy::parse("[[rule]]\nname = \"a\"\ncheck = \"immutable\"\n").is_err());
+        assert!(
+            Policy::parse("[[rule]]\nname = \"a\"\ncheck = \"owners\"\nfiles = [\"[\"]\n").is_err()
+        );
+        assert!(Policy::parse("[[rule]]\nname = \"a\"\ncheck = \"bogus\"\n").is_err());
+        assert!(Policy::parse("").unwrap().rules.is_empty());
+    }
+
+    #[test]
+    fn test_check_referenced_and_description() {
+        let root = Path::new("/project/.context");
+        let policy = Policy::parse(
+            "[[rule]]\nname = \"api\"\ncheck = \"referenced\"\nfiles = [\"src/api/**\"]\n\n\
+             [[rule]]\nname = \"described\"\ncheck = \"description\"\ndocuments = [\"guides/*\"]\nseverity = \"warning\"\n",
+        )
+        .unwrap();
+        let documents = vec![
+            doc(root, "guides/api.md", "", &["src/api/users.rs"]),
+            doc(root, "notes.md", "", &[]),
+        ];
+        let files =
+            ["src/api/users.rs", "src/api/orders.rs", "src/main.rs"].map(ToString::to_string);
+
+        let report = policy.check(root, &documents, &files);
+        let found: Vec<_> = report
+            .violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.path.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("api", PathBuf::from("src/api/orders.rs")),
+                ("described", PathBuf::from("guides/api.md")),
+            ]
+        );
+        assert_eq!(report.errors(), 1);
+    }
+}
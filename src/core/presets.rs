@@ -0,0 +1,210 @@
+//! Built-in init presets tuned to common project shapes
+
+/// A built-in preset selectable with `context init --preset <name>`
+#[derive(Debug, Clone, Copy)]
+pub struct Preset {
+    /// Name used on the command line
+    pub name: &'static str,
+    /// One-line summary shown by `--list-presets`
+    pub description: &'static str,
+    /// Category directories created in addition to guides/ and references/
+    pub directories: &'static [&'static str],
+    /// Files written relative to the context directory; document templates
+    /// go in `.templates/` (see `core::template`), where they aren't loaded
+    /// as documents
+    pub files: &'static [(&'static str, &'static str)],
+    /// `config.toml` written when the tree has none, with lint bounds suited
+    /// to the project shape
+    pub config: &'static str,
+}
+
+const GUIDE_TEMPLATE: &str = r#"---
+required_sections: ["Overview", "How It Works", "Gotchas"]
+---
+
+# Title
+
+## Overview
+
+## How It Works
+
+## Gotchas
+"#;
+
+const REFERENCE_TEMPLATE: &str = r#"---
+required_sections: ["Responsibilities", "Key Types", "Invariants"]
+---
+
+# Module
+
+## Responsibilities
+
+## Key Types
+
+## Invariants
+"#;
+
+const ADR_TEMPLATE: &str = r#"---
+required_sections: ["Context", "Decision", "Consequences"]
+---
+
+# Decision
+
+## Context
+
+## Decision
+
+## Consequences
+"#;
+
+const RUNBOOK_TEMPLATE: &str = r#"---
+required_sections: ["Symptoms", "Diagnosis", "Remediation"]
+---
+
+# Runbook
+
+## Symptoms
+
+## Diagnosis
+
+## Remediation
+"#;
+
+const RUST_CRATE_ARCHITECTURE: &str = r#"---
+slug: architecture
+description: "Crate layout, public API surface, and module boundaries"
+references: {}
+updated: ""
+---
+
+# Architecture
+
+Describe the crate's modules and how they fit together. Reference the entry
+points in backticks, e.g. `src/lib.rs`, then run `context sync`.
+"#;
+
+const WEB_SERVICE_API: &str = r#"---
+slug: api
+description: "HTTP endpoints, request lifecycle, and error handling"
+references: {}
+updated: ""
+---
+
+# API
+
+Describe the routes this service exposes and where their handlers live.
+"#;
+
+const MONOREPO_LAYOUT: &str = r#"---
+slug: layout
+description: "Packages in this repository and how they depend on each other"
+references: {}
+updated: ""
+---
+
+# Repository Layout
+
+List each package, its owner, and the guides that cover it.
+"#;
+
+const RUST_CRATE_CONFIG: &str = r"# Module references stay short and skimmable; examples belong in doc tests
+[readability]
+max_words = 3000
+max_sentence_words = 30
+max_words_per_heading = 500
+max_code_percent = 70
+";
+
+const WEB_SERVICE_CONFIG: &str = r"# Runbooks are read during incidents: keep them short and sectioned
+[readability]
+max_words = 2500
+max_sentence_words = 25
+max_words_per_heading = 400
+max_code_percent = 80
+";
+
+const MONOREPO_CONFIG: &str = r"# Per-package guides are packed together, so each has to stay compact
+[readability]
+max_words = 4000
+max_sentence_words = 30
+max_words_per_heading = 600
+max_code_percent = 80
+";
+
+/// All built-in presets
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "rust-crate",
+        description: "Single Rust library or binary crate",
+        directories: &[".templates"],
+        files: &[
+            (".templates/guide.md", GUIDE_TEMPLATE),
+            (".templates/reference.md", REFERENCE_TEMPLATE),
+            ("guides/architecture.md", RUST_CRATE_ARCHITECTURE),
+        ],
+        config: RUST_CRATE_CONFIG,
+    },
+    Preset {
+        name: "web-service",
+        description: "Deployed service with APIs, runbooks, and decision records",
+        directories: &[".templates", "runbooks", "adr"],
+        files: &[
+            (".templates/guide.md", GUIDE_TEMPLATE),
+            (".templates/reference.md", REFERENCE_TEMPLATE),
+            (".templates/runbook.md", RUNBOOK_TEMPLATE),
+            (".templates/adr.md", ADR_TEMPLATE),
+            ("references/api.md", WEB_SERVICE_API),
+        ],
+        config: WEB_SERVICE_CONFIG,
+    },
+    Preset {
+        name: "monorepo",
+        description: "Many packages or services in one repository",
+        directories: &[".templates", "packages", "adr"],
+        files: &[
+            (".templates/guide.md", GUIDE_TEMPLATE),
+            (".templates/reference.md", REFERENCE_TEMPLATE),
+            (".templates/adr.md", ADR_TEMPLATE),
+            ("guides/layout.md", MONOREPO_LAYOUT),
+        ],
+        config: MONOREPO_CONFIG,
+    },
+];
+
+/// Look up a built-in preset by name
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_preset() {
+        assert!(find("rust-crate").is_some());
+        assert!(find("web-service").is_some());
+        assert!(find("monorepo").is_some());
+        assert!(find("cobol-mainframe").is_none());
+    }
+
+    #[test]
+    fn test_preset_files_live_in_declared_directories() {
+        for preset in PRESETS {
+            for (path, _) in preset.files {
+                let dir = path.split('/').next().unwrap();
+                assert!(
+                    ["guides", "references"].contains(&dir) || preset.directories.contains(&dir),
+                    "{}: {path} is outside the preset's directories",
+                    preset.name
+                );
+            }
+            let config: crate::core::config::Config = toml::from_str(preset.config).unwrap();
+            assert!(
+                config.readability.max_words > 0,
+                "{}: no lint bounds",
+                preset.name
+            );
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Documentation checks per build target in monorepos
+//!
+//! `context query --files-for-target //services/auth:lib` asks a resolver
+//! command configured as `[query] resolver` for the files making up a build
+//! target, then reports the documents referencing any of them. The resolver
+//! runs through `sh -c` in the project root with the target as `$1`, so any
+//! build system can be plugged in, e.g. for Bazel:
+//!
+//! ```toml
+//! [query]
+//! resolver = "bazel query \"kind('source file', deps($1))\" --output=label"
+//! ```
+//!
+//! It prints one file per line, either as a project-relative path or as a
+//! label such as `//services/auth:jwt.rs`; labels of external repositories
+//! (`@repo//...`) are skipped.
+
+use crate::core::models::Status;
+use crate::error::{ContextError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A document referencing files of the target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetDocument {
+    /// Path to the document file
+    pub document: PathBuf,
+    /// Validation status of the document
+    pub status: Status,
+    /// The target's files the document references
+    pub references: Vec<String>,
+}
+
+/// The files of a build target and the documents covering them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetQuery {
+    /// The queried build target
+    pub target: String,
+    /// Files the resolver returned, in path order
+    pub files: Vec<String>,
+    /// Documents referencing any of the files, in path order
+    pub documents: Vec<TargetDocument>,
+}
+
+/// Run `resolver` for `target` in `project_root`, returning its files
+pub fn resolve(resolver: &str, target: &str, project_root: &Path) -> Result<Vec<String>> {
+    let output = Command::new("sh")
+        .args(["-c", resolver, "sh", target])
+        .current_dir(project_root)
+        .output()
+        .map_err(|e| ContextError::Other(format!("failed to run query resolver: {e}")))?;
+    if !output.status.success() {
+        return Err(ContextError::Other(format!(
+            "query resolver failed for {target}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_files(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Project-relative paths from resolver output, sorted and deduplicated
+pub fn parse_files(output: &str) -> Vec<String> {
+    let files: BTreeSet<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('@'))
+        .map(|line| match line.strip_prefix("//") {
+            // `//pkg:file` is `pkg/file`; `//:file` is at the root
+            Some(label) => match label.split_once(':') {
+                Some(("", name)) => name.to_string(),
+                Some((package, name)) => format!("{package}/{name}"),
+                None => label.to_string(),
+            },
+            None => line.trim_start_matches("./").to_string(),
+        })
+        .collect();
+    files.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_paths_and_labels() {
+        let output = "//services/auth:jwt.rs\n//:BUILD\n@crates//serde:lib.rs\n./services/auth/jwt.rs\n\nservices/auth/keys.rs\n";
+        assert_eq!(
+            parse_files(output),
+            vec!["BUILD", "services/auth/jwt.rs", "services/auth/keys.rs"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_passes_target_as_argument() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let files = resolve("echo \"//${1#//}\"", "//pkg:file.rs", dir.path()).unwrap();
+        assert_eq!(files, vec!["pkg/file.rs"]);
+        assert!(resolve("exit 3", "//pkg:lib", dir.path()).is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Length and readability metrics of document bodies
+//!
+//! A document loaded as LLM context has to fit a budget and be navigable
+//! once it's there. `context lint` measures each body and flags documents
+//! past the `[readability]` bounds: too many words (a candidate for
+//! `context split`), long average sentences, too few headings for their
+//! length, or mostly code with little prose explaining it. Fenced code
+//! counts toward the length but not toward sentences.
+
+use crate::core::config::ReadabilityConfig;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Documents with fewer non-blank lines aren't checked for their code
+/// ratio; a short example with one line of introduction is fine
+pub const MIN_LINES_FOR_CODE_RATIO: usize = 40;
+
+/// What `measure` finds in a body
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Words in the body, code included
+    pub words: usize,
+    /// Sentences in paragraphs and list items
+    pub sentences: usize,
+    /// Average words per sentence
+    pub sentence_words: f64,
+    /// Markdown headings, any level
+    pub headings: usize,
+    /// Words per heading (all words when there is none)
+    pub words_per_heading: f64,
+    /// Non-blank lines inside fenced code blocks
+    pub code_lines: usize,
+    /// Other non-blank lines, headings excluded
+    pub prose_lines: usize,
+    /// Share of non-blank lines that are code, from 0 to 1
+    pub code_ratio: f64,
+}
+
+/// Metrics of one document, for `context lint --metrics`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentMetrics {
+    /// Path relative to the context root
+    pub path: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+/// A bound `check` found exceeded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Lint rule name
+    pub rule: &'static str,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+/// Measure a Markdown body
+#[allow(clippy::cast_precision_loss)]
+pub fn measure(body: &str) -> Metrics {
+    let mut metrics = Metrics::default();
+    let mut sentence_words = 0;
+    let mut paragraph = String::new();
+    // The marker character and length of the open code fence
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        match (fence, fence_marker(trimmed)) {
+            (None, Some(opening)) => {
+                fence = Some(opening);
+                continue;
+            }
+            // Only a bare fence of the same character, at least as long as
+            // the opening one, closes it; a `#` line before that is code
+            (Some((c, len)), Some((marker, run)))
+                if marker == c && run >= len && run == trimmed.len() =>
+            {
+                fence = None;
+                continue;
+            }
+            _ => {}
+        }
+        if fence.is_some() {
+            if !trimmed.is_empty() {
+                metrics.code_lines += 1;
+                metrics.words += count_words(trimmed);
+            }
+            continue;
+        }
+
+        metrics.words += count_words(trimmed);
+        let is_item = is_list_item(trimmed);
+        // Headings, tables and blank lines end a paragraph; list items start one
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('|') || is_item {
+            add_sentences(&paragraph, &mut metrics.sentences, &mut sentence_words);
+            paragraph.clear();
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            metrics.headings += 1;
+            continue;
+        }
+        metrics.prose_lines += 1;
+        if !trimmed.starts_with('|') {
+            paragraph.push(' ');
+            paragraph.push_str(trimmed);
+        }
+    }
+    add_sentences(&paragraph, &mut metrics.sentences, &mut sentence_words);
+
+    if metrics.sentences > 0 {
+        metrics.sentence_words = sentence_words as f64 / metrics.sentences as f64;
+    }
+    metrics.words_per_heading = metrics.words as f64 / metrics.headings.max(1) as f64;
+    let lines = metrics.code_lines + metrics.prose_lines;
+    if lines > 0 {
+        metrics.code_ratio = metrics.code_lines as f64 / lines as f64;
+    }
+    metrics
+}
+
+/// The bounds of `config` that `metrics` exceed; a bound of 0 is off
+#[allow(clippy::cast_precision_loss)]
+pub fn check(metrics: &Metrics, config: &ReadabilityConfig) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if config.max_words > 0 && metrics.words > config.max_words {
+        violations.push(Violation {
+            rule: "too-long",
+            message: format!(
+                "{} words exceed the limit of {}; consider `context split`",
+                metrics.words, config.max_words
+            ),
+        });
+    }
+    if config.max_sentence_words > 0 && metrics.sentence_words > config.max_sentence_words as f64 {
+        violations.push(Violation {
+            rule: "long-sentences",
+            message: format!(
+                "sentences average {:.1} words, above {}",
+                metrics.sentence_words, config.max_sentence_words
+            ),
+        });
+    }
+    if config.max_words_per_heading > 0
+        && metrics.words_per_heading > config.max_words_per_heading as f64
+    {
+        violations.push(Violation {
+            rule: "sparse-headings",
+            message: format!(
+                "{:.0} words per heading, above {}; add sections to make it navigable",
+                metrics.words_per_heading, config.max_words_per_heading
+            ),
+        });
+    }
+    if config.max_code_percent > 0
+        && metrics.code_lines + metrics.prose_lines >= MIN_LINES_FOR_CODE_RATIO
+        && metrics.code_ratio * 100.0 > f64::from(config.max_code_percent)
+    {
+        violations.push(Violation {
+            rule: "code-heavy",
+            message: format!(
+                "{:.0}% of lines are code, above {}%; explain what the code shows",
+                metrics.code_ratio * 100.0,
+                config.max_code_percent
+            ),
+        });
+    }
+    violations
+}
+
+/// Words of a line; markup such as list bullets and `=` doesn't count
+fn count_words(line: &str) -> usize {
+    line.split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Count the sentences of a paragraph and their words
+fn add_sentences(paragraph: &str, sentences: &mut usize, words: &mut usize) {
+    let mut current = 0;
+    for word in paragraph.split_whitespace() {
+        if word.chars().any(char::is_alphanumeric) {
+            current += 1;
+        }
+        let end = word.trim_end_matches(['"', '\'', ')', '*', '_', '`']);
+        if current > 0 && end.ends_with(['.', '!', '?']) {
+            *sentences += 1;
+            *words += current;
+            current = 0;
+        }
+    }
+    // A paragraph or list item without final punctuation is still one
+    if current > 0 {
+        *sentences += 1;
+        *words += current;
+    }
+}
+
+/// The character and length of the code fence a trimmed line starts with,
+/// if any
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let c = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let run = line.chars().take_while(|&ch| ch == c).count();
+    (run >= 3).then_some((c, run))
+}
+
+/// Whether a trimmed line starts a bulleted or numbered list item
+fn is_list_item(line: &str) -> bool {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && line[digits..].starts_with(". ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_counts_prose_and_code() {
+        let body = "# Auth\n\nTokens are issued by the auth service. They expire hourly!\n\n\
+                    - Refresh before expiry\n- Retry once\n\n```rust\nlet token = issue();\n```\n";
+        let metrics = measure(body);
+        assert_eq!(metrics.words, 19);
+        assert_eq!(metrics.sentences, 4);
+        assert!((metrics.sentence_words - 3.75).abs() < f64::EPSILON);
+        assert_eq!(metrics.headings, 1);
+        assert_eq!((metrics.code_lines, metrics.prose_lines), (1, 3));
+        assert!((metrics.code_ratio - 0.25).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_fenced_comments_are_not_headings() {
+        let body =
+            "# Setup\n\n````md\n```sh\n# not a heading\n```\n````\n\n~~~\n# nor this\n```\n~~~\n";
+        let metrics = measure(body);
+        assert_eq!(metrics.headings, 1);
+        assert_eq!(metrics.code_lines, 5);
+    }
+
+    #[test]
+    fn test_check_flags_exceeded_bounds() {
+        let config = ReadabilityConfig {
+            max_words: 4000,
+            max_sentence_words: 30,
+            max_words_per_heading: 600,
+            max_code_percent: 80,
+        };
+        let long = format!(
+            "# Guide\n\n{}",
+            "Every word here counts toward the limit. ".repeat(700)
+        );
+        let rules: Vec<_> = check(&measure(&long), &config)
+            .into_iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(rules, vec!["too-long", "sparse-headings"]);
+
+        let rambling = format!("{}.", "word ".repeat(60));
+        assert_eq!(
+            check(&measure(&rambling), &config)[0].rule,
+            "long-sentences"
+        );
+
+        let dump = format!("Example:\n\n```\n{}```\n", "code();\n".repeat(50));
+        assert_eq!(check(&measure(&dump), &config)[0].rule, "code-heavy");
+
+        assert!(check(&measure(&long), &ReadabilityConfig::default()).is_empty());
+    }
+}
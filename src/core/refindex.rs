@@ -0,0 +1,94 @@
+//! Reverse index from referenced source paths to the documents referencing them
+//!
+//! Finding the documents for many source paths would otherwise scan every
+//! document's references once per path. The index is built from the
+//! documents as they are when asked for, so it never goes stale across
+//! syncs or splits; build it once per query batch.
+
+use crate::core::document::Document;
+use std::collections::HashMap;
+
+/// Documents keyed by the project-relative paths they reference, without a
+/// leading `./`
+#[derive(Debug, Default)]
+pub struct ReferenceIndex<'a> {
+    documents: HashMap<&'a str, Vec<&'a Document>>,
+}
+
+impl<'a> ReferenceIndex<'a> {
+    /// Index the references of `documents`, keeping their order per path
+    pub fn build(documents: impl IntoIterator<Item = &'a Document>) -> Self {
+        let mut index: HashMap<&str, Vec<&Document>> = HashMap::new();
+        for doc in documents {
+            for reference in doc.references.keys() {
+                let referencing = index.entry(normalize(reference)).or_default();
+                // `./a.rs` and `a.rs` in one document are one reference
+                if !referencing.last().is_some_and(|d| std::ptr::eq(*d, doc)) {
+                    referencing.push(doc);
+                }
+            }
+        }
+        Self { documents: index }
+    }
+
+    /// Documents referencing `path`, in the order they were indexed
+    pub fn lookup(&self, path: &str) -> &[&'a Document] {
+        self.documents
+            .get(normalize(path))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of distinct referenced paths
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether no document references anything
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+}
+
+/// A reference path without a leading `./`
+pub fn normalize(path: &str) -> &str {
+    path.trim_start_matches("./")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn doc(slug: &str, references: &[&str]) -> Document {
+        Document::new(
+            PathBuf::from(format!("{slug}.md")),
+            slug.to_string(),
+            String::new(),
+            references
+                .iter()
+                .map(|r| ((*r).to_string(), "abc1234".to_string()))
+                .collect(),
+            String::new(),
+            String::new(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_lookup_normalizes_paths() {
+        let docs = [
+            doc("a", &["src/lib.rs", "./src/main.rs"]),
+            doc("b", &["./src/lib.rs", "src/lib.rs"]),
+        ];
+        let index = ReferenceIndex::build(&docs);
+        assert_eq!(index.len(), 2);
+        let slugs: Vec<_> = index
+            .lookup("./src/lib.rs")
+            .iter()
+            .map(|d| d.slug.as_str())
+            .collect();
+        assert_eq!(slugs, vec!["a", "b"]);
+        assert_eq!(index.lookup("src/main.rs").len(), 1);
+        assert!(index.lookup("src/other.rs").is_empty());
+    }
+}
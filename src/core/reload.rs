@@ -0,0 +1,158 @@
+//! Hot reloading of `config.toml` for long-running processes
+//!
+//! `context serve` keeps a `LiveConfig` and polls it: when the file's
+//! content changes and still parses, the new configuration replaces the old
+//! one in a single swap, so a tool call sees either the old settings or the
+//! new ones, never a mix. An edit that fails to parse is rejected and the
+//! last good configuration stays in effect until the file is fixed.
+
+use crate::core::config::{Config, CONFIG_FILE_NAME};
+use crate::error::{ContextError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// The effective configuration of a context directory, reloadable in place
+#[derive(Debug)]
+pub struct LiveConfig {
+    root: PathBuf,
+    state: RwLock<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    /// File content last looked at, applied or not; `None` if absent
+    seen: Option<String>,
+    /// Parsed form of the applied content, for naming changed sections
+    applied: toml::Table,
+    config: Arc<Config>,
+}
+
+impl LiveConfig {
+    /// Load the configuration of the context directory at `root`
+    pub fn load(root: &Path) -> Result<Self> {
+        let content = read(root)?;
+        let (applied, config) = parse(content.as_deref())?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            state: RwLock::new(State {
+                seen: content,
+                applied,
+                config: Arc::new(config),
+            }),
+        })
+    }
+
+    /// The context directory this configuration belongs to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The configuration currently in effect
+    pub fn current(&self) -> Arc<Config> {
+        Arc::clone(
+            &self
+                .state
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .config,
+        )
+    }
+
+    /// Re-read `config.toml` and swap in its configuration if it changed.
+    ///
+    /// Returns the top-level sections that changed, `None` if the file is
+    /// as last seen, or the parse error of a rejected edit (reported once
+    /// per edit; the previous configuration stays in effect).
+    pub fn reload(&self) -> Result<Option<Vec<String>>> {
+        let content = read(&self.root)?;
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        if content == state.seen {
+            return Ok(None);
+        }
+        state.seen.clone_from(&content);
+
+        let (applied, config) = parse(content.as_deref())?;
+        let changed = changed_sections(&state.applied, &applied);
+        state.applied = applied;
+        state.config = Arc::new(config);
+        Ok(Some(changed))
+    }
+}
+
+fn read(root: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(root.join(CONFIG_FILE_NAME)) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn parse(content: Option<&str>) -> Result<(toml::Table, Config)> {
+    let content = content.unwrap_or_default();
+    let table = content
+        .parse::<toml::Table>()
+        .map_err(|e| ContextError::ConfigError(e.to_string()))?;
+    Ok((table, Config::parse(content)?))
+}
+
+/// Top-level keys added, removed or modified between two configurations
+fn changed_sections(old: &toml::Table, new: &toml::Table) -> Vec<String> {
+    let mut changed: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reload_swaps_changed_config() {
+        let dir = TempDir::new().unwrap();
+        let live = LiveConfig::load(dir.path()).unwrap();
+        assert_eq!(live.current().io.retries, 2);
+        assert_eq!(live.reload().unwrap(), None);
+
+        let config = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(
+            &config,
+            "[io]\nretries = 5\n\n[redaction]\npatterns = [\"secret\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            live.reload().unwrap(),
+            Some(vec!["io".to_string(), "redaction".to_string()])
+        );
+        assert_eq!(live.current().io.retries, 5);
+        assert_eq!(live.reload().unwrap(), None);
+
+        std::fs::write(&config, "[io]\nretries = 5\n").unwrap();
+        assert_eq!(live.reload().unwrap(), Some(vec!["redaction".to_string()]));
+        assert!(live.current().redaction.patterns.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_edit_keeps_previous_config() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(&config, "[io]\nretries = 3\n").unwrap();
+        let live = LiveConfig::load(dir.path()).unwrap();
+
+        std::fs::write(&config, "[io]\nretries = \"many\"\n").unwrap();
+        assert!(live.reload().is_err());
+        assert_eq!(live.current().io.retries, 3);
+        // Reported once, until the file changes again
+        assert_eq!(live.reload().unwrap(), None);
+
+        std::fs::write(&config, "[io]\nretries = 4\n").unwrap();
+        assert_eq!(live.reload().unwrap(), Some(vec!["io".to_string()]));
+        assert_eq!(live.current().io.retries, 4);
+    }
+}
@@ -0,0 +1,272 @@
+//! Shared context trees fetched from other repositories
+//!
+//! Platform teams can publish guides in their own repository and have many
+//! service repositories consume them. Each `[[remotes]]` entry in the
+//! config names a git repository; `context remote fetch` makes a shallow
+//! clone of it in `.context/.remote/<name>`, and the `.context` directory
+//! found at the configured path inside the clone is overlaid read-only onto
+//! search and find results. Remote documents validate against their own
+//! repository's files, so they are never reported by the local status or
+//! touched by sync. `context remote update` pulls the latest revision and
+//! `context remote status` compares checkouts with upstream.
+
+use crate::core::config::RemoteConfig;
+use crate::core::{git, markdown_files};
+use crate::error::{ContextError, Result};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory within the context root holding remote checkouts
+pub const REMOTE_DIR: &str = ".remote";
+
+/// A remote that was fetched
+#[derive(Debug, Clone, Serialize)]
+pub struct Fetched {
+    /// Name of the remote
+    pub name: String,
+    /// The remote's context directory within its checkout
+    pub context_dir: PathBuf,
+    /// Number of documents it provides
+    pub documents: usize,
+}
+
+/// A remote that was brought up to date
+#[derive(Debug, Clone, Serialize)]
+pub struct Updated {
+    /// Name of the remote
+    pub name: String,
+    /// Commit checked out before the update, if the remote had been fetched
+    pub from: Option<String>,
+    /// Commit checked out now
+    pub to: String,
+    /// Number of documents the remote provides
+    pub documents: usize,
+}
+
+/// How a remote's checkout compares with upstream
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteStatus {
+    /// Name of the remote
+    pub name: String,
+    /// Git URL of the remote
+    pub url: String,
+    /// Commit checked out locally, or `None` if never fetched
+    pub commit: Option<String>,
+    /// Latest upstream commit, or `None` if upstream couldn't be reached
+    pub upstream: Option<String>,
+    /// Why upstream couldn't be checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Local documents hiding remote documents with the same slug
+    pub shadowed: Vec<Shadow>,
+}
+
+impl RemoteStatus {
+    /// Whether the checkout is known to match upstream
+    pub fn up_to_date(&self) -> bool {
+        self.commit.is_some() && self.commit == self.upstream
+    }
+}
+
+/// A local document sharing its slug with a remote document
+#[derive(Debug, Clone, Serialize)]
+pub struct Shadow {
+    /// The shared slug
+    pub slug: String,
+    /// The local document, which wins slug lookups
+    pub local: PathBuf,
+    /// The remote document it hides
+    pub remote: PathBuf,
+}
+
+/// Where a remote is checked out under the context directory at `root`
+pub fn checkout_dir(root: &Path, name: &str) -> PathBuf {
+    root.join(REMOTE_DIR).join(name)
+}
+
+/// The remote's context directory within its checkout
+pub fn context_dir(root: &Path, remote: &RemoteConfig) -> PathBuf {
+    checkout_dir(root, &remote.name)
+        .join(&remote.path)
+        .join(".context")
+}
+
+/// Clone (or re-clone) a remote, replacing its previous checkout only once
+/// the new one is known to contain a context directory
+pub fn fetch(root: &Path, remote: &RemoteConfig) -> Result<Fetched> {
+    validate(remote)?;
+
+    let dir = root.join(REMOTE_DIR);
+    std::fs::create_dir_all(&dir)?;
+    // Checkouts can always be fetched again, so keep them out of git
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        std::fs::write(ignore, "*\n")?;
+    }
+
+    let staging = dir.join(format!(".{}.tmp", remote.name));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    let mut args = vec!["clone", "--quiet", "--depth", "1"];
+    if let Some(rev) = &remote.rev {
+        args.extend(["--branch", rev]);
+    }
+    let staging_str = staging.to_string_lossy();
+    args.extend(["--", &remote.url, &staging_str]);
+    git::run(&dir, &args).map_err(|e| remote_error(remote, &e.to_string()))?;
+
+    if !staging.join(&remote.path).join(".context").is_dir() {
+        std::fs::remove_dir_all(&staging)?;
+        return Err(remote_error(
+            remote,
+            &format!("no .context directory at '{}'", remote.path.display()),
+        ));
+    }
+
+    let checkout = checkout_dir(root, &remote.name);
+    if checkout.exists() {
+        std::fs::remove_dir_all(&checkout)?;
+    }
+    std::fs::rename(&staging, &checkout)?;
+
+    let context_dir = context_dir(root, remote);
+    Ok(Fetched {
+        name: remote.name.clone(),
+        documents: markdown_files(&context_dir).count(),
+        context_dir,
+    })
+}
+
+/// Pull the latest revision of a fetched remote, or fetch it if it hasn't been
+pub fn update(root: &Path, remote: &RemoteConfig) -> Result<Updated> {
+    validate(remote)?;
+    let checkout = checkout_dir(root, &remote.name);
+    let Some(from) = commit(root, &remote.name) else {
+        let fetched = fetch(root, remote)?;
+        return Ok(Updated {
+            name: fetched.name,
+            from: None,
+            to: commit(root, &remote.name).unwrap_or_default(),
+            documents: fetched.documents,
+        });
+    };
+
+    let rev = remote.rev.as_deref().unwrap_or("HEAD");
+    let git =
+        |args: &[&str]| git::run(&checkout, args).map_err(|e| remote_error(remote, &e.to_string()));
+    git(&["fetch", "--quiet", "--depth", "1", "origin", rev])?;
+    git(&["reset", "--quiet", "--hard", "FETCH_HEAD"])?;
+
+    let context_dir = context_dir(root, remote);
+    if !context_dir.is_dir() {
+        // Keep serving the previous revision rather than nothing
+        git(&["reset", "--quiet", "--hard", &from])?;
+        return Err(remote_error(
+            remote,
+            &format!(
+                "upstream no longer has a .context directory at '{}'",
+                remote.path.display()
+            ),
+        ));
+    }
+
+    Ok(Updated {
+        name: remote.name.clone(),
+        from: Some(from),
+        to: commit(root, &remote.name).unwrap_or_default(),
+        documents: markdown_files(&context_dir).count(),
+    })
+}
+
+/// Compare a remote's checkout with upstream. Shadowing is filled in by the caller.
+pub fn status(root: &Path, remote: &RemoteConfig) -> RemoteStatus {
+    let rev = remote.rev.as_deref().unwrap_or("HEAD");
+    let (upstream, error) = match upstream_commit(&remote.url, rev) {
+        Ok(commit) => (Some(commit), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    RemoteStatus {
+        name: remote.name.clone(),
+        url: remote.url.clone(),
+        commit: commit(root, &remote.name),
+        upstream,
+        error,
+        shadowed: Vec::new(),
+    }
+}
+
+/// Commit checked out for a remote, if it has been fetched
+pub fn commit(root: &Path, name: &str) -> Option<String> {
+    let checkout = checkout_dir(root, name);
+    checkout
+        .is_dir()
+        .then(|| git::run(&checkout, &["rev-parse", "HEAD"]).ok())
+        .flatten()
+}
+
+/// Commit that `rev` points to upstream, peeling annotated tags
+fn upstream_commit(url: &str, rev: &str) -> Result<String> {
+    let cwd = std::env::temp_dir();
+    let listing = git::run(&cwd, &["ls-remote", "--", url, rev])?;
+    let lines: Vec<(&str, &str)> = listing.lines().filter_map(|l| l.split_once('\t')).collect();
+    lines
+        .iter()
+        .find(|(_, name)| name.ends_with("^{}"))
+        .or_else(|| lines.first())
+        .map(|(hash, _)| (*hash).to_string())
+        .ok_or_else(|| ContextError::RemoteError(format!("'{rev}' not found at {url}")))
+}
+
+/// Reject names and paths that would escape the remote directory
+fn validate(remote: &RemoteConfig) -> Result<()> {
+    let valid_name = !remote.name.is_empty()
+        && remote
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(remote_error(
+            remote,
+            "name must use letters, digits, '-' and '_'",
+        ));
+    }
+    if !remote
+        .path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(remote_error(
+            remote,
+            "path must be relative to the repository root",
+        ));
+    }
+    Ok(())
+}
+
+fn remote_error(remote: &RemoteConfig, reason: &str) -> ContextError {
+    ContextError::RemoteError(format!("{}: {reason}", remote.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(name: &str, path: &str) -> RemoteConfig {
+        RemoteConfig {
+            name: name.to_string(),
+            url: "https://example.com/repo.git".to_string(),
+            path: PathBuf::from(path),
+            rev: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_escapes() {
+        assert!(validate(&remote("platform", "")).is_ok());
+        assert!(validate(&remote("platform", "shared/docs")).is_ok());
+        assert!(validate(&remote("../x", "")).is_err());
+        assert!(validate(&remote("platform", "../other")).is_err());
+        assert!(validate(&remote("platform", "/etc")).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Evidence of review before stale documents are re-synced
+//!
+//! Syncing a stale document declares that it matches its references again,
+//! which is only true if someone read the changes. With `[review] require =
+//! true`, `context sync` refuses to re-hash stale or orphaned documents
+//! unless a reviewer is named, either with `--reviewed-by` or by confirming
+//! a listing of the changed references interactively. Each acknowledged
+//! document is appended to `.context/.journal/reviews.jsonl` with the
+//! reviewer and the references they acknowledged. The journal is an audit
+//! trail, so it is never rotated; commit it alongside the documents.
+
+use crate::core::git;
+use crate::core::journal::Journal;
+use crate::core::models::Validation;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Review journal path within the context root
+pub const REVIEW_JOURNAL: &str = ".journal/reviews.jsonl";
+
+/// One acknowledged document, written as one JSON line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Review {
+    /// RFC 3339 timestamp of the sync
+    pub timestamp: String,
+    /// Who reviewed the changes
+    pub reviewer: String,
+    /// Document path relative to the context root
+    pub document: PathBuf,
+    /// References whose files changed since the last sync
+    pub changed: Vec<String>,
+    /// References whose files were deleted since the last sync
+    pub missing: Vec<String>,
+}
+
+impl Review {
+    /// A review by `reviewer` of the changes found by `validation`
+    pub fn new(root: &Path, timestamp: String, reviewer: &str, validation: &Validation) -> Self {
+        Self {
+            timestamp,
+            reviewer: reviewer.to_string(),
+            document: validation
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&validation.path)
+                .to_path_buf(),
+            changed: validation.changed.clone(),
+            missing: validation.missing.clone(),
+        }
+    }
+}
+
+/// The review journal of the context directory at `root`
+pub fn journal(root: &Path) -> Journal {
+    Journal::new(root.join(REVIEW_JOURNAL), u64::MAX, 0)
+}
+
+/// Every review recorded, oldest first. Lines that can't be parsed are skipped.
+pub fn reviews(journal: &Journal) -> Result<Vec<Review>> {
+    Ok(journal
+        .lines()?
+        .iter()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Name to record for an interactive review: git's `user.name`, else `$USER`
+pub fn local_reviewer(project_root: &Path) -> String {
+    git::run(project_root, &["config", "user.name"])
+        .ok()
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -0,0 +1,81 @@
+//! Freshness weighting so trustworthy documents rank ahead of stale ones
+//!
+//! A document's freshness is its status weight, scaled by how recently it
+//! was updated: `status * ((1 - recency) + recency * 0.5^(age / half_life))`.
+//! Documents with no parseable `updated` date get no recency credit.
+
+use crate::core::clock;
+use crate::core::config::ScoringConfig;
+use crate::core::models::Status;
+use chrono::NaiveDate;
+
+/// Freshness of a document in `[0, max status weight]`
+pub fn freshness(config: &ScoringConfig, status: Status, updated: &str, today: NaiveDate) -> f64 {
+    let status_weight = match status {
+        Status::Valid => config.valid,
+        Status::Stale => config.stale,
+        Status::Orphaned => config.orphaned,
+    };
+
+    let decay = clock::parse_updated(updated).map_or(0.0, |date| {
+        #[allow(clippy::cast_precision_loss)]
+        let age = (today - date).num_days().max(0) as f64;
+        0.5_f64.powf(age / config.half_life_days.max(1.0))
+    });
+
+    let recency = config.recency.clamp(0.0, 1.0);
+    status_weight * ((1.0 - recency) + recency * decay)
+}
+
+/// Combine a document's freshness with how many lines matched a query.
+///
+/// Match count has diminishing returns so a stale document can't win on
+/// repetition alone.
+pub fn rank(freshness: f64, matches: usize) -> f64 {
+    #[allow(clippy::cast_precision_loss)]
+    let matches = matches.max(1) as f64;
+    freshness * (1.0 + matches.ln())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_valid_recent_beats_stale_ancient() {
+        let config = ScoringConfig::default();
+        let fresh = freshness(&config, Status::Valid, "2025-05-30", today());
+        let old = freshness(&config, Status::Stale, "2021-01-01", today());
+        assert!(fresh > old);
+    }
+
+    #[test]
+    fn test_recency_halves_per_half_life() {
+        let config = ScoringConfig {
+            recency: 1.0,
+            half_life_days: 10.0,
+            ..ScoringConfig::default()
+        };
+        let score = freshness(&config, Status::Valid, "2025-05-22", today());
+        assert!((score - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_date_gets_no_recency_credit() {
+        let config = ScoringConfig::default();
+        let undated = freshness(&config, Status::Valid, "", today());
+        assert!((undated - (1.0 - config.recency)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rank_diminishing_matches() {
+        assert!(rank(1.0, 1) < rank(1.0, 5));
+        // One match in a valid document beats two in a stale one
+        let config = ScoringConfig::default();
+        assert!(rank(config.valid, 1) > rank(config.stale, 2));
+    }
+}
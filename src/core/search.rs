@@ -0,0 +1,171 @@
+//! Full-text search over document bodies
+
+use crate::core::document::Document;
+use crate::core::models::DocumentState;
+use crate::core::sections;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A line in a document body that matched a search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineMatch {
+    /// 1-based line number within the body
+    pub line: usize,
+    /// The full text of the matching line
+    pub text: String,
+    /// Headings enclosing the match, outermost first
+    pub breadcrumb: Vec<String>,
+    /// Anchor of the innermost enclosing heading, for deep links
+    pub anchor: Option<String>,
+    /// 1-based line range `[start, end]` of the innermost enclosing section
+    pub section_lines: Option<(usize, usize)>,
+}
+
+/// A document that matched a search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    /// Path to the matching document
+    pub document: PathBuf,
+    /// Slug of the matching document
+    pub slug: String,
+    /// Namespace of the slug: `local` or the document's remote
+    pub namespace: String,
+    /// Matching lines, in body order
+    pub matches: Vec<LineMatch>,
+    /// Remote context the document comes from, if it isn't local
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// Lifecycle state of the document
+    #[serde(default)]
+    pub state: DocumentState,
+    /// Freshness-weighted rank, when results were ranked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// Search a document body for a case-insensitive substring.
+///
+/// Returns `None` if no line matches.
+pub fn search_document(doc: &Document, query: &str) -> Option<SearchHit> {
+    let hit = hit(doc, &[query.to_lowercase()]);
+    (!hit.matches.is_empty()).then_some(hit)
+}
+
+/// The words and phrases of a full-text query, lowercased, without FTS5
+/// operators: `"token refresh" OR auth*` yields `token refresh` and `auth`
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (i, part) in query.split('"').enumerate() {
+        if i % 2 == 1 {
+            // Inside quotes: a phrase
+            if !part.trim().is_empty() {
+                terms.push(part.trim().to_lowercase());
+            }
+            continue;
+        }
+        for word in part.split_whitespace() {
+            if matches!(word, "AND" | "OR" | "NOT" | "NEAR") {
+                continue;
+            }
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
+            if !word.is_empty() {
+                terms.push(word.to_lowercase());
+            }
+        }
+    }
+    terms
+}
+
+/// A hit on a document matched by a full-text query, with the lines
+/// mentioning any of its terms. A document can match on its slug or
+/// description alone, so the hit may have no lines.
+pub fn full_text_hit(doc: &Document, query: &str, score: f64) -> SearchHit {
+    let mut hit = hit(doc, &query_terms(query));
+    hit.score = Some(score);
+    hit
+}
+
+/// A hit listing the lines containing any of `needles` (lowercase)
+fn hit(doc: &Document, needles: &[String]) -> SearchHit {
+    let index = sections::index(&doc.body);
+    let matches: Vec<LineMatch> = doc
+        .body
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.to_lowercase();
+            needles.iter().any(|needle| line.contains(needle.as_str()))
+        })
+        .map(|(idx, line)| {
+            let section = sections::section_at(&index, idx + 1);
+            LineMatch {
+                line: idx + 1,
+                text: line.to_string(),
+                breadcrumb: section.map(|s| s.breadcrumb.clone()).unwrap_or_default(),
+                anchor: section.map(|s| s.anchor.clone()),
+                section_lines: section.map(|s| (s.start_line, s.end_line)),
+            }
+        })
+        .collect();
+
+    SearchHit {
+        document: doc.path.clone(),
+        slug: doc.slug.clone(),
+        namespace: doc.namespace().to_string(),
+        matches,
+        remote: doc.remote.clone(),
+        state: doc.state,
+        score: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(body: &str) -> Document {
+        Document::new(
+            PathBuf::from(".context/guides/auth.md"),
+            "auth".to_string(),
+            String::new(),
+            HashMap::new(),
+            String::new(),
+            String::new(),
+            body.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_search_is_case_insensitive() {
+        let hit = search_document(&doc("# Auth\n\nTokens are JWTs.\n"), "jwt").unwrap();
+        assert_eq!(hit.matches.len(), 1);
+        assert_eq!(hit.matches[0].line, 3);
+        assert_eq!(hit.matches[0].breadcrumb, vec!["Auth"]);
+        assert_eq!(hit.matches[0].section_lines, Some((1, 3)));
+        assert_eq!(hit.matches[0].anchor.as_deref(), Some("auth"));
+    }
+
+    #[test]
+    fn test_query_terms_strip_operators() {
+        assert_eq!(
+            query_terms("\"Token Refresh\" OR auth* NOT (jwt)"),
+            vec!["token refresh", "auth", "jwt"]
+        );
+        let hit = full_text_hit(
+            &doc("# Auth\n\nToken refresh runs hourly.\nSee JWT.\n"),
+            "\"token refresh\" jwt",
+            2.0,
+        );
+        assert_eq!(
+            hit.matches.iter().map(|m| m.line).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(hit.score, Some(2.0));
+    }
+
+    #[test]
+    fn test_search_without_match() {
+        assert!(search_document(&doc("# Auth\n"), "database").is_none());
+    }
+}
@@ -0,0 +1,175 @@
+//! Static search index for serving search without a backend
+//!
+//! The index is a JSON inverted index in the spirit of lunr or tinysearch:
+//! a list of documents and, for every term, the documents containing it
+//! with the term's weighted frequency. A static site (or the preview
+//! server) loads it once and ranks matches client-side, e.g. by summing
+//! `frequency * ln(1 + documents / postings)` over the query terms.
+//!
+//! ```json
+//! {"version": 1,
+//!  "documents": [{"slug": "auth", "namespace": "local", "path": "guides/auth.md",
+//!                 "title": "Login", "description": "How login works"}],
+//!  "terms": {"login": [[0, 4]]}}
+//! ```
+//!
+//! Terms from the slug, title, description and headings count several
+//! times, so a document about a term outranks one that mentions it.
+//! Encrypted documents contribute their metadata only, never their body.
+
+use crate::core::document::Document;
+use crate::core::{paths, sections};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the index format, bumped on incompatible changes
+pub const FORMAT_VERSION: u32 = 1;
+
+/// How many times a term in a title, heading or description counts
+const FIELD_BOOST: u32 = 3;
+
+/// A client-side search index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    /// Format version (see `FORMAT_VERSION`)
+    pub version: u32,
+    /// Indexed documents; postings refer to them by position
+    pub documents: Vec<IndexedDocument>,
+    /// Each term with its `(document, weighted frequency)` postings
+    pub terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+/// What a search result needs to show and link a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDocument {
+    /// Slug of the document
+    pub slug: String,
+    /// Namespace of the slug: `local` or the document's remote
+    pub namespace: String,
+    /// Path relative to the context directory, with `/` separators
+    pub path: String,
+    /// First heading of the body, or the slug if there is none
+    pub title: String,
+    /// Brief summary of the document
+    pub description: String,
+    /// Assets the document references, relative to the context directory,
+    /// for sites that copy them alongside the index
+    #[serde(default)]
+    pub assets: Vec<String>,
+}
+
+impl SearchIndex {
+    /// An empty index
+    pub fn new() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            ..Self::default()
+        }
+    }
+
+    /// Index a document whose path is under `root`, using `body` (which may
+    /// have been redacted) as its text
+    pub fn add(&mut self, root: &Path, doc: &Document, body: &str) {
+        let headings = sections::index(body);
+        let title = headings
+            .first()
+            .map_or_else(|| doc.slug.clone(), |s| s.title.clone());
+
+        let mut frequencies: BTreeMap<String, u32> = BTreeMap::new();
+        let boosted = [doc.slug.as_str(), title.as_str(), doc.description.as_str()]
+            .into_iter()
+            .chain(headings.iter().map(|s| s.title.as_str()));
+        for text in boosted {
+            for term in tokenize(text) {
+                *frequencies.entry(term).or_default() += FIELD_BOOST;
+            }
+        }
+        if !doc.encrypted {
+            for term in tokenize(body) {
+                *frequencies.entry(term).or_default() += 1;
+            }
+        }
+
+        let id = self.documents.len();
+        for (term, frequency) in frequencies {
+            self.terms.entry(term).or_default().push((id, frequency));
+        }
+        let path = doc.path.strip_prefix(root).unwrap_or(&doc.path);
+        let mut assets: Vec<String> = doc
+            .asset_references()
+            .filter_map(|r| paths::asset_path(r))
+            .map(ToString::to_string)
+            .collect();
+        assets.sort();
+        self.documents.push(IndexedDocument {
+            slug: doc.slug.clone(),
+            namespace: doc.namespace().to_string(),
+            path: path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            title,
+            description: doc.description.clone(),
+            assets,
+        });
+    }
+}
+
+/// Split text into lowercase alphanumeric terms of at least two characters.
+/// Clients must tokenize queries the same way.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2)
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    fn doc(path: &str, slug: &str, body: &str) -> Document {
+        Document::new(
+            PathBuf::from(path),
+            slug.to_string(),
+            "Token handling".to_string(),
+            HashMap::new(),
+            "2024-01-01".to_string(),
+            String::new(),
+            body.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_postings_weight_fields() {
+        let mut index = SearchIndex::new();
+        let auth = doc(
+            "/ctx/guides/auth.md",
+            "auth",
+            "# Login\n\nTokens are JWTs. Tokens expire.\n",
+        );
+        index.add(Path::new("/ctx"), &auth, &auth.body);
+
+        assert_eq!(index.documents[0].path, "guides/auth.md");
+        assert_eq!(index.documents[0].title, "Login");
+        // Description (boosted) plus two body mentions
+        assert_eq!(index.terms["tokens"], vec![(0, 2)]);
+        assert_eq!(index.terms["token"], vec![(0, 3)]);
+        // Title and heading (both boosted) plus the heading line itself
+        assert_eq!(index.terms["login"], vec![(0, 7)]);
+        assert!(!index.terms.contains_key("a"));
+    }
+
+    #[test]
+    fn test_encrypted_bodies_not_indexed() {
+        let mut index = SearchIndex::new();
+        let mut secret = doc("/ctx/runbook.md", "runbook", "rotate hunter2\n");
+        secret.encrypted = true;
+        index.add(Path::new("/ctx"), &secret, &secret.body);
+        assert!(index.terms.contains_key("runbook"));
+        assert!(!index.terms.contains_key("hunter2"));
+    }
+}
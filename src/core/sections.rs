@@ -0,0 +1,207 @@
+//! Heading-level structure of markdown document bodies
+//!
+//! A section containing the `<!-- context:scope -->` directive scopes the
+//! references it mentions: when they change, validation names the section
+//! as stale, so readers (and agents) know which part of the document to
+//! revisit instead of re-reading all of it.
+
+use serde::{Deserialize, Serialize};
+
+/// A heading-delimited section of a document body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Section {
+    /// Heading level (1 for `#`, 2 for `##`, ...)
+    pub level: usize,
+    /// Heading text without the leading `#` markers
+    pub title: String,
+    /// GitHub-style anchor for deep-linking to the heading
+    pub anchor: String,
+    /// Titles of enclosing headings followed by this heading's title
+    pub breadcrumb: Vec<String>,
+    /// 1-based line of the heading within the body
+    pub start_line: usize,
+    /// 1-based last line of the section (inclusive), before the next heading
+    /// of the same or a higher level
+    pub end_line: usize,
+}
+
+/// Parse an ATX heading line into (level, title)
+pub(crate) fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim().to_string()))
+}
+
+/// Index all sections of a body, ignoring `#` lines inside fenced code blocks
+pub fn index(body: &str) -> Vec<Section> {
+    let total_lines = body.lines().count();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut in_code_block = false;
+
+    for (idx, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        let Some((level, title)) = parse_heading(line) else {
+            continue;
+        };
+        let line_no = idx + 1;
+
+        // Close any open sections at the same or deeper level
+        while let Some(&open) = stack.last() {
+            if sections[open].level < level {
+                break;
+            }
+            sections[open].end_line = line_no - 1;
+            stack.pop();
+        }
+
+        let mut breadcrumb: Vec<String> =
+            stack.iter().map(|&i| sections[i].title.clone()).collect();
+        breadcrumb.push(title.clone());
+
+        stack.push(sections.len());
+        sections.push(Section {
+            level,
+            anchor: anchor(&title),
+            title,
+            breadcrumb,
+            start_line: line_no,
+            end_line: total_lines,
+        });
+    }
+
+    sections
+}
+
+/// Convert a heading title into a GitHub-style anchor (`Token Refresh` -> `token-refresh`)
+pub fn anchor(title: &str) -> String {
+    title
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Directive scoping a section's references to that section
+pub const SCOPE_DIRECTIVE: &str = "<!-- context:scope -->";
+
+/// Sections carrying the scope directive (the innermost section around
+/// each directive outside fenced code), in body order
+pub fn scoped<'a>(body: &str, sections: &'a [Section]) -> Vec<&'a Section> {
+    let mut scoped: Vec<&Section> = Vec::new();
+    let mut in_code_block = false;
+    for (idx, line) in body.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && trimmed == SCOPE_DIRECTIVE {
+            if let Some(section) = section_at(sections, idx + 1) {
+                if !scoped.iter().any(|s| s.start_line == section.start_line) {
+                    scoped.push(section);
+                }
+            }
+        }
+    }
+    scoped
+}
+
+/// Find the innermost section containing a 1-based body line
+pub fn section_at(sections: &[Section], line: usize) -> Option<&Section> {
+    sections
+        .iter()
+        .filter(|s| s.start_line <= line && line <= s.end_line)
+        .max_by_key(|s| s.level)
+}
+
+/// Find a section by heading, accepting `"## Title"` or just `"Title"` (case-insensitive)
+pub fn find<'a>(sections: &'a [Section], heading: &str) -> Option<&'a Section> {
+    let (level, title) = parse_heading(heading).unwrap_or((0, heading.trim().to_string()));
+    sections
+        .iter()
+        .find(|s| s.title.eq_ignore_ascii_case(&title) && (level == 0 || s.level == level))
+}
+
+/// Extract the text of a section from the body it was indexed from
+pub fn extract(body: &str, section: &Section) -> String {
+    let mut text: String = body
+        .lines()
+        .skip(section.start_line - 1)
+        .take(section.end_line + 1 - section.start_line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "# Auth\n\nIntro.\n\n## Tokens\n\nJWTs.\n\n### Token refresh\n\nHourly.\n\n## Sessions\n\n```\n# not a heading\n```\n";
+
+    #[test]
+    fn test_index_sections() {
+        let sections = index(BODY);
+        let titles: Vec<_> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Auth", "Tokens", "Token refresh", "Sessions"]);
+        assert_eq!(sections[1].start_line, 5);
+        assert_eq!(sections[1].end_line, 12);
+        assert_eq!(
+            sections[2].breadcrumb,
+            vec!["Auth", "Tokens", "Token refresh"]
+        );
+    }
+
+    #[test]
+    fn test_anchor() {
+        assert_eq!(anchor("Token refresh"), "token-refresh");
+        assert_eq!(anchor("What's `Cache::load`?"), "whats-cacheload");
+    }
+
+    #[test]
+    fn test_section_at_line() {
+        let sections = index(BODY);
+        assert_eq!(section_at(&sections, 11).unwrap().title, "Token refresh");
+        assert_eq!(section_at(&sections, 3).unwrap().title, "Auth");
+    }
+
+    #[test]
+    fn test_find_and_extract_section() {
+        let sections = index(BODY);
+        let section = find(&sections, "## Tokens").unwrap();
+        let text = extract(BODY, section);
+        assert!(text.starts_with("## Tokens"));
+        assert!(text.contains("Hourly."));
+        assert!(!text.contains("Sessions"));
+        assert!(find(&sections, "token REFRESH").is_some());
+        assert!(find(&sections, "# Tokens").is_none());
+    }
+
+    #[test]
+    fn test_scoped_sections() {
+        let body = "# Auth\n\n## Tokens\n\n<!-- context:scope -->\n\n### Token refresh\n\n<!-- context:scope -->\n<!-- context:scope -->\n\n## Sessions\n\n```\n<!-- context:scope -->\n```\n";
+        let sections = index(body);
+        let titles: Vec<_> = scoped(body, &sections)
+            .iter()
+            .map(|s| s.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Tokens", "Token refresh"]);
+    }
+}
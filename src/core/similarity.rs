@@ -0,0 +1,150 @@
+//! Near-duplicate detection between document bodies
+//!
+//! Guides copied from one another and then edited drift apart in ways no
+//! reference check notices. Each body is reduced to the set of its
+//! overlapping word 5-grams ("shingles"), and each set to a MinHash
+//! signature: the minimum of every shingle under 128 independent hash
+//! functions. The share of positions where two signatures agree estimates
+//! the Jaccard similarity of the shingle sets, so pairs can be compared in
+//! constant time however long the documents are.
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Words per shingle
+pub const SHINGLE_WORDS: usize = 5;
+
+/// Hash functions per signature; the estimate's error shrinks with its root
+pub const SIGNATURE_LEN: usize = 128;
+
+/// Estimated similarity from which `context doctor` reports a pair
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// MinHash signature of a body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature(Vec<u64>);
+
+impl Signature {
+    /// Signature of `text`, or `None` if it has fewer words than a shingle
+    pub fn of(text: &str) -> Option<Self> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if words.len() < SHINGLE_WORDS {
+            return None;
+        }
+
+        let mut mins = vec![u64::MAX; SIGNATURE_LEN];
+        for shingle in words.windows(SHINGLE_WORDS) {
+            let base = fnv1a(shingle);
+            for (seed, min) in (0u64..).zip(mins.iter_mut()) {
+                *min = (*min).min(mix(base ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15)));
+            }
+        }
+        Some(Self(mins))
+    }
+
+    /// Estimated Jaccard similarity of the two shingle sets, from 0 to 1
+    #[allow(clippy::cast_precision_loss)]
+    pub fn similarity(&self, other: &Self) -> f64 {
+        let agreeing = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        agreeing as f64 / SIGNATURE_LEN as f64
+    }
+}
+
+/// Two documents whose bodies overlap heavily
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarPair {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    /// Estimated share of shingles the bodies have in common
+    pub similarity: f64,
+}
+
+/// Every pair of `documents` (path and body) at least `threshold` similar,
+/// most similar first
+pub fn similar_pairs(documents: &[(PathBuf, &str)], threshold: f64) -> Vec<SimilarPair> {
+    let signed: Vec<(&PathBuf, Signature)> = documents
+        .iter()
+        .filter_map(|(path, body)| Some((path, Signature::of(body)?)))
+        .collect();
+
+    let mut pairs = Vec::new();
+    for (i, (a, first)) in signed.iter().enumerate() {
+        for (b, second) in &signed[i + 1..] {
+            let similarity = first.similarity(second);
+            if similarity >= threshold {
+                pairs.push(SimilarPair {
+                    a: (*a).clone(),
+                    b: (*b).clone(),
+                    similarity,
+                });
+            }
+        }
+    }
+    pairs.sort_by(|x, y| {
+        y.similarity
+            .total_cmp(&x.similarity)
+            .then_with(|| x.a.cmp(&y.a))
+    });
+    pairs
+}
+
+/// FNV-1a over the words of a shingle, separated so word boundaries count
+fn fnv1a(words: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in words.iter().flat_map(|w| w.bytes().chain([0])) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// SplitMix64 finalizer, turning one hash into many independent ones
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUIDE: &str = "Tokens are issued by the auth service and refreshed every hour. \
+        Clients send the refresh token to the token endpoint, which checks the signing key \
+        and returns a new access token. Expired refresh tokens are rejected with a 401.";
+
+    #[test]
+    fn test_copied_guides_are_similar() {
+        let copy = GUIDE.replace("every hour", "every two hours");
+        let unrelated = "Deployments run from the release branch through the staging cluster \
+            before a manual approval promotes them to production behind a feature flag.";
+        let original = Signature::of(GUIDE).unwrap();
+        assert!((original.similarity(&original) - 1.0).abs() < f64::EPSILON);
+        assert!(original.similarity(&Signature::of(&copy).unwrap()) > 0.6);
+        assert!(original.similarity(&Signature::of(unrelated).unwrap()) < 0.1);
+        assert_eq!(Signature::of("too short"), None);
+    }
+
+    #[test]
+    fn test_pairs_above_threshold() {
+        let documents = vec![
+            (PathBuf::from("a.md"), GUIDE),
+            (PathBuf::from("b.md"), GUIDE),
+            (
+                PathBuf::from("c.md"),
+                "An entirely different page about release trains and their schedule.",
+            ),
+        ];
+        let pairs = similar_pairs(&documents, DEFAULT_THRESHOLD);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(
+            (pairs[0].a.to_str(), pairs[0].b.to_str()),
+            (Some("a.md"), Some("b.md"))
+        );
+        assert!((pairs[0].similarity - 1.0).abs() < f64::EPSILON);
+    }
+}
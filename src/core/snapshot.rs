@@ -0,0 +1,215 @@
+//! Snapshots of the whole context directory
+//!
+//! A snapshot is a gzipped tarball of everything under `.context` (documents,
+//! configuration and any other metadata) kept in `.context/.snapshots`.
+//! Remote checkouts are left out since they can be fetched again. Take one
+//! before risky bulk operations so the tree can be put back exactly,
+//! independent of what has been committed to git. Archives are created and
+//! extracted with the system `tar`.
+
+use crate::core::clock;
+use crate::core::git;
+use crate::core::remote::REMOTE_DIR;
+use crate::error::{ContextError, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory within the context root holding snapshots
+pub const SNAPSHOT_DIR: &str = ".snapshots";
+
+const EXTENSION: &str = ".tar.gz";
+
+/// A stored snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snapshot {
+    /// Name used to restore the snapshot: a timestamp, the short HEAD commit
+    /// if the project is in git, and the optional label
+    pub name: String,
+    /// Path to the archive
+    pub path: PathBuf,
+    /// Size of the archive in bytes
+    pub bytes: u64,
+}
+
+/// Outcome of restoring a snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct Restored {
+    /// The snapshot that was restored
+    pub restored: Snapshot,
+    /// Snapshot of the tree as it was just before restoring
+    pub backup: Snapshot,
+}
+
+/// Capture the context directory at `root`, optionally labelled
+pub fn create(root: &Path, label: Option<&str>) -> Result<Snapshot> {
+    if let Some(label) = label {
+        if label.is_empty()
+            || !label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(ContextError::SnapshotError(format!(
+                "invalid label '{label}': use letters, digits, '-' and '_'"
+            )));
+        }
+    }
+
+    let mut name = clock::now().format("%Y%m%d-%H%M%S").to_string();
+    let commit = root
+        .parent()
+        .and_then(|project| git::run(project, &["rev-parse", "--short", "HEAD"]).ok());
+    for part in [commit.as_deref(), label].into_iter().flatten() {
+        name.push('-');
+        name.push_str(part);
+    }
+
+    let dir = root.join(SNAPSHOT_DIR);
+    std::fs::create_dir_all(&dir)?;
+    // Archives are a local safety net, not something to commit
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        std::fs::write(ignore, "*\n")?;
+    }
+    // Two snapshots within the same second get distinct names
+    let mut candidate = name.clone();
+    let mut n = 1;
+    while dir.join(format!("{candidate}{EXTENSION}")).exists() {
+        n += 1;
+        candidate = format!("{name}.{n}");
+    }
+
+    let path = dir.join(format!("{candidate}{EXTENSION}"));
+    tar(&[
+        "-czf".as_ref(),
+        path.as_os_str(),
+        format!("--exclude=./{SNAPSHOT_DIR}").as_ref(),
+        // Remote checkouts can be fetched again
+        format!("--exclude=./{REMOTE_DIR}").as_ref(),
+        "-C".as_ref(),
+        root.as_os_str(),
+        ".".as_ref(),
+    ])?;
+    snapshot(candidate, path)
+}
+
+/// Snapshots stored under `root`, oldest first
+pub fn list(root: &Path) -> Result<Vec<Snapshot>> {
+    let dir = root.join(SNAPSHOT_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(EXTENSION));
+        if let Some(name) = name {
+            snapshots.push(snapshot(name.to_string(), path.clone())?);
+        }
+    }
+    // Names start with a timestamp, so name order is creation order
+    snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(snapshots)
+}
+
+/// Replace the contents of `root` with the snapshot called `name`.
+///
+/// The current tree is snapshotted first (labelled `pre-restore`) so a
+/// restore can itself be undone. Other snapshots and remote checkouts are
+/// left untouched.
+pub fn restore(root: &Path, name: &str) -> Result<Restored> {
+    let restored = list(root)?
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| ContextError::SnapshotError(format!("no snapshot named '{name}'")))?;
+
+    // Check the archive is readable before touching anything
+    tar(&["-tzf".as_ref(), restored.path.as_os_str()])?;
+    let backup = create(root, Some("pre-restore"))?;
+
+    for entry in std::fs::read_dir(root)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|n| n == SNAPSHOT_DIR || n == REMOTE_DIR)
+        {
+            continue;
+        }
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    tar(&[
+        "-xzf".as_ref(),
+        restored.path.as_os_str(),
+        "-C".as_ref(),
+        root.as_os_str(),
+    ])?;
+
+    Ok(Restored { restored, backup })
+}
+
+fn snapshot(name: String, path: PathBuf) -> Result<Snapshot> {
+    let bytes = std::fs::metadata(&path)?.len();
+    Ok(Snapshot { name, path, bytes })
+}
+
+fn tar(args: &[&std::ffi::OsStr]) -> Result<()> {
+    let output = Command::new("tar")
+        .args(args)
+        .output()
+        .map_err(|e| ContextError::SnapshotError(format!("failed to run tar: {e}")))?;
+    if !output.status.success() {
+        return Err(ContextError::SnapshotError(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_list_restore() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join(".context");
+        std::fs::create_dir_all(root.join("guides")).unwrap();
+        std::fs::write(root.join("guides/a.md"), "original").unwrap();
+
+        let taken = create(&root, Some("before-rename")).unwrap();
+        assert!(taken.name.ends_with("-before-rename"));
+        assert!(taken.bytes > 0);
+        assert!(root.join(SNAPSHOT_DIR).join(".gitignore").exists());
+
+        std::fs::write(root.join("guides/a.md"), "changed").unwrap();
+        std::fs::write(root.join("guides/b.md"), "added").unwrap();
+
+        let restored = restore(&root, &taken.name).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("guides/a.md")).unwrap(),
+            "original"
+        );
+        assert!(!root.join("guides/b.md").exists());
+        assert!(restored.backup.name.ends_with("-pre-restore"));
+
+        let names: Vec<_> = list(&root).unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec![taken.name, restored.backup.name]);
+    }
+
+    #[test]
+    fn test_rejects_bad_label_and_unknown_name() {
+        let dir = TempDir::new().unwrap();
+        assert!(create(dir.path(), Some("../escape")).is_err());
+        assert!(restore(dir.path(), "missing").is_err());
+    }
+}
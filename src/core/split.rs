@@ -0,0 +1,171 @@
+//! Splitting oversized documents into one document per top-level section
+
+use crate::core::document::Document;
+use crate::core::paths::extract_paths;
+use crate::core::sections::{self, Section};
+use crate::error::{ContextError, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// The documents produced by splitting one document
+#[derive(Debug, Clone)]
+pub struct SplitPlan {
+    /// The original document, reduced to its preamble plus links to the pieces
+    pub remainder: Document,
+    /// One new document per split section
+    pub pieces: Vec<Document>,
+}
+
+/// Heading level to split at: `##` when the document has a single `#` title,
+/// otherwise `#`.
+fn split_level(index: &[Section]) -> usize {
+    let titles = index.iter().filter(|s| s.level == 1).count();
+    if titles == 1 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Keep only the stored references that are mentioned in `body`
+fn references_for(doc: &Document, body: &str) -> HashMap<String, String> {
+    extract_paths(body)
+        .into_iter()
+        .filter_map(|p| doc.references.get(&p).map(|h| (p, h.clone())))
+        .collect()
+}
+
+/// Plan a split of `doc` by its top-level sections.
+///
+/// Each section becomes a sibling document named `<slug>-<anchor>.md` that
+/// carries over the stored hashes of the references it mentions, so split
+/// documents keep their current staleness. The original keeps everything
+/// before the first section and gains links to each piece.
+pub fn plan(doc: &Document) -> Result<SplitPlan> {
+    if doc.is_locked() {
+        return Err(ContextError::EncryptionError(
+            "cannot split an encrypted document without the decryption key".to_string(),
+        ));
+    }
+
+    let index = sections::index(&doc.body);
+    let level = split_level(&index);
+    let parts: Vec<&Section> = index.iter().filter(|s| s.level == level).collect();
+    if parts.len() < 2 {
+        return Err(ContextError::InvalidDocument(format!(
+            "{} has fewer than two level-{level} sections to split",
+            doc.path.display()
+        )));
+    }
+
+    let dir = doc.path.parent().map(PathBuf::from).unwrap_or_default();
+    let parent_file = doc
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut pieces = Vec::new();
+    let mut links = String::new();
+    for section in parts.iter().copied() {
+        let slug = format!("{}-{}", doc.slug, section.anchor);
+        let file_name = format!("{slug}.md");
+
+        let content = sections::extract(&doc.body, section);
+        let content = content
+            .split_once('\n')
+            .map_or("", |(_, rest)| rest)
+            .trim_matches('\n');
+        let body = format!(
+            "# {}\n\n{content}\n\nPart of [{}]({parent_file}).\n",
+            section.title, doc.slug
+        );
+
+        let mut piece = Document::new(
+            dir.join(&file_name),
+            slug,
+            section.title.clone(),
+            references_for(doc, &body),
+            doc.updated.clone(),
+            String::new(),
+            body,
+        );
+        piece.tags.clone_from(&doc.tags);
+        piece.owners.clone_from(&doc.owners);
+        piece.encrypted = doc.encrypted;
+        pieces.push(piece);
+
+        let _ = writeln!(links, "- [{}]({file_name})", section.title);
+    }
+
+    let preamble: String = doc
+        .body
+        .lines()
+        .take(parts[0].start_line - 1)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let body = format!("{}\n\n## Sections\n\n{links}", preamble.trim_end());
+
+    let mut remainder = doc.clone();
+    remainder.references = references_for(doc, &body);
+    remainder.body = body;
+
+    Ok(SplitPlan { remainder, pieces })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> Document {
+        let mut references = HashMap::new();
+        references.insert("src/auth.rs".to_string(), "aaaaaaa".to_string());
+        references.insert("src/jwt.rs".to_string(), "bbbbbbb".to_string());
+        Document::new(
+            PathBuf::from(".context/guides/auth.md"),
+            "auth".to_string(),
+            "Auth".to_string(),
+            references,
+            "2025-01-01".to_string(),
+            String::new(),
+            "# Auth\n\nSee `src/auth.rs`.\n\n## Tokens\n\nUses `src/jwt.rs`.\n\n## Sessions\n\nCookies.\n"
+                .to_string(),
+        )
+    }
+
+    #[test]
+    fn test_plan_splits_by_second_level_under_single_title() {
+        let plan = plan(&doc()).unwrap();
+        let slugs: Vec<_> = plan.pieces.iter().map(|p| p.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["auth-tokens", "auth-sessions"]);
+        assert!(plan.pieces[0].path.ends_with("guides/auth-tokens.md"));
+    }
+
+    #[test]
+    fn test_plan_carries_over_references() {
+        let plan = plan(&doc()).unwrap();
+        assert_eq!(
+            plan.pieces[0].references.get("src/jwt.rs"),
+            Some(&"bbbbbbb".to_string())
+        );
+        assert!(plan.pieces[1].references.is_empty());
+        assert_eq!(plan.remainder.references.len(), 1);
+        assert!(plan.remainder.references.contains_key("src/auth.rs"));
+    }
+
+    #[test]
+    fn test_plan_cross_links() {
+        let plan = plan(&doc()).unwrap();
+        assert!(plan.remainder.body.contains("- [Tokens](auth-tokens.md)"));
+        assert!(plan.pieces[0].body.contains("Part of [auth](auth.md)"));
+        assert!(!plan.remainder.body.contains("Cookies"));
+    }
+
+    #[test]
+    fn test_plan_requires_two_sections() {
+        let mut d = doc();
+        d.body = "# Auth\n\n## Only\n\nOne section.\n".to_string();
+        assert!(plan(&d).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Full-text index of document bodies, kept in the SQLite store with FTS5
+//!
+//! The index is brought up to date before every query, and on demand by
+//! `context index`. Files whose size or modification time changed since
+//! they were indexed are read again, but only reindexed if their body hash
+//! differs, and deleted files are dropped, so edits made outside `context`
+//! are picked up without a rebuild. Updates run in one immediate
+//! transaction: concurrent readers keep seeing the previous index and
+//! concurrent writers wait their turn. Encrypted documents are never
+//! indexed, since the index would hold their plaintext; they are listed for
+//! callers to scan.
+
+use crate::core::document::{self, Document};
+use crate::core::markdown_files;
+use crate::error::Result;
+use rusqlite::{params, Connection, TransactionBehavior};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Version of the index tables; older ones are dropped and rebuilt, since
+/// everything in them can be derived from the documents again
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS bodies USING fts5(
+        path UNINDEXED, slug, description, body, tokenize = 'unicode61'
+    );
+    CREATE TABLE IF NOT EXISTS indexed (
+        path TEXT PRIMARY KEY,
+        modified INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        body_hash TEXT,
+        encrypted INTEGER NOT NULL
+    );
+";
+
+/// Column weights for BM25: matches in the slug count most, then the description
+const WEIGHTS: &str = "bm25(bodies, 0.0, 5.0, 3.0, 1.0)";
+
+/// Documents matching a full-text query
+#[derive(Debug, Clone, Default)]
+pub struct FullText {
+    /// Matching documents with their BM25 relevance (higher is better), best first
+    pub matches: Vec<(PathBuf, f64)>,
+    /// Local documents left out of the index, which callers should scan
+    pub unindexed: Vec<PathBuf>,
+}
+
+/// What an index update changed, as paths relative to the context root
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexUpdate {
+    /// Documents (re)indexed
+    pub indexed: Vec<PathBuf>,
+    /// Documents already up to date
+    pub unchanged: usize,
+    /// Documents dropped because their files are gone
+    pub removed: Vec<PathBuf>,
+}
+
+/// How the index compares with the documents on disk, as paths relative
+/// to the context root
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexVerification {
+    /// Documents checked
+    pub checked: usize,
+    /// Documents missing from the index
+    pub missing: Vec<PathBuf>,
+    /// Documents whose indexed body differs from the file
+    pub outdated: Vec<PathBuf>,
+    /// Indexed documents whose files are gone
+    pub orphaned: Vec<PathBuf>,
+    /// Problem reported by the FTS5 integrity check, if any
+    pub corruption: Option<String>,
+}
+
+impl IndexVerification {
+    /// Whether the index matches the documents exactly
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+            && self.outdated.is_empty()
+            && self.orphaned.is_empty()
+            && self.corruption.is_none()
+    }
+}
+
+/// Create the index tables, replacing those of an older schema
+pub(super) fn migrate(conn: &Connection) -> Result<()> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version < SCHEMA_VERSION {
+        conn.execute_batch("DROP TABLE IF EXISTS bodies; DROP TABLE IF EXISTS indexed;")?;
+    }
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    Ok(())
+}
+
+/// Reindex documents under `root` whose bodies changed since they were
+/// indexed, or every document with `rebuild`, calling `progress` with each
+/// document (re)indexed
+pub fn update(
+    conn: &mut Connection,
+    root: &Path,
+    rebuild: bool,
+    progress: &dyn Fn(&Path),
+) -> Result<IndexUpdate> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    if rebuild {
+        tx.execute_batch("DELETE FROM bodies; DELETE FROM indexed;")?;
+    }
+    let mut known: HashMap<String, ((i64, i64), Option<String>)> = tx
+        .prepare("SELECT path, modified, size, body_hash FROM indexed")?
+        .query_map([], |row| {
+            Ok((row.get(0)?, ((row.get(1)?, row.get(2)?), row.get(3)?)))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut update = IndexUpdate::default();
+    for path in markdown_files(root) {
+        let key = key(root, &path);
+        let stamp = stamp(&path)?;
+        let previous = known.remove(&key);
+        if previous.as_ref().is_some_and(|(s, _)| *s == stamp) {
+            update.unchanged += 1;
+            continue;
+        }
+
+        // A document that doesn't parse (e.g. mid-merge) is indexed once fixed
+        let doc = Document::load(&path).ok();
+        let body_hash = doc.as_ref().map(|d| document::body_hash(&d.body));
+        let encrypted = doc.as_ref().is_some_and(|d| d.encrypted);
+        let reindex = previous.is_none_or(|(_, hash)| hash != body_hash);
+        if reindex {
+            tx.execute("DELETE FROM bodies WHERE path = ?1", params![key])?;
+            if let Some(doc) = doc.filter(|d| !d.encrypted) {
+                tx.execute(
+                    "INSERT INTO bodies (path, slug, description, body) VALUES (?1, ?2, ?3, ?4)",
+                    params![key, doc.slug, doc.description, doc.body],
+                )?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO indexed (path, modified, size, body_hash, encrypted) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(path) DO UPDATE SET modified = excluded.modified, size = excluded.size,
+                body_hash = excluded.body_hash, encrypted = excluded.encrypted",
+            params![key, stamp.0, stamp.1, body_hash, encrypted],
+        )?;
+        if reindex {
+            progress(&path);
+            update.indexed.push(PathBuf::from(key));
+        } else {
+            update.unchanged += 1;
+        }
+    }
+
+    for key in known.into_keys() {
+        tx.execute("DELETE FROM bodies WHERE path = ?1", params![key])?;
+        tx.execute("DELETE FROM indexed WHERE path = ?1", params![key])?;
+        update.removed.push(PathBuf::from(key));
+    }
+    tx.commit()?;
+    update.removed.sort();
+    Ok(update)
+}
+
+/// Compare the index with every document under `root`, reading each body
+pub fn verify(conn: &Connection, root: &Path) -> Result<IndexVerification> {
+    let mut known: HashMap<String, Option<String>> = conn
+        .prepare("SELECT path, body_hash FROM indexed")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut verification = IndexVerification::default();
+    for path in markdown_files(root) {
+        let key = key(root, &path);
+        verification.checked += 1;
+        let body_hash = Document::load(&path)
+            .ok()
+            .map(|d| document::body_hash(&d.body));
+        match known.remove(&key) {
+            None => verification.missing.push(PathBuf::from(key)),
+            Some(indexed) if indexed != body_hash => verification.outdated.push(PathBuf::from(key)),
+            Some(_) => {}
+        }
+    }
+    verification.orphaned = known.into_keys().map(PathBuf::from).collect();
+    verification.orphaned.sort();
+
+    if let Err(e) = conn.execute("INSERT INTO bodies(bodies) VALUES('integrity-check')", []) {
+        verification.corruption = Some(e.to_string());
+    }
+    Ok(verification)
+}
+
+/// Run an FTS5 query (words, `"phrases"`, `prefix*`, `AND`/`OR`/`NOT`)
+pub fn query(conn: &Connection, root: &Path, query: &str) -> Result<FullText> {
+    let matches = conn
+        .prepare(&format!(
+            "SELECT path, {WEIGHTS} FROM bodies WHERE bodies MATCH ?1 ORDER BY {WEIGHTS}"
+        ))?
+        .query_map(params![query], |row| {
+            // BM25 is negative, more so for better matches
+            Ok((root.join(row.get::<_, String>(0)?), -row.get::<_, f64>(1)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let unindexed = conn
+        .prepare("SELECT path FROM indexed WHERE encrypted = 1 ORDER BY path")?
+        .query_map([], |row| Ok(root.join(row.get::<_, String>(0)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(FullText { matches, unindexed })
+}
+
+/// Index key of a document: its path relative to the context root
+pub(super) fn key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Modification time (nanoseconds) and size of a file, to detect changes
+fn stamp(path: &Path) -> Result<(i64, i64)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX));
+    Ok((modified, i64::try_from(metadata.len()).unwrap_or(i64::MAX)))
+}
@@ -0,0 +1,100 @@
+//! Persistence of reference hashes and sync metadata
+//!
+//! By default a document's references, body hash and content address live
+//! in its YAML frontmatter, so they travel with the file. The `sqlite`
+//! backend keeps them in `.context/.cache/context.db` instead, leaving
+//! frontmatter to the human-edited fields and giving the daemon and editor
+//! integrations one store to share. Documents without a row yet keep the
+//! metadata found in their frontmatter, which moves to the database the
+//! next time they are saved. Remote documents always use their frontmatter.
+//! The database also holds a full-text index of bodies (see `fts`) that
+//! `context search` queries instead of scanning files.
+
+pub mod fts;
+mod sqlite;
+
+pub use fts::{FullText, IndexUpdate, IndexVerification};
+pub use sqlite::SqliteStorage;
+
+use crate::core::config::{StorageBackend, StorageConfig};
+use crate::core::document::Document;
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A place to keep document metadata
+pub trait Storage: std::fmt::Debug + Send + Sync {
+    /// Name of the backend, as configured
+    fn name(&self) -> &'static str;
+
+    /// Whether metadata is kept outside document files, so documents loaded
+    /// with `external_metadata` unset still need saving to move it there
+    fn external(&self) -> bool {
+        false
+    }
+
+    /// Fill in metadata kept outside a freshly loaded document's file
+    fn load(&self, doc: &mut Document) -> Result<()>;
+
+    /// Write a document file along with its metadata
+    fn save(&self, doc: &Document) -> Result<()>;
+
+    /// Forget the metadata of a document file that was deleted
+    fn remove(&self, path: &Path) -> Result<()>;
+
+    /// Forget the metadata and index entries of document files that no
+    /// longer exist, returning their paths relative to the context root;
+    /// with `dry_run`, only list them
+    fn cleanup(&self, _dry_run: bool) -> Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+
+    /// Run a full-text query over local documents, or return `None` if the
+    /// backend keeps no full-text index and bodies must be scanned instead
+    fn search(&self, _query: &str) -> Result<Option<FullText>> {
+        Ok(None)
+    }
+
+    /// Bring the full-text index up to date, from scratch with `rebuild`,
+    /// calling `progress` with each document indexed. `None` if the backend
+    /// keeps no index.
+    fn reindex(&self, _rebuild: bool, _progress: &dyn Fn(&Path)) -> Result<Option<IndexUpdate>> {
+        Ok(None)
+    }
+
+    /// Compare the full-text index with the documents on disk. `None` if the
+    /// backend keeps no index.
+    fn verify_index(&self) -> Result<Option<IndexVerification>> {
+        Ok(None)
+    }
+}
+
+/// Metadata kept in each document's frontmatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrontmatterStorage;
+
+impl Storage for FrontmatterStorage {
+    fn name(&self) -> &'static str {
+        "frontmatter"
+    }
+
+    fn load(&self, _doc: &mut Document) -> Result<()> {
+        Ok(())
+    }
+
+    fn save(&self, doc: &Document) -> Result<()> {
+        doc.save()
+    }
+
+    fn remove(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Open the configured backend for the context directory at `root`
+pub fn open(root: &Path, config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    Ok(match config.backend {
+        StorageBackend::Frontmatter => Arc::new(FrontmatterStorage),
+        StorageBackend::Sqlite => Arc::new(SqliteStorage::open(root)?),
+    })
+}
@@ -0,0 +1,229 @@
+//! SQLite storage backend
+
+use super::fts::{self, FullText, IndexUpdate, IndexVerification};
+use super::Storage;
+use crate::core::document::Document;
+use crate::error::{ContextError, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// Database path within the context root
+pub const DATABASE: &str = ".cache/context.db";
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS documents (
+        path TEXT PRIMARY KEY,
+        hash TEXT NOT NULL,
+        body_hash TEXT
+    );
+    CREATE TABLE IF NOT EXISTS refs (
+        document TEXT NOT NULL REFERENCES documents(path) ON DELETE CASCADE,
+        path TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        PRIMARY KEY (document, path)
+    );
+    CREATE INDEX IF NOT EXISTS refs_by_path ON refs(path);
+";
+
+/// Metadata kept in a SQLite database, keyed by document path relative to
+/// the context root
+#[derive(Debug)]
+pub struct SqliteStorage {
+    root: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open (creating if needed) the database of the context directory at `root`
+    pub fn open(root: &Path) -> Result<Self> {
+        let path = root.join(DATABASE);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&path)?;
+        // Other processes (the daemon, editors) may be writing at the same time
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        fts::migrate(&conn)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn key(&self, path: &Path) -> String {
+        fts::key(&self.root, path)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn external(&self) -> bool {
+        true
+    }
+
+    fn load(&self, doc: &mut Document) -> Result<()> {
+        let key = self.key(&doc.path);
+        let conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let row = conn
+            .query_row(
+                "SELECT hash, body_hash FROM documents WHERE path = ?1",
+                params![key],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+            )
+            .optional()?;
+        // Not stored yet: keep what the frontmatter had until the next save
+        let Some((hash, body_hash)) = row else {
+            return Ok(());
+        };
+        doc.external_metadata = true;
+
+        let mut statement =
+            conn.prepare_cached("SELECT path, hash FROM refs WHERE document = ?1")?;
+        doc.references = statement
+            .query_map(params![key], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        doc.hash = hash;
+        doc.body_hash = body_hash;
+        Ok(())
+    }
+
+    fn save(&self, doc: &Document) -> Result<()> {
+        // Metadata first, so a failure never leaves it in neither place
+        let key = self.key(&doc.path);
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO documents (path, hash, body_hash) VALUES (?1, ?2, ?3)
+             ON CONFLICT(path) DO UPDATE SET hash = excluded.hash, body_hash = excluded.body_hash",
+            params![key, doc.hash, doc.body_hash],
+        )?;
+        tx.execute("DELETE FROM refs WHERE document = ?1", params![key])?;
+        for (path, hash) in &doc.references {
+            tx.execute(
+                "INSERT INTO refs (document, path, hash) VALUES (?1, ?2, ?3)",
+                params![key, path, hash],
+            )?;
+        }
+        tx.commit()?;
+        drop(conn);
+
+        if doc.external_metadata {
+            doc.save()
+        } else {
+            // A document created since loading, e.g. by split or merge
+            let mut doc = doc.clone();
+            doc.external_metadata = true;
+            doc.save()
+        }
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        conn.execute(
+            "DELETE FROM documents WHERE path = ?1",
+            params![self.key(path)],
+        )?;
+        Ok(())
+    }
+
+    fn cleanup(&self, dry_run: bool) -> Result<Vec<PathBuf>> {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        let tx = conn.transaction()?;
+        let known: Vec<String> = tx
+            .prepare("SELECT path FROM documents UNION SELECT path FROM indexed")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut removed = Vec::new();
+        for key in known {
+            if self.root.join(&key).is_file() {
+                continue;
+            }
+            if !dry_run {
+                // Refs go with their document (ON DELETE CASCADE)
+                tx.execute("DELETE FROM documents WHERE path = ?1", params![key])?;
+                tx.execute("DELETE FROM bodies WHERE path = ?1", params![key])?;
+                tx.execute("DELETE FROM indexed WHERE path = ?1", params![key])?;
+            }
+            removed.push(PathBuf::from(key));
+        }
+        tx.commit()?;
+        removed.sort();
+        Ok(removed)
+    }
+
+    fn search(&self, query: &str) -> Result<Option<FullText>> {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        fts::update(&mut conn, &self.root, false, &|_| {})?;
+        fts::query(&conn, &self.root, query).map(Some)
+    }
+
+    fn reindex(&self, rebuild: bool, progress: &dyn Fn(&Path)) -> Result<Option<IndexUpdate>> {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        fts::update(&mut conn, &self.root, rebuild, progress).map(Some)
+    }
+
+    fn verify_index(&self) -> Result<Option<IndexVerification>> {
+        let conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        fts::verify(&conn, &self.root).map(Some)
+    }
+}
+
+impl From<rusqlite::Error> for ContextError {
+    fn from(e: rusqlite::Error) -> Self {
+        ContextError::StorageError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip_keeps_metadata_out_of_frontmatter() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join(".context");
+        std::fs::create_dir_all(&root).unwrap();
+        let path = root.join("a.md");
+        let references = HashMap::from([("src/a.rs".to_string(), "abc1234".to_string())]);
+        let doc = Document::new(
+            path.clone(),
+            "a".to_string(),
+            String::new(),
+            references.clone(),
+            "2025-01-01".to_string(),
+            "def5678".to_string(),
+            "# A\n".to_string(),
+        );
+
+        let storage = SqliteStorage::open(&root).unwrap();
+        storage.save(&doc).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("references"));
+        assert!(!content.contains("def5678"));
+        assert!(content.contains("updated: 2025-01-01"));
+
+        let mut loaded = Document::load(&path).unwrap();
+        assert!(loaded.references.is_empty());
+        storage.load(&mut loaded).unwrap();
+        assert_eq!(loaded.references, references);
+        assert_eq!(loaded.hash, "def5678");
+
+        storage.remove(&path).unwrap();
+        let mut forgotten = Document::load(&path).unwrap();
+        storage.load(&mut forgotten).unwrap();
+        assert!(forgotten.references.is_empty());
+    }
+}
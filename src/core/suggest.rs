@@ -0,0 +1,394 @@
+//! Reference suggestions for documents
+//!
+//! Authors often mention code by name (`TokenStore`, `auth::refresh`) without
+//! referencing the files it lives in, so the document never goes stale when
+//! that code changes. Suggestions come from three signals, strongest first:
+//!
+//! - a source file defines a mentioned type or function
+//!   (`struct TokenStore`, `def refresh`, ...)
+//! - a mentioned module path matches a file path (`auth::refresh` ->
+//!   `src/auth/refresh.rs`)
+//! - a file is named after a mentioned identifier or a heading word
+//!   (`TokenStore` -> `token_store.rs`)
+//!
+//! Candidate files are those tracked by git, or every non-hidden file when
+//! the project isn't a git repository.
+
+use crate::core::git;
+use crate::core::sections;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use walkdir::WalkDir;
+
+/// Heading of the section `--apply` adds suggested references to
+pub const RELATED_FILES: &str = "Related files";
+
+/// Source files larger than this are not scanned for definitions
+const MAX_SCAN_BYTES: u64 = 256 * 1024;
+
+/// Extensions of files scanned for definitions
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "go", "ts", "tsx", "js", "jsx", "java", "kt", "swift", "rb", "c", "h", "cc", "cpp",
+    "hpp", "cs", "scala", "php", "ex", "exs",
+];
+
+/// Directories never suggested from, when walking outside git
+pub(crate) const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "vendor", "dist", "build"];
+
+static CODE_SPAN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`([^`\n]+)`").expect("valid regex"));
+static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]{2,}$").expect("valid regex"));
+static MODULE_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*(::[A-Za-z_][A-Za-z0-9_]*)+$").expect("valid regex")
+});
+
+/// A file the document probably should reference
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    /// Path relative to the project root
+    pub path: String,
+    /// Why the file was suggested
+    pub reasons: Vec<String>,
+    /// Strength of the evidence; higher is stronger
+    pub score: u32,
+}
+
+/// Suggestions for one document
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestions {
+    /// Path to the document file
+    pub document: PathBuf,
+    /// Suggested references, strongest first
+    pub suggestions: Vec<Suggestion>,
+    /// Whether the suggestions were added to the document
+    pub applied: bool,
+}
+
+/// What a body mentions that could point at files
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Mentions {
+    /// Identifiers in code spans, e.g. `TokenStore`
+    pub identifiers: BTreeSet<String>,
+    /// Module paths in code spans, e.g. `auth::refresh`
+    pub modules: BTreeSet<String>,
+    /// Lowercase words of four or more letters from headings
+    pub heading_words: BTreeSet<String>,
+}
+
+/// Collect mentions from a body, ignoring fenced code blocks
+pub fn mentions(body: &str) -> Mentions {
+    let mut mentions = Mentions::default();
+    let mut in_code_block = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some((_, title)) = sections::parse_heading(line) {
+            mentions.heading_words.extend(
+                title
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|w| w.len() >= 4)
+                    .map(str::to_lowercase),
+            );
+        }
+        for span in CODE_SPAN.captures_iter(line) {
+            let text = span[1].trim().trim_end_matches("()");
+            if MODULE_PATH.is_match(text) {
+                mentions.modules.insert(text.to_string());
+            } else if IDENTIFIER.is_match(text) {
+                mentions.identifiers.insert(text.to_string());
+            }
+        }
+    }
+    mentions
+}
+
+/// Files in the project that could be referenced, relative to its root:
+/// tracked files and untracked ones `.gitignore` doesn't exclude
+pub fn candidate_files(project_root: &Path) -> Vec<String> {
+    if let Ok(listing) = git::run(
+        project_root,
+        &["ls-files", "--cached", "--others", "--exclude-standard"],
+    ) {
+        return listing.lines().map(str::to_string).collect();
+    }
+    WalkDir::new(project_root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0
+                || !(name.starts_with('.')
+                    || (e.file_type().is_dir() && SKIPPED_DIRS.contains(&&*name)))
+        })
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(project_root).ok()?;
+            Some(rel.to_string_lossy().replace('\\', "/"))
+        })
+        .collect()
+}
+
+/// Rank `files` by how strongly `mentions` point at them, skipping files
+/// already in `existing` and the context directory itself
+pub fn suggest(
+    project_root: &Path,
+    mentions: &Mentions,
+    files: &[String],
+    existing: &BTreeSet<String>,
+) -> Vec<Suggestion> {
+    let definition = definition_pattern(&mentions.identifiers);
+    let modules: Vec<(&str, Vec<String>)> = mentions
+        .modules
+        .iter()
+        .map(|module| (module.as_str(), module_paths(module)))
+        .collect();
+
+    let mut suggestions = Vec::new();
+    for file in files {
+        if existing.contains(file) || file.starts_with(".context/") {
+            continue;
+        }
+        let path = Path::new(file);
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let without_ext = file.rsplit_once('.').map_or(file.as_str(), |(p, _)| p);
+        let mut suggestion = Suggestion {
+            path: file.clone(),
+            reasons: Vec::new(),
+            score: 0,
+        };
+        let mut hit = |score: u32, reason: String| {
+            suggestion.score += score;
+            suggestion.reasons.push(reason);
+        };
+
+        if let Some(definition) = &definition {
+            let scannable = path
+                .extension()
+                .is_some_and(|e| SOURCE_EXTENSIONS.iter().any(|s| e == *s))
+                && std::fs::metadata(project_root.join(file))
+                    .is_ok_and(|m| m.len() <= MAX_SCAN_BYTES);
+            if let Some(content) = scannable
+                .then(|| std::fs::read_to_string(project_root.join(file)).ok())
+                .flatten()
+            {
+                let defined: BTreeSet<&str> = definition
+                    .captures_iter(&content)
+                    .filter_map(|c| c.get(1))
+                    .map(|m| m.as_str())
+                    .collect();
+                for name in defined {
+                    hit(3, format!("defines `{name}`"));
+                }
+            }
+        }
+
+        for (module, paths) in &modules {
+            let matches = paths.iter().any(|p| {
+                [
+                    p.clone(),
+                    format!("{p}/mod"),
+                    format!("{p}/__init__"),
+                    format!("{p}/index"),
+                ]
+                .iter()
+                .any(|candidate| {
+                    without_ext == candidate || without_ext.ends_with(&format!("/{candidate}"))
+                })
+            });
+            if matches {
+                hit(3, format!("matches module `{module}`"));
+            }
+        }
+
+        for identifier in &mentions.identifiers {
+            if stem == snake_case(identifier) || stem == identifier.to_lowercase() {
+                hit(2, format!("named after `{identifier}`"));
+            }
+        }
+
+        if mentions.heading_words.contains(&stem) {
+            hit(1, format!("named after heading word \"{stem}\""));
+        }
+
+        if suggestion.score > 0 {
+            suggestions.push(suggestion);
+        }
+    }
+
+    // Stable, so ties stay in path order
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.score));
+    suggestions
+}
+
+/// Add `paths` as a backtick list under the body's "Related files" section,
+/// creating the section at the end if there isn't one
+pub fn append_references(body: &str, paths: &[String]) -> String {
+    let items = paths.iter().fold(String::new(), |mut items, p| {
+        let _ = writeln!(items, "- `{p}`");
+        items
+    });
+    let index = sections::index(body);
+    let Some(section) = index
+        .iter()
+        .find(|s| s.title.eq_ignore_ascii_case(RELATED_FILES))
+    else {
+        return format!("{}\n\n## {RELATED_FILES}\n\n{items}", body.trim_end());
+    };
+
+    let lines: Vec<&str> = body.lines().collect();
+    let mut end = section.end_line;
+    while end > section.start_line && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    let mut out = lines[..end].join("\n");
+    out.push('\n');
+    out.push_str(&items);
+    if end < lines.len() {
+        out.push_str(&lines[end..].join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Match definitions of any of `identifiers` in common languages, capturing the name
+fn definition_pattern(identifiers: &BTreeSet<String>) -> Option<Regex> {
+    if identifiers.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = identifiers.iter().map(|i| regex::escape(i)).collect();
+    let pattern = format!(
+        r"\b(?:struct|enum|trait|union|type|fn|class|interface|def|func|function|module|object)\s+({})\b",
+        names.join("|")
+    );
+    Regex::new(&pattern).ok()
+}
+
+/// File paths (without extension) a module path could live at: the whole
+/// path, then without its last segment in case that names an item
+fn module_paths(module: &str) -> Vec<String> {
+    let segments: Vec<&str> = module
+        .split("::")
+        .skip_while(|s| matches!(*s, "crate" | "self" | "super"))
+        .collect();
+    (1..=segments.len())
+        .rev()
+        .take(2)
+        .filter(|&n| n >= 2 || segments.len() == 1)
+        .map(|n| segments[..n].join("/"))
+        .collect()
+}
+
+/// `TokenStore` -> `token_store`
+fn snake_case(identifier: &str) -> String {
+    let mut out = String::new();
+    let mut previous_lower = false;
+    for c in identifier.chars() {
+        if c.is_uppercase() && previous_lower {
+            out.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mentions() {
+        let body = "# Token Refresh\n\nThe `TokenStore` in `auth::refresh` calls `rotate()`.\nSee `src/lib.rs`.\n\n```\n`Ignored`\n```\n";
+        let mentions = mentions(body);
+        assert_eq!(
+            mentions.identifiers,
+            BTreeSet::from(["TokenStore".to_string(), "rotate".to_string()])
+        );
+        assert_eq!(
+            mentions.modules,
+            BTreeSet::from(["auth::refresh".to_string()])
+        );
+        assert_eq!(
+            mentions.heading_words,
+            BTreeSet::from(["token".to_string(), "refresh".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_suggest_ranks_by_evidence() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+        std::fs::write(
+            dir.path().join("src/auth/refresh.rs"),
+            "pub struct TokenStore;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/auth/token_store.rs"),
+            "// re-exports\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "mod auth;\n").unwrap();
+        let files = candidate_files(dir.path());
+        assert_eq!(
+            files,
+            vec![
+                "src/auth/refresh.rs",
+                "src/auth/token_store.rs",
+                "src/lib.rs"
+            ]
+        );
+
+        let mentions = mentions("# Refresh\n\nThe `TokenStore` lives in `crate::auth::refresh`.\n");
+        let suggestions = suggest(dir.path(), &mentions, &files, &BTreeSet::new());
+        let ranked: Vec<_> = suggestions
+            .iter()
+            .map(|s| (s.path.as_str(), s.score))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![("src/auth/refresh.rs", 7), ("src/auth/token_store.rs", 2)]
+        );
+
+        let existing = BTreeSet::from(["src/auth/refresh.rs".to_string()]);
+        assert_eq!(suggest(dir.path(), &mentions, &files, &existing).len(), 1);
+    }
+
+    #[test]
+    fn test_append_references() {
+        let paths = vec!["src/a.rs".to_string()];
+        assert_eq!(
+            append_references("# Auth\n\nText.\n", &paths),
+            "# Auth\n\nText.\n\n## Related files\n\n- `src/a.rs`\n"
+        );
+        assert_eq!(
+            append_references("## Related files\n\n- `src/b.rs`\n\n## Next\n", &paths),
+            "## Related files\n\n- `src/b.rs`\n- `src/a.rs`\n\n## Next\n"
+        );
+    }
+
+    #[test]
+    fn test_module_paths_and_snake_case() {
+        assert_eq!(
+            module_paths("crate::core::cache::Cache"),
+            vec!["core/cache/Cache", "core/cache"]
+        );
+        assert_eq!(snake_case("TokenStore"), "token_store");
+        assert_eq!(snake_case("HTTPClient"), "httpclient");
+    }
+}
@@ -0,0 +1,282 @@
+//! Templates: whole context trees to bootstrap a directory from, and
+//! document templates under `.context/.templates`
+//!
+//! A document names the template it was created from with `template: <name>`
+//! in its frontmatter, and the version of the template it started from with
+//! `template_version: <hash>` (the hash of the template file). The
+//! template's own frontmatter may declare `required_sections` (e.g.
+//! `"## Overview"`, or just `"Gotchas"` for any level), which `context lint`
+//! checks every such document still has, with some content under each.
+//! `context templates diff` compares a document's headings with its
+//! template's current ones, and `context templates status` lists documents
+//! created from older template versions. Level-one headings are titles, so
+//! they are left out of the comparison.
+
+use crate::core::document::{hash, Document};
+use crate::core::frontmatter::extract_frontmatter;
+use crate::core::sections;
+use crate::core::CONTEXT_DIR_NAME;
+use crate::error::{ContextError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Directory within the context root holding document templates
+pub const TEMPLATE_DIR: &str = ".templates";
+
+/// A document template and what it requires of documents created from it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct DocumentTemplate {
+    /// Headings documents must keep, as `"## Title"` or `"Title"`
+    #[serde(default)]
+    pub required_sections: Vec<String>,
+    /// Hash of the template file, recorded as `template_version`
+    #[serde(skip)]
+    pub version: String,
+    /// Body of the template
+    #[serde(skip)]
+    pub body: String,
+}
+
+/// How a document's structure differs from its template's current version
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateDrift {
+    /// Path to the document file
+    pub document: PathBuf,
+    /// Name of the template
+    pub template: String,
+    /// Template version the document was created from, if recorded
+    pub version: Option<String>,
+    /// Current version of the template
+    pub current: String,
+    /// Template headings the document lacks, as `"## Title"`
+    pub missing: Vec<String>,
+    /// Document headings the template lacks
+    pub extra: Vec<String>,
+}
+
+impl TemplateDrift {
+    /// Whether the document was created from an older (or unrecorded) version
+    pub fn outdated(&self) -> bool {
+        self.version.as_deref() != Some(self.current.as_str())
+    }
+}
+
+/// Compare the headings of `doc` with those of its template
+pub fn drift(doc: &Document, name: &str, template: &DocumentTemplate) -> TemplateDrift {
+    let outline = |body: &str| -> Vec<String> {
+        sections::index(body)
+            .into_iter()
+            .filter(|s| s.level > 1)
+            .map(|s| format!("{} {}", "#".repeat(s.level), s.title))
+            .collect()
+    };
+    let (expected, actual) = (outline(&template.body), outline(&doc.body));
+    let lacks = |outline: &[String], heading: &String| {
+        !outline.iter().any(|h| h.eq_ignore_ascii_case(heading))
+    };
+
+    TemplateDrift {
+        document: doc.path.clone(),
+        template: name.to_string(),
+        version: doc.template_version.clone(),
+        current: template.version.clone(),
+        missing: expected
+            .iter()
+            .filter(|h| lacks(&actual, h))
+            .cloned()
+            .collect(),
+        extra: actual
+            .iter()
+            .filter(|h| lacks(&expected, h))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Load the document template called `name`, `None` if there is none
+pub fn document_template(root: &Path, name: &str) -> Result<Option<DocumentTemplate>> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(ContextError::TemplateError(format!(
+            "invalid template name '{name}'"
+        )));
+    }
+    let path = root.join(TEMPLATE_DIR).join(format!("{name}.md"));
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let mut template = match extract_frontmatter(&content) {
+        Some((frontmatter, body)) => DocumentTemplate {
+            body,
+            ..serde_yaml::from_str::<Option<DocumentTemplate>>(&frontmatter)
+                .map_err(|e| ContextError::TemplateError(format!("{}: {e}", path.display())))?
+                .unwrap_or_default()
+        },
+        None => DocumentTemplate {
+            body: content.clone(),
+            ..DocumentTemplate::default()
+        },
+    };
+    template.version = hash(content.as_bytes());
+    Ok(Some(template))
+}
+
+/// Where a context template is loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// A directory on the local filesystem
+    Local(PathBuf),
+    /// A git repository URL, cloned shallowly before copying
+    Git(String),
+}
+
+impl TemplateSource {
+    /// Interpret a `--from` argument as either a git URL or a local path
+    pub fn parse(source: &str) -> Self {
+        let is_git = source.contains("://")
+            || source.starts_with("git@")
+            || Path::new(source)
+                .extension()
+                .is_some_and(|ext| ext == "git");
+        if is_git && !Path::new(source).is_dir() {
+            Self::Git(source.to_string())
+        } else {
+            Self::Local(PathBuf::from(source))
+        }
+    }
+}
+
+impl std::fmt::Display for TemplateSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::Git(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+/// Copy the contents of a template into the given context directory.
+///
+/// If the template contains a `.context/` directory, only that directory is
+/// copied; otherwise the template root itself is treated as the context tree.
+/// Any `.git` directory in the template is skipped.
+pub fn apply(source: &TemplateSource, root: &Path) -> Result<()> {
+    match source {
+        TemplateSource::Local(path) => copy_template(path, root),
+        TemplateSource::Git(url) => {
+            let checkout = clone(url)?;
+            let result = copy_template(&checkout, root);
+            let _ = std::fs::remove_dir_all(&checkout);
+            result
+        }
+    }
+}
+
+/// Shallow-clone a git repository into a temporary directory
+fn clone(url: &str) -> Result<PathBuf> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let dest =
+        std::env::temp_dir().join(format!("context-template-{}-{nanos}", std::process::id()));
+
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", "--quiet", url])
+        .arg(&dest)
+        .output()
+        .map_err(|e| ContextError::TemplateError(format!("failed to run git: {e}")))?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_dir_all(&dest);
+        return Err(ContextError::TemplateError(format!(
+            "failed to clone {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(dest)
+}
+
+/// Recursively copy a template directory into the context root
+fn copy_template(template: &Path, root: &Path) -> Result<()> {
+    if !template.is_dir() {
+        return Err(ContextError::TemplateError(format!(
+            "template is not a directory: {}",
+            template.display()
+        )));
+    }
+
+    let nested = template.join(CONTEXT_DIR_NAME);
+    let source = if nested.is_dir() {
+        nested
+    } else {
+        template.to_path_buf()
+    };
+
+    std::fs::create_dir_all(root)?;
+
+    for entry in WalkDir::new(&source)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry.map_err(|e| ContextError::TemplateError(e.to_string()))?;
+        let relative = entry
+            .path()
+            .strip_prefix(&source)
+            .map_err(|e| ContextError::TemplateError(e.to_string()))?;
+        let dest = root.join(relative);
+
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_sources() {
+        assert_eq!(
+            TemplateSource::parse("https://github.com/org/docs.git"),
+            TemplateSource::Git("https://github.com/org/docs.git".to_string())
+        );
+        assert_eq!(
+            TemplateSource::parse("git@github.com:org/docs.git"),
+            TemplateSource::Git("git@github.com:org/docs.git".to_string())
+        );
+    }
+
+    #[test]
+    fn test_document_template_required_sections() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(TEMPLATE_DIR)).unwrap();
+        std::fs::write(
+            dir.path().join(TEMPLATE_DIR).join("adr.md"),
+            "---\nslug: adr\nrequired_sections:\n- \"## Context\"\n- Decision\n---\n\n# ADR\n",
+        )
+        .unwrap();
+
+        let template = document_template(dir.path(), "adr").unwrap().unwrap();
+        assert_eq!(template.required_sections, vec!["## Context", "Decision"]);
+        assert_eq!(document_template(dir.path(), "missing").unwrap(), None);
+        assert!(document_template(dir.path(), "../adr").is_err());
+    }
+
+    #[test]
+    fn test_parse_local_source() {
+        assert_eq!(
+            TemplateSource::parse("../templates/rust"),
+            TemplateSource::Local(PathBuf::from("../templates/rust"))
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Summary metrics over time
+//!
+//! After each full sync, the counts per status and reference coverage are
+//! appended as a JSON line to the trend journal, `.context/.journal/trend.jsonl`
+//! (rotated like the usage journal). `context stats --trend` charts the
+//! latest points so improvements and regressions over weeks are visible. Commit the journal to share the
+//! history between machines and CI.
+
+use crate::core::config::TrendConfig;
+use crate::core::journal::Journal;
+use crate::core::models::Summary;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Trend journal path within the context root
+pub const TREND_JOURNAL: &str = ".journal/trend.jsonl";
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Summary metrics of one run, written as one JSON line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    /// RFC 3339 timestamp of the run
+    pub timestamp: String,
+    /// Total number of documents
+    pub documents: usize,
+    /// Documents whose references all match
+    pub valid: usize,
+    /// Documents with changed references
+    pub stale: usize,
+    /// Documents with missing references
+    pub orphaned: usize,
+    /// Share of project files (outside `.context`) referenced by a document
+    pub coverage: f64,
+}
+
+impl Point {
+    /// A point for `summary`, with `referenced` of `files` project files covered
+    pub fn new(timestamp: String, summary: &Summary, referenced: usize, files: usize) -> Self {
+        let coverage = match (u32::try_from(referenced), u32::try_from(files)) {
+            (Ok(referenced), Ok(files)) if files > 0 => f64::from(referenced) / f64::from(files),
+            _ => 0.0,
+        };
+        Self {
+            timestamp,
+            documents: summary.documents,
+            valid: summary.valid,
+            stale: summary.stale,
+            orphaned: summary.orphaned,
+            coverage: (coverage * 1000.0).round() / 1000.0,
+        }
+    }
+}
+
+/// The trend journal of the context directory at `root`
+pub fn journal(root: &Path, config: &TrendConfig) -> Journal {
+    Journal::new(root.join(TREND_JOURNAL), config.max_bytes, config.keep)
+}
+
+/// The last `limit` points still in the journal, oldest first. Lines that
+/// can't be parsed are skipped.
+pub fn points(journal: &Journal, limit: usize) -> Result<Vec<Point>> {
+    let points: Vec<Point> = journal
+        .lines()?
+        .iter()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = points.len().saturating_sub(limit);
+    Ok(points.into_iter().skip(skip).collect())
+}
+
+/// One bar per value, scaled between the smallest and largest
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|&value| {
+            if (max - min).abs() < f64::EPSILON {
+                return BARS[BARS.len() / 2];
+            }
+            let step = (value - min) / (max - min) * 7.0;
+            // In 0..=7 by construction
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            BARS[step.round() as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_scales_between_extremes() {
+        assert_eq!(sparkline(&[0.0, 5.0, 10.0]), "▁▅█");
+        assert_eq!(sparkline(&[3.0, 3.0]), "▅▅");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_points_keeps_latest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = Journal::new(dir.path().join("trend.jsonl"), 200, 3);
+        for valid in 0..5 {
+            let summary = Summary {
+                documents: 5,
+                valid,
+                ..Summary::default()
+            };
+            let point = Point::new(format!("2025-01-0{}T00:00:00Z", valid + 1), &summary, 1, 4);
+            journal
+                .append(&serde_json::to_string(&point).unwrap())
+                .unwrap();
+        }
+        journal.append("{truncated").unwrap();
+
+        let points = points(&journal, 3).unwrap();
+        let valid: Vec<_> = points.iter().map(|p| p.valid).collect();
+        assert_eq!(valid, vec![2, 3, 4]);
+        assert!((points[0].coverage - 0.25).abs() < 1e-9);
+    }
+}
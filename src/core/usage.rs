@@ -0,0 +1,170 @@
+//! Opt-in tracking of which documents agents actually read
+//!
+//! With `[usage] track = true`, every document served by the MCP
+//! `context_get` tool is appended as a JSON line to the usage journal,
+//! `.context/.journal/usage.jsonl` (rotated like the audit log, and safe
+//! to share between server processes). `context stats --usage` tallies the
+//! journal per document, so teams can see which documentation is consumed
+//! and which is dead weight. Only paths, slugs and timestamps are recorded.
+
+use crate::core::config::UsageConfig;
+use crate::core::journal::Journal;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Usage journal path within the context root
+pub const USAGE_JOURNAL: &str = ".journal/usage.jsonl";
+
+/// One document read, written as one JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Read {
+    /// RFC 3339 timestamp of the read
+    pub timestamp: String,
+    /// Path of the document relative to the context root
+    pub document: PathBuf,
+    /// Slug of the document at the time it was read
+    pub slug: String,
+    /// What read it, e.g. `mcp:context_get`
+    pub via: String,
+}
+
+/// How often one document was read
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentUsage {
+    /// Path of the document relative to the context root
+    pub document: PathBuf,
+    /// Slug of the document
+    pub slug: String,
+    /// Number of recorded reads
+    pub reads: usize,
+    /// Timestamp of the most recent read
+    pub last_read: Option<String>,
+}
+
+/// Reads per document, most-read first
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// Whether tracking is currently enabled
+    pub tracking: bool,
+    /// Timestamp of the oldest read still in the journal
+    pub since: Option<String>,
+    /// Total recorded reads, including of documents that no longer exist
+    pub total: usize,
+    /// Every current document, including those never read
+    pub documents: Vec<DocumentUsage>,
+}
+
+/// The usage journal of the context directory at `root`
+pub fn journal(root: &Path, config: &UsageConfig) -> Journal {
+    Journal::new(root.join(USAGE_JOURNAL), config.max_bytes, config.keep)
+}
+
+/// Every read still in the journal (including rotated files), oldest first.
+/// Lines that can't be parsed, e.g. from a crash mid-write, are skipped.
+pub fn reads(journal: &Journal) -> Result<Vec<Read>> {
+    Ok(journal
+        .lines()?
+        .iter()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Tally `reads` for each of `documents` (relative path and slug)
+pub fn report(
+    tracking: bool,
+    reads: &[Read],
+    documents: impl IntoIterator<Item = (PathBuf, String)>,
+) -> UsageReport {
+    let mut tally: HashMap<&Path, (usize, &str)> = HashMap::new();
+    for read in reads {
+        let entry = tally.entry(&read.document).or_default();
+        entry.0 += 1;
+        // Journal order is chronological, so the last read wins
+        entry.1 = &read.timestamp;
+    }
+
+    let mut documents: Vec<DocumentUsage> = documents
+        .into_iter()
+        .map(|(document, slug)| {
+            let (reads, last_read) = tally.get(document.as_path()).copied().unwrap_or_default();
+            DocumentUsage {
+                document,
+                slug,
+                reads,
+                last_read: (reads > 0).then(|| last_read.to_string()),
+            }
+        })
+        .collect();
+    // Stable, so documents with equal counts stay in path order
+    documents.sort_by_key(|d| std::cmp::Reverse(d.reads));
+
+    UsageReport {
+        tracking,
+        since: reads.first().map(|r| r.timestamp.clone()),
+        total: reads.len(),
+        documents,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(document: &str, timestamp: &str) -> Read {
+        Read {
+            timestamp: timestamp.to_string(),
+            document: PathBuf::from(document),
+            slug: String::new(),
+            via: "mcp:context_get".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_report_counts_and_orders() {
+        let reads = vec![
+            read("guides/auth.md", "2025-01-01T00:00:00Z"),
+            read("guides/deleted.md", "2025-01-02T00:00:00Z"),
+            read("guides/auth.md", "2025-01-03T00:00:00Z"),
+            read("guides/billing.md", "2025-01-04T00:00:00Z"),
+        ];
+        let docs = ["billing", "auth", "deploy"]
+            .map(|s| (PathBuf::from(format!("guides/{s}.md")), s.to_string()));
+        let report = report(true, &reads, docs);
+
+        let summary: Vec<_> = report
+            .documents
+            .iter()
+            .map(|d| (d.slug.as_str(), d.reads))
+            .collect();
+        assert_eq!(summary, vec![("auth", 2), ("billing", 1), ("deploy", 0)]);
+        assert_eq!(
+            report.documents[0].last_read.as_deref(),
+            Some("2025-01-03T00:00:00Z")
+        );
+        assert_eq!(report.documents[2].last_read, None);
+        assert_eq!(report.total, 4);
+        assert_eq!(report.since.as_deref(), Some("2025-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_reads_span_rotations_and_skip_garbage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = Journal::new(dir.path().join("usage.jsonl"), 120, 3);
+        for day in 1..=4 {
+            let line =
+                serde_json::to_string(&read("a.md", &format!("2025-01-0{day}T00:00:00Z"))).unwrap();
+            journal.append(&line).unwrap();
+        }
+        journal.append("{truncated").unwrap();
+
+        let timestamps: Vec<_> = reads(&journal)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.timestamp)
+            .collect();
+        assert_eq!(timestamps.len(), 4);
+        assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -0,0 +1,293 @@
+//! Live validation for `context watch`
+//!
+//! A filesystem watcher (inotify, FSEvents or ReadDirectoryChangesW
+//! through `notify`) follows the whole project, `.context/` included, minus
+//! what `.gitignore` excludes, such as build output. After a burst of events
+//! settles, the documents are reloaded and validated, and status transitions
+//! since the last check reported.
+
+use crate::core::git;
+use crate::core::models::{Status, Validation};
+use crate::core::suggest::SKIPPED_DIRS;
+use crate::error::{ContextError, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+/// How long changes must settle before validating, in milliseconds; saving
+/// a file or checking out a branch raises many events at once
+pub const DEFAULT_DEBOUNCE_MS: u64 = 200;
+
+/// A document whose status differs between two checks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Transition {
+    /// Path to the document file
+    pub path: PathBuf,
+    /// Status at the previous check, `None` if the document is new
+    pub from: Option<Status>,
+    /// Status now, `None` if the document was removed
+    pub to: Option<Status>,
+    /// Referenced files whose content changed, as of now
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<String>,
+    /// Referenced files that are missing, as of now
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+/// Status per document, as compared between checks
+pub type Statuses = BTreeMap<PathBuf, Status>;
+
+/// Status per document of a set of validations
+pub fn statuses(validations: &[Validation]) -> Statuses {
+    validations
+        .iter()
+        .map(|v| (v.path.clone(), v.status))
+        .collect()
+}
+
+/// Documents whose status changed, appeared or disappeared since `before`,
+/// in path order
+pub fn transitions(before: &Statuses, after: &[Validation]) -> Vec<Transition> {
+    let mut transitions: Vec<Transition> = after
+        .iter()
+        .filter(|v| before.get(&v.path) != Some(&v.status))
+        .map(|v| Transition {
+            path: v.path.clone(),
+            from: before.get(&v.path).copied(),
+            to: Some(v.status),
+            changed: v.changed.clone(),
+            missing: v.missing.clone(),
+        })
+        .collect();
+    let remaining = statuses(after);
+    transitions.extend(
+        before
+            .iter()
+            .filter(|(path, _)| !remaining.contains_key(*path))
+            .map(|(path, status)| Transition {
+                path: path.clone(),
+                from: Some(*status),
+                to: None,
+                changed: Vec::new(),
+                missing: Vec::new(),
+            }),
+    );
+    transitions.sort_by(|a, b| a.path.cmp(&b.path));
+    transitions
+}
+
+/// Which project paths `.gitignore` excludes, decided the way
+/// `suggest::candidate_files` lists files: by git in a repository, else by
+/// skipping the usual build and dependency directories
+pub struct Ignores {
+    project_root: PathBuf,
+    git: bool,
+    /// Verdicts on directories, kept for the whole watch since a build
+    /// writes many files into the same few
+    dirs: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl Ignores {
+    /// Ignore rules of the project at `project_root`
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            project_root: project_root.to_path_buf(),
+            git: git::run(project_root, &["rev-parse", "--is-inside-work-tree"]).is_ok(),
+            dirs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the path (relative to the project root) or a directory
+    /// containing it is ignored
+    pub fn is_ignored(&self, relative: &Path) -> bool {
+        let mut dirs: Vec<&Path> = relative
+            .ancestors()
+            .skip(1)
+            .filter(|d| !d.as_os_str().is_empty())
+            .collect();
+        dirs.reverse();
+        let mut verdicts = self.dirs.lock().unwrap_or_else(PoisonError::into_inner);
+        for dir in dirs {
+            let ignored = *verdicts
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| self.check(dir, true));
+            if ignored {
+                return true;
+            }
+        }
+        self.check(relative, false)
+    }
+
+    fn check(&self, relative: &Path, dir: bool) -> bool {
+        if !self.git {
+            return dir
+                && relative
+                    .file_name()
+                    .is_some_and(|name| SKIPPED_DIRS.contains(&&*name.to_string_lossy()));
+        }
+        // A trailing slash matches directory-only patterns such as `target/`
+        let mut path = relative.to_string_lossy().replace('\\', "/");
+        if dir {
+            path.push('/');
+        }
+        git::run(&self.project_root, &["check-ignore", "-q", "--", &path]).is_ok()
+    }
+}
+
+/// Watch `project_root` recursively, `.context/` included, calling
+/// `changed` for every filesystem event that can affect validation (see
+/// `is_relevant`). Watching stops when the returned watcher is dropped.
+pub fn watch(
+    project_root: &Path,
+    changed: impl Fn() + Send + 'static,
+) -> Result<RecommendedWatcher> {
+    // Events name canonical paths on some platforms (e.g. /private/var on macOS)
+    let project_root = &project_root.canonicalize()?;
+    let ignores = Ignores::new(project_root);
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        // Errors (e.g. an overflowed event queue) may hide changes
+        if event.is_err() || event.is_ok_and(|event| is_relevant(&ignores, &event)) {
+            changed();
+        }
+    })
+    .map_err(|e| ContextError::Other(format!("Failed to watch {}: {e}", project_root.display())))?;
+    watcher
+        .watch(project_root, RecursiveMode::Recursive)
+        .map_err(|e| {
+            ContextError::Other(format!("Failed to watch {}: {e}", project_root.display()))
+        })?;
+    Ok(watcher)
+}
+
+/// Whether an event can change a document's status: anything but reads,
+/// outside `.git/`, ignored paths and the hidden runtime directories of
+/// `.context/` (journals, storage), which validation itself may write to.
+/// A file no document references counts too, since a body may start
+/// mentioning it.
+pub fn is_relevant(ignores: &Ignores, event: &Event) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|path| {
+        let Ok(relative) = path.strip_prefix(&ignores.project_root) else {
+            return false;
+        };
+        let mut components = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy());
+        match components.next().as_deref() {
+            Some(".git") | None => false,
+            Some(crate::core::CONTEXT_DIR_NAME) => {
+                let inner: Vec<_> = components.collect();
+                // The last component is the file itself; dotfiles like .gitignore are fine
+                inner.iter().rev().skip(1).all(|c| !c.starts_with('.'))
+            }
+            Some(_) => !ignores.is_ignored(relative),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn validation(path: &str, status: Status) -> Validation {
+        Validation {
+            path: PathBuf::from(path),
+            status,
+            changed: if status == Status::Stale {
+                vec!["src/a.rs".to_string()]
+            } else {
+                Vec::new()
+            },
+            missing: Vec::new(),
+            sections: Vec::new(),
+            unreadable: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_transitions() {
+        let before = statuses(&[
+            validation("a.md", Status::Valid),
+            validation("b.md", Status::Valid),
+            validation("c.md", Status::Stale),
+        ]);
+        let after = [
+            validation("a.md", Status::Stale),
+            validation("b.md", Status::Valid),
+            validation("d.md", Status::Valid),
+        ];
+        let found = transitions(&before, &after);
+        let summary: Vec<_> = found
+            .iter()
+            .map(|t| (t.path.to_str().unwrap(), t.from, t.to))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.md", Some(Status::Valid), Some(Status::Stale)),
+                ("c.md", Some(Status::Stale), None),
+                ("d.md", None, Some(Status::Valid)),
+            ]
+        );
+        assert_eq!(found[0].changed, vec!["src/a.rs"]);
+    }
+
+    #[test]
+    fn test_relevant_events() {
+        let root = Path::new("/p");
+        let ignores = Ignores::new(root);
+        let is_relevant = |event| is_relevant(&ignores, &event);
+        let event = |kind, path: &str| Event::new(kind).add_path(root.join(path));
+        let modify = EventKind::Modify(notify::event::ModifyKind::Any);
+        let create = EventKind::Create(notify::event::CreateKind::File);
+        assert!(is_relevant(event(modify, "src/a.rs")));
+        assert!(is_relevant(event(create, "src/unreferenced.rs")));
+        assert!(is_relevant(event(modify, ".context/guides/a.md")));
+        assert!(is_relevant(event(modify, ".context/.gitignore")));
+        assert!(is_relevant(event(modify, ".github/workflows/ci.yml")));
+        assert!(!is_relevant(event(modify, ".git/index")));
+        // Outside git, the usual build directories are ignored
+        assert!(!is_relevant(event(modify, "node_modules/pkg/index.js")));
+        assert!(!is_relevant(event(modify, ".context/.journal/usage.jsonl")));
+        assert!(!is_relevant(event(
+            EventKind::Access(notify::event::AccessKind::Any),
+            "src/a.rs"
+        )));
+    }
+
+    #[test]
+    fn test_gitignored_paths_are_not_relevant() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        git::run(root, &["init", "-q"]).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        let ignores = Ignores::new(root);
+        let modify = |path: &str| {
+            Event::new(EventKind::Modify(notify::event::ModifyKind::Any)).add_path(root.join(path))
+        };
+        assert!(!is_relevant(&ignores, &modify("target/debug/deps/app.d")));
+        assert!(!is_relevant(&ignores, &modify("server.log")));
+        assert!(is_relevant(&ignores, &modify("src/target.rs")));
+        assert!(is_relevant(&ignores, &modify("build/app.js")));
+    }
+
+    #[test]
+    fn test_watch_reports_new_files() {
+        let dir = TempDir::new().unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let _watcher = watch(dir.path(), move || {
+            let _ = sender.send(());
+        })
+        .unwrap();
+        std::fs::write(dir.path().join("new.rs"), "fn new() {}").unwrap();
+        assert!(receiver
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .is_ok());
+    }
+}
@@ -62,6 +62,29 @@ pub enum ContextError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    #[error("Template error: {0}")]
+    TemplateError(String),
+
+    #[error("Snapshot error: {0}")]
+    SnapshotError(String),
+
+    #[error("Remote error: {0}")]
+    RemoteError(String),
+
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
+    #[error("Unresolved merge conflict in {}: {reason} (run `context resolve`)", path.display())]
+    MergeConflict {
+        /// Document containing the conflict
+        path: PathBuf,
+        /// What was found
+        reason: String,
+    },
+
     #[error("Path is not within .context directory: {0}")]
     DocumentNotInContext(String),
 
@@ -73,6 +96,34 @@ pub enum ContextError {
         documents: Vec<(PathBuf, Vec<InvalidReference>)>,
     },
 
+    #[error(
+        "Review required before syncing {}: review the changes, then pass --reviewed-by <NAME>",
+        documents.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+    )]
+    ReviewRequired {
+        /// Stale or orphaned documents that would have been synced
+        documents: Vec<PathBuf>,
+    },
+
+    #[error(
+        "Duplicate slugs: {}",
+        collisions
+            .iter()
+            .map(|(slug, paths)| format!(
+                "'{slug}' is claimed by {}",
+                paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" and ")
+            ))
+            .collect::<Vec<_>>()
+            .join("; ")
+    )]
+    DuplicateSlugs {
+        /// Each slug claimed by more than one document, with those documents
+        collisions: Vec<(String, Vec<PathBuf>)>,
+    },
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("{0}")]
     Other(String),
 }
@@ -9,6 +9,9 @@ pub mod cli;
 pub mod core;
 pub mod error;
 pub mod mcp;
+pub mod preview;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use core::Cache;
 pub use error::{ContextError, Result};
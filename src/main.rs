@@ -1,14 +1,23 @@
 use clap::Parser;
-use context::cli::{execute, map_exit_code, Cli};
+use context::cli::{console, execute, map_exit_code, Cli, LogFormat};
+use context::core::events::Event;
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let log_format = cli.log_format;
 
     match execute(cli).await {
         Ok(exit_code) => std::process::exit(exit_code),
         Err(e) => {
-            eprintln!("Error: {e}");
+            if log_format == LogFormat::Json {
+                console::log_event(&Event::Error {
+                    document: None,
+                    message: e.to_string(),
+                });
+            } else {
+                eprintln!("Error: {e}");
+            }
             std::process::exit(map_exit_code(false, Some(&e)));
         }
     }
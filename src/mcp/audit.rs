@@ -0,0 +1,105 @@
+//! Append-only audit log of MCP tool invocations
+
+use crate::core::journal::Journal;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Default size at which the audit log is rotated (10 MiB)
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Number of rotated files kept alongside the active log
+pub const DEFAULT_KEEP: usize = 5;
+
+/// A single audited tool invocation, written as one JSON line
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    /// RFC 3339 timestamp of when the call completed
+    pub timestamp: String,
+    /// Name of the MCP tool that was invoked
+    pub tool: String,
+    /// Arguments the tool was called with
+    pub arguments: serde_json::Value,
+    /// Client name and version from the MCP handshake, if provided
+    pub client: Option<String>,
+    /// Caller-provided request metadata (`_meta`), if any
+    pub session: Option<serde_json::Value>,
+    /// "ok" or "error"
+    pub status: &'static str,
+    /// Wall-clock duration of the call in milliseconds
+    pub duration_ms: u128,
+}
+
+/// Audit log file with size-based rotation, safe to share between processes
+#[derive(Debug)]
+pub struct AuditLog {
+    journal: Journal,
+}
+
+impl AuditLog {
+    /// Create an audit log writing to `path`, rotating once it exceeds `max_bytes`
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        Self {
+            journal: Journal::new(path, max_bytes, keep),
+        }
+    }
+
+    /// Path of the active log file
+    pub fn path(&self) -> &Path {
+        self.journal.path()
+    }
+
+    /// Append an entry, rotating the log first if it has grown too large
+    pub fn record(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        self.journal.append(&serde_json::to_string(entry)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(tool: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            tool: tool.to_string(),
+            arguments: serde_json::json!({}),
+            client: None,
+            session: None,
+            status: "ok",
+            duration_ms: 1,
+        }
+    }
+
+    #[test]
+    fn test_record_appends_json_lines() {
+        let dir = TempDir::new().unwrap();
+        let log = AuditLog::new(
+            dir.path().join("audit.log"),
+            DEFAULT_MAX_BYTES,
+            DEFAULT_KEEP,
+        );
+        log.record(&entry("context_status")).unwrap();
+        log.record(&entry("context_sync")).unwrap();
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains("\"tool\":\"context_sync\""));
+    }
+
+    #[test]
+    fn test_record_rotates_when_full() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::new(path.clone(), 1, 2);
+        for _ in 0..4 {
+            log.record(&entry("context_find")).unwrap();
+        }
+
+        assert!(path.exists());
+        assert!(dir.path().join("audit.log.1").exists());
+        assert!(dir.path().join("audit.log.2").exists());
+        assert!(!dir.path().join("audit.log.3").exists());
+    }
+}
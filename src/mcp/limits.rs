@@ -0,0 +1,165 @@
+//! Concurrency, rate, and response-size limits for served tool calls
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Configured limits; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    /// Maximum number of tool calls executing at once
+    pub max_concurrent: Option<usize>,
+    /// Maximum number of tool calls accepted in any sliding 60-second window
+    pub calls_per_minute: Option<usize>,
+    /// Maximum size of a tool response in bytes
+    pub max_response_bytes: Option<usize>,
+}
+
+/// Why a tool call was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// Too many calls already in flight
+    Concurrency(usize),
+    /// The per-minute call budget is spent
+    RateLimited(usize),
+    /// The response was larger than allowed
+    ResponseTooLarge { size: usize, limit: usize },
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Concurrency(max) => {
+                write!(
+                    f,
+                    "too many concurrent tool calls (limit {max}), retry shortly"
+                )
+            }
+            Self::RateLimited(max) => {
+                write!(
+                    f,
+                    "rate limit exceeded ({max} calls per minute), retry later"
+                )
+            }
+            Self::ResponseTooLarge { size, limit } => write!(
+                f,
+                "response of {size} bytes exceeds the {limit} byte limit, narrow the request"
+            ),
+        }
+    }
+}
+
+/// Enforces [`Limits`] across concurrent tool calls
+#[derive(Debug, Default)]
+pub struct Limiter {
+    limits: Limits,
+    in_flight: AtomicUsize,
+    recent: Mutex<VecDeque<Instant>>,
+}
+
+/// Marks a tool call as in flight until dropped
+#[derive(Debug)]
+pub struct Permit<'a> {
+    limiter: &'a Limiter,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.limiter.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Limiter {
+    const WINDOW: Duration = Duration::from_mins(1);
+
+    /// Create a limiter enforcing the given limits
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            in_flight: AtomicUsize::new(0),
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Admit a tool call, or explain why it must be rejected
+    pub fn acquire(&self) -> Result<Permit<'_>, LimitError> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let permit = Permit { limiter: self };
+
+        if let Some(max) = self.limits.max_concurrent {
+            if in_flight >= max {
+                return Err(LimitError::Concurrency(max));
+            }
+        }
+
+        if let Some(max) = self.limits.calls_per_minute {
+            let now = Instant::now();
+            let mut recent = self
+                .recent
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            while recent
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= Self::WINDOW)
+            {
+                recent.pop_front();
+            }
+            if recent.len() >= max {
+                return Err(LimitError::RateLimited(max));
+            }
+            recent.push_back(now);
+        }
+
+        Ok(permit)
+    }
+
+    /// Check a response against the size limit
+    pub fn check_response(&self, response: &str) -> Result<(), LimitError> {
+        match self.limits.max_response_bytes {
+            Some(limit) if response.len() > limit => Err(LimitError::ResponseTooLarge {
+                size: response.len(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrency_limit() {
+        let limiter = Limiter::new(Limits {
+            max_concurrent: Some(1),
+            ..Limits::default()
+        });
+        let first = limiter.acquire().unwrap();
+        assert_eq!(limiter.acquire().unwrap_err(), LimitError::Concurrency(1));
+        drop(first);
+        assert!(limiter.acquire().is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let limiter = Limiter::new(Limits {
+            calls_per_minute: Some(2),
+            ..Limits::default()
+        });
+        assert!(limiter.acquire().is_ok());
+        assert!(limiter.acquire().is_ok());
+        assert_eq!(limiter.acquire().unwrap_err(), LimitError::RateLimited(2));
+    }
+
+    #[test]
+    fn test_response_size_limit() {
+        let limiter = Limiter::new(Limits {
+            max_response_bytes: Some(4),
+            ..Limits::default()
+        });
+        assert!(limiter.check_response("ok").is_ok());
+        assert!(limiter.check_response("too long").is_err());
+    }
+}
@@ -1 +1,3 @@
+pub mod audit;
+pub mod limits;
 pub mod server;
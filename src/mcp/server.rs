@@ -5,38 +5,203 @@ use tracing_subscriber::{self, EnvFilter};
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{ServerCapabilities, ServerInfo},
-    schemars, tool, tool_handler, tool_router, ServerHandler,
+    schemars,
+    service::RequestContext,
+    tool, tool_handler, tool_router, RoleServer, ServerHandler,
 };
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::core::{find_context_root_from_cwd, Cache, FindResult, Status, SyncResult, Validation};
+use super::audit::{AuditEntry, AuditLog};
+use super::limits::{Limiter, Limits};
+
+use crate::core::cancel::CancellationToken;
+use crate::core::config::SCHEMA_VERSION;
+use crate::core::events::Event;
+use crate::core::pack::{self, Excerpt};
+use crate::core::reload::LiveConfig;
+use crate::core::search::SearchHit;
+use crate::core::sections;
+use crate::core::{
+    resolve_context_root, Cache, ContextRoot, DocumentState, FindResult, Page, Status,
+    StatusFilter, SyncResult, Validation,
+};
 use crate::error::ContextError;
 
 // ============================================================================
 // Request types for MCP tools
 // ============================================================================
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct StatusRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
     #[schemars(description = "If true, only return stale or orphaned documents")]
     pub invalid_only: Option<bool>,
+    #[schemars(
+        description = "Only documents under this directory, relative to .context/ (e.g., \"guides\")"
+    )]
+    pub directory: Option<String>,
+    #[schemars(description = "Only documents carrying this tag")]
+    pub tag: Option<String>,
+    #[schemars(description = "Only documents with this status: valid, stale, or orphaned")]
+    pub status: Option<String>,
+    #[schemars(
+        description = "Only documents referencing one of these source paths or files beneath them (e.g., [\"src/cli\"])"
+    )]
+    pub paths: Option<Vec<String>>,
+    #[schemars(
+        description = "Only documents in this lifecycle state: draft, published, or deprecated"
+    )]
+    pub state: Option<String>,
+    #[schemars(description = "Maximum number of documents to return. If omitted, returns all.")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Continuation token from a previous response's next_cursor")]
+    pub cursor: Option<String>,
 }
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct SyncRequest {
-    #[schemars(description = "Path to a specific document to sync. If omitted, syncs all documents.")]
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
+    #[schemars(
+        description = "Slug or path of a specific document to sync. If omitted, syncs all documents except protected ones."
+    )]
     pub path: Option<String>,
+    #[schemars(
+        description = "If true, report the references each document would gain or lose and the hashes that would change, without writing anything"
+    )]
+    pub dry_run: Option<bool>,
+    #[schemars(
+        description = "If true, drop references to files that no longer exist instead of failing, and report them as pruned"
+    )]
+    pub prune_missing: Option<bool>,
+    #[schemars(
+        description = "If true, report references dropped because bodies no longer mention them; a sync of all documents also drops them from documents it doesn't re-hash and forgets stored data of deleted documents"
+    )]
+    pub cleanup: Option<bool>,
+    #[schemars(
+        description = "If true and no path is given, validate first and only sync stale or orphaned documents, reporting how many valid ones were skipped"
+    )]
+    pub only_stale: Option<bool>,
 }
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct FindRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
     #[schemars(description = "Source file paths to search for (e.g., [\"src/core/models.rs\"])")]
     pub paths: Vec<String>,
+    #[schemars(
+        description = "Maximum number of matches to return per path. If omitted, returns all."
+    )]
+    pub limit: Option<usize>,
+    #[schemars(description = "Continuation token from a previous response's next_cursor")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct SearchRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
+    #[schemars(description = "Text to search for in document bodies (case-insensitive)")]
+    pub query: String,
+    #[schemars(
+        description = "Only documents in this lifecycle state: draft, published, or deprecated. Each hit reports its document's state."
+    )]
+    pub state: Option<String>,
+    #[schemars(description = "If true, also search documents moved into .context/archive/")]
+    pub include_archived: Option<bool>,
+    #[schemars(description = "Maximum number of documents to return in this chunk (default 20)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Continuation token from a previous response's next_cursor")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
+    #[schemars(
+        description = "Document slug, namespaced slug or path (e.g., \"auth\", \"platform/auth\" or \"guides/auth.md\")"
+    )]
+    pub document: String,
+    #[schemars(
+        description = "Only return the section under this heading (e.g., \"## Token refresh\")"
+    )]
+    pub section: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct InfoRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct PackRequest {
+    #[schemars(
+        description = "Served root to use, by project directory name (see context_info). Required when the server serves several roots."
+    )]
+    pub root: Option<String>,
+    #[schemars(
+        description = "Source file paths the task touches (e.g., [\"src/core/models.rs\"]). Give either paths or task."
+    )]
+    pub paths: Option<Vec<String>>,
+    #[schemars(
+        description = "Free-text description of the task, matched against document text. Give either paths or task."
+    )]
+    pub task: Option<String>,
+    #[schemars(
+        description = "Token budget for the returned excerpts (default 8000, estimated at four characters per token)"
+    )]
+    pub max_tokens: Option<usize>,
+    #[schemars(description = "Links to follow from matching documents (default 1)")]
+    pub hops: Option<usize>,
 }
 
 // ============================================================================
 // Response types for MCP tools
 // ============================================================================
 
+/// Envelope for paginated responses
+#[derive(Debug, serde::Serialize)]
+struct PageResponse<T> {
+    items: Vec<T>,
+    total: usize,
+    next_cursor: Option<String>,
+}
+
+impl<T> From<Page<T>> for PageResponse<T> {
+    fn from(page: Page<T>) -> Self {
+        Self {
+            next_cursor: page.next_offset().map(|o| o.to_string()),
+            total: page.total,
+            items: page.items,
+        }
+    }
+}
+
+/// Decode a continuation token into an offset
+fn parse_cursor(cursor: Option<&str>) -> std::result::Result<usize, String> {
+    cursor.map_or(Ok(0), |c| {
+        c.parse()
+            .map_err(|_| format!("Error: invalid cursor '{c}'"))
+    })
+}
+
 #[derive(Debug, serde::Serialize)]
 struct StatusItem {
     path: String,
@@ -60,7 +225,16 @@ impl From<Validation> for StatusItem {
 struct SyncResponse {
     count: usize,
     updated: Vec<String>,
+    unchanged: Vec<String>,
     failed: Vec<String>,
+    protected: Vec<String>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pruned: std::collections::BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    cleaned_references: std::collections::BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cleaned_artifacts: Vec<String>,
+    skipped: usize,
 }
 
 impl From<SyncResult> for SyncResponse {
@@ -68,7 +242,33 @@ impl From<SyncResult> for SyncResponse {
         Self {
             count: r.count,
             updated: r.updated.iter().map(|p| p.display().to_string()).collect(),
+            unchanged: r
+                .unchanged
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect(),
             failed: r.failed,
+            protected: r
+                .protected
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect(),
+            pruned: r
+                .pruned
+                .into_iter()
+                .map(|(p, refs)| (p.display().to_string(), refs))
+                .collect(),
+            cleaned_references: r
+                .cleaned_references
+                .into_iter()
+                .map(|(p, refs)| (p.display().to_string(), refs))
+                .collect(),
+            cleaned_artifacts: r
+                .cleaned_artifacts
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect(),
+            skipped: r.skipped,
         }
     }
 }
@@ -78,18 +278,31 @@ struct FindMatchItem {
     document: String,
     reference: String,
     status: String,
+    reference_status: String,
+    line: Option<usize>,
+    anchor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize)]
 struct FindResultItem {
     query: String,
+    total: usize,
     matches: Vec<FindMatchItem>,
 }
 
+#[derive(Debug, serde::Serialize)]
+struct FindResponse {
+    results: Vec<FindResultItem>,
+    next_cursor: Option<String>,
+}
+
 impl From<FindResult> for FindResultItem {
     fn from(r: FindResult) -> Self {
         Self {
             query: r.query,
+            total: r.total,
             matches: r
                 .matches
                 .into_iter()
@@ -97,38 +310,262 @@ impl From<FindResult> for FindResultItem {
                     document: m.document.display().to_string(),
                     reference: m.reference,
                     status: m.status.to_string(),
+                    reference_status: m.reference_status.to_string(),
+                    line: m.line,
+                    anchor: m.anchor,
+                    remote: m.remote,
                 })
                 .collect(),
         }
     }
 }
 
+#[derive(Debug, serde::Serialize)]
+struct SearchResponse {
+    items: Vec<SearchHit>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct GetResponse {
+    path: String,
+    slug: String,
+    namespace: String,
+    description: String,
+    breadcrumb: Vec<String>,
+    body: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct InfoResponse {
+    name: &'static str,
+    version: &'static str,
+    /// Newest document schema version the server understands
+    schema_version: u32,
+    /// Schema version declared in config.toml, if any
+    repository_schema_version: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    root: ContextRoot,
+    /// Names of every root this server serves, empty if it serves the
+    /// working directory
+    roots: Vec<String>,
+    documents: usize,
+    remote_documents: usize,
+    valid: usize,
+    stale: usize,
+    orphaned: usize,
+    last_updated: Option<String>,
+    health: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct PackResponse {
+    excerpts: Vec<Excerpt>,
+    tokens: usize,
+    omitted: usize,
+}
+
 // ============================================================================
 // MCP Server implementation
 // ============================================================================
 
+/// How often `config.toml` is checked for edits
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug, Clone)]
 pub struct ContextServer {
     tool_router: ToolRouter<Self>,
+    audit_log: Option<Arc<AuditLog>>,
+    limiter: Arc<Limiter>,
+    roots: Vec<Arc<ServedRoot>>,
+}
+
+/// A context directory served under a name, with its reloadable config
+#[derive(Debug)]
+struct ServedRoot {
+    name: String,
+    root: ContextRoot,
+    config: Option<LiveConfig>,
 }
 
 impl ContextServer {
     pub fn new() -> Self {
         Self {
             tool_router: Self::tool_router(),
+            audit_log: None,
+            limiter: Arc::new(Limiter::default()),
+            roots: Vec::new(),
+        }
+    }
+
+    /// Enforce concurrency, rate, and response-size limits on tool calls
+    #[must_use]
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limiter = Arc::new(Limiter::new(limits));
+        self
+    }
+
+    /// Record every tool invocation to the given audit log
+    #[must_use]
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(Arc::new(audit_log));
+        self
+    }
+
+    /// Serve the context directory of `root` under `name` instead of
+    /// resolving one from the working directory on every call. Its
+    /// `config.toml` is loaded once and kept reloaded by `run_server`; if it
+    /// doesn't load, every call reads it afresh and reports the error.
+    #[must_use]
+    pub fn with_root(mut self, name: String, root: ContextRoot) -> Self {
+        let config = LiveConfig::load(&root.context_dir).ok();
+        self.roots.push(Arc::new(ServedRoot { name, root, config }));
+        self
+    }
+
+    /// Names of the served roots, in the order they were added
+    fn root_names(&self) -> Vec<&str> {
+        self.roots.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    /// The served root a call names, or the only one if it names none.
+    /// `None` means no roots were configured and the working directory is used.
+    fn served_root(&self, name: Option<&str>) -> std::result::Result<Option<&ServedRoot>, String> {
+        match (name, self.roots.as_slice()) {
+            (None, []) => Ok(None),
+            (Some(name), []) => Err(format!(
+                "unknown root '{name}': this server serves the working directory only"
+            )),
+            (None, [only]) => Ok(Some(only)),
+            (None, _) => Err(format!(
+                "several roots are served, pass root: one of {}",
+                self.root_names().join(", ")
+            )),
+            (Some(name), roots) => roots
+                .iter()
+                .find(|r| r.name == name)
+                .map(|r| Some(&**r))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown root '{name}', expected one of {}",
+                        self.root_names().join(", ")
+                    )
+                }),
+        }
+    }
+
+    /// Resolve the context root a call addresses
+    fn context_root(&self, name: Option<&str>) -> std::result::Result<ContextRoot, String> {
+        if let Some(served) = self.served_root(name)? {
+            return Ok(served.root.clone());
         }
+        std::env::current_dir()
+            .map_err(ContextError::from)
+            .and_then(|cwd| resolve_context_root(&cwd))
+            .map_err(|e| match e {
+                ContextError::NotARepository => {
+                    "Not a context repository (no .context directory found)".to_string()
+                }
+                _ => format!("Failed to find context root: {e}"),
+            })
     }
 
-    /// Load the cache from the current working directory
-    fn load_cache() -> std::result::Result<Cache, String> {
-        let root = find_context_root_from_cwd().map_err(|e| match e {
-            ContextError::NotARepository => {
-                "Not a context repository (no .context directory found)".to_string()
+    /// Run a tool body within the configured limits, recording the
+    /// invocation to the audit log if enabled. The body receives a token
+    /// that is cancelled if the client cancels the request or disconnects.
+    fn invoke<R: serde::Serialize>(
+        &self,
+        tool: &str,
+        args: &R,
+        ctx: RequestContext<RoleServer>,
+        run: impl FnOnce(&CancellationToken) -> String,
+    ) -> String {
+        let started = Instant::now();
+
+        // Stop cache work early if the client cancels the request or goes away
+        let token = CancellationToken::new();
+        let watcher = {
+            let token = token.clone();
+            let client_ct = ctx.ct.clone();
+            tokio::spawn(async move {
+                client_ct.cancelled().await;
+                token.cancel();
+            })
+        };
+
+        let response = match self.limiter.acquire() {
+            Ok(_permit) => {
+                let response = run(&token);
+                match self.limiter.check_response(&response) {
+                    Ok(()) => response,
+                    Err(e) => format!("Error: {e}"),
+                }
             }
-            _ => format!("Failed to find context root: {e}"),
-        })?;
+            Err(e) => format!("Error: {e}"),
+        };
+        watcher.abort();
 
-        let mut cache = Cache::create(root).map_err(|e| format!("Failed to create cache: {e}"))?;
+        if let Some(audit_log) = &self.audit_log {
+            let entry = AuditEntry {
+                timestamp: crate::core::clock::now_utc().to_rfc3339(),
+                tool: tool.to_string(),
+                arguments: serde_json::to_value(args).unwrap_or_default(),
+                client: ctx
+                    .peer
+                    .peer_info()
+                    .map(|info| format!("{} {}", info.client_info.name, info.client_info.version)),
+                session: (!ctx.meta.is_empty())
+                    .then(|| serde_json::to_value(ctx.meta).unwrap_or_default()),
+                status: if response.starts_with("Error") {
+                    "error"
+                } else {
+                    "ok"
+                },
+                duration_ms: started.elapsed().as_millis(),
+            };
+            if let Err(e) = audit_log.record(&entry) {
+                tracing::error!("failed to write audit log: {e}");
+            }
+        }
+
+        response
+    }
+
+    /// Open the cache for the named root (or the current working directory)
+    /// without loading documents
+    fn open_cache(
+        &self,
+        root: Option<&str>,
+        ct: &CancellationToken,
+    ) -> std::result::Result<Cache, String> {
+        let cache = match self.served_root(root)? {
+            Some(ServedRoot {
+                root,
+                config: Some(live),
+                ..
+            }) => Cache::create_with_config(root.context_dir.clone(), (*live.current()).clone()),
+            Some(served) => Cache::create(served.root.context_dir.clone()),
+            None => Cache::create(self.context_root(None)?.context_dir),
+        };
+        let mut cache = cache.map_err(|e| format!("Failed to create cache: {e}"))?;
+        cache.set_cancellation(ct.clone());
+        // Stdout carries the protocol, so warnings go to the log
+        cache.subscribe(|event: &Event| {
+            if let Event::Warning { document, message } = event {
+                tracing::warn!(?document, "{message}");
+            }
+        });
+        Ok(cache)
+    }
+
+    /// Load the cache for the named root (or the current working directory)
+    fn load_cache(
+        &self,
+        root: Option<&str>,
+        ct: &CancellationToken,
+    ) -> std::result::Result<Cache, String> {
+        let mut cache = self.open_cache(root, ct)?;
         cache
             .load()
             .map_err(|e| format!("Failed to load cache: {e}"))?;
@@ -145,19 +582,180 @@ impl Default for ContextServer {
 
 #[tool_router]
 impl ContextServer {
-    #[tool(description = "Validate all context documents and return their status (valid, stale, or orphaned)")]
-    #[allow(clippy::unused_self)]
-    fn context_status(&self, Parameters(req): Parameters<StatusRequest>) -> String {
-        let cache = match Self::load_cache() {
+    #[tool(
+        description = "Report the server version, the resolved context root, document counts and freshness. Call this first to check you are connected to the right repository; a warning is included when the server is older than the repository's document schema."
+    )]
+    fn context_info(
+        &self,
+        Parameters(req): Parameters<InfoRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_info", &req, ctx, |ct| self.info(&req, ct))
+    }
+
+    #[tool(
+        description = "Validate all context documents and return their status (valid, stale, or orphaned)"
+    )]
+    fn context_status(
+        &self,
+        Parameters(req): Parameters<StatusRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_status", &req, ctx, |ct| self.status(&req, ct))
+    }
+
+    #[tool(
+        description = "Update reference hashes for context documents, marking them as reviewed, or with dry_run preview the references and hashes that would change"
+    )]
+    fn context_sync(
+        &self,
+        Parameters(req): Parameters<SyncRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_sync", &req, ctx, |ct| self.sync(&req, ct))
+    }
+
+    #[tool(
+        description = "Read a context document by slug or path, optionally only one section of it"
+    )]
+    fn context_get(
+        &self,
+        Parameters(req): Parameters<GetRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_get", &req, ctx, |ct| self.get(&req, ct))
+    }
+
+    #[tool(
+        description = "Search context document bodies for text, returning matching lines in chunks ranked by freshness (valid, recently updated documents first)"
+    )]
+    fn context_search(
+        &self,
+        Parameters(req): Parameters<SearchRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_search", &req, ctx, |ct| self.search(&req, ct))
+    }
+
+    #[tool(description = "Find all context documents that reference the given source file path(s)")]
+    fn context_find(
+        &self,
+        Parameters(req): Parameters<FindRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_find", &req, ctx, |ct| self.find(&req, ct))
+    }
+
+    #[tool(
+        description = "Assemble context for a task: section excerpts of the documents referencing the given source files, or matching a task description, plus documents they link to, most relevant first and within a token budget. Each excerpt names its document, section, and status."
+    )]
+    fn context_pack(
+        &self,
+        Parameters(req): Parameters<PackRequest>,
+        ctx: RequestContext<RoleServer>,
+    ) -> String {
+        self.invoke("context_pack", &req, ctx, |ct| self.pack(&req, ct))
+    }
+}
+
+impl ContextServer {
+    fn info(&self, req: &InfoRequest, ct: &CancellationToken) -> String {
+        let root = match self.context_root(req.root.as_deref()) {
+            Ok(r) => r,
+            Err(e) => return format!("Error: {e}"),
+        };
+        // Counts only need references, so skip reading bodies
+        let cache = match self.open_cache(req.root.as_deref(), ct).and_then(|mut c| {
+            c.load_metadata()
+                .map_err(|e| format!("Failed to load cache: {e}"))?;
+            Ok(c)
+        }) {
             Ok(c) => c,
             Err(e) => return format!("Error: {e}"),
         };
+        let summary = match cache.summary() {
+            Ok(s) => s,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let repository_schema_version = cache.config().schema_version;
+        let warning = repository_schema_version
+            .filter(|v| *v > SCHEMA_VERSION)
+            .map(|v| {
+                format!(
+                    "documents use schema version {v} but this server (context {}) only understands {SCHEMA_VERSION}; upgrade the context binary",
+                    env!("CARGO_PKG_VERSION")
+                )
+            });
+
+        let response = InfoResponse {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            schema_version: SCHEMA_VERSION,
+            repository_schema_version,
+            warning,
+            root,
+            roots: self.root_names().into_iter().map(str::to_string).collect(),
+            documents: summary.documents,
+            remote_documents: cache.remote_documents().len(),
+            valid: summary.valid,
+            stale: summary.stale,
+            orphaned: summary.orphaned,
+            health: summary.health(),
+            last_updated: summary.last_updated,
+        };
+        match serde_json::to_string_pretty(&response) {
+            Ok(json) => json,
+            Err(e) => format!("Error serializing response: {e}"),
+        }
+    }
 
-        let validations = match cache.status() {
+    fn status(&self, req: &StatusRequest, ct: &CancellationToken) -> String {
+        // Validation only needs references, so skip reading bodies
+        let cache = match self.open_cache(req.root.as_deref(), ct).and_then(|mut c| {
+            c.load_metadata()
+                .map_err(|e| format!("Failed to load cache: {e}"))?;
+            Ok(c)
+        }) {
+            Ok(c) => c,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let statuses = match req.status.as_deref().map(str::parse::<Status>).transpose() {
+            Ok(s) => s.into_iter().collect(),
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let states = match req
+            .state
+            .as_deref()
+            .map(str::parse::<DocumentState>)
+            .transpose()
+        {
+            Ok(s) => s.into_iter().collect(),
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let filter = StatusFilter {
+            directory: req.directory.as_ref().map(std::path::PathBuf::from),
+            tag: req.tag.clone(),
+            statuses,
+            paths: req.paths.clone().unwrap_or_default(),
+            owners: Vec::new(),
+            states,
+            document: None,
+        };
+
+        let validations = match cache.status_filtered(&filter) {
             Ok(v) => v,
             Err(e) => return format!("Error: {e}"),
         };
 
+        let offset = match parse_cursor(req.cursor.as_deref()) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+
         let invalid_only = req.invalid_only.unwrap_or(false);
 
         let items: Vec<StatusItem> = validations
@@ -166,33 +764,51 @@ impl ContextServer {
             .map(StatusItem::from)
             .collect();
 
-        match serde_json::to_string_pretty(&items) {
+        let response = PageResponse::from(Page::new(items, offset, req.limit));
+        match serde_json::to_string_pretty(&response) {
             Ok(json) => json,
             Err(e) => format!("Error serializing response: {e}"),
         }
     }
 
-    #[tool(description = "Update reference hashes for context documents, marking them as reviewed")]
-    #[allow(clippy::unused_self)]
-    fn context_sync(&self, Parameters(req): Parameters<SyncRequest>) -> String {
-        let mut cache = match Self::load_cache() {
+    fn sync(&self, req: &SyncRequest, ct: &CancellationToken) -> String {
+        let mut cache = match self.load_cache(req.root.as_deref(), ct) {
             Ok(c) => c,
             Err(e) => return format!("Error: {e}"),
         };
 
-        let doc_path = match &req.path {
-            Some(p) => {
-                let path = std::path::Path::new(p);
-                match cache.resolve_doc_path(path) {
-                    Ok(resolved) => Some(resolved),
-                    Err(e) => return format!("Error: {e}"),
-                }
-            }
-            None => None,
+        let doc_path = match req
+            .path
+            .as_deref()
+            .map(|p| cache.sync_target(p))
+            .transpose()
+        {
+            Ok(doc_path) => doc_path,
+            Err(e) => return format!("Error: {e}"),
+        };
+        cache.set_prune_missing(req.prune_missing.unwrap_or(false));
+        cache.set_cleanup(req.cleanup.unwrap_or(false));
+        let filter = StatusFilter {
+            statuses: if req.only_stale.unwrap_or(false) {
+                Status::OUT_OF_DATE.to_vec()
+            } else {
+                Vec::new()
+            },
+            ..StatusFilter::default()
         };
 
-        let result = match cache.sync(doc_path.as_deref()) {
-            Ok(r) => r,
+        let response = if req.dry_run.unwrap_or(false) {
+            cache
+                .sync_plan(doc_path.as_deref(), &filter)
+                .map(|plan| serde_json::to_string_pretty(&plan))
+        } else {
+            cache
+                .sync_matching(doc_path.as_deref(), &filter, None)
+                .map(|result| serde_json::to_string_pretty(&SyncResponse::from(result)))
+        };
+        match response {
+            Ok(Ok(json)) => json,
+            Ok(Err(e)) => format!("Error serializing response: {e}"),
             Err(ContextError::InvalidReferences { count, documents }) => {
                 // Format a detailed error message for invalid references
                 use std::fmt::Write;
@@ -203,36 +819,184 @@ impl ContextServer {
                         let _ = writeln!(msg, "  - {}: {}", r.path, r.reason);
                     }
                 }
-                return msg;
+                msg
             }
+            Err(e) => format!("Error: {e}"),
+        }
+    }
+
+    fn get(&self, req: &GetRequest, ct: &CancellationToken) -> String {
+        let cache = match self.load_cache(req.root.as_deref(), ct) {
+            Ok(c) => c,
             Err(e) => return format!("Error: {e}"),
         };
 
-        let response = SyncResponse::from(result);
+        let doc = match cache.get_with_body(&req.document) {
+            Ok(d) => d,
+            Err(e) => return format!("Error: {e}"),
+        };
+        if let Err(e) = cache.record_read(&doc, "mcp:context_get") {
+            tracing::warn!("failed to record document read: {e}");
+        }
+
+        let body = cache.redacted_body(&doc);
+        let (breadcrumb, body) = match &req.section {
+            Some(heading) => {
+                let index = sections::index(&body);
+                match sections::find(&index, heading) {
+                    Some(s) => (s.breadcrumb.clone(), sections::extract(&body, s)),
+                    None => {
+                        return format!("Error: no section '{heading}' in {}", doc.path.display())
+                    }
+                }
+            }
+            None => (Vec::new(), body),
+        };
+
+        let response = GetResponse {
+            path: doc.path.display().to_string(),
+            slug: doc.slug.clone(),
+            namespace: doc.namespace().to_string(),
+            description: doc.description.clone(),
+            breadcrumb,
+            body,
+        };
         match serde_json::to_string_pretty(&response) {
             Ok(json) => json,
             Err(e) => format!("Error serializing response: {e}"),
         }
     }
 
-    #[tool(description = "Find all context documents that reference the given source file path(s)")]
-    #[allow(clippy::unused_self)]
-    fn context_find(&self, Parameters(req): Parameters<FindRequest>) -> String {
-        let cache = match Self::load_cache() {
+    fn search(&self, req: &SearchRequest, ct: &CancellationToken) -> String {
+        const DEFAULT_LIMIT: usize = 20;
+
+        let mut cache = match self.open_cache(req.root.as_deref(), ct) {
             Ok(c) => c,
             Err(e) => return format!("Error: {e}"),
         };
+        cache.set_include_archived(req.include_archived.unwrap_or(false));
+
+        let offset = match parse_cursor(req.cursor.as_deref()) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let limit = req.limit.unwrap_or(DEFAULT_LIMIT);
+
+        let state = match req
+            .state
+            .as_deref()
+            .map(str::parse::<DocumentState>)
+            .transpose()
+        {
+            Ok(s) => s,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        // Agents get the most trustworthy documents first
+        let mut hits = match cache.search_ranked(&req.query) {
+            Ok(h) => h,
+            Err(e) => return format!("Error: {e}"),
+        };
+        if let Some(state) = state {
+            hits.retain(|hit| hit.state == state);
+        }
+        let has_more = hits.len() > offset.saturating_add(limit);
+        let items: Vec<_> = hits
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|mut hit| {
+                for m in &mut hit.matches {
+                    m.text = cache.redact(&hit.document, &m.text);
+                }
+                hit
+            })
+            .collect();
+
+        let response = SearchResponse {
+            next_cursor: has_more.then(|| (offset + items.len()).to_string()),
+            items,
+        };
+        match serde_json::to_string_pretty(&response) {
+            Ok(json) => json,
+            Err(e) => format!("Error serializing response: {e}"),
+        }
+    }
+
+    fn find(&self, req: &FindRequest, ct: &CancellationToken) -> String {
+        let cache = match self.load_cache(req.root.as_deref(), ct) {
+            Ok(c) => c,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let offset = match parse_cursor(req.cursor.as_deref()) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
 
         let mut results: Vec<FindResultItem> = Vec::new();
+        let mut has_more = false;
+
+        let found = match cache.find_by_references(&req.paths) {
+            Ok(found) => found,
+            Err(e) => return format!("Error: {e}"),
+        };
+        for r in found {
+            let r = r.paginate(offset, req.limit);
+            has_more |= offset + r.matches.len() < r.total;
+            results.push(FindResultItem::from(r));
+        }
+
+        let response = FindResponse {
+            results,
+            next_cursor: (req.limit.is_some() && has_more)
+                .then(|| (offset + req.limit.unwrap_or_default()).to_string()),
+        };
+        match serde_json::to_string_pretty(&response) {
+            Ok(json) => json,
+            Err(e) => format!("Error serializing response: {e}"),
+        }
+    }
+
+    fn pack(&self, req: &PackRequest, ct: &CancellationToken) -> String {
+        let cache = match self.load_cache(req.root.as_deref(), ct) {
+            Ok(c) => c,
+            Err(e) => return format!("Error: {e}"),
+        };
+
+        let hops = req.hops.unwrap_or(pack::DEFAULT_HOPS);
+        let (documents, terms) = match (&req.paths, &req.task) {
+            (Some(paths), None) => (cache.pack_files(paths, hops), Vec::new()),
+            (None, Some(task)) => (cache.pack_task(task, hops), pack::task_terms(task)),
+            _ => return "Error: give either paths or task".to_string(),
+        };
+        let documents = match documents {
+            Ok(d) => d,
+            Err(e) => return format!("Error: {e}"),
+        };
 
-        for path in &req.paths {
-            match cache.find_by_reference(path) {
-                Ok(r) => results.push(FindResultItem::from(r)),
-                Err(e) => return format!("Error searching for '{path}': {e}"),
+        let packed = pack::excerpts(
+            &documents,
+            &terms,
+            req.max_tokens.unwrap_or(pack::DEFAULT_MAX_TOKENS),
+        );
+        for doc in &documents {
+            if !packed.excerpts.iter().any(|e| e.path == doc.path) {
+                continue;
+            }
+            if let Ok(doc) = cache.get(&doc.path.display().to_string()) {
+                if let Err(e) = cache.record_read(doc, "mcp:context_pack") {
+                    tracing::warn!("failed to record document read: {e}");
+                }
             }
         }
 
-        match serde_json::to_string_pretty(&results) {
+        let response = PackResponse {
+            excerpts: packed.excerpts,
+            tokens: packed.tokens,
+            omitted: packed.omitted,
+        };
+        match serde_json::to_string_pretty(&response) {
             Ok(json) => json,
             Err(e) => format!("Error serializing response: {e}"),
         }
@@ -244,9 +1008,12 @@ impl ServerHandler for ContextServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             instructions: Some(
-                "Context documentation cache server. Use context_status to check document validity, \
-                 context_find to locate documents referencing source files, and context_sync to \
-                 update hashes after reviewing documentation."
+                "Context documentation cache server. Use context_info to check the server version and \
+                 repository, context_status to check document validity, \
+                 context_find to locate documents referencing source files, context_search to \
+                 search document text, context_get to read a document or section, context_pack to \
+                 assemble excerpts for a task within a token budget, and context_sync to update hashes after reviewing \
+                 documentation."
                     .into(),
             ),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
@@ -255,8 +1022,29 @@ impl ServerHandler for ContextServer {
     }
 }
 
+/// Poll `config.toml` and swap in edits, logging what changed
+async fn watch_config(served: Arc<ServedRoot>) {
+    let Some(config) = &served.config else { return };
+    let root = &served.name;
+    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        match config.reload() {
+            Ok(None) => {}
+            Ok(Some(changed)) if changed.is_empty() => tracing::info!(root, "reloaded config.toml"),
+            Ok(Some(changed)) => {
+                tracing::info!(root, "reloaded config.toml: {} changed", changed.join(", "));
+            }
+            Err(e) => tracing::error!(
+                root,
+                "ignoring invalid config.toml, keeping the previous configuration: {e}"
+            ),
+        }
+    }
+}
+
 /// Start the Context MCP server over stdio
-pub async fn run_server() -> Result<()> {
+pub async fn run_server(server: ContextServer) -> Result<()> {
     // Initialize the tracing subscriber with stderr logging
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
@@ -266,13 +1054,51 @@ pub async fn run_server() -> Result<()> {
 
     tracing::info!("Starting Context MCP server");
 
-    let service = ContextServer::new()
-        .serve(stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("serving error: {:?}", e);
-        })?;
+    for root in &server.roots {
+        if root.config.is_some() {
+            tokio::spawn(watch_config(Arc::clone(root)));
+        }
+    }
+
+    let service = server.serve(stdio()).await.inspect_err(|e| {
+        tracing::error!("serving error: {:?}", e);
+    })?;
 
     service.waiting().await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn root(dir: &str) -> ContextRoot {
+        ContextRoot {
+            context_dir: PathBuf::from(dir).join(".context"),
+            marker: None,
+            searched_from: PathBuf::from(dir),
+        }
+    }
+
+    fn name(served: Option<&ServedRoot>) -> Option<&str> {
+        served.map(|r| r.name.as_str())
+    }
+
+    #[test]
+    fn test_served_root_selection() {
+        let cwd = ContextServer::new();
+        assert!(cwd.served_root(None).unwrap().is_none());
+        assert!(cwd.served_root(Some("a")).is_err());
+
+        let single = ContextServer::new().with_root("a".to_string(), root("/nonexistent/a"));
+        assert_eq!(name(single.served_root(None).unwrap()), Some("a"));
+
+        let multi = ContextServer::new()
+            .with_root("a".to_string(), root("/nonexistent/a"))
+            .with_root("b".to_string(), root("/nonexistent/b"));
+        assert_eq!(name(multi.served_root(Some("b")).unwrap()), Some("b"));
+        assert!(multi.served_root(None).unwrap_err().contains("one of a, b"));
+        assert!(multi.served_root(Some("c")).is_err());
+    }
+}
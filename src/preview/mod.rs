@@ -0,0 +1,4 @@
+//! Local website for browsing the context tree (`context preview`)
+
+pub mod render;
+pub mod server;
@@ -0,0 +1,367 @@
+//! Minimal markdown to HTML rendering for the preview server
+//!
+//! Covers what context documents use in practice: ATX headings (with the
+//! same anchors as search deep links), paragraphs, lists, block quotes,
+//! fenced code, tables, rules, and inline code, emphasis, links and images.
+//! Anything else is shown as text. All text is HTML-escaped, so raw HTML in
+//! a document is displayed rather than interpreted.
+
+use crate::core::sections::{anchor, parse_heading};
+use std::fmt::Write;
+
+/// Escape text for use in HTML element content or attribute values
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Render a markdown body, passing every link and image target through `link`
+pub fn markdown(body: &str, link: &dyn Fn(&str) -> String) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let raw = lines[i];
+        let trimmed = raw.trim();
+        let starts_block = trimmed.is_empty()
+            || fence(trimmed).is_some()
+            || parse_heading(raw).is_some()
+            || is_rule(trimmed)
+            || trimmed.starts_with('>')
+            || list_item(trimmed).is_some()
+            || (trimmed.starts_with('|')
+                && lines.get(i + 1).is_some_and(|next| is_separator(next)));
+        if !starts_block {
+            paragraph.push(trimmed);
+            i += 1;
+            continue;
+        }
+        flush_paragraph(&mut html, &mut paragraph, link);
+
+        if trimmed.is_empty() {
+            i += 1;
+        } else if let Some(marker) = fence(trimmed) {
+            let language = trimmed.trim_start_matches(marker).split_whitespace().next();
+            let mut code = String::new();
+            i += 1;
+            while i < lines.len() && !lines[i].trim().starts_with(marker) {
+                code.push_str(lines[i]);
+                code.push('\n');
+                i += 1;
+            }
+            i += 1;
+            let _ = match language {
+                Some(lang) => writeln!(
+                    html,
+                    "<pre><code class=\"language-{}\">{}</code></pre>",
+                    escape(lang),
+                    escape(&code)
+                ),
+                None => writeln!(html, "<pre><code>{}</code></pre>", escape(&code)),
+            };
+        } else if let Some((level, title)) = parse_heading(raw) {
+            let _ = writeln!(
+                html,
+                "<h{level} id=\"{}\">{}</h{level}>",
+                escape(&anchor(&title)),
+                inline(&title, link)
+            );
+            i += 1;
+        } else if is_rule(trimmed) {
+            html.push_str("<hr>\n");
+            i += 1;
+        } else if trimmed.starts_with('>') {
+            let mut quoted = String::new();
+            while i < lines.len() && lines[i].trim().starts_with('>') {
+                let inner = lines[i].trim().trim_start_matches('>');
+                quoted.push_str(inner.strip_prefix(' ').unwrap_or(inner));
+                quoted.push('\n');
+                i += 1;
+            }
+            let _ = write!(
+                html,
+                "<blockquote>\n{}</blockquote>\n",
+                markdown(&quoted, link)
+            );
+        } else if let Some((ordered, _)) = list_item(trimmed) {
+            i = render_list(&mut html, &lines, i, ordered, link);
+        } else {
+            i = render_table(&mut html, &lines, i, link);
+        }
+    }
+    flush_paragraph(&mut html, &mut paragraph, link);
+    html
+}
+
+fn flush_paragraph(html: &mut String, paragraph: &mut Vec<&str>, link: &dyn Fn(&str) -> String) {
+    if !paragraph.is_empty() {
+        let _ = writeln!(html, "<p>{}</p>", inline(&paragraph.join("\n"), link));
+        paragraph.clear();
+    }
+}
+
+/// The fence marker a line opens a code block with
+fn fence(line: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| line.starts_with(marker))
+}
+
+/// `---`, `***` or `___`, optionally spaced out
+fn is_rule(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|m| marks.chars().all(|c| c == *m))
+}
+
+/// Whether a list item is ordered, and its text
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some((false, text));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    (digits > 0)
+        .then(|| rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")))
+        .flatten()
+        .map(|text| (true, text))
+}
+
+/// Render consecutive items of one list, returning the next line to read
+fn render_list(
+    html: &mut String,
+    lines: &[&str],
+    mut i: usize,
+    ordered: bool,
+    link: &dyn Fn(&str) -> String,
+) -> usize {
+    let mut items: Vec<String> = Vec::new();
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        match list_item(trimmed) {
+            Some((o, text)) if o == ordered => items.push(text.to_string()),
+            Some(_) => break,
+            None if trimmed.is_empty() => {
+                // A blank line only continues the list if another item follows
+                let next = lines.get(i + 1).and_then(|l| list_item(l.trim()));
+                if next.is_none_or(|(o, _)| o != ordered) {
+                    break;
+                }
+            }
+            None if fence(trimmed).is_some() || parse_heading(lines[i]).is_some() => break,
+            None => {
+                if let Some(item) = items.last_mut() {
+                    item.push('\n');
+                    item.push_str(trimmed);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let tag = if ordered { "ol" } else { "ul" };
+    let _ = writeln!(html, "<{tag}>");
+    for item in items {
+        let (checkbox, text) = if let Some(text) = item.strip_prefix("[ ] ") {
+            ("<input type=\"checkbox\" disabled> ", text)
+        } else if let Some(text) = item
+            .strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+        {
+            ("<input type=\"checkbox\" checked disabled> ", text)
+        } else {
+            ("", item.as_str())
+        };
+        let _ = writeln!(html, "<li>{checkbox}{}</li>", inline(text, link));
+    }
+    let _ = writeln!(html, "</{tag}>");
+    i
+}
+
+fn cells(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(str::trim).collect()
+}
+
+/// The `|---|:---:|` row between a table's header and body
+fn is_separator(line: &str) -> bool {
+    line.trim().starts_with('|')
+        && cells(line)
+            .iter()
+            .all(|c| c.contains('-') && c.chars().all(|ch| matches!(ch, '-' | ':' | ' ')))
+}
+
+/// Render a table starting at its header row, returning the next line to read
+fn render_table(
+    html: &mut String,
+    lines: &[&str],
+    mut i: usize,
+    link: &dyn Fn(&str) -> String,
+) -> usize {
+    html.push_str("<table>\n<thead><tr>");
+    for cell in cells(lines[i]) {
+        let _ = write!(html, "<th>{}</th>", inline(cell, link));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    i += 2;
+    while i < lines.len() && lines[i].trim().starts_with('|') {
+        html.push_str("<tr>");
+        for cell in cells(lines[i]) {
+            let _ = write!(html, "<td>{}</td>", inline(cell, link));
+        }
+        html.push_str("</tr>\n");
+        i += 1;
+    }
+    html.push_str("</tbody>\n</table>\n");
+    i
+}
+
+/// Render inline code, emphasis, links, images and autolinks
+fn inline(text: &str, link: &dyn Fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                let _ = write!(out, "<code>{}</code>", escape(&rest[1..=end]));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if let Some(inner) = rest.strip_prefix("**") {
+            if let Some(end) = inner.find("**").filter(|&end| end > 0) {
+                let _ = write!(out, "<strong>{}</strong>", inline(&inner[..end], link));
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        if c == '*' && !rest[1..].starts_with([' ', '*']) {
+            if let Some(end) = rest[1..].find('*').filter(|&end| end > 0) {
+                let _ = write!(out, "<em>{}</em>", inline(&rest[1..=end], link));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if c == '!' {
+            if let Some((alt, target, len)) = parse_link(&rest[1..]) {
+                let _ = write!(
+                    out,
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape(&link(target)),
+                    escape(alt)
+                );
+                rest = &rest[1 + len..];
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((label, target, len)) = parse_link(rest) {
+                let _ = write!(
+                    out,
+                    "<a href=\"{}\">{}</a>",
+                    escape(&link(target)),
+                    inline(label, link)
+                );
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        if c == '<' && (rest.starts_with("<http://") || rest.starts_with("<https://")) {
+            if let Some(end) = rest.find('>') {
+                let url = &rest[1..end];
+                let _ = write!(out, "<a href=\"{0}\">{0}</a>", escape(url));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push_str(&escape(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Parse `[label](target "title")` at the start of `text` into the label,
+/// the target and the length consumed
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let inner = text.strip_prefix('[')?;
+    let mut depth = 0;
+    let close = inner.char_indices().find_map(|(i, c)| match c {
+        '[' => {
+            depth += 1;
+            None
+        }
+        ']' if depth == 0 => Some(i),
+        ']' => {
+            depth -= 1;
+            None
+        }
+        _ => None,
+    })?;
+    let after = inner[close + 1..].strip_prefix('(')?;
+    let end = after.find(')')?;
+    let target = after[..end].split_whitespace().next().unwrap_or("");
+    Some((&inner[..close], target, 1 + close + 2 + end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(body: &str) -> String {
+        markdown(body, &|target| target.to_string())
+    }
+
+    #[test]
+    fn test_blocks() {
+        let html = render(
+            "# Token Refresh\n\nTokens *expire*\nhourly.\n\n- [x] rotate keys\n- see `auth.rs`\n\n```rust\nlet a = 1 < 2;\n```\n",
+        );
+        assert_eq!(
+            html,
+            "<h1 id=\"token-refresh\">Token Refresh</h1>\n\
+             <p>Tokens <em>expire</em>\nhourly.</p>\n\
+             <ul>\n<li><input type=\"checkbox\" checked disabled> rotate keys</li>\n<li>see <code>auth.rs</code></li>\n</ul>\n\
+             <pre><code class=\"language-rust\">let a = 1 &lt; 2;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_tables_and_quotes() {
+        let html = render("| Key | Value |\n|-----|:-----:|\n| a | **b** |\n\n> Note\n");
+        assert!(html.contains("<thead><tr><th>Key</th><th>Value</th></tr></thead>"));
+        assert!(html.contains("<tr><td>a</td><td><strong>b</strong></td></tr>"));
+        assert!(html.contains("<blockquote>\n<p>Note</p>\n</blockquote>"));
+    }
+
+    #[test]
+    fn test_links_are_rewritten_and_html_escaped() {
+        let html = markdown("See [the guide](auth.md \"Auth\") and <script>.", &|t| {
+            format!("/doc/{t}")
+        });
+        assert_eq!(
+            html,
+            "<p>See <a href=\"/doc/auth.md\">the guide</a> and &lt;script&gt;.</p>\n"
+        );
+        let html = render("![diagram](img/flow.png) <https://example.com>");
+        assert!(html.contains("<img src=\"img/flow.png\" alt=\"diagram\">"));
+        assert!(html.contains("<a href=\"https://example.com\">https://example.com</a>"));
+    }
+}
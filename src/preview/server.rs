@@ -0,0 +1,509 @@
+//! HTTP server behind `context preview`
+//!
+//! Serves the context tree as a small website: an index of every document
+//! with its status and a search box backed by the static search index, and
+//! one page per document with its body rendered and its references linked
+//! to a source browser. Pages poll a fingerprint of
+//! the tree and reload when anything under `.context` changes. The tree is
+//! read afresh for every request, so there is no state to invalidate.
+//!
+//! The server only speaks enough HTTP/1.1 for a browser: `GET` requests,
+//! one per connection. It binds to loopback and rejects requests for any
+//! other host name, since the source browser can show any project file.
+
+use crate::core::document::Document;
+use crate::core::snapshot::SNAPSHOT_DIR;
+use crate::core::{Cache, Status};
+use crate::error::{ContextError, Result};
+use crate::preview::render::{escape, markdown};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use walkdir::WalkDir;
+
+/// Largest request head accepted, in bytes
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// Largest file shown by the built-in source browser, in bytes
+const MAX_SOURCE_BYTES: u64 = 1024 * 1024;
+
+/// How often pages check whether the tree changed, in milliseconds
+const RELOAD_INTERVAL_MS: u32 = 1000;
+
+/// A response to one request
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// HTTP status code
+    pub status: u16,
+    /// Value of the Content-Type header
+    pub content_type: &'static str,
+    /// Response body
+    pub body: String,
+}
+
+/// Serve the context directory at `root` on `listener` until the process stops
+pub async fn run(listener: TcpListener, root: PathBuf) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let root = root.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, root).await {
+                tracing::debug!("preview connection failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, root: PathBuf) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut lines = request.lines();
+    let mut parts = lines.next().unwrap_or_default().split_whitespace();
+    let host = lines
+        .find_map(|l| {
+            l.split_once(':')
+                .filter(|(name, _)| name.eq_ignore_ascii_case("host"))
+        })
+        .map(|(_, value)| value.trim().to_string());
+
+    let response = match (parts.next(), parts.next()) {
+        _ if !host.as_deref().is_some_and(is_loopback) => {
+            error_page(403, "Forbidden: unexpected host")
+        }
+        (Some("GET"), Some(target)) => {
+            let target = target.to_string();
+            tokio::task::spawn_blocking(move || respond(&root, &target))
+                .await
+                .unwrap_or_else(|e| error_page(500, &e.to_string()))
+        }
+        _ => error_page(405, "Only GET is supported"),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Whether a Host header names this machine, guarding against DNS rebinding
+fn is_loopback(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "::1")
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Answer a `GET` for `target` against the context directory at `root`
+pub fn respond(root: &Path, target: &str) -> Response {
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let path = percent_decode(path);
+    let result = if path == "/" {
+        index(root)
+    } else if path == "/search-index.json" {
+        search_index(root)
+    } else if path == "/_version" {
+        Ok(Response {
+            status: 200,
+            content_type: "text/plain; charset=utf-8",
+            body: version(root),
+        })
+    } else if let Some(rel) = path.strip_prefix("/doc/") {
+        document(root, rel)
+    } else if let Some(rel) = path.strip_prefix("/source/") {
+        source(root, rel)
+    } else {
+        Err(ContextError::DocumentNotFound(path))
+    };
+
+    result.unwrap_or_else(|e| match e {
+        ContextError::DocumentNotFound(_) => error_page(404, &e.to_string()),
+        e => error_page(500, &e.to_string()),
+    })
+}
+
+/// Every document, grouped by namespace, with its status
+fn index(root: &Path) -> Result<Response> {
+    let cache = Cache::create(root.to_path_buf())?;
+    let mut docs = Vec::new();
+    for doc in cache.walk().filter_map(std::result::Result::ok) {
+        let status = doc.validate()?.status;
+        docs.push((doc, status));
+    }
+
+    let count = |status| docs.iter().filter(|(_, s)| *s == status).count();
+    let mut html = format!(
+        "<h1>Context</h1>\n<p>{} documents: {} valid, {} stale, {} orphaned</p>\n",
+        docs.len(),
+        count(Status::Valid),
+        count(Status::Stale),
+        count(Status::Orphaned)
+    );
+    html.push_str(SEARCH_BOX);
+    let mut namespace = None;
+    for (doc, status) in &docs {
+        if namespace != Some(doc.namespace()) {
+            if namespace.is_some() {
+                html.push_str("</tbody>\n</table>\n");
+            }
+            namespace = Some(doc.namespace());
+            let _ = write!(
+                html,
+                "<h2>{}</h2>\n<table>\n<thead><tr><th>Slug</th><th>Description</th><th>Status</th><th>Updated</th></tr></thead>\n<tbody>\n",
+                escape(doc.namespace())
+            );
+        }
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            doc_href(root, &doc.path),
+            escape(&doc.slug),
+            escape(&doc.description),
+            badge(*status),
+            escape(&doc.updated)
+        );
+    }
+    if namespace.is_some() {
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    Ok(page("Context", &html, Some(&version(root))))
+}
+
+/// The index the search box queries (see `core::search_index`)
+fn search_index(root: &Path) -> Result<Response> {
+    let cache = Cache::create(root.to_path_buf())?;
+    Ok(Response {
+        status: 200,
+        content_type: "application/json",
+        body: serde_json::to_string(&cache.search_index()?)?,
+    })
+}
+
+/// One rendered document with its references
+fn document(root: &Path, rel: &str) -> Result<Response> {
+    let cache = Cache::create(root.to_path_buf())?;
+    let wanted = root.join(rel);
+    let doc = cache
+        .walk()
+        .filter_map(std::result::Result::ok)
+        .find(|d| d.path == wanted)
+        .ok_or_else(|| ContextError::DocumentNotFound(rel.to_string()))?;
+    let validation = doc.validate()?;
+
+    let mut html = format!(
+        "<nav><a href=\"/\">&larr; All documents</a></nav>\n<h1>{}</h1>\n<p>{} <span class=\"badge\">{}</span> <span class=\"meta\">updated {}</span>",
+        escape(&doc.slug),
+        badge(validation.status),
+        escape(doc.namespace()),
+        escape(&doc.updated)
+    );
+    if !doc.owners.is_empty() {
+        let _ = write!(
+            html,
+            " <span class=\"meta\">owned by {}</span>",
+            escape(&doc.owners.join(", "))
+        );
+    }
+    for tag in &doc.tags {
+        let _ = write!(html, " <span class=\"badge\">#{}</span>", escape(tag));
+    }
+    let _ = writeln!(
+        html,
+        "</p>\n<p class=\"meta\">{}</p>",
+        escape(&doc.description)
+    );
+
+    let body = cache.redacted_body(&doc);
+    html.push_str("<article>\n");
+    html.push_str(&markdown(&body, &|target| body_link(root, &doc, target)));
+    html.push_str("</article>\n");
+
+    if !doc.references.is_empty() {
+        html.push_str("<h2>References</h2>\n<ul>\n");
+        let mut references: Vec<_> = doc.references.keys().collect();
+        references.sort();
+        for reference in references {
+            let state = if validation.missing.contains(reference) {
+                " <span class=\"badge orphaned\">missing</span>"
+            } else if validation.changed.contains(reference) {
+                " <span class=\"badge stale\">changed</span>"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                html,
+                "<li><a href=\"{}\"><code>{}</code></a>{state}</li>",
+                escape(&source_href(&cache, root, &doc, reference)),
+                escape(reference)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    Ok(page(&doc.slug, &html, Some(&version(root))))
+}
+
+/// A project file, as plain text
+fn source(root: &Path, rel: &str) -> Result<Response> {
+    let not_found = || ContextError::DocumentNotFound(rel.to_string());
+    let project = root.parent().ok_or_else(not_found)?.canonicalize()?;
+    let path = project.join(rel).canonicalize().map_err(|_| not_found())?;
+    if !path.starts_with(&project) || !path.is_file() {
+        return Err(not_found());
+    }
+
+    let html = if std::fs::metadata(&path)?.len() > MAX_SOURCE_BYTES {
+        format!(
+            "<h1><code>{}</code></h1>\n<p>Too large to show.</p>\n",
+            escape(rel)
+        )
+    } else {
+        let content = std::fs::read(&path)?;
+        format!(
+            "<h1><code>{}</code></h1>\n<pre><code>{}</code></pre>\n",
+            escape(rel),
+            escape(&String::from_utf8_lossy(&content))
+        )
+    };
+    Ok(page(rel, &html, None))
+}
+
+/// Fingerprint of every file under `root`, which changes whenever any
+/// document, remote checkout or the config changes
+pub fn version(root: &Path) -> String {
+    let mut hasher = Sha256::new();
+    let entries = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !(e.depth() == 1 && e.file_name() == SNAPSHOT_DIR))
+        .filter_map(std::result::Result::ok);
+    for entry in entries {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        hasher.update(entry.path().to_string_lossy().as_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+        hasher.update(modified.as_nanos().to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// Where a link in a document body points: relative links to other
+/// documents become preview pages, anything else is left alone
+fn body_link(root: &Path, doc: &Document, target: &str) -> String {
+    let (file, fragment) = target
+        .split_once('#')
+        .map_or((target, None), |(f, a)| (f, Some(a)));
+    let relative = !file.is_empty() && !file.contains("://") && !file.starts_with('/');
+    if !relative || Path::new(file).extension().is_none_or(|ext| ext != "md") {
+        return target.to_string();
+    }
+    let Some(dir) = doc.path.parent() else {
+        return target.to_string();
+    };
+    let mut href = doc_href(root, &normalize(&dir.join(file)));
+    if let Some(fragment) = fragment {
+        href.push('#');
+        href.push_str(fragment);
+    }
+    href
+}
+
+/// Where a reference can be viewed: the configured source URL for local
+/// documents, or the built-in source browser
+fn source_href(cache: &Cache, root: &Path, doc: &Document, reference: &str) -> String {
+    if doc.remote.is_none() {
+        if let Some(template) = &cache.config().preview.source_url {
+            return template.replace("{path}", reference);
+        }
+    }
+    let resolved = normalize(&doc.resolve_ref_path(reference));
+    let project = root.parent().unwrap_or(root);
+    let rel = resolved.strip_prefix(project).unwrap_or(&resolved);
+    format!("/source/{}", percent_encode(rel))
+}
+
+fn doc_href(root: &Path, path: &Path) -> String {
+    format!(
+        "/doc/{}",
+        percent_encode(path.strip_prefix(root).unwrap_or(path))
+    )
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Encode a relative path for use in a URL, keeping `/` separators
+fn percent_encode(path: &Path) -> String {
+    let mut out = String::new();
+    for (i, component) in path.components().enumerate() {
+        if i > 0 {
+            out.push('/');
+        }
+        for byte in component.as_os_str().to_string_lossy().bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                out.push(char::from(byte));
+            } else {
+                let _ = write!(out, "%{byte:02X}");
+            }
+        }
+    }
+    out
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn badge(status: Status) -> String {
+    format!("<span class=\"badge {status}\">{status}</span>")
+}
+
+fn error_page(status: u16, message: &str) -> Response {
+    let mut response = page(
+        reason(status),
+        &format!("<h1>{}</h1>\n<p>{}</p>\n", reason(status), escape(message)),
+        None,
+    );
+    response.status = status;
+    response
+}
+
+/// Wrap content in the site layout, reloading on changes when `version` is given
+fn page(title: &str, content: &str, version: Option<&str>) -> Response {
+    let reload = version.map_or_else(String::new, |version| {
+        format!(
+            "<script>\nconst version = \"{version}\";\nsetInterval(() => fetch(\"/_version\").then(r => r.text()).then(v => {{ if (v !== version) location.reload(); }}).catch(() => {{}}), {RELOAD_INTERVAL_MS});\n</script>\n"
+        )
+    });
+    let body = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n{content}{reload}</body>\n</html>\n",
+        escape(title)
+    );
+    Response {
+        status: 200,
+        content_type: "text/html; charset=utf-8",
+        body,
+    }
+}
+
+/// Ranks documents by summed `frequency * idf` over the query terms,
+/// treating the last term as a prefix so results appear while typing
+const SEARCH_BOX: &str = r#"<input id="search" type="search" placeholder="Search documents" autofocus>
+<ul id="results"></ul>
+<script>
+let searchIndex;
+const input = document.getElementById("search"), results = document.getElementById("results");
+input.addEventListener("input", async () => {
+  searchIndex = searchIndex || await fetch("/search-index.json").then(r => r.json());
+  const terms = input.value.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter(t => t.length >= 2);
+  const scores = new Map();
+  terms.forEach((term, i) => {
+    const prefix = i === terms.length - 1;
+    for (const [t, postings] of Object.entries(searchIndex.terms)) {
+      if (t !== term && !(prefix && t.startsWith(term))) continue;
+      const idf = Math.log(1 + searchIndex.documents.length / postings.length);
+      for (const [id, frequency] of postings) scores.set(id, (scores.get(id) || 0) + frequency * idf);
+    }
+  });
+  results.replaceChildren(...[...scores].sort((a, b) => b[1] - a[1]).slice(0, 20).map(([id]) => {
+    const doc = searchIndex.documents[id], item = document.createElement("li"), link = document.createElement("a");
+    link.href = "/doc/" + doc.path.split("/").map(encodeURIComponent).join("/");
+    link.textContent = doc.namespace + "/" + doc.slug;
+    item.append(link, " — " + doc.title);
+    return item;
+  }));
+});
+</script>
+"#;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:56rem;margin:2rem auto;padding:0 1rem;line-height:1.5;color:#1f2328}\
+a{color:#0969da}pre{background:#f6f8fa;padding:.75rem;overflow:auto}code{font-family:ui-monospace,monospace}\
+table{border-collapse:collapse}th,td{border:1px solid #d0d7de;padding:.25rem .5rem;text-align:left;vertical-align:top}\
+blockquote{margin:0;padding-left:1rem;border-left:.25rem solid #d0d7de;color:#59636e}\
+.badge{display:inline-block;padding:0 .5rem;border-radius:1rem;font-size:.8rem;background:#eaeef2}\
+.valid{background:#dafbe1}.stale{background:#fff8c5}.orphaned{background:#ffebe9}.meta{color:#59636e}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_round_trip() {
+        let encoded = percent_encode(Path::new("guides/on call.md"));
+        assert_eq!(encoded, "guides/on%20call.md");
+        assert_eq!(percent_decode(&encoded), "guides/on call.md");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_loopback_hosts() {
+        assert!(is_loopback("localhost:4000"));
+        assert!(is_loopback("127.0.0.1:4000"));
+        assert!(is_loopback("[::1]:4000"));
+        assert!(!is_loopback("attacker.example:4000"));
+    }
+}
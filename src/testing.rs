@@ -0,0 +1,174 @@
+//! Fixtures for testing tools that embed the crate
+//!
+//! Enabled by the `testing` feature. A [`Fixture`] is a throwaway project
+//! with a `.context` directory, in which documents can be written directly
+//! or put into a given status, then checked with [`assert_status`]:
+//!
+//! ```ignore
+//! use context::core::Status;
+//! use context::testing::{assert_status, Fixture};
+//!
+//! let fixture = Fixture::new();
+//! fixture.document_with_status("guides/auth.md", "auth", Status::Stale);
+//! assert_status(&fixture.cache(), "auth", Status::Stale);
+//! ```
+//!
+//! Helpers panic on failure, as test code would.
+
+use crate::core::document::Document;
+use crate::core::{Cache, Status, CONTEXT_DIR_NAME};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A temporary project with `.context/guides` and `.context/references`,
+/// deleted when dropped
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fixture {
+    /// Create an empty project
+    pub fn new() -> Self {
+        let dir = TempDir::new().expect("create temporary directory");
+        for sub in ["guides", "references"] {
+            fs::create_dir_all(dir.path().join(CONTEXT_DIR_NAME).join(sub))
+                .expect("create context directory");
+        }
+        Self { dir }
+    }
+
+    /// The project root
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The `.context` directory
+    pub fn context_dir(&self) -> PathBuf {
+        self.root().join(CONTEXT_DIR_NAME)
+    }
+
+    /// Write a file relative to the project root, creating its directories
+    pub fn write(&self, path: &str, content: &str) -> PathBuf {
+        let path = self.root().join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent directory");
+        }
+        fs::write(&path, content).expect("write file");
+        path
+    }
+
+    /// Write an unsynced document at `path` (relative to `.context`) whose
+    /// body mentions each of `references`
+    pub fn document(&self, path: &str, slug: &str, references: &[&str]) -> PathBuf {
+        let mentions: Vec<String> = references.iter().map(|r| format!("- `{r}`\n")).collect();
+        let content = format!(
+            "---\nslug: {slug}\ndescription: {slug}\n---\n\n# {slug}\n\n{}",
+            mentions.concat()
+        );
+        self.write(&format!("{CONTEXT_DIR_NAME}/{path}"), &content)
+    }
+
+    /// Write a document referencing a source file of its own
+    /// (`src/<slug>.rs`), sync it, then change or delete that file to make
+    /// the document stale or orphaned
+    pub fn document_with_status(&self, path: &str, slug: &str, status: Status) -> PathBuf {
+        let source = format!("src/{slug}.rs");
+        let source_path = self.write(&source, &format!("// {slug}\n"));
+        let doc_path = self.document(path, slug, &[&source]);
+        let mut doc = Document::load(&doc_path).expect("load document");
+        doc.sync().expect("sync document");
+        match status {
+            Status::Valid => {}
+            Status::Stale => {
+                fs::write(&source_path, format!("// {slug}, changed\n"))
+                    .expect("change source file");
+            }
+            Status::Orphaned => fs::remove_file(&source_path).expect("remove source file"),
+        }
+        doc_path
+    }
+
+    /// A cache of the fixture's context directory, loaded
+    pub fn cache(&self) -> Cache {
+        let mut cache = Cache::create(self.context_dir()).expect("create cache");
+        cache.load().expect("load documents");
+        cache
+    }
+
+    /// Sync every document
+    pub fn sync(&self) {
+        self.cache().sync(None).expect("sync documents");
+    }
+}
+
+/// Assert that a document, by slug or path, has `expected` status, naming
+/// its changed and missing references otherwise
+pub fn assert_status(cache: &Cache, slug_or_path: &str, expected: Status) {
+    let doc = cache
+        .get(slug_or_path)
+        .unwrap_or_else(|e| panic!("no document '{slug_or_path}': {e}"));
+    let validation = doc.validate().expect("validate document");
+    assert_eq!(
+        validation.status, expected,
+        "document '{slug_or_path}' is {} (changed: {:?}, missing: {:?})",
+        validation.status, validation.changed, validation.missing
+    );
+}
+
+/// Assert the status of several documents at once
+pub fn assert_statuses(cache: &Cache, expected: &[(&str, Status)]) {
+    for (slug_or_path, status) in expected {
+        assert_status(cache, slug_or_path, *status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_documents_with_status() {
+        let fixture = Fixture::new();
+        fixture.document_with_status("guides/valid.md", "valid", Status::Valid);
+        fixture.document_with_status("guides/stale.md", "stale", Status::Stale);
+        fixture.document_with_status("references/orphaned.md", "orphaned", Status::Orphaned);
+        assert_statuses(
+            &fixture.cache(),
+            &[
+                ("valid", Status::Valid),
+                ("stale", Status::Stale),
+                ("references/orphaned.md", Status::Orphaned),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unsynced_document_becomes_valid_after_sync() {
+        let fixture = Fixture::new();
+        fixture.write("src/lib.rs", "// lib");
+        fixture.document("guides/lib.md", "lib", &["src/lib.rs"]);
+        fixture.sync();
+        let cache = fixture.cache();
+        assert!(cache
+            .get("lib")
+            .unwrap()
+            .references
+            .contains_key("src/lib.rs"));
+        assert_status(&cache, "lib", Status::Valid);
+    }
+
+    #[test]
+    #[should_panic(expected = "document 'lib' is stale")]
+    fn test_assert_status_explains_mismatch() {
+        let fixture = Fixture::new();
+        fixture.document_with_status("guides/lib.md", "lib", Status::Stale);
+        assert_status(&fixture.cache(), "lib", Status::Valid);
+    }
+}
@@ -0,0 +1,100 @@
+//! Integration tests for archiving deprecated documents
+
+use context::core::archive::{self, ArchiveEntry};
+use context::core::{Cache, StatusFilter};
+use std::fs;
+use tempfile::TempDir;
+
+/// A project with a deprecated guide linked from its successor and a reference
+fn setup_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/old.rs"), "// old").unwrap();
+    fs::write(dir.path().join("src/new.rs"), "// new").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::create_dir_all(context_dir.join("references")).unwrap();
+    fs::write(
+        context_dir.join("guides/old.md"),
+        "---\nslug: old\nstate: deprecated\nsuperseded_by: new\n---\n\nSee `src/old.rs` and [new](new.md). Legacy tokens.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/new.md"),
+        "---\nslug: new\n---\n\nSee `src/new.rs`; formerly [old](old.md).\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("references/api.md"),
+        "---\nslug: api\n---\n\nSee [the old guide](../guides/old.md).\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    dir
+}
+
+fn cache(dir: &TempDir) -> Cache {
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache
+}
+
+#[test]
+fn test_archive_moves_deprecated_documents_and_relinks() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+
+    // Nothing is written in a dry run, and the document isn't old enough yet
+    let plan = cache(&dir).archive(None, true).unwrap();
+    assert_eq!(plan.archived.len(), 1);
+    assert_eq!(plan.relinked.len(), 2);
+    assert!(context_dir.join("guides/old.md").exists());
+    assert!(cache(&dir)
+        .archive(Some(30), false)
+        .unwrap()
+        .archived
+        .is_empty());
+
+    let result = cache(&dir).archive(None, false).unwrap();
+    let archived = context_dir.join("archive/guides/old.md");
+    assert_eq!(result.archived[0].to, archived);
+    assert!(!context_dir.join("guides/old.md").exists());
+    assert!(fs::read_to_string(&archived)
+        .unwrap()
+        .contains("[new](../../guides/new.md)"));
+    assert!(fs::read_to_string(context_dir.join("guides/new.md"))
+        .unwrap()
+        .contains("[old](../archive/guides/old.md)"));
+    assert!(fs::read_to_string(context_dir.join("references/api.md"))
+        .unwrap()
+        .contains("[the old guide](../archive/guides/old.md)"));
+
+    let lines = archive::journal(&context_dir).lines().unwrap();
+    let entry: ArchiveEntry = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(
+        (entry.slug.as_str(), entry.to.as_path()),
+        ("old", archived.strip_prefix(&context_dir).unwrap())
+    );
+
+    // Archived documents drop out of status and coverage, but not search
+    let mut cache = cache(&dir);
+    let slugs: Vec<_> = cache
+        .list(&StatusFilter::default())
+        .unwrap()
+        .into_iter()
+        .map(|d| d.slug)
+        .collect();
+    assert_eq!(slugs, vec!["new", "api"]);
+    assert!(cache
+        .coverage(&[])
+        .unwrap()
+        .uncovered
+        .contains(&"src/old.rs".to_string()));
+    assert_eq!(cache.search("Legacy").count(), 0);
+    cache.set_include_archived(true);
+    assert_eq!(cache.search("Legacy").count(), 1);
+}
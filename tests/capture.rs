@@ -0,0 +1,97 @@
+//! Integration tests for drafting documents from git history
+
+use context::core::capture::PROSE_PLACEHOLDER;
+use context::core::{Cache, Status};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_capture_drafts_document_from_branch() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q", "-b", "main"]);
+    write(root, "src/auth.rs", "fn login() {}");
+    write(root, "src/old.rs", "fn old() {}");
+    write(
+        root,
+        ".context/index.md",
+        "---\nslug: index\n---\n\n# Docs\n",
+    );
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    git(root, &["checkout", "-q", "-b", "feature/Token-Refresh"]);
+    write(root, "src/auth.rs", "fn login() { refresh() }");
+    git(root, &["commit", "-qam", "Refresh tokens on login"]);
+    write(root, "src/refresh.rs", "fn refresh() {}");
+    git(root, &["rm", "-q", "src/old.rs"]);
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "Add refresh module"]);
+
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+
+    let draft = cache
+        .capture("main..feature/Token-Refresh", None, None, true)
+        .unwrap();
+    assert!(!draft.written);
+    assert_eq!(draft.slug, "feature-token-refresh");
+    assert!(!draft.path.exists());
+    let subjects: Vec<_> = draft.commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(
+        subjects,
+        vec!["Refresh tokens on login", "Add refresh module"]
+    );
+    assert_eq!(draft.references, vec!["src/auth.rs", "src/refresh.rs"]);
+    assert!(draft.body.contains(PROSE_PLACEHOLDER));
+    assert!(draft.body.contains("- Add refresh module ("));
+
+    let written = cache
+        .capture(
+            "main..feature/Token-Refresh",
+            Some("token-refresh"),
+            Some(Path::new("guides/refresh.md")),
+            false,
+        )
+        .unwrap();
+    assert!(written.written);
+    assert_eq!(written.path, root.join(".context/guides/refresh.md"));
+
+    let mut reloaded = Cache::create(root.join(".context")).unwrap();
+    reloaded.load().unwrap();
+    let doc = reloaded.get("token-refresh").unwrap();
+    assert_eq!(doc.description, "Refresh tokens on login");
+    let mut references: Vec<_> = doc.references.keys().cloned().collect();
+    references.sort();
+    assert_eq!(references, vec!["src/auth.rs", "src/refresh.rs"]);
+    assert_eq!(doc.validate().unwrap().status, Status::Valid);
+
+    let again = cache.capture(
+        "main..feature/Token-Refresh",
+        Some("token-refresh"),
+        Some(Path::new("guides/refresh.md")),
+        false,
+    );
+    assert!(again.is_err());
+    assert!(cache
+        .capture("feature/Token-Refresh..main", None, None, true)
+        .is_err());
+}
@@ -0,0 +1,111 @@
+//! Pinning the clock for `updated` dates, from the library and the CLI
+
+use context::core::clock::{self, FixedClock, FAKE_TIME_ENV};
+use context::core::document::Document;
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn setup_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "// lib").unwrap();
+    fs::write(
+        dir.path().join(".context/lib.md"),
+        "---\nslug: lib\n---\n\nSee `src/lib.rs`.\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_installed_clock_dates_synced_documents() {
+    let dir = setup_project();
+    clock::set(Some(Arc::new(FixedClock(
+        clock::parse("2024-02-29T09:00:00+01:00").unwrap(),
+    ))));
+    let mut doc = Document::load(dir.path().join(".context/lib.md")).unwrap();
+    doc.sync().unwrap();
+    clock::set(None);
+    assert_eq!(doc.updated, "2024-02-29");
+}
+
+#[test]
+fn test_fake_time_environment_variable() {
+    let dir = setup_project();
+    let run = |fake_time: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_context"))
+            .args(args)
+            .env(FAKE_TIME_ENV, fake_time)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+    assert!(run("2023-06-01", &["sync"]).status.success());
+    let written = fs::read_to_string(dir.path().join(".context/lib.md")).unwrap();
+    assert!(written.contains("updated: 2023-06-01"), "{written}");
+
+    // The flag wins over the variable, which must be valid
+    fs::write(
+        dir.path().join(".context/lib.md"),
+        "---\nslug: lib\n---\n\nSee `src/lib.rs` again.\n",
+    )
+    .unwrap();
+    assert!(run(
+        "2023-06-01",
+        &["--fixed-time", "2023-07-01T10:00:00Z", "sync"]
+    )
+    .status
+    .success());
+    let written = fs::read_to_string(dir.path().join(".context/lib.md")).unwrap();
+    assert!(written.contains("updated: 2023-07-01"), "{written}");
+    let invalid = run("next tuesday", &["status"]);
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains(FAKE_TIME_ENV));
+}
+
+#[test]
+fn test_updated_stamp_follows_configured_zone_and_format() {
+    let dir = setup_project();
+    let doc = dir.path().join(".context/lib.md");
+    let sync_with = |config: &str, body: &str| {
+        fs::write(dir.path().join(".context/config.toml"), config).unwrap();
+        fs::write(
+            &doc,
+            format!("---\nslug: lib\n---\n\nSee `src/lib.rs`{body}.\n"),
+        )
+        .unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_context"))
+            .args(["--fixed-time", "2024-01-15T23:30:00-05:00", "sync"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let mut cache = context::Cache::create(dir.path().join(".context")).unwrap();
+        cache.load().unwrap();
+        cache.get("lib").unwrap().updated.clone()
+    };
+
+    assert_eq!(sync_with("", ""), "2024-01-15");
+    assert_eq!(
+        sync_with("[updated]\ntimezone = \"utc\"\n", " now"),
+        "2024-01-16"
+    );
+    assert_eq!(
+        sync_with(
+            "[updated]\ntimezone = \"utc\"\nformat = \"timestamp\"\n",
+            " again"
+        ),
+        "2024-01-16T04:30:00Z"
+    );
+    assert_eq!(
+        sync_with("[updated]\nformat = \"timestamp\"\n", " once more"),
+        "2024-01-15T23:30:00-05:00"
+    );
+}
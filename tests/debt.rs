@@ -0,0 +1,59 @@
+//! Integration tests for documentation debt scoring
+
+use chrono::{Days, Local};
+use context::core::{Cache, Status};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_debt_scores_out_of_date_documents() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+    fs::write(dir.path().join("src/b.rs"), "fn b() {}\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::write(
+        context_dir.join("guides/auth.md"),
+        "# Auth\n\nSee `src/a.rs` and `src/b.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("overview.md"),
+        "# Overview\n\nSee `src/b.rs`.\n",
+    )
+    .unwrap();
+    fs::write(context_dir.join("config.toml"), "[usage]\ntrack = true\n").unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    cache
+        .record_read(cache.get("auth").unwrap(), "mcp:context_get")
+        .unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a2() {}\n").unwrap();
+
+    let today = Local::now()
+        .date_naive()
+        .checked_add_days(Days::new(45))
+        .unwrap();
+    let report = cache.debt(today).unwrap();
+
+    let auth = &report.documents[0];
+    assert_eq!(auth.slug, "auth");
+    assert_eq!(auth.status, Status::Stale);
+    assert_eq!(
+        (auth.stale_days, auth.references, auth.broken, auth.reads),
+        (45, 2, 1, 1)
+    );
+    // Outside git each broken reference counts as one commit:
+    // staleness 20 + churn 3 + coverage 10 + usage 10
+    assert!((auth.score - 43.0).abs() < 1e-9);
+
+    let overview = &report.documents[1];
+    assert!(overview.score.abs() < 1e-9);
+    assert!((report.directories["guides"].score - 43.0).abs() < 1e-9);
+    assert!(report.directories["."].score.abs() < 1e-9);
+    assert!((report.score - 21.5).abs() < 1e-9);
+}
@@ -0,0 +1,68 @@
+//! Integration tests for diagram checks and rendering
+
+use context::core::Cache;
+use std::fs;
+use tempfile::TempDir;
+
+const BODY: &str = "# Flow\n\n```mermaid\nflowchart LR\n  A[Login] --> B(Token)\n```\n\n```mermaid\ngrpah TD\n  A --> B\n```\n";
+
+fn setup_project(config: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(context_dir.join("config.toml"), config).unwrap();
+    fs::write(
+        context_dir.join("auth.md"),
+        format!("---\nslug: auth\ndescription: Login\n---\n\n{BODY}"),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_lint_flags_broken_diagrams() {
+    let dir = setup_project("");
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+
+    let issues = cache.lint();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].rule, "broken-diagram");
+    assert!(
+        issues[0].message.contains("line 8"),
+        "{}",
+        issues[0].message
+    );
+    assert!(issues[0].message.contains("grpah"));
+}
+
+#[test]
+fn test_render_diagrams_with_configured_command() {
+    // Stands in for `mmdc`: rejects diagrams mentioning "fail", renders the rest
+    let dir = setup_project("[diagrams]\nmermaid = \"grep -q fail && exit 1; echo '<svg/>'\"\n");
+    fs::write(
+        dir.path().join(".context/auth.md"),
+        "---\nslug: auth\n---\n\n```mermaid\ngraph TD\n  A --> B\n```\n\n```mermaid\ngraph TD\n  fail --> B\n```\n\n```plantuml\n@startuml\n@enduml\n```\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+
+    let output = dir.path().join("svg");
+    let rendered = cache.render_diagrams(&output).unwrap();
+    assert_eq!(rendered.len(), 3);
+    assert_eq!(rendered[0].svg, Some(output.join("auth-1.svg")));
+    assert_eq!(
+        fs::read_to_string(output.join("auth-1.svg")).unwrap(),
+        "<svg/>\n"
+    );
+    assert!(rendered[1].error.is_some());
+    // No PlantUML command is configured
+    assert!(rendered[2].svg.is_none() && rendered[2].error.is_none());
+
+    let issues = cache.lint();
+    assert_eq!(
+        issues.iter().filter(|i| i.rule == "broken-diagram").count(),
+        1
+    );
+}
@@ -0,0 +1,111 @@
+//! Integration tests for summarizing changes to the context tree
+
+use context::core::docdiff::Change;
+use context::core::{Cache, Status};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn synced_cache(root: &Path) -> Cache {
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    cache
+}
+
+#[test]
+fn test_diff_docs_between_revisions_and_working_tree() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q", "-b", "main"]);
+    write(root, "src/auth.rs", "fn login() {}");
+    write(root, "src/billing.rs", "fn charge() {}");
+    write(root, "src/old.rs", "fn old() {}");
+    write(
+        root,
+        ".context/auth.md",
+        "---\nslug: auth\ndescription: Login\n---\n\nSee `src/auth.rs`.\n",
+    );
+    write(
+        root,
+        ".context/billing.md",
+        "---\nslug: billing\n---\n\nSee `src/billing.rs`.\n",
+    );
+    write(
+        root,
+        ".context/old.md",
+        "---\nslug: old\n---\n\nSee `src/old.rs`.\n",
+    );
+    synced_cache(root);
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    // Edit one document, remove one, add one, and let billing go stale
+    write(root, ".context/auth.md", "---\nslug: auth\ndescription: Login and refresh\n---\n\nSee `src/auth.rs` and `src/billing.rs`.\n");
+    fs::remove_file(root.join(".context/old.md")).unwrap();
+    write(
+        root,
+        ".context/new.md",
+        "---\nslug: new\n---\n\nSee `src/auth.rs`.\n",
+    );
+    let cache = synced_cache(root);
+    write(root, "src/billing.rs", "fn charge(amount: u64) {}");
+
+    let diff = cache.diff_docs("main", None).unwrap();
+    assert_eq!(diff.to, None);
+    let changes: Vec<_> = diff
+        .documents
+        .iter()
+        .map(|d| (d.path.clone(), d.change))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (PathBuf::from(".context/auth.md"), Change::Modified),
+            (PathBuf::from(".context/billing.md"), Change::Unchanged),
+            (PathBuf::from(".context/new.md"), Change::Added),
+            (PathBuf::from(".context/old.md"), Change::Removed),
+        ]
+    );
+
+    let auth = &diff.documents[0];
+    assert_eq!(auth.description.as_ref().unwrap().to, "Login and refresh");
+    assert_eq!(auth.references_added, vec!["src/billing.rs"]);
+    assert!(auth.references_dropped.is_empty());
+    assert!(auth.body_changed);
+    assert_eq!(auth.status.from, Some(Status::Valid));
+    // Synced before billing.rs changed
+    assert_eq!(auth.status.to, Some(Status::Stale));
+
+    let billing = &diff.documents[1];
+    assert_eq!(
+        (billing.status.from, billing.status.to),
+        (Some(Status::Valid), Some(Status::Stale))
+    );
+    assert_eq!(diff.documents[2].status.from, None);
+    assert_eq!(diff.documents[3].status.to, None);
+
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "second"]);
+    let between = cache.diff_docs("HEAD~1", Some("HEAD")).unwrap();
+    assert_eq!(between.documents.len(), 4);
+    assert!(cache.diff_docs("HEAD", None).unwrap().documents.is_empty());
+    assert!(cache.diff_docs("no-such-rev", None).is_err());
+}
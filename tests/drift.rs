@@ -0,0 +1,106 @@
+//! Integration tests for finding when documents went stale
+
+use context::core::Cache;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn commit(dir: &Path, file: &str, content: &str, message: &str) {
+    fs::write(dir.join(file), content).unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+fn head(dir: &Path) -> String {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn test_when_stale_finds_first_change_after_sync() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    fs::create_dir_all(root.join("src")).unwrap();
+    let context_dir = root.join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("auth.md"),
+        "---\nslug: auth\n---\n\n# Auth\n\nSee `src/auth.rs`, `src/db.rs` and `src/old.rs`.\n",
+    )
+    .unwrap();
+    commit(root, "src/auth.rs", "fn a() {}\n", "add auth");
+    commit(root, "src/db.rs", "fn d() {}\n", "add db");
+    commit(root, "src/old.rs", "fn o() {}\n", "add old");
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    commit(root, "src/auth.rs", "fn a2() {}\n", "rework auth");
+    let culprit = head(root);
+    commit(root, "src/auth.rs", "fn a3() {}\n", "rework auth again");
+    fs::remove_file(root.join("src/old.rs")).unwrap();
+    git(root, &["commit", "-q", "-a", "-m", "drop old"]);
+    fs::write(root.join("src/db.rs"), "fn d2() {}\n").unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let report = cache.when_stale("auth").unwrap();
+    let refs: Vec<_> = report
+        .references
+        .iter()
+        .map(|d| d.reference.as_str())
+        .collect();
+    assert_eq!(refs, vec!["src/auth.rs", "src/db.rs", "src/old.rs"]);
+
+    let auth = &report.references[0];
+    let commit = auth.commit.as_ref().unwrap();
+    assert_eq!(commit.hash, culprit);
+    assert_eq!(commit.subject, "rework auth");
+    assert_eq!(commit.author, "test");
+    assert!(!auth.approximate);
+
+    // Uncommitted edits have no commit to blame
+    assert!(report.references[1].commit.is_none());
+
+    let old = &report.references[2];
+    assert!(old.missing);
+    assert_eq!(old.commit.as_ref().unwrap().subject, "drop old");
+}
+
+#[test]
+fn test_when_stale_reports_nothing_for_valid_documents() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    fs::create_dir_all(root.join("src")).unwrap();
+    let context_dir = root.join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("a.md"),
+        "---\nslug: a\n---\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+    commit(root, "src/a.rs", "fn a() {}\n", "add a");
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    assert!(cache.when_stale("a").unwrap().references.is_empty());
+}
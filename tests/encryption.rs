@@ -0,0 +1,105 @@
+//! Integration tests for encrypted documents
+
+use context::core::crypto::REDACTED_BODY;
+use context::core::document::Document;
+use context::core::frontmatter;
+use sha2::{Digest, Sha256};
+use std::fs;
+use tempfile::TempDir;
+
+const ENCRYPTED_DOC: &str = r#"---
+slug: runbook
+description: "Incident runbook"
+references:
+  src/main.rs: abc1234
+updated: "2025-01-01"
+encrypted: true
+---
+
+-----BEGIN AGE ENCRYPTED FILE-----
+YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSBmYWtlCg==
+-----END AGE ENCRYPTED FILE-----
+"#;
+
+fn setup_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::create_dir_all(dir.path().join(".context/guides")).unwrap();
+    dir
+}
+
+#[test]
+fn test_encrypted_document_is_redacted_without_key() {
+    if std::env::var_os("CONTEXT_AGE_IDENTITY").is_some() {
+        return;
+    }
+
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/runbook.md");
+    fs::write(&doc_path, ENCRYPTED_DOC).unwrap();
+
+    let doc = Document::load(&doc_path).unwrap();
+    assert!(doc.encrypted);
+    assert!(doc.is_locked());
+    assert_eq!(doc.body, REDACTED_BODY);
+
+    // Validation still works from frontmatter alone
+    assert!(doc.validate().is_ok());
+}
+
+#[test]
+fn test_locked_document_save_preserves_ciphertext() {
+    if std::env::var_os("CONTEXT_AGE_IDENTITY").is_some() {
+        return;
+    }
+
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/runbook.md");
+    fs::write(&doc_path, ENCRYPTED_DOC).unwrap();
+
+    let mut doc = Document::load(&doc_path).unwrap();
+    doc.description = "Updated description".to_string();
+    doc.save().unwrap();
+
+    let saved = fs::read_to_string(&doc_path).unwrap();
+    assert!(saved.contains("-----BEGIN AGE ENCRYPTED FILE-----"));
+    assert!(saved.contains("encrypted: true"));
+    assert!(!saved.contains(REDACTED_BODY.trim()));
+}
+
+#[test]
+fn test_locked_document_sync_fails() {
+    if std::env::var_os("CONTEXT_AGE_IDENTITY").is_some() {
+        return;
+    }
+
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/runbook.md");
+    fs::write(&doc_path, ENCRYPTED_DOC).unwrap();
+
+    let mut doc = Document::load(&doc_path).unwrap();
+    assert!(doc.sync().is_err());
+
+    // References are left intact on disk
+    let reloaded = Document::load(&doc_path).unwrap();
+    assert!(reloaded.references.contains_key("src/main.rs"));
+}
+
+#[test]
+fn test_encrypted_frontmatter_records_no_plaintext_hash() {
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/runbook.md");
+    let content = "---\nslug: runbook\nencrypted: true\n---\n\nRestart `src/main.rs`.\n";
+    let mut doc = Document::from_content(&doc_path, content).unwrap();
+    let plaintext = doc.body.clone();
+    doc.refresh().unwrap();
+    assert!(doc.references.contains_key("src/main.rs"));
+
+    let ciphertext =
+        "-----BEGIN AGE ENCRYPTED FILE-----\nZmFrZQ==\n-----END AGE ENCRYPTED FILE-----\n";
+    let saved = frontmatter::serialize_with_body(&doc, ciphertext).unwrap();
+    let digest = format!("{:x}", Sha256::digest(plaintext.as_bytes()));
+    assert!(!saved.contains(&digest[..7]));
+    assert!(!saved.contains("hash:"));
+}
@@ -0,0 +1,67 @@
+//! Integration tests for the cache event API
+
+use context::core::events::{Event, ReferenceOutcome, Warnings};
+use context::core::Cache;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+#[test]
+fn test_events_for_load_validate_and_sync() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "// lib").unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    let doc_path = context_dir.join("lib.md");
+    fs::write(&doc_path, "---\nslug: lib\n---\n\nSee `src/lib.rs`.\n").unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut cache = Cache::create(context_dir).unwrap();
+    let sink = Arc::clone(&events);
+    cache.subscribe(move |event: &Event| sink.lock().unwrap().push(event.clone()));
+
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "// changed").unwrap();
+    cache.status().unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        *events,
+        vec![
+            Event::DocumentLoaded {
+                path: doc_path.clone()
+            },
+            Event::SyncApplied {
+                document: doc_path.clone()
+            },
+            Event::ReferenceValidated {
+                document: doc_path,
+                reference: "src/lib.rs".to_string(),
+                outcome: ReferenceOutcome::Changed,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_warnings_are_collected_and_shared() {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    let doc_path = context_dir.join("latin1.md");
+    fs::write(&doc_path, b"---\nslug: latin1\n---\n\nCaf\xe9\n").unwrap();
+
+    let shared = Warnings::new();
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.set_warnings(shared.clone());
+    assert!(cache.warnings().is_empty());
+    cache.load().unwrap();
+
+    let warnings = cache.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].document, Some(doc_path));
+    assert!(warnings[0].message.contains("invalid UTF-8"));
+    assert_eq!(shared.to_vec(), warnings);
+}
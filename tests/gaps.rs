@@ -0,0 +1,71 @@
+//! Integration tests for documentation gap detection
+
+use context::core::gaps::GapKind;
+use context::core::Cache;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn commit(dir: &Path, file: &str, content: &str) {
+    fs::write(dir.join(file), content).unwrap();
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", file]);
+}
+
+#[test]
+fn test_gaps_rank_undocumented_and_stale_files_by_churn() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    fs::create_dir_all(root.join("src")).unwrap();
+
+    let context_dir = root.join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("auth.md"),
+        "# Auth\n\nSee `src/auth.rs`.\n",
+    )
+    .unwrap();
+    fs::write(context_dir.join("db.md"), "# Db\n\nSee `src/db.rs`.\n").unwrap();
+    commit(root, "src/auth.rs", "fn a() {}\n");
+    commit(root, "src/db.rs", "fn d() {}\n");
+    commit(root, "src/billing.rs", "fn b() {}\n");
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    // The db guide goes stale; billing has no guide at all
+    commit(root, "src/db.rs", "fn d2() {}\n");
+    commit(root, "src/billing.rs", "fn b2() {}\n");
+    commit(root, "src/billing.rs", "fn b3() {}\n");
+    commit(root, "src/auth.rs", "fn a() {} // unchanged body\n");
+    cache.sync(Some(&context_dir.join("auth.md"))).unwrap();
+
+    let report = cache.gaps(30).unwrap();
+    let gaps: Vec<_> = report
+        .gaps
+        .iter()
+        .map(|g| (g.path.as_str(), g.commits, g.kind))
+        .collect();
+    assert_eq!(
+        gaps,
+        vec![
+            ("src/billing.rs", 3, GapKind::Undocumented),
+            ("src/db.rs", 2, GapKind::Stale)
+        ]
+    );
+    assert_eq!(report.gaps[1].documents, vec![context_dir.join("db.md")]);
+    assert_eq!(report.churned, 3);
+}
@@ -0,0 +1,109 @@
+//! Integration tests for the git hooks against the built binary
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// PATH with the built binary first, so installed hooks can find it
+fn path_with_binary() -> String {
+    let bin_dir = PathBuf::from(env!("CARGO_BIN_EXE_context"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    )
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .env("PATH", path_with_binary())
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn context(dir: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_commit_msg_hook_adds_trailers_for_affected_documents() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q", "-b", "main"]);
+    write(root, "src/auth.rs", "fn login() {}");
+    write(root, "src/billing.rs", "fn charge() {}");
+    write(
+        root,
+        ".context/auth.md",
+        "---\nslug: auth\n---\n\nSee `src/auth.rs`.\n",
+    );
+    write(
+        root,
+        ".context/billing.md",
+        "---\nslug: billing\n---\n\nSee `src/billing.rs`.\n",
+    );
+    assert!(context(root, &["sync"]).status.success());
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    let installed = context(root, &["hooks", "install", "--commit-msg"]);
+    assert!(installed.status.success());
+    assert!(root.join(".git/hooks/commit-msg").exists());
+
+    write(root, "src/auth.rs", "fn login() { check() }");
+    git(root, &["commit", "-qam", "feat(auth): check credentials"]);
+    let message = git(root, &["log", "-1", "--format=%B"]);
+    assert!(
+        message.contains("Context-Docs: .context/auth.md"),
+        "{message}"
+    );
+    assert!(!message.contains("billing"));
+
+    // Documents updated in the same commit need no reminder
+    write(root, "src/auth.rs", "fn login() { check(); log() }");
+    write(
+        root,
+        ".context/auth.md",
+        "---\nslug: auth\n---\n\nSee `src/auth.rs`, now logged.\n",
+    );
+    git(root, &["commit", "-qam", "feat(auth): log logins"]);
+    assert!(!git(root, &["log", "-1", "--format=%B"]).contains("Context-Docs"));
+
+    write(root, "src/billing.rs", "fn charge() { round() }");
+    git(root, &["commit", "-qam", "docs: note rounding"]);
+    assert!(!git(root, &["log", "-1", "--format=%B"]).contains("Context-Docs"));
+
+    // Hooks not written by context are left alone without --force
+    write(root, ".git/hooks/commit-msg", "#!/bin/sh\nexit 0\n");
+    assert!(!context(root, &["hooks", "install", "--commit-msg"])
+        .status
+        .success());
+    assert!(
+        context(root, &["hooks", "install", "--commit-msg", "--force"])
+            .status
+            .success()
+    );
+}
@@ -0,0 +1,93 @@
+//! Integration tests for translated document variants
+
+use context::core::Cache;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn setup_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/auth.rs"), "fn login() {}").unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("config.toml"),
+        "[i18n]\nlocales = [\"de\", \"fr\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("auth.md"),
+        "---\nslug: auth\n---\n\nLogin lives in `src/auth.rs` and `src/main.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("auth.de.md"),
+        "---\nslug: auth-de\n---\n\nDie Anmeldung steht in `src/auth.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("billing.fr.md"),
+        "---\nslug: billing-fr\n---\n\nFacturation.\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_translations_share_references_and_go_stale_with_source() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let report = cache.i18n().unwrap();
+    assert_eq!(report.sources, 1);
+    assert_eq!(
+        report.missing,
+        vec![(context_dir.join("auth.md"), "fr".to_string())]
+    );
+    assert_eq!(report.orphaned, vec![context_dir.join("billing.fr.md")]);
+    assert_eq!(report.outdated.len(), 1);
+    assert!(report.outdated[0].never_synced);
+
+    cache.sync(None).unwrap();
+    let translation = cache.get("auth-de").unwrap();
+    let mut references: Vec<_> = translation.references.keys().cloned().collect();
+    references.sort();
+    assert_eq!(references, vec!["src/auth.rs", "src/main.rs"]);
+    assert!(cache.i18n().unwrap().outdated.is_empty());
+
+    // Editing the source outdates the translation until it is synced again
+    fs::write(
+        context_dir.join("auth.md"),
+        "---\nslug: auth\n---\n\nLogin and logout live in `src/auth.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let report = cache.i18n().unwrap();
+    let outdated: Vec<PathBuf> = report
+        .outdated
+        .iter()
+        .map(|o| o.translation.clone())
+        .collect();
+    assert_eq!(outdated, vec![context_dir.join("auth.de.md")]);
+    assert!(!report.outdated[0].never_synced);
+    assert!(!report.is_complete());
+}
+
+#[test]
+fn test_i18n_disabled_without_locales() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(context_dir.join("config.toml"), "").unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let report = cache.i18n().unwrap();
+    assert!(report.is_complete());
+    assert_eq!(report.sources, 0);
+}
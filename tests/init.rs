@@ -0,0 +1,125 @@
+//! Integration tests for the init command
+
+use context::core::template::{self, TemplateSource};
+use context::core::{Cache, StatusFilter};
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_init_from_local_template() {
+    let template = TempDir::new().unwrap();
+    fs::create_dir_all(template.path().join("guides")).unwrap();
+    fs::create_dir_all(template.path().join("adr")).unwrap();
+    fs::write(
+        template.path().join("guides/onboarding.md"),
+        "# Onboarding\n",
+    )
+    .unwrap();
+    fs::write(
+        template.path().join("guides/index.md"),
+        "# Custom guides index\n",
+    )
+    .unwrap();
+
+    let project = TempDir::new().unwrap();
+    let context_dir = project.path().join(".context");
+    let source = TemplateSource::Local(template.path().to_path_buf());
+    Cache::init_from(context_dir.clone(), &source).unwrap();
+
+    // Template content is copied
+    assert!(context_dir.join("guides/onboarding.md").exists());
+    assert!(context_dir.join("adr").is_dir());
+
+    // Template files are not overwritten by the default scaffold
+    let guides_index = fs::read_to_string(context_dir.join("guides/index.md")).unwrap();
+    assert_eq!(guides_index, "# Custom guides index\n");
+
+    // Missing standard structure is filled in
+    assert!(context_dir.join("index.md").exists());
+    assert!(context_dir.join("references/index.md").exists());
+}
+
+#[test]
+fn test_init_from_template_with_nested_context_dir() {
+    let template = TempDir::new().unwrap();
+    fs::create_dir_all(template.path().join(".context/guides")).unwrap();
+    fs::create_dir_all(template.path().join(".git")).unwrap();
+    fs::write(template.path().join("README.md"), "# Template repo\n").unwrap();
+    fs::write(
+        template.path().join(".context/guides/style.md"),
+        "# Style\n",
+    )
+    .unwrap();
+
+    let project = TempDir::new().unwrap();
+    let context_dir = project.path().join(".context");
+    let source = TemplateSource::Local(template.path().to_path_buf());
+    Cache::init_from(context_dir.clone(), &source).unwrap();
+
+    assert!(context_dir.join("guides/style.md").exists());
+    assert!(!context_dir.join("README.md").exists());
+    assert!(!context_dir.join(".git").exists());
+}
+
+#[test]
+fn test_init_from_missing_template_fails() {
+    let project = TempDir::new().unwrap();
+    let source = TemplateSource::Local(project.path().join("does-not-exist"));
+    let result = Cache::init_from(project.path().join(".context"), &source);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_init_preset_creates_categories_and_templates() {
+    let project = TempDir::new().unwrap();
+    let context_dir = project.path().join(".context");
+    let preset = context::core::presets::find("web-service").unwrap();
+    Cache::init_preset(context_dir.clone(), preset).unwrap();
+
+    assert!(context_dir.join("runbooks").is_dir());
+    assert!(context_dir.join("adr").is_dir());
+    assert!(context_dir.join(".templates/runbook.md").exists());
+    assert!(context_dir.join("references/api.md").exists());
+    assert!(context_dir.join("index.md").exists());
+
+    // The resulting tree loads cleanly, with the preset's lint bounds, and
+    // templates are not documents
+    let template = template::document_template(&context_dir, "runbook")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        template.required_sections,
+        vec!["Symptoms", "Diagnosis", "Remediation"]
+    );
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    assert_eq!(cache.config().readability.max_words, 2500);
+    let slugs: Vec<_> = cache
+        .list(&StatusFilter::default())
+        .unwrap()
+        .into_iter()
+        .map(|d| d.slug)
+        .collect();
+    assert_eq!(slugs, vec!["index", "index", "api", "index"]);
+}
+
+#[test]
+fn test_status_after_init_preset_reports_no_documents() {
+    let project = TempDir::new().unwrap();
+    fs::create_dir(project.path().join(".git")).unwrap();
+    let context_dir = project.path().join(".context");
+    Cache::init_preset(
+        context_dir,
+        context::core::presets::find("monorepo").unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(["status"])
+        .current_dir(project.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+}
@@ -0,0 +1,65 @@
+//! Stress tests for journals shared by concurrent writers
+
+use context::core::journal::Journal;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use tempfile::TempDir;
+
+const WRITERS: usize = 8;
+const LINES: usize = 250;
+
+#[test]
+fn test_concurrent_writers_never_interleave_or_lose_lines() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("journal.log");
+    // Small files force many rotations while writers are racing
+    let keep = 1000;
+    let barrier = Arc::new(Barrier::new(WRITERS));
+
+    let handles: Vec<_> = (0..WRITERS)
+        .map(|writer| {
+            // A journal per writer, like separate processes opening the same path
+            let journal = Journal::new(path.clone(), 4096, keep);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                for line in 0..LINES {
+                    let payload = "x".repeat((writer * 37 + line) % 200);
+                    journal
+                        .append(&format!("{writer}:{line}:{}:{payload}", payload.len()))
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let journal = Journal::new(path.clone(), 4096, keep);
+    let mut seen = BTreeSet::new();
+    let files = std::iter::once(path).chain((1..=keep).map(|n| journal.rotated(n)));
+    for file in files.filter(|f| f.exists()) {
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert!(
+            content.ends_with('\n'),
+            "torn write at end of {}",
+            file.display()
+        );
+        for line in content.lines() {
+            let parts: Vec<_> = line.splitn(4, ':').collect();
+            assert_eq!(parts.len(), 4, "malformed line {line:?}");
+            assert_eq!(
+                parts[2].parse::<usize>().unwrap(),
+                parts[3].len(),
+                "interleaved line {line:?}"
+            );
+            assert!(
+                seen.insert((parts[0].to_string(), parts[1].to_string())),
+                "duplicate line {line:?}"
+            );
+        }
+    }
+    assert_eq!(seen.len(), WRITERS * LINES);
+}
@@ -0,0 +1,82 @@
+//! Integration tests for diagnostics on stderr against the built binary
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_json_log_format_writes_ndjson_events_to_stderr() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}").unwrap();
+    fs::write(
+        dir.path().join(".context/a.md"),
+        "---\nslug: a\n---\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(["--log-format", "json", "sync"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, vec!["document_loaded", "sync_applied"]);
+    assert!(events[1]["document"].as_str().unwrap().ends_with("a.md"));
+
+    let failed = Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(["--log-format", "json", "get", "missing"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!failed.status.success());
+    let last = String::from_utf8(failed.stderr).unwrap();
+    let error: serde_json::Value = serde_json::from_str(last.lines().last().unwrap()).unwrap();
+    assert_eq!(error["event"], "error");
+    assert_eq!(error["message"], "Document not found: missing");
+}
+
+#[test]
+fn test_deny_warnings_fails_an_otherwise_successful_command() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    fs::write(
+        dir.path().join(".context/latin1.md"),
+        b"---\nslug: latin1\n---\n\nCaf\xe9\n",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_context"))
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+    assert_eq!(run(&["get", "latin1"]).status.code(), Some(0));
+    assert_eq!(
+        run(&["--deny-warnings", "get", "latin1"]).status.code(),
+        Some(4)
+    );
+
+    // JSON output keeps stdout for the result and reports warnings on stderr
+    let output = run(&["--output", "json", "get", "latin1"]);
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["slug"], "latin1");
+    let report: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(report["warnings"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid UTF-8"));
+}
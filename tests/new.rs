@@ -0,0 +1,65 @@
+//! Integration tests for scaffolding documents with `context new`
+
+use context::core::models::DocumentKind;
+use context::core::Cache;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_new_document_has_frontmatter_and_loads() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().join(".context");
+    fs::create_dir_all(&root).unwrap();
+    let mut cache = Cache::create(root.clone()).unwrap();
+    cache.load().unwrap();
+
+    let path = cache
+        .new_document(DocumentKind::Guide, "auth-flow")
+        .unwrap();
+    assert_eq!(path, root.join("guides/auth-flow.md"));
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.starts_with("---\nslug: auth-flow\ndescription: ''\nreferences: {}\nupdated: "));
+    assert!(content.ends_with("# Auth flow\n"));
+
+    let mut reloaded = Cache::create(root.clone()).unwrap();
+    reloaded.load().unwrap();
+    assert_eq!(reloaded.get("auth-flow").unwrap().path, path);
+
+    let taken = reloaded
+        .new_document(DocumentKind::Reference, "auth-flow")
+        .unwrap_err();
+    assert!(taken.to_string().contains("already used"));
+    assert!(reloaded
+        .new_document(DocumentKind::Reference, "../escape")
+        .is_err());
+    assert!(!root.join("references").exists());
+}
+
+#[test]
+fn test_new_command_opens_editor() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    let editor = dir.path().join("editor.sh");
+    fs::write(&editor, "#!/bin/sh\necho 'Edited.' >> \"$1\"\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(["new", "reference", "billing"])
+        .env_remove("VISUAL")
+        .env("EDITOR", &editor)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let content = fs::read_to_string(dir.path().join(".context/references/billing.md")).unwrap();
+    assert!(content.ends_with("# Billing\nEdited.\n"));
+}
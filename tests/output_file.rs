@@ -0,0 +1,63 @@
+//! Integration test for `--output-file` against the built binary
+
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_output_file_receives_the_report_instead_of_stdout() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}").unwrap();
+    fs::write(
+        dir.path().join(".context/a.md"),
+        "---\nslug: a\n---\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+
+    let context = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_context"))
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap()
+    };
+    assert!(context(&["sync"]).status.success());
+    fs::write(dir.path().join("src/a.rs"), "fn a() { changed() }").unwrap();
+
+    // Stale documents still produce the report, with status's exit code
+    let report = dir.path().join("reports/ci/status.json");
+    let output = context(&[
+        "--output",
+        "json",
+        "--output-file",
+        "reports/ci/status.json",
+        "status",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(json[0]["status"], "stale");
+
+    // No temporary files are left next to the report
+    let entries: Vec<_> = fs::read_dir(report.parent().unwrap()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+
+    // A failing command leaves the previous report untouched
+    let previous = fs::read_to_string(&report).unwrap();
+    let failed = context(&["--output-file", "reports/ci/status.json", "get", "missing"]);
+    assert!(!failed.status.success());
+    assert_eq!(fs::read_to_string(&report).unwrap(), previous);
+
+    // So does a status lookup of a missing document
+    let missing = context(&[
+        "--output-file",
+        "reports/ci/status.json",
+        "status",
+        "missing",
+    ]);
+    assert_eq!(missing.status.code(), Some(5));
+    assert_eq!(fs::read_to_string(&report).unwrap(), previous);
+}
@@ -0,0 +1,265 @@
+//! Integration tests for packing the documents relevant to a change
+
+use context::core::models::Status;
+use context::core::{pack, Cache};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_pack_diff_gathers_referencing_and_linked_documents() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q", "-b", "main"]);
+    write(root, "src/auth.rs", "fn login() {}");
+    write(root, "src/billing.rs", "fn charge() {}");
+    write(
+        root,
+        ".context/guides/auth.md",
+        "---\nslug: auth\ndescription: Login flow\n---\n\nSee `src/auth.rs` and [tokens](tokens.md).\n",
+    );
+    write(
+        root,
+        ".context/guides/tokens.md",
+        "---\nslug: tokens\n---\n\nTokens, see [sessions](sessions.md).\n",
+    );
+    write(
+        root,
+        ".context/guides/sessions.md",
+        "---\nslug: sessions\n---\n\nSessions.\n",
+    );
+    write(
+        root,
+        ".context/guides/billing.md",
+        "---\nslug: billing\n---\n\nSee `src/billing.rs`.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    git(root, &["checkout", "-q", "-b", "feature"]);
+    write(root, "src/auth.rs", "fn login() { check() }");
+    git(root, &["commit", "-qam", "change auth"]);
+
+    let pack = cache.pack_diff("main..feature", 1).unwrap();
+    assert_eq!(pack.changed, vec!["src/auth.rs"]);
+    let slugs: Vec<_> = pack.documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["auth", "tokens"]);
+    assert_eq!(pack.documents[0].references, vec!["src/auth.rs"]);
+    assert_eq!(pack.documents[0].hops, 0);
+    assert!(pack.documents[0].body.contains("See `src/auth.rs`"));
+    assert_eq!(pack.documents[1].hops, 1);
+    assert_eq!(
+        pack.documents[1].linked_from,
+        Some(root.join(".context/guides/auth.md"))
+    );
+
+    let deeper = cache.pack_diff("main..feature", 2).unwrap();
+    let slugs: Vec<_> = deeper.documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["auth", "tokens", "sessions"]);
+
+    let direct = cache.pack_diff("main..feature", 0).unwrap();
+    assert_eq!(direct.documents.len(), 1);
+
+    assert!(cache.pack_diff("--output=x", 1).is_err());
+}
+
+#[test]
+fn test_pack_from_files_and_task_into_excerpts() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    write(root, "src/auth.rs", "fn login() {}");
+    write(
+        root,
+        ".context/auth.md",
+        "---\nslug: auth\n---\n\n# Auth\n\nSee `src/auth.rs` and [tokens](tokens.md).\n\n## Refresh\n\nTokens refresh hourly.\n",
+    );
+    write(
+        root,
+        ".context/tokens.md",
+        "---\nslug: tokens\n---\n\n# Tokens\n\nSigned JWTs.\n",
+    );
+    write(
+        root,
+        ".context/billing.md",
+        "---\nslug: billing\n---\n\n# Billing\n\nInvoices.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    let documents = cache.pack_files(&["./src/auth.rs".to_string()], 1).unwrap();
+    let slugs: Vec<_> = documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["auth", "tokens"]);
+
+    let documents = cache
+        .pack_task("How often do invoices refresh?", 0)
+        .unwrap();
+    let slugs: Vec<_> = documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["auth", "billing"]);
+
+    let terms = pack::task_terms("token refresh");
+    let result = pack::excerpts(&documents, &terms, pack::DEFAULT_MAX_TOKENS);
+    let first = &result.excerpts[0];
+    assert_eq!(first.section, vec!["Auth", "Refresh"]);
+    assert_eq!(first.anchor.as_deref(), Some("refresh"));
+    assert_eq!(first.path, root.join(".context/auth.md"));
+    assert_eq!(first.status, Status::Valid);
+    assert_eq!(result.excerpts.len(), 3);
+    assert_eq!(
+        result.tokens,
+        result.excerpts.iter().map(|e| e.tokens).sum::<usize>()
+    );
+
+    let tight = pack::excerpts(&documents, &terms, first.tokens);
+    assert_eq!(tight.excerpts.len(), 1);
+    assert_eq!(tight.omitted, 2);
+}
+
+#[test]
+fn test_pack_ranks_fresh_documents_ahead_of_stale_ones() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    write(root, "src/a.rs", "fn a() {}");
+    write(root, "src/b.rs", "fn b() {}");
+    write(
+        root,
+        ".context/a.md",
+        "---\nslug: a\n---\n\n# Tokens\n\nSee `src/a.rs`.\n",
+    );
+    write(
+        root,
+        ".context/b.md",
+        "---\nslug: b\n---\n\n# Tokens\n\nSee `src/b.rs`.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    write(root, "src/a.rs", "fn a() { changed() }");
+
+    let files = ["src/a.rs".to_string(), "src/b.rs".to_string()];
+    let documents = cache.pack_files(&files, 0).unwrap();
+    let slugs: Vec<_> = documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["b", "a"]);
+
+    let documents = cache.pack_task("tokens", 0).unwrap();
+    let slugs: Vec<_> = documents.iter().map(|d| d.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["b", "a"]);
+    assert_eq!(documents[1].status, Status::Stale);
+
+    // Equal matches: the budget goes to the valid document's section
+    let terms = pack::task_terms("tokens");
+    let first = pack::excerpts(&documents, &terms, pack::DEFAULT_MAX_TOKENS).excerpts[0].clone();
+    let tight = pack::excerpts(&documents, &terms, first.tokens);
+    assert_eq!(tight.excerpts[0].slug, "b");
+    assert_eq!(tight.omitted, 1);
+}
+
+#[test]
+fn test_pack_keeps_only_stale_scoped_sections() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    write(root, "src/login.rs", "fn login() {}");
+    write(root, "src/refresh.rs", "fn refresh() {}");
+    write(
+        root,
+        ".context/auth.md",
+        "---\nslug: auth\n---\n\n# Auth\n\nLogin is in `src/login.rs`.\n\n## Token refresh\n\n<!-- context:scope -->\n\nSee `src/refresh.rs`.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    let files = ["src/refresh.rs".to_string()];
+    let whole = cache.pack_files(&files, 0).unwrap();
+    assert!(whole[0].body.contains("Login is in"));
+
+    write(root, "src/refresh.rs", "fn refresh() { todo!() }");
+    let stale = cache.pack_files(&files, 0).unwrap();
+    assert_eq!(stale[0].status, Status::Stale);
+    assert_eq!(
+        stale[0].body,
+        "## Token refresh\n\n<!-- context:scope -->\n\nSee `src/refresh.rs`.\n"
+    );
+
+    // A stale reference outside the scoped section brings the whole body
+    write(root, "src/login.rs", "fn login() { todo!() }");
+    let both = cache.pack_files(&files, 0).unwrap();
+    assert!(both[0].body.contains("Login is in"));
+}
+
+#[test]
+fn test_checklist_lists_documents_referencing_changed_files() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q", "-b", "main"]);
+    write(root, "src/auth/jwt.rs", "fn sign() {}");
+    write(root, "src/auth/old.rs", "fn old() {}");
+    write(root, "src/billing.rs", "fn charge() {}");
+    write(
+        root,
+        ".context/guides/auth.md",
+        "---\nslug: auth\n---\n\nSee `src/auth/old.rs` and `src/auth/jwt.rs`.\n",
+    );
+    write(
+        root,
+        ".context/billing.md",
+        "---\nslug: billing\n---\n\nSee `src/billing.rs`.\n",
+    );
+    write(
+        root,
+        ".context/unrelated.md",
+        "---\nslug: unrelated\n---\n\nNo references.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    git(root, &["checkout", "-q", "-b", "feature"]);
+    write(root, "src/auth/jwt.rs", "fn sign(key: &str) {}");
+    git(root, &["rm", "-q", "src/auth/old.rs"]);
+    write(root, "src/billing.rs", "fn charge(amount: u64) {}");
+    write(
+        root,
+        ".context/billing.md",
+        "---\nslug: billing\n---\n\nSee `src/billing.rs`, now with amounts.\n",
+    );
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(Some(&root.join(".context/billing.md"))).unwrap();
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "feature"]);
+
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    let checklist = cache.checklist("main..feature").unwrap();
+    let rendered = context::core::checklist::markdown(&checklist, None);
+    assert_eq!(
+        rendered,
+        "### Documentation to review (main..feature)\n\n\
+         - [x] `.context/billing.md` — references `src/billing.rs` (changed)\n\
+         - [ ] `.context/guides/auth.md` — references `src/auth/jwt.rs` (changed), `src/auth/old.rs` (deleted)\n"
+    );
+    assert!(cache.checklist("main..main").unwrap().items.is_empty());
+}
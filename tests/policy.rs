@@ -0,0 +1,118 @@
+//! Integration tests for policy checks
+
+use context::core::policy::Check;
+use context::core::Cache;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn commit_all(dir: &Path, message: &str) {
+    git(dir, &["add", "-A"]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+#[test]
+fn test_policy_check_reports_structured_violations() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    git(root, &["init", "-q"]);
+    fs::create_dir_all(root.join("src/api")).unwrap();
+    fs::write(root.join("src/api/users.rs"), "fn users() {}\n").unwrap();
+    fs::write(root.join("src/api/orders.rs"), "fn orders() {}\n").unwrap();
+
+    let context_dir = root.join(".context");
+    fs::create_dir_all(context_dir.join("decisions")).unwrap();
+    fs::write(
+        context_dir.join("api.md"),
+        "---\nslug: api\ndescription: The API\n---\n\nSee `src/api/users.rs`.\n",
+    )
+    .unwrap();
+    let adr = context_dir.join("decisions/0001.md");
+    fs::write(
+        &adr,
+        "---\nslug: adr-1\ndescription: Use Rust\n---\n\nProposed.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("policy.toml"),
+        r#"
+[[rule]]
+name = "api-documented"
+check = "referenced"
+files = ["src/api/**"]
+
+[[rule]]
+name = "adrs-frozen"
+check = "immutable"
+documents = ["decisions/**"]
+tag = "accepted"
+"#,
+    )
+    .unwrap();
+    commit_all(root, "proposed");
+
+    // Editing a proposed ADR is fine; after acceptance it isn't
+    fs::write(
+        &adr,
+        "---\nslug: adr-1\ndescription: Use Rust\ntags:\n- accepted\n---\n\nAccepted.\n",
+    )
+    .unwrap();
+    commit_all(root, "accept");
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    let report = cache.check_policy().unwrap();
+    assert_eq!(report.rules, 2);
+    let found: Vec<_> = report
+        .violations
+        .iter()
+        .map(|v| (v.check, v.path.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(Check::Referenced, PathBuf::from("src/api/orders.rs"))]
+    );
+
+    fs::write(
+        &adr,
+        "---\nslug: adr-1\ndescription: Use Rust\ntags:\n- accepted\n---\n\nRewritten.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let report = cache.check_policy().unwrap();
+    let adr = report
+        .violations
+        .iter()
+        .find(|v| v.check == Check::Immutable)
+        .unwrap();
+    assert_eq!(adr.rule, "adrs-frozen");
+    assert_eq!(adr.path, PathBuf::from("decisions/0001.md"));
+    assert!(adr.message.contains("tagged 'accepted'"));
+    assert_eq!(report.errors(), 2);
+}
+
+#[test]
+fn test_policy_check_without_policy_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    let cache = Cache::create(context_dir).unwrap();
+    assert!(cache
+        .check_policy()
+        .unwrap_err()
+        .to_string()
+        .contains("policy.toml"));
+}
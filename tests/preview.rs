@@ -0,0 +1,117 @@
+//! Integration tests for the preview website
+
+use context::core::Cache;
+use context::preview::server::{respond, run, version};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// A project with a stale auth guide linking to a valid overview
+fn setup_project() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/auth.rs"), "fn login() {}\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::write(
+        context_dir.join("guides/auth.md"),
+        "---\nslug: auth\ndescription: How login works\n---\n\n# Login\n\nSee `src/auth.rs` and the [overview](../index.md#start).\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("index.md"),
+        "---\nslug: index\n---\n\n# Start\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    fs::write(dir.path().join("src/auth.rs"), "fn login(user: &str) {}\n").unwrap();
+    (dir, context_dir)
+}
+
+#[test]
+fn test_index_and_document_pages() {
+    let (_dir, root) = setup_project();
+
+    let index = respond(&root, "/");
+    assert_eq!(index.status, 200);
+    assert!(index
+        .body
+        .contains("2 documents: 1 valid, 1 stale, 0 orphaned"));
+    assert!(index
+        .body
+        .contains("<a href=\"/doc/guides/auth.md\">auth</a>"));
+    assert!(index.body.contains("fetch(\"/_version\")"));
+
+    let page = respond(&root, "/doc/guides/auth.md");
+    assert_eq!(page.status, 200);
+    assert!(page.body.contains("<h1 id=\"login\">Login</h1>"));
+    assert!(page
+        .body
+        .contains("<a href=\"/doc/index.md#start\">overview</a>"));
+    assert!(page
+        .body
+        .contains("<a href=\"/source/src/auth.rs\"><code>src/auth.rs</code></a>"));
+    assert!(page.body.contains("changed"));
+
+    let source = respond(&root, "/source/src/auth.rs");
+    assert_eq!(source.status, 200);
+    assert!(source.body.contains("fn login(user: &amp;str) {}"));
+
+    let index = respond(&root, "/search-index.json");
+    assert_eq!(index.content_type, "application/json");
+    assert!(index.body.contains("\"path\":\"guides/auth.md\""));
+
+    assert_eq!(respond(&root, "/doc/guides/missing.md").status, 404);
+    assert_eq!(respond(&root, "/source/../../etc/passwd").status, 404);
+    assert_eq!(
+        respond(&root, "/source/%2E%2E/%2E%2E/etc/passwd").status,
+        404
+    );
+}
+
+#[test]
+fn test_source_url_config_and_version() {
+    let (_dir, root) = setup_project();
+    fs::write(
+        root.join("config.toml"),
+        "[preview]\nsource_url = \"https://example.com/blob/main/{path}\"\n",
+    )
+    .unwrap();
+
+    let page = respond(&root, "/doc/guides/auth.md");
+    assert!(page
+        .body
+        .contains("href=\"https://example.com/blob/main/src/auth.rs\""));
+
+    let before = version(&root);
+    fs::write(root.join("guides/new.md"), "---\nslug: new\n---\n").unwrap();
+    assert_ne!(version(&root), before);
+}
+
+#[tokio::test]
+async fn test_serves_http_to_loopback_hosts_only() {
+    let (_dir, root) = setup_project();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(run(listener, root));
+
+    for (host, status) in [
+        (format!("127.0.0.1:{}", addr.port()), "200 OK"),
+        ("evil.example".to_string(), "403 Forbidden"),
+    ] {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET /doc/guides/auth.md HTTP/1.1\r\nHost: {host}\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(
+            response.starts_with(&format!("HTTP/1.1 {status}")),
+            "{response}"
+        );
+    }
+}
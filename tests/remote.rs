@@ -0,0 +1,181 @@
+//! Integration tests for remote context trees
+
+use context::core::document::Document;
+use context::core::{Cache, Status};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+/// A repository publishing one synced guide under `shared/.context`
+fn platform_repo() -> TempDir {
+    let repo = TempDir::new().unwrap();
+    let context_dir = repo.path().join("shared/.context/guides");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::create_dir_all(repo.path().join("shared/ci")).unwrap();
+    fs::write(
+        repo.path().join("shared/ci/deploy.yaml"),
+        "stages: [build]\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("deploy.md"),
+        "---\nslug: deploy\n---\n\nPipelines are defined in `ci/deploy.yaml`.\n",
+    )
+    .unwrap();
+    Document::load(context_dir.join("deploy.md"))
+        .unwrap()
+        .sync()
+        .unwrap();
+
+    git(repo.path(), &["init", "--quiet"]);
+    git(repo.path(), &["add", "-A"]);
+    git(repo.path(), &["commit", "--quiet", "-m", "Publish guides"]);
+    repo
+}
+
+/// A service project with one local guide and the platform remote configured
+fn service_project(remote_url: &str) -> (TempDir, Cache) {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::write(
+        context_dir.join("guides/local.md"),
+        "---\nslug: local\n---\n\nDeploys use the shared pipeline.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("config.toml"),
+        format!("[[remotes]]\nname = \"platform\"\nurl = \"{remote_url}\"\npath = \"shared\"\n"),
+    )
+    .unwrap();
+
+    let cache = Cache::create(context_dir).unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_remote_documents_overlay_search_and_find() {
+    let repo = platform_repo();
+    let (_dir, mut cache) = service_project(&format!("file://{}", repo.path().display()));
+
+    let fetched = cache.fetch_remotes(None).unwrap();
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].documents, 1);
+    cache.load().unwrap();
+
+    // Local status and sync never see remote documents
+    assert_eq!(cache.status().unwrap().len(), 1);
+    assert_eq!(cache.remote_documents().len(), 1);
+
+    let hits: Vec<_> = cache.search("pipeline").map(Result::unwrap).collect();
+    let remotes: Vec<_> = hits.iter().map(|h| h.remote.as_deref()).collect();
+    assert_eq!(remotes, vec![None, Some("platform")]);
+
+    // Remote references validate against the remote's own checkout
+    let found = cache.find_by_reference("ci/deploy.yaml").unwrap();
+    assert_eq!(found.matches.len(), 1);
+    assert_eq!(found.matches[0].remote.as_deref(), Some("platform"));
+    assert_eq!(found.matches[0].status, Status::Valid);
+
+    let doc = cache.get("deploy").unwrap();
+    assert!(doc.save().is_err(), "remote documents are read-only");
+}
+
+#[test]
+fn test_fetch_unknown_remote_fails() {
+    let (_dir, cache) = service_project("file:///nonexistent");
+    assert!(cache.fetch_remotes(Some("other")).is_err());
+    assert!(cache.fetch_remotes(Some("platform")).is_err());
+}
+
+#[test]
+fn test_remote_update_and_status() {
+    let repo = platform_repo();
+    let (dir, mut cache) = service_project(&format!("file://{}", repo.path().display()));
+
+    let status = cache.remote_status().unwrap();
+    assert_eq!(status[0].commit, None);
+    assert!(status[0].upstream.is_some());
+
+    // Updating a remote that was never fetched fetches it
+    let updated = cache.update_remotes(None).unwrap();
+    assert_eq!(updated[0].from, None);
+    assert!(cache.remote_status().unwrap()[0].up_to_date());
+
+    // Upstream publishes a new guide
+    let guides = repo.path().join("shared/.context/guides");
+    fs::write(
+        guides.join("rollback.md"),
+        "---\nslug: rollback\n---\n\nRoll back.\n",
+    )
+    .unwrap();
+    git(repo.path(), &["add", "-A"]);
+    git(repo.path(), &["commit", "--quiet", "-m", "Add rollback"]);
+
+    let status = cache.remote_status().unwrap();
+    assert!(!status[0].up_to_date());
+    assert_ne!(status[0].commit, status[0].upstream);
+
+    let updated = cache.update_remotes(Some("platform")).unwrap();
+    assert_eq!(updated[0].documents, 2);
+    assert_ne!(updated[0].from.as_deref(), Some(updated[0].to.as_str()));
+
+    // A local document with a remote slug shadows it
+    fs::write(
+        dir.path().join(".context/guides/deploy.md"),
+        "---\nslug: deploy\n---\n\nOur own deploys.\n",
+    )
+    .unwrap();
+    cache.load().unwrap();
+    let status = cache.remote_status().unwrap();
+    assert!(status[0].up_to_date());
+    assert_eq!(status[0].shadowed.len(), 1);
+    assert_eq!(status[0].shadowed[0].slug, "deploy");
+    assert_eq!(status[0].shadowed[0].local, Path::new("guides/deploy.md"));
+}
+
+#[test]
+fn test_namespaced_slugs() {
+    let repo = platform_repo();
+    let (dir, mut cache) = service_project(&format!("file://{}", repo.path().display()));
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("guides/deploy.md"),
+        "---\nslug: deploy\n---\n\nOur own deploys.\n",
+    )
+    .unwrap();
+    cache.fetch_remotes(None).unwrap();
+    cache.load().unwrap();
+
+    assert_eq!(cache.get("deploy").unwrap().namespace(), "local");
+    assert_eq!(cache.get("local/deploy").unwrap().namespace(), "local");
+    let remote = cache.get("platform/deploy").unwrap();
+    assert_eq!(remote.qualified_slug(), "platform/deploy");
+    assert!(cache.get("platform/local").is_err());
+
+    let hits: Vec<_> = cache.search("deploys").map(Result::unwrap).collect();
+    assert_eq!(hits[0].namespace, "local");
+
+    // The default namespace decides which document a bare slug means
+    let config = fs::read_to_string(context_dir.join("config.toml")).unwrap();
+    fs::write(
+        context_dir.join("config.toml"),
+        format!("{config}\n[namespaces]\ndefault = \"platform\"\n"),
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    assert_eq!(cache.get("deploy").unwrap().namespace(), "platform");
+    assert_eq!(cache.get("local").unwrap().namespace(), "local");
+}
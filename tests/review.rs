@@ -0,0 +1,82 @@
+//! Integration tests for review-gated sync
+
+use context::core::review::{self, REVIEW_JOURNAL};
+use context::core::{Cache, Status};
+use context::error::ContextError;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn setup_project(config: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+    fs::write(dir.path().join("src/b.rs"), "fn b() {}\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("a.md"),
+        "---\nslug: a\n---\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("b.md"),
+        "---\nslug: b\n---\n\nSee `src/b.rs`.\n",
+    )
+    .unwrap();
+    fs::write(context_dir.join("config.toml"), config).unwrap();
+    dir
+}
+
+#[test]
+fn test_required_review_blocks_blind_sync_of_stale_documents() {
+    let dir = setup_project("[review]\nrequire = true\n");
+    let context_dir = dir.path().join(".context");
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    // Nothing is stale yet, so the first sync needs no review
+    cache.sync(None).unwrap();
+
+    fs::write(dir.path().join("src/a.rs"), "fn a2() {}\n").unwrap();
+    let err = cache.sync(None).unwrap_err();
+    assert!(
+        matches!(&err, ContextError::ReviewRequired { documents } if *documents == vec![context_dir.join("a.md")]),
+        "{err}"
+    );
+    assert_eq!(
+        cache.get("a").unwrap().validate().unwrap().status,
+        Status::Stale
+    );
+
+    // Syncing only a valid document needs no review either
+    cache.sync(Some(&context_dir.join("b.md"))).unwrap();
+
+    let result = cache.sync_reviewed(None, Some("alice")).unwrap();
+    assert_eq!(result.count, 2);
+
+    let reviews = review::reviews(&review::journal(&context_dir)).unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].reviewer, "alice");
+    assert_eq!(reviews[0].document, PathBuf::from("a.md"));
+    assert_eq!(reviews[0].changed, vec!["src/a.rs".to_string()]);
+}
+
+#[test]
+fn test_reviews_are_recorded_without_being_required() {
+    let dir = setup_project("");
+    let context_dir = dir.path().join(".context");
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    fs::write(dir.path().join("src/b.rs"), "fn b2() {}\n").unwrap();
+    cache.sync(None).unwrap();
+    assert!(!context_dir.join(REVIEW_JOURNAL).exists());
+
+    fs::write(dir.path().join("src/b.rs"), "fn b3() {}\n").unwrap();
+    cache.sync_reviewed(None, Some("bob")).unwrap();
+    let reviews = review::reviews(&review::journal(&context_dir)).unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].document, PathBuf::from("b.md"));
+}
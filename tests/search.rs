@@ -0,0 +1,233 @@
+//! Integration tests for body search
+
+use context::core::Cache;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_project() -> (TempDir, Cache) {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::write(
+        context_dir.join("guides/auth.md"),
+        "---\nslug: auth\n---\n\n# Auth\n\nTokens are refreshed hourly.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/billing.md"),
+        "---\nslug: billing\n---\n\n# Billing\n\nInvoices use refresh tokens too.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/deploy.md"),
+        "---\nslug: deploy\n---\n\n# Deploy\n\nNothing to see.\n",
+    )
+    .unwrap();
+
+    let cache = Cache::create(context_dir).unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_search_streams_hits_in_stable_order() {
+    let (_dir, cache) = setup_project();
+    let slugs: Vec<_> = cache
+        .search("TOKENS")
+        .map(|hit| hit.unwrap().slug)
+        .collect();
+    assert_eq!(slugs, vec!["auth", "billing"]);
+}
+
+#[test]
+fn test_search_is_lazy() {
+    let (_dir, cache) = setup_project();
+    let first = cache.search("refresh").next().unwrap().unwrap();
+    assert_eq!(first.slug, "auth");
+    assert_eq!(first.matches[0].line, 3);
+}
+
+#[test]
+fn test_search_without_matches() {
+    let (_dir, cache) = setup_project();
+    assert_eq!(cache.search("kubernetes").count(), 0);
+}
+
+#[test]
+fn test_search_ranked_prefers_fresh_documents() {
+    let (dir, cache) = setup_project();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/auth.rs"), "// changed").unwrap();
+    fs::write(
+        dir.path().join(".context/guides/auth.md"),
+        "---\nslug: auth\nreferences:\n  src/auth.rs: 0000000\nupdated: 2020-01-01\n---\n\n# Auth\n\nTokens are refreshed hourly.\n",
+    )
+    .unwrap();
+    let today = chrono::Local::now().format("%Y-%m-%d");
+    fs::write(
+        dir.path().join(".context/guides/billing.md"),
+        format!("---\nslug: billing\nupdated: {today}\n---\n\n# Billing\n\nInvoices use refresh tokens too.\n"),
+    )
+    .unwrap();
+
+    let hits = cache.search_ranked("tokens").unwrap();
+    let slugs: Vec<_> = hits.iter().map(|hit| hit.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["billing", "auth"]);
+    assert!(hits[0].score > hits[1].score);
+}
+
+#[test]
+fn test_get_by_slug_and_relative_path() {
+    let (_dir, mut cache) = setup_project();
+    cache.load().unwrap();
+
+    assert_eq!(cache.get("billing").unwrap().slug, "billing");
+    assert_eq!(cache.get("guides/deploy.md").unwrap().slug, "deploy");
+    assert!(matches!(
+        cache.get("missing"),
+        Err(context::ContextError::DocumentNotFound(_))
+    ));
+}
+
+#[test]
+fn test_get_by_path_slug_and_leaf() {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides/auth")).unwrap();
+    fs::write(
+        context_dir.join("guides/auth/tokens.md"),
+        "---\nslug: token-lifecycle\n---\n\nTokens\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+
+    assert_eq!(
+        cache.get("guides/auth/tokens").unwrap().slug,
+        "token-lifecycle"
+    );
+    assert_eq!(
+        cache.get("token-lifecycle").unwrap().path_slug(),
+        "guides/auth/tokens"
+    );
+    assert_eq!(cache.get("tokens").unwrap().slug, "token-lifecycle");
+    assert_eq!(
+        cache.get("local/guides/auth/tokens").unwrap().slug,
+        "token-lifecycle"
+    );
+    assert_eq!(cache.get("guides/billing").unwrap().slug, "billing");
+}
+
+#[test]
+fn test_load_rejects_duplicate_slugs() {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    // Frontmatter slug clashing with another document's path slug
+    fs::write(
+        context_dir.join("misc.md"),
+        "---\nslug: guides/auth\n---\n\nMisc\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("billing.md"),
+        "---\nslug: billing\n---\n\nAgain\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+
+    let Err(context::ContextError::DuplicateSlugs { collisions }) = cache.load() else {
+        panic!("expected duplicate slugs");
+    };
+    let slugs: Vec<_> = collisions.iter().map(|(slug, _)| slug.as_str()).collect();
+    assert_eq!(slugs, vec!["billing", "guides/auth"]);
+    assert_eq!(
+        collisions[0].1,
+        vec![
+            context_dir.join("billing.md"),
+            context_dir.join("guides/billing.md")
+        ]
+    );
+}
+
+#[test]
+fn test_search_index_covers_all_documents_redacted() {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("config.toml"),
+        "[redaction]\npatterns = [\"hourly\"]\n",
+    )
+    .unwrap();
+    let cache = Cache::create(context_dir).unwrap();
+
+    let index = cache.search_index().unwrap();
+    let paths: Vec<_> = index.documents.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec!["guides/auth.md", "guides/billing.md", "guides/deploy.md"]
+    );
+    assert_eq!(index.terms["tokens"], vec![(0, 1), (1, 1)]);
+    assert!(!index.terms.contains_key("hourly"));
+
+    let json = serde_json::to_value(&index).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["terms"]["billing"][0], serde_json::json!([1, 10]));
+}
+
+fn setup_indexed_project() -> (TempDir, Cache) {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("config.toml"),
+        "[storage]\nbackend = \"sqlite\"\n",
+    )
+    .unwrap();
+    let cache = Cache::create(context_dir).unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_full_text_search_supports_phrases_and_prefixes() {
+    let (_dir, cache) = setup_indexed_project();
+    let slugs = |query: &str| -> Vec<String> {
+        let mut slugs: Vec<_> = cache.search(query).map(|hit| hit.unwrap().slug).collect();
+        slugs.sort();
+        slugs
+    };
+
+    assert_eq!(slugs("refresh*"), vec!["auth", "billing"]);
+    assert_eq!(slugs("\"refresh tokens\""), vec!["billing"]);
+    assert_eq!(slugs("invoices OR nothing"), vec!["billing", "deploy"]);
+
+    let hit = cache.search("\"refresh tokens\"").next().unwrap().unwrap();
+    assert_eq!(hit.matches[0].line, 3);
+    assert!(hit.score.unwrap() > 0.0);
+}
+
+#[test]
+fn test_full_text_index_follows_edits() {
+    let (dir, cache) = setup_indexed_project();
+    let guides = dir.path().join(".context/guides");
+    assert_eq!(cache.search("tokens").count(), 2);
+
+    fs::write(
+        guides.join("deploy.md"),
+        "---\nslug: deploy\n---\n\n# Deploy\n\nRotate tokens first.\n",
+    )
+    .unwrap();
+    fs::remove_file(guides.join("billing.md")).unwrap();
+    let mut slugs: Vec<_> = cache
+        .search("tokens")
+        .map(|hit| hit.unwrap().slug)
+        .collect();
+    slugs.sort();
+    assert_eq!(slugs, vec!["auth", "deploy"]);
+}
+
+#[test]
+fn test_queries_the_index_rejects_fall_back_to_scanning() {
+    let (_dir, cache) = setup_indexed_project();
+    let hits: Vec<_> = cache.search("hourly.").map(|hit| hit.unwrap()).collect();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].slug, "auth");
+    assert_eq!(hits[0].score, None);
+}
@@ -0,0 +1,12 @@
+//! Integration test running the self-test against the built binary
+
+use context::cli::selftest;
+use std::path::Path;
+
+#[test]
+fn test_selftest_passes_against_built_binary() {
+    let report = selftest::run(Path::new(env!("CARGO_BIN_EXE_context")), false).unwrap();
+    let failed: Vec<_> = report.checks.iter().filter(|c| !c.passed).collect();
+    assert!(failed.is_empty(), "failed checks: {failed:?}");
+    assert!(report.project.is_none());
+}
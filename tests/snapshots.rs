@@ -0,0 +1,120 @@
+//! Golden-file snapshots of command output for a canned tree
+//!
+//! Every reporting command runs in text and JSON against the same project,
+//! with the clock pinned by `--fixed-time` and the temporary directory
+//! replaced by `[ROOT]`, so any change to what a command prints shows up
+//! as a snapshot diff. Review and accept changes with `cargo insta review`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const FIXED_TIME: &str = "2024-01-15T12:00:00Z";
+
+/// Reporting commands snapshotted in both output formats; commands that
+/// write, serve or need git are left to their own tests
+const COMMANDS: &[(&str, &[&str])] = &[
+    ("status", &["status"]),
+    ("status_document", &["status", "auth"]),
+    ("status_missing", &["status", "nope"]),
+    ("ls", &["ls"]),
+    ("ls_tree", &["ls", "--tree"]),
+    ("show", &["show", "auth"]),
+    ("show_missing", &["show", "nope"]),
+    ("get", &["get", "auth"]),
+    ("find", &["find", "src/lib.rs"]),
+    ("search", &["search", "login"]),
+    ("explain", &["explain", "src/auth/login.rs"]),
+    ("stats", &["stats"]),
+    ("info", &["info"]),
+    ("doctor", &["doctor"]),
+    ("lint", &["lint"]),
+    ("lint_metrics", &["lint", "--metrics"]),
+    ("order", &["order"]),
+    ("graph", &["graph"]),
+    ("graph_mermaid", &["graph", "--format", "mermaid"]),
+    ("coverage", &["coverage"]),
+];
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// Three synced documents, one of them made stale, and one source file
+/// nothing references
+fn setup_tree() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    write(root, "src/lib.rs", "pub mod auth;\n");
+    write(root, "src/main.rs", "fn main() {}\n");
+    write(root, "src/auth/login.rs", "pub fn login() {}\n");
+    write(root, "src/util.rs", "pub fn util() {}\n");
+    write(
+        root,
+        ".context/index.md",
+        "---\nslug: index\ndescription: Project documentation\n---\n\n# Docs\n\nSee [auth](guides/auth.md).\n",
+    );
+    write(
+        root,
+        ".context/guides/auth.md",
+        "---\nslug: auth\ndescription: How login works\ntags: [security]\nowners: [team-auth]\n---\n\n# Authentication\n\n\
+         Login lives in `src/auth/login.rs`.\n\n## Sessions\n\nSessions are wired up in `src/lib.rs`.\n",
+    );
+    write(
+        root,
+        ".context/references/crate.md",
+        "---\nslug: crate\ndescription: Crate layout\n---\n\n# Crate\n\nEntry point: `src/main.rs`.\n",
+    );
+    assert!(context(root, &["sync"]).status.success());
+    write(root, "src/auth/login.rs", "pub fn login() { todo!() }\n");
+    dir
+}
+
+fn context(root: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_context"))
+        .args(["--fixed-time", FIXED_TIME, "--no-color"])
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap()
+}
+
+/// Exit code, stdout and stderr, with the project root and crate version
+/// made portable
+fn render(root: &Path, args: &[&str]) -> String {
+    let output = context(root, args);
+    let text = format!(
+        "exit: {:?}\n--- stdout\n{}--- stderr\n{}",
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let canonical = root.canonicalize().unwrap();
+    text.replace(&canonical.display().to_string(), "[ROOT]")
+        .replace(&root.display().to_string(), "[ROOT]")
+        .replace(env!("CARGO_PKG_VERSION"), "[VERSION]")
+}
+
+#[test]
+fn test_text_output() {
+    let dir = setup_tree();
+    for (name, args) in COMMANDS {
+        insta::assert_snapshot!(format!("text_{name}"), render(dir.path(), args));
+    }
+}
+
+#[test]
+fn test_json_output() {
+    let dir = setup_tree();
+    for (name, args) in COMMANDS {
+        let args: Vec<&str> = ["--output", "json"]
+            .iter()
+            .chain(args.iter())
+            .copied()
+            .collect();
+        insta::assert_snapshot!(format!("json_{name}"), render(dir.path(), &args));
+    }
+}
@@ -0,0 +1,665 @@
+//! Integration tests for status validation and filtering
+
+use context::core::document::{self, Document};
+use context::core::events::ReferenceOutcome;
+use context::core::models::DocumentState;
+use context::core::{Cache, Status, StatusFilter};
+use context::error::ContextError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Set up a project with one stale guide and one valid tagged reference
+fn setup_project() -> (TempDir, Cache) {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src/cli")).unwrap();
+    fs::write(dir.path().join("src/cli/args.rs"), "// args").unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "// lib").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    fs::create_dir_all(context_dir.join("references")).unwrap();
+
+    fs::write(
+        context_dir.join("guides/cli.md"),
+        "---\nslug: cli\nowners: ['@alice', team-cli]\n---\n\nSee `src/cli/args.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("references/lib.md"),
+        "---\nslug: lib\ntags: [core]\n---\n\nSee `src/lib.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    // Make the CLI guide stale
+    fs::write(dir.path().join("src/cli/args.rs"), "// changed").unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_show_annotates_references_inline() {
+    let (_dir, cache) = setup_project();
+    let shown = cache.show("cli").unwrap();
+    assert_eq!(shown.status, Status::Stale);
+    assert_eq!(
+        shown.references["src/cli/args.rs"],
+        ReferenceOutcome::Changed
+    );
+    assert_eq!(
+        cache.show("lib").unwrap().references["src/lib.rs"],
+        ReferenceOutcome::Unchanged
+    );
+
+    let labels = [("src/cli/args.rs".to_string(), "stale")]
+        .into_iter()
+        .collect();
+    let body = "See `src/cli/args.rs`.\n```\n`src/cli/args.rs`\n```\nAnd `./src/cli/args.rs`.\n";
+    assert_eq!(
+        document::annotate_references(body, &labels),
+        "See `src/cli/args.rs` [stale].\n```\n`src/cli/args.rs`\n```\nAnd `./src/cli/args.rs` [stale].\n"
+    );
+}
+
+fn names(validations: &[context::core::Validation]) -> Vec<String> {
+    let mut names: Vec<_> = validations
+        .iter()
+        .map(|v| v.path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_status_filter_by_status() {
+    let (_dir, cache) = setup_project();
+    let filter = StatusFilter {
+        statuses: vec![Status::Stale],
+        ..StatusFilter::default()
+    };
+    assert_eq!(
+        names(&cache.status_filtered(&filter).unwrap()),
+        vec!["cli.md"]
+    );
+}
+
+#[test]
+fn test_status_filter_by_directory_and_tag() {
+    let (_dir, cache) = setup_project();
+
+    let by_dir = StatusFilter {
+        directory: Some(PathBuf::from("references")),
+        ..StatusFilter::default()
+    };
+    assert_eq!(
+        names(&cache.status_filtered(&by_dir).unwrap()),
+        vec!["lib.md"]
+    );
+
+    let by_tag = StatusFilter {
+        tag: Some("core".to_string()),
+        ..StatusFilter::default()
+    };
+    assert_eq!(
+        names(&cache.status_filtered(&by_tag).unwrap()),
+        vec!["lib.md"]
+    );
+}
+
+#[test]
+fn test_status_filter_by_document() {
+    let (_dir, cache) = setup_project();
+    for slug_or_path in ["lib", "references/lib.md"] {
+        let filter = StatusFilter {
+            document: Some(cache.get(slug_or_path).unwrap().path.clone()),
+            ..StatusFilter::default()
+        };
+        assert_eq!(
+            names(&cache.status_filtered(&filter).unwrap()),
+            vec!["lib.md"]
+        );
+    }
+    assert!(matches!(
+        cache.get("missing"),
+        Err(ContextError::DocumentNotFound(_))
+    ));
+}
+
+#[test]
+fn test_list_documents_with_status_and_reference_count() {
+    let (_dir, cache) = setup_project();
+    let listed = cache.list(&StatusFilter::default()).unwrap();
+    let rows: Vec<_> = listed
+        .iter()
+        .map(|d| (d.path.clone(), d.slug.as_str(), d.status, d.references))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (PathBuf::from("guides/cli.md"), "cli", Status::Stale, 1),
+            (PathBuf::from("references/lib.md"), "lib", Status::Valid, 1),
+        ]
+    );
+
+    let by_dir = StatusFilter {
+        directory: Some(PathBuf::from("guides")),
+        ..StatusFilter::default()
+    };
+    assert_eq!(cache.list(&by_dir).unwrap().len(), 1);
+}
+
+#[test]
+fn test_query_target_reports_documents_for_resolved_files() {
+    let (dir, cache) = setup_project();
+    assert!(cache.query_target("//src/cli:cli").is_err());
+
+    fs::write(
+        dir.path().join(".context/config.toml"),
+        "[query]\nresolver = \"echo \\\"$1\\\"; echo //src:lib.rs\"\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    let query = cache.query_target("//src/cli:args.rs").unwrap();
+    assert_eq!(query.files, vec!["src/cli/args.rs", "src/lib.rs"]);
+    let documents: Vec<_> = query
+        .documents
+        .iter()
+        .map(|d| {
+            (
+                d.document
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+                d.status,
+                d.references.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        documents,
+        vec![
+            (
+                "cli.md".to_string(),
+                Status::Stale,
+                vec!["src/cli/args.rs".to_string()]
+            ),
+            (
+                "lib.md".to_string(),
+                Status::Valid,
+                vec!["src/lib.rs".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_status_filter_by_owner() {
+    let (_dir, cache) = setup_project();
+    for owner in ["alice", "@Alice", "TEAM-CLI"] {
+        let filter = StatusFilter {
+            owners: vec!["nobody".to_string(), owner.to_string()],
+            ..StatusFilter::default()
+        };
+        assert_eq!(
+            names(&cache.status_filtered(&filter).unwrap()),
+            vec!["cli.md"],
+            "{owner}"
+        );
+    }
+
+    let filter = StatusFilter {
+        owners: vec!["bob".to_string()],
+        ..StatusFilter::default()
+    };
+    assert!(cache.status_filtered(&filter).unwrap().is_empty());
+}
+
+#[test]
+fn test_status_filter_by_referenced_path_prefix() {
+    let (_dir, cache) = setup_project();
+    let filter = StatusFilter {
+        paths: vec!["src/cli".to_string()],
+        statuses: vec![Status::Stale],
+        ..StatusFilter::default()
+    };
+    assert_eq!(
+        names(&cache.status_filtered(&filter).unwrap()),
+        vec!["cli.md"]
+    );
+}
+
+#[test]
+fn test_find_reports_line_and_anchor() {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("guides/arch.md"),
+        "---\nslug: arch\nreferences:\n  src/lib.rs: abc1234\n---\n\n# Architecture\n\n## Core Library\n\nLives in `./src/lib.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let result = cache.find_by_reference("src/lib.rs").unwrap();
+    let arch = result
+        .matches
+        .iter()
+        .find(|m| m.document.ends_with("arch.md"))
+        .unwrap();
+    assert_eq!(arch.line, Some(5));
+    assert_eq!(arch.anchor.as_deref(), Some("core-library"));
+}
+
+#[test]
+fn test_explain_source_file() {
+    let (_dir, cache) = setup_project();
+
+    let explanation = cache.explain("src/cli/args.rs").unwrap();
+    assert!(explanation.exists);
+    assert_eq!(explanation.documents.len(), 1);
+
+    let doc = &explanation.documents[0];
+    assert_eq!(doc.slug, "cli");
+    assert_eq!(doc.status, Status::Stale);
+    assert!(doc.changed);
+    assert!(!doc.updated.is_empty());
+    assert_eq!(doc.excerpt.as_deref(), Some("See `src/cli/args.rs`."));
+
+    let unknown = cache.explain("src/nope.rs").unwrap();
+    assert!(!unknown.exists);
+    assert!(unknown.documents.is_empty());
+}
+
+#[test]
+fn test_results_are_sorted_by_document_then_reference() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    for name in ["a", "b", "c", "d"] {
+        fs::write(dir.path().join(format!("src/{name}.rs")), name).unwrap();
+    }
+    let context_dir = dir.path().join(".context");
+    for doc in ["zeta.md", "guides/beta.md", "alpha.md", "guides/alpha.md"] {
+        let path = context_dir.join(doc);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(
+            path,
+            format!(
+                "---\nslug: {}\n---\n\n`src/d.rs` `src/b.rs` `src/c.rs` `src/a.rs`\n",
+                doc.trim_end_matches(".md")
+            ),
+        )
+        .unwrap();
+    }
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let synced = cache.sync(None).unwrap();
+    for name in ["a", "b", "c", "d"] {
+        fs::write(dir.path().join(format!("src/{name}.rs")), "changed").unwrap();
+    }
+
+    let expected: Vec<PathBuf> = ["alpha.md", "guides/alpha.md", "guides/beta.md", "zeta.md"]
+        .iter()
+        .map(|doc| context_dir.join(doc))
+        .collect();
+    assert_eq!(synced.updated, expected);
+
+    let validations = cache.status().unwrap();
+    let paths: Vec<_> = validations.iter().map(|v| v.path.clone()).collect();
+    assert_eq!(paths, expected);
+    assert_eq!(
+        validations[0].changed,
+        vec!["src/a.rs", "src/b.rs", "src/c.rs", "src/d.rs"]
+    );
+
+    let found = cache.find_by_reference("src/c.rs").unwrap();
+    let paths: Vec<_> = found.matches.iter().map(|m| m.document.clone()).collect();
+    assert_eq!(paths, expected);
+
+    let content = fs::read_to_string(context_dir.join("alpha.md")).unwrap();
+    let positions: Vec<_> = ["src/a.rs:", "src/b.rs:", "src/c.rs:", "src/d.rs:"]
+        .iter()
+        .map(|r| content.find(r).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_find_reports_per_reference_status() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    for name in ["a", "b", "c"] {
+        fs::write(dir.path().join(format!("src/{name}.rs")), name).unwrap();
+    }
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("abc.md"),
+        "---\nslug: abc\n---\n\nSee `src/a.rs`, `src/b.rs` and `./src/c.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    fs::write(dir.path().join("src/b.rs"), "changed").unwrap();
+    fs::remove_file(dir.path().join("src/c.rs")).unwrap();
+
+    let paths = ["./src/a.rs", "src/b.rs", "src/c.rs", "src/d.rs"].map(str::to_string);
+    let found = cache.find_by_references(&paths).unwrap();
+    let statuses: Vec<_> = found
+        .iter()
+        .map(|r| r.matches.first().map(|m| (m.status, m.reference_status)))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            Some((Status::Orphaned, Status::Valid)),
+            Some((Status::Orphaned, Status::Stale)),
+            Some((Status::Orphaned, Status::Orphaned)),
+            None,
+        ]
+    );
+    assert_eq!(found[0].query, "./src/a.rs");
+}
+
+#[test]
+fn test_summary_counts_categories_and_statuses() {
+    let (_dir, cache) = setup_project();
+    let summary = cache.summary().unwrap();
+    assert_eq!(summary.documents, 2);
+    assert_eq!(summary.categories.get("guides"), Some(&1));
+    assert_eq!(summary.categories.get("references"), Some(&1));
+    assert_eq!((summary.valid, summary.stale, summary.orphaned), (1, 1, 0));
+    assert!(summary.last_updated.is_some());
+    assert_eq!(summary.oldest_updated, summary.last_updated);
+    assert_eq!(summary.references, 2);
+    let largest: Vec<_> = summary
+        .largest
+        .iter()
+        .map(|d| d.path.file_name().unwrap().to_owned())
+        .collect();
+    assert_eq!(largest, vec!["cli.md", "lib.md"]);
+    assert!(summary.largest[0].bytes >= summary.largest[1].bytes);
+}
+
+#[test]
+fn test_invalid_utf8_and_bom_tolerated() {
+    let (dir, _cache) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("guides/pasted.md"),
+        b"\xEF\xBB\xBF---\nslug: pasted\n---\n\nBad \xFF byte in `src/lib.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let doc = cache.get("pasted").unwrap();
+    assert_eq!(doc.invalid_utf8_at, Some(29));
+    assert!(doc.body.contains("Bad \u{FFFD} byte"));
+
+    let issues = cache.lint();
+    assert!(issues
+        .iter()
+        .any(|i| i.rule == "non-utf8" && i.path.ends_with("pasted.md")));
+}
+
+#[test]
+fn test_load_metadata_skips_bodies() {
+    let (dir, _cache) = setup_project();
+    let context_dir = dir.path().join(".context");
+
+    let doc = Document::load_metadata(context_dir.join("references/lib.md")).unwrap();
+    assert_eq!(doc.slug, "lib");
+    assert_eq!(doc.tags, vec!["core"]);
+    assert!(doc.references.contains_key("src/lib.rs"));
+    assert!(!doc.has_body());
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load_metadata().unwrap();
+    assert!(cache.is_low_memory());
+    assert_eq!(
+        names(&cache.status_filtered(&StatusFilter::default()).unwrap()),
+        vec!["cli.md", "lib.md"]
+    );
+    let doc = cache.get_with_body("lib").unwrap();
+    assert!(doc.body.contains("`src/lib.rs`"));
+}
+
+#[test]
+fn test_dependency_order_follows_links() {
+    let (dir, _cache) = setup_project();
+    let context_dir = dir.path().join(".context");
+    // The guide builds on the reference, which sorts after it by path
+    fs::write(
+        context_dir.join("guides/cli.md"),
+        "---\nslug: cli\n---\n\nSee [lib](../references/lib.md) and `src/cli/args.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    let order = cache.dependency_order().unwrap();
+    let names: Vec<_> = order
+        .order
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["lib.md", "cli.md"]);
+    assert!(order.cycles.is_empty());
+}
+
+#[test]
+fn test_status_names_stale_scoped_sections() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+    fs::create_dir_all(dir.path().join(".context")).unwrap();
+    fs::write(dir.path().join("src/auth/login.rs"), "fn login() {}").unwrap();
+    fs::write(dir.path().join("src/auth/refresh.rs"), "fn refresh() {}").unwrap();
+    fs::write(
+        dir.path().join(".context/auth.md"),
+        "---\nslug: auth\n---\n\n# Auth\n\nLogin is in `src/auth/login.rs`.\n\n## Token refresh\n\n<!-- context:scope -->\n\nSee `src/auth/refresh.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    fs::write(
+        dir.path().join("src/auth/refresh.rs"),
+        "fn refresh() { todo!() }",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load_metadata().unwrap();
+    let status = cache.status().unwrap();
+    assert_eq!(status[0].status, Status::Stale);
+    assert_eq!(status[0].sections.len(), 1);
+    assert_eq!(status[0].sections[0].title, "Token refresh");
+    assert_eq!(status[0].sections[0].anchor, "token-refresh");
+    assert_eq!(status[0].sections[0].changed, vec!["src/auth/refresh.rs"]);
+
+    // References outside scoped sections only mark the whole document
+    fs::write(dir.path().join("src/auth/refresh.rs"), "fn refresh() {}").unwrap();
+    fs::write(
+        dir.path().join("src/auth/login.rs"),
+        "fn login() { todo!() }",
+    )
+    .unwrap();
+    let status = cache.status().unwrap();
+    assert_eq!(status[0].status, Status::Stale);
+    assert!(status[0].sections.is_empty());
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_status_since_merge_base_ignores_changes_from_base_branch() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join(".context")).unwrap();
+    fs::write(root.join("src/a.rs"), "// a").unwrap();
+    fs::write(root.join("src/b.rs"), "// b").unwrap();
+    fs::write(
+        root.join(".context/guide.md"),
+        "---\nslug: guide\n---\n\nSee `src/a.rs` and `src/b.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    git(root, &["init", "-q", "-b", "main"]);
+    git(root, &["add", "-A"]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+
+    // The base branch changes a reference without syncing the guide
+    fs::write(root.join("src/a.rs"), "// a, edited on main").unwrap();
+    git(root, &["commit", "-qam", "edit a"]);
+    git(root, &["checkout", "-q", "-b", "feature"]);
+
+    let mut cache = Cache::create(root.join(".context")).unwrap();
+    cache.load().unwrap();
+    let filter = StatusFilter::default();
+    assert_eq!(
+        cache.status_filtered(&filter).unwrap()[0].status,
+        Status::Stale
+    );
+    let since = cache.status_since_merge_base(&filter, "main").unwrap();
+    assert_eq!(since[0].status, Status::Valid);
+    assert!(since[0].changed.is_empty());
+
+    // Only the pull request's own change counts, committed or not
+    fs::write(root.join("src/b.rs"), "// b, edited on feature").unwrap();
+    let since = cache.status_since_merge_base(&filter, "main").unwrap();
+    assert_eq!(since[0].status, Status::Stale);
+    assert_eq!(since[0].changed, vec!["src/b.rs"]);
+
+    fs::remove_file(root.join("src/b.rs")).unwrap();
+    let since = cache.status_since_merge_base(&filter, "main").unwrap();
+    assert_eq!(since[0].status, Status::Orphaned);
+    assert_eq!(since[0].missing, vec!["src/b.rs"]);
+
+    assert!(cache
+        .status_since_merge_base(&filter, "no-such-branch")
+        .is_err());
+}
+
+#[test]
+fn test_coverage_respects_gitignore_and_prefixes() {
+    let (dir, cache) = setup_project();
+    let root = dir.path();
+    fs::write(root.join("src/cli/run.rs"), "// run").unwrap();
+    fs::create_dir_all(root.join("target")).unwrap();
+    fs::write(root.join("target/build.rs"), "// generated").unwrap();
+    fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    git(root, &["init", "-q"]);
+
+    let coverage = cache.coverage(&[]).unwrap();
+    assert_eq!(coverage.covered, vec!["src/cli/args.rs", "src/lib.rs"]);
+    assert_eq!(coverage.uncovered, vec![".gitignore", "src/cli/run.rs"]);
+    assert!((coverage.percent() - 50.0).abs() < f64::EPSILON);
+
+    let cli = cache.coverage(&["src/cli".to_string()]).unwrap();
+    assert_eq!(cli.covered, vec!["src/cli/args.rs"]);
+    assert_eq!(cli.uncovered, vec!["src/cli/run.rs"]);
+}
+
+#[test]
+fn test_document_states() {
+    let (dir, _) = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(dir.path().join("src/old.rs"), "// old").unwrap();
+    fs::write(dir.path().join("src/new.rs"), "// new").unwrap();
+    fs::write(
+        context_dir.join("guides/old.md"),
+        "---\nslug: old\nstate: deprecated\nsuperseded_by: new\n---\n\nSee `src/old.rs` and `src/lib.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/new.md"),
+        "---\nslug: new\nstate: draft\n---\n\nSee `src/new.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/gone.md"),
+        "---\nslug: gone\nstate: deprecated\n---\n\nGone.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    // Drafts are left out of coverage and packs unless asked for
+    assert!(!cache
+        .coverage(&[])
+        .unwrap()
+        .covered
+        .contains(&"src/new.rs".to_string()));
+    let packed = |cache: &Cache| {
+        let pack = cache.pack_files(&["src/new.rs".to_string()], 0).unwrap();
+        pack.into_iter().map(|d| d.slug).collect::<Vec<_>>()
+    };
+    assert!(packed(&cache).is_empty());
+    cache.set_include_drafts(true);
+    assert_eq!(packed(&cache), vec!["new"]);
+    assert!(cache
+        .coverage(&[])
+        .unwrap()
+        .covered
+        .contains(&"src/new.rs".to_string()));
+
+    // Surfacing a deprecated document warns and names its successor
+    assert!(cache.warnings().is_empty());
+    assert_eq!(
+        cache.find_by_reference("src/old.rs").unwrap().matches.len(),
+        1
+    );
+    let warnings = cache.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].message, "'old' is deprecated; see 'new'");
+
+    let filter = StatusFilter {
+        states: vec![DocumentState::Deprecated],
+        ..StatusFilter::default()
+    };
+    let listed: Vec<_> = cache
+        .list(&filter)
+        .unwrap()
+        .into_iter()
+        .map(|d| d.slug)
+        .collect();
+    assert_eq!(listed, vec!["gone", "old"]);
+
+    // Deprecation must name an existing, published successor
+    let rules: Vec<_> = cache
+        .lint()
+        .into_iter()
+        .map(|i| (i.path.display().to_string(), i.rule))
+        .collect();
+    assert!(rules
+        .iter()
+        .any(|(path, rule)| path.ends_with("gone.md") && *rule == "deprecated-without-successor"));
+    assert!(rules
+        .iter()
+        .any(|(path, rule)| path.ends_with("old.md") && *rule == "draft-successor"));
+}
@@ -0,0 +1,165 @@
+//! Integration tests for storage backends
+
+use context::core::{Cache, Status, StatusFilter};
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_project(config: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(
+        context_dir.join("a.md"),
+        "---\nslug: a\n---\n\n# A\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+    fs::write(context_dir.join("config.toml"), config).unwrap();
+    dir
+}
+
+fn statuses(cache: &Cache) -> Vec<Status> {
+    cache
+        .status()
+        .unwrap()
+        .into_iter()
+        .map(|v| v.status)
+        .collect()
+}
+
+#[test]
+fn test_sqlite_backend_keeps_hashes_out_of_frontmatter() {
+    let dir = setup_project("[storage]\nbackend = \"sqlite\"\n");
+    let context_dir = dir.path().join(".context");
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    assert!(context_dir.join(".cache/context.db").exists());
+
+    let content = fs::read_to_string(context_dir.join("a.md")).unwrap();
+    assert!(!content.contains("references"));
+    assert!(content.contains("slug: a"));
+
+    // A fresh cache reads the hashes back from the database
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    assert!(cache.get("a").unwrap().references.contains_key("src/a.rs"));
+    assert_eq!(statuses(&cache), vec![Status::Valid]);
+
+    fs::write(dir.path().join("src/a.rs"), "fn b() {}\n").unwrap();
+    assert_eq!(statuses(&cache), vec![Status::Stale]);
+}
+
+#[test]
+fn test_switching_to_sqlite_keeps_frontmatter_hashes_until_saved() {
+    let dir = setup_project("");
+    let context_dir = dir.path().join(".context");
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    fs::write(
+        context_dir.join("config.toml"),
+        "[storage]\nbackend = \"sqlite\"\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    assert_eq!(statuses(&cache), vec![Status::Valid]);
+
+    cache.sync(None).unwrap();
+    assert!(!fs::read_to_string(context_dir.join("a.md"))
+        .unwrap()
+        .contains("references"));
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    assert_eq!(statuses(&cache), vec![Status::Valid]);
+}
+
+#[test]
+fn test_index_updates_only_changed_bodies() {
+    let dir = setup_project("[storage]\nbackend = \"sqlite\"\n");
+    let context_dir = dir.path().join(".context");
+    fs::write(context_dir.join("b.md"), "---\nslug: b\n---\n\n# B\n").unwrap();
+
+    let cache = Cache::create(context_dir.clone()).unwrap();
+    let update = cache.index(false).unwrap();
+    assert_eq!(update.indexed.len(), 2);
+    assert!(cache.verify_index().unwrap().is_ok());
+
+    // New frontmatter with the same body is restamped, not reindexed
+    fs::write(
+        context_dir.join("b.md"),
+        "---\nslug: b\ndescription: Bee\n---\n\n# B\n",
+    )
+    .unwrap();
+    fs::remove_file(context_dir.join("a.md")).unwrap();
+    let update = cache.index(false).unwrap();
+    assert!(update.indexed.is_empty());
+    assert_eq!(update.unchanged, 1);
+    assert_eq!(update.removed, vec![std::path::PathBuf::from("a.md")]);
+
+    fs::write(
+        context_dir.join("b.md"),
+        "---\nslug: b\n---\n\n# B\n\nMore.\n",
+    )
+    .unwrap();
+    let verification = cache.verify_index().unwrap();
+    assert_eq!(
+        verification.outdated,
+        vec![std::path::PathBuf::from("b.md")]
+    );
+    assert!(!verification.is_ok());
+
+    let update = cache.index(true).unwrap();
+    assert_eq!(update.indexed, vec![std::path::PathBuf::from("b.md")]);
+    assert!(cache.verify_index().unwrap().is_ok());
+}
+
+#[test]
+fn test_index_requires_sqlite_backend() {
+    let dir = setup_project("");
+    let cache = Cache::create(dir.path().join(".context")).unwrap();
+    let err = cache.index(false).unwrap_err().to_string();
+    assert!(
+        err.contains("frontmatter backend keeps no search index"),
+        "{err}"
+    );
+    assert!(cache.verify_index().is_err());
+}
+
+#[test]
+fn test_cleanup_forgets_deleted_documents() {
+    let dir = setup_project("[storage]\nbackend = \"sqlite\"\n");
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("b.md"),
+        "---\nslug: b\n---\n\n# B\n\nSee `src/a.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    cache.index(false).unwrap();
+    fs::remove_file(context_dir.join("b.md")).unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    assert!(cache.sync(None).unwrap().cleaned_artifacts.is_empty());
+    cache.set_cleanup(true);
+    let plan = cache.sync_plan(None, &StatusFilter::default()).unwrap();
+    assert_eq!(
+        plan.cleaned_artifacts,
+        vec![std::path::PathBuf::from("b.md")]
+    );
+    let result = cache.sync(None).unwrap();
+    assert_eq!(
+        result.cleaned_artifacts,
+        vec![std::path::PathBuf::from("b.md")]
+    );
+    assert!(cache.verify_index().unwrap().orphaned.is_empty());
+}
@@ -0,0 +1,63 @@
+//! Integration tests for reference suggestions
+
+use context::core::Cache;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_suggest_and_apply() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+    fs::write(
+        dir.path().join("src/auth/store.rs"),
+        "pub struct TokenStore;\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "pub mod auth;\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    let doc_path = context_dir.join("guides/auth.md");
+    fs::write(
+        &doc_path,
+        "---\nslug: auth\n---\n\n# Auth\n\nTokens live in the `TokenStore`. Entry point: `src/lib.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    let suggestions = cache.suggest(&doc_path, 10, false).unwrap();
+    let paths: Vec<_> = suggestions
+        .suggestions
+        .iter()
+        .map(|s| s.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["src/auth/store.rs"]);
+    assert_eq!(
+        suggestions.suggestions[0].reasons,
+        vec!["defines `TokenStore`"]
+    );
+    assert!(!suggestions.applied);
+
+    let applied = cache.suggest(&doc_path, 10, true).unwrap();
+    assert!(applied.applied);
+    let content = fs::read_to_string(&doc_path).unwrap();
+    assert!(content.contains("## Related files\n\n- `src/auth/store.rs`\n"));
+
+    // The new reference is tracked straight away and survives a sync
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    assert!(cache
+        .get("auth")
+        .unwrap()
+        .references
+        .contains_key("src/auth/store.rs"));
+    cache.sync(None).unwrap();
+    assert!(cache
+        .suggest(&doc_path, 10, false)
+        .unwrap()
+        .suggestions
+        .is_empty());
+}
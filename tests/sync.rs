@@ -1,8 +1,9 @@
 //! Integration tests for the sync command
 
 use context::core::document::Document;
-use context::core::Cache;
+use context::core::{Cache, Status, StatusFilter};
 use std::fs;
+use std::path::PathBuf;
 use tempfile::TempDir;
 
 /// Set up a test project with a .context directory
@@ -378,3 +379,557 @@ See `src/main.rs`.
     // Updated date should be changed (since hash was different)
     assert_ne!(doc.updated, "2020-01-01");
 }
+
+#[test]
+fn test_resolve_conflicted_references() {
+    let dir = setup_project();
+
+    let doc_content = r"---
+slug: main
+description: Entry point
+references:
+<<<<<<< HEAD
+  src/main.rs: 1111111
+=======
+  src/main.rs: 2222222
+  src/gone.rs: 3333333
+>>>>>>> feature
+updated: 2025-01-01
+hash: abcdef0
+---
+
+# Main
+
+The entry point is in `src/main.rs`.
+";
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(&doc_path, doc_content).unwrap();
+
+    // Conflicted documents refuse to load
+    assert!(matches!(
+        Document::load(&doc_path),
+        Err(context::error::ContextError::MergeConflict { .. })
+    ));
+
+    let cache = Cache::create(dir.path().join(".context")).unwrap();
+    let result = cache.resolve(None).unwrap();
+    assert_eq!(result.resolved, vec![doc_path.clone()]);
+    assert!(result.failed.is_empty());
+
+    let doc = Document::load(&doc_path).unwrap();
+    assert_eq!(doc.description, "Entry point");
+    assert_eq!(doc.references.len(), 1);
+    assert_ne!(doc.references["src/main.rs"], "1111111");
+}
+
+#[test]
+fn test_imported_references_survive_sync() {
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(
+        &doc_path,
+        "---\nslug: main\n---\n\n# Main\n\nThe entry point is in `src/main.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+
+    let input = concat!(
+        "{\"path\": \"src/lib.rs\", \"doc\": \"main\"}\n",
+        "{\"path\": \"src/missing.rs\", \"doc\": \"main\"}\n",
+        "{\"path\": \"src/lib.rs\", \"doc\": \"nope\"}\n",
+    );
+    let declarations = context::core::import::parse_jsonl(input.as_bytes()).unwrap();
+    let result = cache.import_references(&declarations).unwrap();
+    assert_eq!(result.imported, 1);
+    assert_eq!(result.updated, vec![doc_path.clone()]);
+    assert_eq!(result.failed.len(), 2);
+
+    let doc = Document::load(&doc_path).unwrap();
+    assert_eq!(doc.external_references, vec!["src/lib.rs"]);
+    assert!(doc.references.contains_key("src/lib.rs"));
+
+    // A later sync re-derives body references but keeps the imported one
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    let doc = Document::load(&doc_path).unwrap();
+    assert!(doc.references.contains_key("src/main.rs"));
+    assert!(doc.references.contains_key("src/lib.rs"));
+}
+
+#[test]
+fn test_cancelled_operations_stop_early() {
+    use context::core::cancel::CancellationToken;
+    use context::error::ContextError;
+
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(&doc_path, "---\nslug: main\n---\n\nSee `src/main.rs`.\n").unwrap();
+
+    let token = CancellationToken::new();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.set_cancellation(token.clone());
+    cache.load().unwrap();
+
+    token.cancel();
+    assert!(matches!(cache.sync(None), Err(ContextError::Cancelled)));
+    assert!(matches!(cache.status(), Err(ContextError::Cancelled)));
+    assert!(matches!(cache.load(), Err(ContextError::Cancelled)));
+
+    // Nothing was written
+    let doc = Document::load(&doc_path).unwrap();
+    assert!(doc.references.is_empty());
+}
+
+#[test]
+fn test_interrupted_operations_return_partial_results() {
+    use context::core::cancel::CancellationToken;
+    use context::core::events::Event;
+
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("guides/a.md"),
+        "---\nslug: a\n---\n\nSee `src/main.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/b.md"),
+        "---\nslug: b\n---\n\nSee `src/main.rs`.\n",
+    )
+    .unwrap();
+
+    let token = CancellationToken::new();
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.set_cancellation(token.clone());
+    let interrupter = token.clone();
+    cache.subscribe(move |event: &Event| {
+        if matches!(
+            event,
+            Event::SyncApplied { .. } | Event::ReferenceValidated { .. }
+        ) {
+            interrupter.interrupt();
+        }
+    });
+    cache.load().unwrap();
+
+    let result = cache.sync(None).unwrap();
+    assert!(result.partial);
+    assert_eq!(result.updated, vec![context_dir.join("guides/a.md")]);
+    assert!(Document::load(context_dir.join("guides/b.md"))
+        .unwrap()
+        .references
+        .is_empty());
+
+    // Once interrupted, status returns without checking anything further
+    assert!(cache.status().unwrap().is_empty());
+}
+
+#[test]
+fn test_low_memory_mode_loads_bodies_on_demand() {
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(
+        &doc_path,
+        "---\nslug: main\ndescription: Entry\n---\n\n# Main\n\nSee `src/main.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.set_low_memory(1);
+    cache.load().unwrap();
+
+    let doc = cache.get("main").unwrap();
+    assert!(!doc.has_body());
+    assert!(doc.body.is_empty());
+    assert!(cache.get_with_body("main").unwrap().body.contains("# Main"));
+
+    // Operations that need bodies read them back from disk
+    cache.sync(None).unwrap();
+    let found = cache.find_by_reference("src/main.rs").unwrap();
+    assert_eq!(found.matches[0].line, Some(3));
+    assert!(!cache.get("main").unwrap().has_body());
+
+    // Saving metadata alone keeps the body on disk intact
+    let reloaded = Document::load(&doc_path).unwrap();
+    assert!(reloaded.references.contains_key("src/main.rs"));
+    assert!(reloaded.body.contains("See `src/main.rs`."));
+}
+
+#[test]
+fn test_sync_records_body_hash() {
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(&doc_path, "---\nslug: main\n---\n\nSee `src/main.rs`.\n").unwrap();
+
+    let mut doc = Document::load(&doc_path).unwrap();
+    assert_eq!(doc.body_hash, None);
+    doc.sync().unwrap();
+
+    let reloaded = Document::load(&doc_path).unwrap();
+    let recorded = reloaded.body_hash.clone().unwrap();
+    assert_eq!(recorded, context::core::document::body_hash(&reloaded.body));
+    assert_eq!(recorded.len(), "sha256:".len() + 64);
+
+    // Only the body contributes, so frontmatter edits leave it alone
+    let mut doc = reloaded;
+    doc.description = "Entry point".to_string();
+    doc.sync().unwrap();
+    assert_eq!(doc.body_hash.as_deref(), Some(recorded.as_str()));
+
+    doc.body.push_str("\nMore detail.\n");
+    doc.sync().unwrap();
+    assert_ne!(doc.body_hash.as_deref(), Some(recorded.as_str()));
+}
+
+#[test]
+fn test_bulk_sync_skips_protected_documents() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    let runbook = context_dir.join("guides/runbook.md");
+    fs::write(
+        &runbook,
+        "---\nslug: runbook\nprotected: true\n---\n\nRestart `src/main.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/lib.md"),
+        "---\nslug: lib\n---\n\nSee `src/lib.rs`.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let result = cache.sync(None).unwrap();
+    assert_eq!(result.updated, vec![context_dir.join("guides/lib.md")]);
+    assert_eq!(result.protected, vec![runbook.clone()]);
+    assert!(Document::load(&runbook).unwrap().references.is_empty());
+
+    // Naming the document syncs it, and it stays protected
+    let result = cache.sync(Some(&runbook)).unwrap();
+    assert_eq!(result.updated, vec![runbook.clone()]);
+    assert!(result.protected.is_empty());
+    let doc = Document::load(&runbook).unwrap();
+    assert!(doc.references.contains_key("src/main.rs"));
+    assert!(doc.protected);
+}
+
+#[test]
+fn test_sync_records_assets_inside_context() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("assets")).unwrap();
+    fs::write(
+        context_dir.join("assets/architecture.png"),
+        [0x89, b'P', b'N', b'G'],
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/main.md"),
+        "---\nslug: main\n---\n\nSee `assets/architecture.png` for how `src/main.rs` fits in.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    let doc = cache.get("main").unwrap();
+    let assets: Vec<_> = doc.asset_references().cloned().collect();
+    let sources: Vec<_> = doc.source_references().cloned().collect();
+    assert_eq!(assets, vec![".context/assets/architecture.png"]);
+    assert_eq!(sources, vec!["src/main.rs"]);
+    assert_eq!(
+        doc.locate_reference(&assets[0]).map(|(line, _)| line),
+        Some(1)
+    );
+    assert_eq!(
+        cache.search_index().unwrap().documents[0].assets,
+        vec!["assets/architecture.png"]
+    );
+
+    // Redrawing the diagram makes the document stale like any other reference
+    fs::write(
+        context_dir.join("assets/architecture.png"),
+        [0x89, b'P', b'N', b'G', 0],
+    )
+    .unwrap();
+    let validation = cache.get("main").unwrap().validate().unwrap();
+    assert_eq!(validation.changed, vec![".context/assets/architecture.png"]);
+}
+
+#[test]
+fn test_sync_matching_only_stale_documents_under_directory() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    for (path, slug) in [
+        ("guides/main.md", "main"),
+        ("guides/lib.md", "lib"),
+        ("references/main.md", "main-ref"),
+    ] {
+        let source = if slug == "lib" {
+            "src/lib.rs"
+        } else {
+            "src/main.rs"
+        };
+        fs::write(
+            context_dir.join(path),
+            format!("---\nslug: {slug}\n---\n\nSee `{source}`.\n"),
+        )
+        .unwrap();
+    }
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() { run() }").unwrap();
+
+    let filter = StatusFilter {
+        directory: Some(PathBuf::from("guides")),
+        statuses: vec![Status::Stale],
+        ..StatusFilter::default()
+    };
+    let result = cache.sync_matching(None, &filter, None).unwrap();
+    assert_eq!(result.updated, vec![context_dir.join("guides/main.md")]);
+    assert_eq!(result.skipped, 1);
+    assert_eq!(
+        cache.get("main-ref").unwrap().validate().unwrap().status,
+        Status::Stale
+    );
+
+    // `--only-stale` leaves every valid document alone and counts it
+    let only_stale = StatusFilter {
+        statuses: Status::OUT_OF_DATE.to_vec(),
+        ..StatusFilter::default()
+    };
+    let result = cache.sync_matching(None, &only_stale, None).unwrap();
+    assert_eq!((result.count, result.skipped), (1, 2));
+    assert_eq!(result.updated, vec![context_dir.join("references/main.md")]);
+}
+
+#[test]
+fn test_sync_reports_unchanged_documents() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    fs::write(
+        context_dir.join("guides/main.md"),
+        "---\nslug: main\n---\n\nSee `src/main.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("guides/lib.md"),
+        "---\nslug: lib\n---\n\nSee `src/lib.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let result = cache.sync(None).unwrap();
+    assert_eq!(result.updated.len(), 2);
+    assert!(result.unchanged.is_empty());
+
+    fs::write(dir.path().join("src/lib.rs"), "// lib v2").unwrap();
+    let synced = fs::read_to_string(context_dir.join("guides/main.md")).unwrap();
+    let result = cache.sync(None).unwrap();
+    assert_eq!(result.count, 2);
+    assert_eq!(result.updated, vec![context_dir.join("guides/lib.md")]);
+    assert_eq!(result.unchanged, vec![context_dir.join("guides/main.md")]);
+    assert_eq!(
+        fs::read_to_string(context_dir.join("guides/main.md")).unwrap(),
+        synced
+    );
+}
+
+#[test]
+fn test_no_op_sync_leaves_files_byte_identical() {
+    let dir = setup_project();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    // Hand-written frontmatter that re-serializing would reformat
+    fs::write(&doc_path, "---\nslug: main\n---\n\nSee `src/main.rs`.\n").unwrap();
+    let mut doc = Document::load(&doc_path).unwrap();
+    doc.sync().unwrap();
+    let synced = fs::read(&doc_path).unwrap();
+
+    // Backdate the review so a bump would be visible
+    let backdated = String::from_utf8(synced)
+        .unwrap()
+        .replace(&doc.updated, "2020-01-01");
+    fs::write(&doc_path, &backdated).unwrap();
+    Document::load(&doc_path).unwrap().sync().unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+    assert_eq!(fs::read_to_string(&doc_path).unwrap(), backdated);
+
+    // A changed reference is a change to what was reviewed
+    fs::write(dir.path().join("src/main.rs"), "fn main() { run() }").unwrap();
+    cache.sync(None).unwrap();
+    assert_ne!(cache.get("main").unwrap().updated, "2020-01-01");
+}
+
+#[test]
+fn test_dry_run_reports_changes_without_writing() {
+    let dir = setup_project();
+    fs::write(dir.path().join("src/util.rs"), "// util").unwrap();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(
+        &doc_path,
+        "---\nslug: main\n---\n\nSee `src/main.rs` and `src/lib.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(".context/references/util.md"),
+        "---\nslug: util\n---\n\nSee `src/util.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    // Swap one reference for another and change the one kept
+    fs::write(
+        &doc_path,
+        fs::read_to_string(&doc_path)
+            .unwrap()
+            .replace("and `src/lib.rs`", "and `src/util.rs`"),
+    )
+    .unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() { run() }").unwrap();
+    let before = fs::read(&doc_path).unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    let recorded = cache.get("main").unwrap().references.clone();
+
+    let plan = cache.sync_plan(None, &StatusFilter::default()).unwrap();
+    assert_eq!(
+        plan.unchanged,
+        vec![dir.path().join(".context/references/util.md")]
+    );
+    assert_eq!(plan.changes.len(), 1);
+    let change = &plan.changes[0];
+    assert_eq!(change.path, doc_path);
+    assert_eq!(change.added, vec!["src/util.rs"]);
+    assert_eq!(change.removed, vec!["src/lib.rs"]);
+    let rehashed = &change.rehashed["src/main.rs"];
+    assert_eq!(rehashed.from, recorded["src/main.rs"]);
+    assert_ne!(rehashed.from, rehashed.to);
+    assert!(change.hash.is_some());
+    assert!(change.body_hash.is_some());
+
+    assert_eq!(fs::read(&doc_path).unwrap(), before);
+    assert_eq!(cache.get("main").unwrap().references, recorded);
+}
+
+#[test]
+fn test_sync_single_document_by_slug_or_path() {
+    let dir = setup_project();
+    fs::write(
+        dir.path().join(".context/guides/main.md"),
+        "---\nslug: main\n---\n\nSee `src/main.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(".context/references/lib.md"),
+        "---\nslug: lib\n---\n\nSee `src/lib.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+
+    let target = cache.sync_target("main").unwrap();
+    assert_eq!(cache.sync_target("guides/main.md").unwrap(), target);
+    let result = cache.sync(Some(&target)).unwrap();
+    assert_eq!(result.updated, vec![target]);
+    assert!(cache.get("lib").unwrap().references.is_empty());
+    assert!(cache.sync_target("missing").is_err());
+}
+
+#[test]
+fn test_prune_missing_drops_deleted_references() {
+    let dir = setup_project();
+    fs::write(dir.path().join("src/old.rs"), "// old").unwrap();
+    let doc_path = dir.path().join(".context/guides/main.md");
+    fs::write(
+        &doc_path,
+        "---\nslug: main\n---\n\nSee `src/main.rs` and `src/old.rs`.\n",
+    )
+    .unwrap();
+    Document::load(&doc_path).unwrap().sync().unwrap();
+    fs::remove_file(dir.path().join("src/old.rs")).unwrap();
+
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    assert!(cache.sync(None).is_err());
+
+    cache.set_prune_missing(true);
+    let result = cache.sync(None).unwrap();
+    assert_eq!(result.pruned[&doc_path], vec!["src/old.rs"]);
+    let doc = Document::load(&doc_path).unwrap();
+    assert_eq!(
+        doc.references.keys().collect::<Vec<_>>(),
+        vec!["src/main.rs"]
+    );
+    assert_eq!(doc.validate().unwrap().status, Status::Valid);
+
+    // Traversal and absolute paths are still rejected
+    fs::write(
+        &doc_path,
+        "---\nslug: main\n---\n\nSee `../outside.rs` and `src/old.rs`.\n",
+    )
+    .unwrap();
+    let mut doc = Document::load(&doc_path).unwrap();
+    assert!(doc.sync_pruning_missing().is_err());
+    fs::write(&doc_path, "---\nslug: main\n---\n\nSee `src/old.rs`.\n").unwrap();
+    let mut doc = Document::load(&doc_path).unwrap();
+    assert_eq!(doc.sync_pruning_missing().unwrap(), vec!["src/old.rs"]);
+    assert!(doc.references.is_empty());
+}
+
+#[test]
+fn test_cleanup_reports_and_drops_unmentioned_references() {
+    let dir = setup_project();
+    let main = dir.path().join(".context/guides/main.md");
+    let lib = dir.path().join(".context/references/lib.md");
+    fs::write(
+        &main,
+        "---\nslug: main\n---\n\nSee `src/main.rs` and `src/lib.rs`.\n",
+    )
+    .unwrap();
+    fs::write(
+        &lib,
+        "---\nslug: lib\n---\n\nSee `src/lib.rs` and `src/main.rs`.\n",
+    )
+    .unwrap();
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.sync(None).unwrap();
+
+    // Edit the bodies, keeping the recorded references
+    for (path, dropped) in [(&main, " and `src/lib.rs`"), (&lib, " and `src/main.rs`")] {
+        fs::write(path, fs::read_to_string(path).unwrap().replace(dropped, "")).unwrap();
+    }
+    let mut cache = Cache::create(dir.path().join(".context")).unwrap();
+    cache.load().unwrap();
+    cache.set_cleanup(true);
+    let filter = StatusFilter {
+        directory: Some(PathBuf::from("guides")),
+        ..StatusFilter::default()
+    };
+    // A dry run lists the drops and writes nothing
+    let plan = cache.sync_plan(None, &filter).unwrap();
+    assert_eq!(plan.changes[0].removed, vec!["src/lib.rs"]);
+    assert_eq!(plan.cleaned_references[&lib], vec!["src/main.rs"]);
+    assert_eq!(Document::load(&lib).unwrap().references.len(), 2);
+    let result = cache.sync_matching(None, &filter, None).unwrap();
+
+    // The guide was re-synced; the reference outside the filter only lost
+    // its dead reference
+    assert_eq!(result.updated, vec![main.clone()]);
+    assert_eq!(result.cleaned_references[&main], vec!["src/lib.rs"]);
+    assert_eq!(result.cleaned_references[&lib], vec!["src/main.rs"]);
+    let lib_doc = Document::load(&lib).unwrap();
+    assert_eq!(
+        lib_doc.references.keys().collect::<Vec<_>>(),
+        vec!["src/lib.rs"]
+    );
+}
@@ -0,0 +1,66 @@
+//! Integration tests for document templates
+
+use context::core::template::{document_template, TemplateDrift, TEMPLATE_DIR};
+use context::core::Cache;
+use std::fs;
+use tempfile::TempDir;
+
+const TEMPLATE: &str = "---\nrequired_sections:\n- \"## Context\"\n---\n\n# ADR: <title>\n\n## Context\n\n## Decision\n";
+
+fn setup_project() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join(TEMPLATE_DIR)).unwrap();
+    fs::write(context_dir.join(TEMPLATE_DIR).join("adr.md"), TEMPLATE).unwrap();
+    dir
+}
+
+#[test]
+fn test_template_drift_and_outdated_versions() {
+    let dir = setup_project();
+    let context_dir = dir.path().join(".context");
+    let version = document_template(&context_dir, "adr")
+        .unwrap()
+        .unwrap()
+        .version;
+
+    fs::write(
+        context_dir.join("adr-1.md"),
+        format!(
+            "---\nslug: adr-1\ntemplate: adr\ntemplate_version: {version}\n---\n\n# ADR: Rust\n\n## Context\n\nSpeed.\n\n## Alternatives\n\nGo.\n"
+        ),
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("adr-2.md"),
+        "---\nslug: adr-2\ntemplate: adr\n---\n\n## Context\n\n## Decision\n",
+    )
+    .unwrap();
+    fs::write(
+        context_dir.join("notes.md"),
+        "---\nslug: notes\n---\n\nNotes.\n",
+    )
+    .unwrap();
+
+    let mut cache = Cache::create(context_dir.clone()).unwrap();
+    cache.load().unwrap();
+    let drift = cache.template_drift("adr-1").unwrap();
+    assert_eq!(drift.missing, vec!["## Decision"]);
+    assert_eq!(drift.extra, vec!["## Alternatives"]);
+    assert!(!drift.outdated());
+    assert!(cache.template_drift("notes").is_err());
+
+    // adr-2 never recorded a version; changing the template outdates adr-1 too
+    let status = cache.template_status().unwrap();
+    let outdated: Vec<_> = status.iter().map(TemplateDrift::outdated).collect();
+    assert_eq!(outdated, vec![false, true]);
+
+    fs::write(
+        context_dir.join(TEMPLATE_DIR).join("adr.md"),
+        format!("{TEMPLATE}\n## Status\n"),
+    )
+    .unwrap();
+    let status = cache.template_status().unwrap();
+    assert!(status.iter().all(TemplateDrift::outdated));
+    assert_eq!(status[1].missing, vec!["## Status"]);
+}
@@ -0,0 +1,66 @@
+//! Integration tests for trend tracking
+
+use context::core::trend::TREND_JOURNAL;
+use context::core::Cache;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn setup_project(config: &str) -> (TempDir, Cache) {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a() {}\n").unwrap();
+    fs::write(dir.path().join("src/b.rs"), "fn b() {}\n").unwrap();
+
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(&context_dir).unwrap();
+    fs::write(context_dir.join("a.md"), "# A\n\nSee `src/a.rs`.\n").unwrap();
+    fs::write(context_dir.join("config.toml"), config).unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_full_syncs_record_points() {
+    let (dir, mut cache) = setup_project("");
+    cache.sync(None).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "fn a2() {}\n").unwrap();
+    cache.record_trend(&cache.summary().unwrap()).unwrap();
+    // Syncing a single document doesn't record a point
+    let doc = dir.path().join(".context/a.md");
+    cache.sync(Some(&doc)).unwrap();
+
+    let points = cache.trend(10).unwrap();
+    let counts: Vec<_> = points
+        .iter()
+        .map(|p| (p.documents, p.valid, p.stale))
+        .collect();
+    assert_eq!(counts, vec![(1, 1, 0), (1, 0, 1)]);
+    // One of the two source files is referenced; config.toml is ignored
+    assert!((points[0].coverage - 0.5).abs() < 1e-9);
+    assert_eq!(cache.trend(1).unwrap(), points[1..]);
+}
+
+#[test]
+fn test_recording_can_be_disabled() {
+    let (dir, mut cache) = setup_project("[trend]\nrecord = false\n");
+    cache.sync(None).unwrap();
+    cache.record_trend(&cache.summary().unwrap()).unwrap();
+    assert!(!dir.path().join(".context").join(TREND_JOURNAL).exists());
+    assert!(cache.trend(10).unwrap().is_empty());
+}
+
+#[test]
+fn test_stats_records_nothing() {
+    let (dir, _cache) = setup_project("");
+    fs::create_dir(dir.path().join(".git")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_context"))
+        .arg("stats")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!dir.path().join(".context").join(TREND_JOURNAL).exists());
+}
@@ -0,0 +1,59 @@
+//! Integration tests for opt-in usage tracking
+
+use context::core::usage::USAGE_JOURNAL;
+use context::core::Cache;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_project(config: &str) -> (TempDir, Cache) {
+    let dir = TempDir::new().unwrap();
+    let context_dir = dir.path().join(".context");
+    fs::create_dir_all(context_dir.join("guides")).unwrap();
+    for slug in ["auth", "billing", "deploy"] {
+        fs::write(
+            context_dir.join(format!("guides/{slug}.md")),
+            format!("---\nslug: {slug}\n---\n\n# {slug}\n"),
+        )
+        .unwrap();
+    }
+    fs::write(context_dir.join("config.toml"), config).unwrap();
+
+    let mut cache = Cache::create(context_dir).unwrap();
+    cache.load().unwrap();
+    (dir, cache)
+}
+
+#[test]
+fn test_reads_are_tallied_per_document() {
+    let (dir, cache) = setup_project("[usage]\ntrack = true\n");
+    for slug in ["auth", "billing", "auth"] {
+        let doc = cache.get(slug).unwrap();
+        cache.record_read(doc, "mcp:context_get").unwrap();
+    }
+    assert!(dir.path().join(".context").join(USAGE_JOURNAL).exists());
+
+    let report = cache.usage().unwrap();
+    assert!(report.tracking);
+    assert_eq!(report.total, 3);
+    let reads: Vec<_> = report
+        .documents
+        .iter()
+        .map(|d| (d.slug.as_str(), d.reads))
+        .collect();
+    assert_eq!(reads, vec![("auth", 2), ("billing", 1), ("deploy", 0)]);
+    assert!(report.documents[0].last_read.is_some());
+}
+
+#[test]
+fn test_nothing_recorded_unless_opted_in() {
+    let (dir, cache) = setup_project("");
+    cache
+        .record_read(cache.get("auth").unwrap(), "mcp:context_get")
+        .unwrap();
+    assert!(!dir.path().join(".context").join(USAGE_JOURNAL).exists());
+
+    let report = cache.usage().unwrap();
+    assert!(!report.tracking);
+    assert_eq!(report.total, 0);
+    assert!(report.documents.iter().all(|d| d.reads == 0));
+}